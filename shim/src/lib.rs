@@ -3,14 +3,29 @@
 //!
 //! This allows Zig to use libgit2 without @cImport by providing
 //! stable wrapper functions.
+//!
+//! Most shims are thin pass-throughs whose safety contract is exactly the
+//! one documented for the wrapped libgit2 function.
+
+#![allow(clippy::missing_safety_doc)]
+#![allow(non_camel_case_types)]
 
-use libc::{c_char, c_int, c_uint, size_t};
-use std::ffi::CStr;
+// Only linked for the libgit2 (and its TLS and SSH libraries) that its
+// build provides; every call goes through `raw`.
+extern crate git2;
+
+use libc::{c_char, c_int, c_uint, c_void, size_t};
+use std::collections::{HashMap, HashSet};
+use std::ffi::{CStr, CString};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
 use std::ptr;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 // Raw bindings to libgit2
 mod raw {
-    use libc::{c_char, c_int, c_uint, size_t};
+    use libc::{c_char, c_int, c_uint, c_void, size_t};
 
     pub const GIT_OID_RAWSZ: usize = 20;
 
@@ -20,6 +35,7 @@ pub struct git_oid {
     }
 
     #[repr(C)]
+    #[derive(Clone, Copy)]
     pub struct git_strarray {
         pub strings: *mut *mut c_char,
         pub count: size_t,
@@ -35,9 +51,732 @@ pub struct git_status_options {
         pub rename_threshold: u16,
     }
 
+    #[repr(C)]
+    pub struct git_time {
+        pub time: i64,
+        pub offset: c_int,
+        pub sign: c_char,
+    }
+
+    #[repr(C)]
+    pub struct git_signature {
+        pub name: *mut c_char,
+        pub email: *mut c_char,
+        pub when: git_time,
+    }
+
+    pub const GIT_CHECKOUT_OPTIONS_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct git_checkout_options {
+        pub version: c_uint,
+        pub checkout_strategy: c_uint,
+        pub disable_filters: c_int,
+        pub dir_mode: c_uint,
+        pub file_mode: c_uint,
+        pub file_open_flags: c_int,
+        pub notify_flags: c_uint,
+        pub notify_cb: Option<git_checkout_notify_cb>,
+        pub notify_payload: *mut c_void,
+        pub progress_cb: Option<git_checkout_progress_cb>,
+        pub progress_payload: *mut c_void,
+        pub paths: git_strarray,
+        pub baseline: *mut git_tree,
+        pub baseline_index: *mut git_index,
+        pub target_directory: *const c_char,
+        pub ancestor_label: *const c_char,
+        pub our_label: *const c_char,
+        pub their_label: *const c_char,
+        pub perfdata_cb: Option<git_checkout_perfdata_cb>,
+        pub perfdata_payload: *mut c_void,
+    }
+
+    pub const GIT_STASH_SAVE_OPTIONS_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    pub struct git_stash_save_options {
+        pub version: c_uint,
+        pub flags: u32,
+        pub stasher: *const git_signature,
+        pub message: *const c_char,
+        pub paths: git_strarray,
+    }
+
+    pub const GIT_STASH_APPLY_OPTIONS_VERSION: c_uint = 1;
+    pub const GIT_STASH_APPLY_REINSTATE_INDEX: u32 = 1 << 0;
+
+    #[repr(C)]
+    pub struct git_stash_apply_options {
+        pub version: c_uint,
+        pub flags: u32,
+        pub checkout_options: git_checkout_options,
+        pub progress_cb: Option<git_stash_apply_progress_cb>,
+        pub progress_payload: *mut c_void,
+    }
+
     pub enum git_repository {}
     pub enum git_reference {}
     pub enum git_status_list {}
+    pub enum git_tree {}
+    pub enum git_index {}
+    pub enum git_diff_file {}
+    pub enum git_checkout_perfdata {}
+
+    pub type git_checkout_notify_cb = extern "C" fn(
+        why: c_uint,
+        path: *const c_char,
+        baseline: *const git_diff_file,
+        target: *const git_diff_file,
+        workdir: *const git_diff_file,
+        payload: *mut c_void,
+    ) -> c_int;
+    pub type git_checkout_progress_cb = extern "C" fn(
+        path: *const c_char,
+        completed_steps: size_t,
+        total_steps: size_t,
+        payload: *mut c_void,
+    );
+    pub type git_checkout_perfdata_cb =
+        extern "C" fn(perfdata: *const git_checkout_perfdata, payload: *mut c_void);
+    pub type git_stash_apply_progress_cb =
+        extern "C" fn(progress: c_int, payload: *mut c_void) -> c_int;
+
+    pub type git_stash_cb = extern "C" fn(
+        index: size_t,
+        message: *const c_char,
+        stash_id: *const git_oid,
+        payload: *mut c_void,
+    ) -> c_int;
+
+    pub const GIT_MERGE_OPTIONS_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct git_merge_options {
+        pub version: c_uint,
+        pub flags: u32,
+        pub rename_threshold: c_uint,
+        pub target_limit: c_uint,
+        pub metric: *mut c_void,
+        pub recursion_limit: c_uint,
+        pub default_driver: *const c_char,
+        pub file_favor: c_uint,
+        pub file_flags: u32,
+    }
+
+    pub enum git_annotated_commit {}
+
+    pub enum git_commit {}
+
+    pub const GIT_MERGE_FILE_INPUT_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    pub struct git_merge_file_input {
+        pub version: c_uint,
+        pub ptr: *const c_char,
+        pub size: size_t,
+        pub path: *const c_char,
+        pub mode: c_uint,
+    }
+
+    pub const GIT_MERGE_FILE_OPTIONS_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct git_merge_file_options {
+        pub version: c_uint,
+        pub ancestor_label: *const c_char,
+        pub our_label: *const c_char,
+        pub their_label: *const c_char,
+        pub favor: c_uint,
+        pub flags: u32,
+        pub marker_size: u16,
+    }
+
+    #[repr(C)]
+    pub struct git_merge_file_result {
+        pub automergeable: c_uint,
+        pub path: *const c_char,
+        pub mode: c_uint,
+        pub ptr: *const c_char,
+        pub len: size_t,
+    }
+
+    #[repr(C)]
+    pub struct git_index_time {
+        pub seconds: i32,
+        pub nanoseconds: u32,
+    }
+
+    #[repr(C)]
+    pub struct git_index_entry {
+        pub ctime: git_index_time,
+        pub mtime: git_index_time,
+        pub dev: u32,
+        pub ino: u32,
+        pub mode: u32,
+        pub uid: u32,
+        pub gid: u32,
+        pub file_size: u32,
+        pub id: git_oid,
+        pub flags: u16,
+        pub flags_extended: u16,
+        pub path: *const c_char,
+    }
+
+    pub const GIT_CHERRYPICK_OPTIONS_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    pub struct git_cherrypick_options {
+        pub version: c_uint,
+        pub mainline: c_uint,
+        pub merge_opts: git_merge_options,
+        pub checkout_opts: git_checkout_options,
+    }
+
+    pub const GIT_REVERT_OPTIONS_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    pub struct git_revert_options {
+        pub version: c_uint,
+        pub mainline: c_uint,
+        pub merge_opts: git_merge_options,
+        pub checkout_opts: git_checkout_options,
+    }
+
+    #[repr(C)]
+    pub struct git_buf {
+        pub ptr: *mut c_char,
+        pub reserved: size_t,
+        pub size: size_t,
+    }
+
+    pub const GIT_REBASE_OPTIONS_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    pub struct git_rebase_options {
+        pub version: c_uint,
+        pub quiet: c_int,
+        pub inmemory: c_int,
+        pub rewrite_notes_ref: *const c_char,
+        pub merge_options: git_merge_options,
+        pub checkout_options: git_checkout_options,
+        pub commit_create_cb: Option<git_commit_create_cb>,
+        pub signing_cb: Option<git_commit_signing_cb>,
+        pub payload: *mut c_void,
+    }
+
+    #[repr(C)]
+    pub struct git_rebase_operation {
+        pub type_: c_uint,
+        pub id: git_oid,
+        pub exec: *const c_char,
+    }
+
+    pub enum git_rebase {}
+
+    pub type git_commit_create_cb = extern "C" fn(
+        out: *mut git_oid,
+        author: *const git_signature,
+        committer: *const git_signature,
+        message_encoding: *const c_char,
+        message: *const c_char,
+        tree: *const git_tree,
+        parent_count: size_t,
+        parents: *const *const git_commit,
+        payload: *mut c_void,
+    ) -> c_int;
+    pub type git_commit_signing_cb = extern "C" fn(
+        signature: *mut git_buf,
+        signature_field: *mut git_buf,
+        commit_content: *const c_char,
+        payload: *mut c_void,
+    ) -> c_int;
+
+    pub const GIT_REFERENCE_SYMBOLIC: c_int = 2;
+    pub const GIT_PASSTHROUGH: c_int = -30;
+    pub const GIT_ENOTSUPPORTED: c_int = -39;
+    pub const GIT_ERROR_INVALID: c_int = 3;
+    pub const GIT_CREDENTIAL_USERPASS_PLAINTEXT: c_uint = 1 << 0;
+
+    pub const GIT_BLAME_OPTIONS_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    pub struct git_blame_options {
+        pub version: c_uint,
+        pub flags: u32,
+        pub min_match_characters: u16,
+        pub newest_commit: git_oid,
+        pub oldest_commit: git_oid,
+        pub min_line: size_t,
+        pub max_line: size_t,
+    }
+
+    #[repr(C)]
+    pub struct git_blame_hunk {
+        pub lines_in_hunk: size_t,
+        pub final_commit_id: git_oid,
+        pub final_start_line_number: size_t,
+        pub final_signature: *mut git_signature,
+        pub orig_commit_id: git_oid,
+        pub orig_path: *const c_char,
+        pub orig_start_line_number: size_t,
+        pub orig_signature: *mut git_signature,
+        pub boundary: c_char,
+    }
+
+    pub enum git_blame {}
+
+    pub enum git_revwalk {}
+
+    pub type git_revwalk_hide_cb =
+        extern "C" fn(commit_id: *const git_oid, payload: *mut c_void) -> c_int;
+
+    pub enum git_object {}
+
+    #[repr(C)]
+    pub struct git_revspec {
+        pub from: *mut git_object,
+        pub to: *mut git_object,
+        pub flags: c_uint,
+    }
+
+    #[repr(C)]
+    pub struct git_error {
+        pub message: *mut c_char,
+        pub klass: c_int,
+    }
+
+    pub enum git_remote {}
+    pub enum git_credential {}
+    #[repr(C)]
+    pub struct git_cert {
+        pub cert_type: c_int,
+    }
+
+    pub const GIT_CERT_X509: c_int = 1;
+    pub const GIT_CERT_HOSTKEY_LIBSSH2: c_int = 2;
+
+    pub const GIT_CERT_SSH_MD5: c_uint = 1 << 0;
+    pub const GIT_CERT_SSH_SHA1: c_uint = 1 << 1;
+    pub const GIT_CERT_SSH_SHA256: c_uint = 1 << 2;
+    pub const GIT_CERT_SSH_RAW: c_uint = 1 << 3;
+
+    #[repr(C)]
+    pub struct git_cert_hostkey {
+        pub parent: git_cert,
+        pub type_: c_uint,
+        pub hash_md5: [u8; 16],
+        pub hash_sha1: [u8; 20],
+        pub hash_sha256: [u8; 32],
+        pub raw_type: c_uint,
+        pub hostkey: *const c_char,
+        pub hostkey_len: size_t,
+    }
+
+    #[repr(C)]
+    pub struct git_cert_x509 {
+        pub parent: git_cert,
+        pub data: *mut c_void,
+        pub len: size_t,
+    }
+    pub enum git_transport {}
+
+    #[repr(C)]
+    pub struct git_indexer_progress {
+        pub total_objects: c_uint,
+        pub indexed_objects: c_uint,
+        pub received_objects: c_uint,
+        pub local_objects: c_uint,
+        pub total_deltas: c_uint,
+        pub indexed_deltas: c_uint,
+        pub received_bytes: size_t,
+    }
+
+    #[repr(C)]
+    pub struct git_push_update {
+        pub src_refname: *mut c_char,
+        pub dst_refname: *mut c_char,
+        pub src: git_oid,
+        pub dst: git_oid,
+    }
+
+    pub type git_transport_message_cb =
+        extern "C" fn(str: *const c_char, len: c_int, payload: *mut c_void) -> c_int;
+    pub type git_remote_completion_cb = extern "C" fn(type_: c_uint, data: *mut c_void) -> c_int;
+    pub type git_credential_acquire_cb = extern "C" fn(
+        out: *mut *mut git_credential,
+        url: *const c_char,
+        username_from_url: *const c_char,
+        allowed_types: c_uint,
+        payload: *mut c_void,
+    ) -> c_int;
+    pub type git_transport_certificate_check_cb = extern "C" fn(
+        cert: *mut git_cert,
+        valid: c_int,
+        host: *const c_char,
+        payload: *mut c_void,
+    ) -> c_int;
+    pub type git_indexer_progress_cb =
+        extern "C" fn(stats: *const git_indexer_progress, payload: *mut c_void) -> c_int;
+    pub type git_update_tips_cb = extern "C" fn(
+        refname: *const c_char,
+        a: *const git_oid,
+        b: *const git_oid,
+        data: *mut c_void,
+    ) -> c_int;
+    pub type git_packbuilder_progress =
+        extern "C" fn(stage: c_int, current: u32, total: u32, payload: *mut c_void) -> c_int;
+    pub type git_push_transfer_progress_cb =
+        extern "C" fn(current: c_uint, total: c_uint, bytes: size_t, payload: *mut c_void) -> c_int;
+    pub type git_push_update_reference_cb =
+        extern "C" fn(refname: *const c_char, status: *const c_char, data: *mut c_void) -> c_int;
+    pub type git_push_negotiation = extern "C" fn(
+        updates: *mut *const git_push_update,
+        len: size_t,
+        payload: *mut c_void,
+    ) -> c_int;
+    pub type git_transport_cb = extern "C" fn(
+        out: *mut *mut git_transport,
+        owner: *mut git_remote,
+        param: *mut c_void,
+    ) -> c_int;
+    pub type git_remote_ready_cb =
+        extern "C" fn(remote: *mut git_remote, direction: c_int, payload: *mut c_void) -> c_int;
+    pub type git_url_resolve_cb = extern "C" fn(
+        url_resolved: *mut git_buf,
+        url: *const c_char,
+        direction: c_int,
+        payload: *mut c_void,
+    ) -> c_int;
+
+    pub const GIT_REMOTE_CALLBACKS_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct git_remote_callbacks {
+        pub version: c_uint,
+        pub sideband_progress: Option<git_transport_message_cb>,
+        pub completion: Option<git_remote_completion_cb>,
+        pub credentials: Option<git_credential_acquire_cb>,
+        pub certificate_check: Option<git_transport_certificate_check_cb>,
+        pub transfer_progress: Option<git_indexer_progress_cb>,
+        pub update_tips: Option<git_update_tips_cb>,
+        pub pack_progress: Option<git_packbuilder_progress>,
+        pub push_transfer_progress: Option<git_push_transfer_progress_cb>,
+        pub push_update_reference: Option<git_push_update_reference_cb>,
+        pub push_negotiation: Option<git_push_negotiation>,
+        pub transport: Option<git_transport_cb>,
+        pub remote_ready: Option<git_remote_ready_cb>,
+        pub payload: *mut c_void,
+        pub resolve_url: Option<git_url_resolve_cb>,
+    }
+
+    pub const GIT_PROXY_OPTIONS_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct git_proxy_options {
+        pub version: c_uint,
+        pub type_: c_uint,
+        pub url: *const c_char,
+        pub credentials: Option<git_credential_acquire_cb>,
+        pub certificate_check: Option<git_transport_certificate_check_cb>,
+        pub payload: *mut c_void,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct git_fetch_options {
+        pub version: c_int,
+        pub callbacks: git_remote_callbacks,
+        pub prune: c_uint,
+        pub update_fetchhead: c_uint,
+        pub download_tags: c_uint,
+        pub proxy_opts: git_proxy_options,
+        pub depth: c_int,
+        pub follow_redirects: c_uint,
+        pub custom_headers: git_strarray,
+    }
+
+    pub type git_repository_create_cb = extern "C" fn(
+        out: *mut *mut git_repository,
+        path: *const c_char,
+        bare: c_int,
+        payload: *mut c_void,
+    ) -> c_int;
+    pub type git_remote_create_cb = extern "C" fn(
+        out: *mut *mut git_remote,
+        repo: *mut git_repository,
+        name: *const c_char,
+        url: *const c_char,
+        payload: *mut c_void,
+    ) -> c_int;
+
+    pub const GIT_CLONE_OPTIONS_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    pub struct git_clone_options {
+        pub version: c_uint,
+        pub checkout_opts: git_checkout_options,
+        pub fetch_opts: git_fetch_options,
+        pub bare: c_int,
+        pub local: c_uint,
+        pub checkout_branch: *const c_char,
+        pub repository_cb: Option<git_repository_create_cb>,
+        pub repository_cb_payload: *mut c_void,
+        pub remote_cb: Option<git_remote_create_cb>,
+        pub remote_cb_payload: *mut c_void,
+    }
+
+    pub const GIT_FETCH_OPTIONS_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct git_remote_connect_options {
+        pub version: c_uint,
+        pub callbacks: git_remote_callbacks,
+        pub proxy_opts: git_proxy_options,
+        pub follow_redirects: c_uint,
+        pub custom_headers: git_strarray,
+    }
+
+    pub const GIT_REMOTE_CONNECT_OPTIONS_VERSION: c_uint = 1;
+
+    pub const GIT_PUSH_OPTIONS_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct git_push_options {
+        pub version: c_uint,
+        pub pb_parallelism: c_uint,
+        pub callbacks: git_remote_callbacks,
+        pub proxy_opts: git_proxy_options,
+        pub follow_redirects: c_uint,
+        pub custom_headers: git_strarray,
+        pub remote_push_options: git_strarray,
+    }
+
+    #[repr(C)]
+    pub struct git_remote_head {
+        pub local: c_int,
+        pub oid: git_oid,
+        pub loid: git_oid,
+        pub name: *mut c_char,
+        pub symref_target: *mut c_char,
+    }
+
+    pub enum git_refspec {}
+
+    pub const GIT_OPT_GET_SEARCH_PATH: c_int = 4;
+    pub const GIT_OPT_SET_SEARCH_PATH: c_int = 5;
+    pub const GIT_OPT_SET_SERVER_CONNECT_TIMEOUT: c_int = 39;
+    pub const GIT_OPT_GET_SERVER_CONNECT_TIMEOUT: c_int = 40;
+    pub const GIT_OPT_SET_SERVER_TIMEOUT: c_int = 41;
+    pub const GIT_OPT_GET_SERVER_TIMEOUT: c_int = 42;
+
+    pub enum git_smart_subtransport {}
+
+    pub type git_smart_subtransport_cb = extern "C" fn(
+        out: *mut *mut git_smart_subtransport,
+        owner: *mut git_transport,
+        param: *mut c_void,
+    ) -> c_int;
+
+    #[repr(C)]
+    pub struct git_smart_subtransport_definition {
+        pub callback: git_smart_subtransport_cb,
+        pub rpc: c_uint,
+        pub param: *mut c_void,
+    }
+
+    pub type git_repository_fetchhead_foreach_cb = extern "C" fn(
+        ref_name: *const c_char,
+        remote_url: *const c_char,
+        oid: *const git_oid,
+        is_merge: c_uint,
+        payload: *mut c_void,
+    ) -> c_int;
+
+    pub const GIT_OBJECT_ANY: c_int = -2;
+    pub const GIT_OBJECT_COMMIT: c_int = 1;
+    pub const GIT_OBJECT_TREE: c_int = 2;
+    pub const GIT_OBJECT_BLOB: c_int = 3;
+    pub const GIT_OBJECT_TAG: c_int = 4;
+    pub const GIT_ITEROVER: c_int = -31;
+    pub const GIT_ERROR_OS: c_int = 2;
+    pub const GIT_REPOSITORY_ITEM_OBJECTS: c_int = 4;
+    pub const GIT_FILEMODE_COMMIT: u32 = 0o160000;
+
+    pub enum git_tag {}
+    pub enum git_tree_entry {}
+    pub enum git_reflog {}
+    pub enum git_reflog_entry {}
+    pub enum git_packbuilder {}
+    pub enum git_odb {}
+
+    /// Only `free` is ever called from the shim; the thirteen callbacks
+    /// before it (`read` to `freshen`) are libgit2's business.
+    #[repr(C)]
+    pub struct git_odb_backend {
+        pub version: c_uint,
+        pub odb: *mut git_odb,
+        pub callbacks: [*mut c_void; 13],
+        pub free: Option<extern "C" fn(backend: *mut git_odb_backend)>,
+    }
+
+    pub type git_reference_foreach_cb =
+        extern "C" fn(reference: *mut git_reference, payload: *mut c_void) -> c_int;
+    pub type git_odb_foreach_cb = extern "C" fn(id: *const git_oid, payload: *mut c_void) -> c_int;
+
+    pub const GIT_ENOTFOUND: c_int = -3;
+    pub const GIT_ENONFASTFORWARD: c_int = -11;
+
+    pub enum git_indexer {}
+
+    pub type git_packbuilder_foreach_cb =
+        extern "C" fn(buf: *mut c_void, size: size_t, payload: *mut c_void) -> c_int;
+
+    pub enum git_config {}
+
+    pub enum git_config_iterator {}
+
+    #[repr(C)]
+    pub struct git_config_entry {
+        pub name: *const c_char,
+        pub value: *const c_char,
+        pub backend_type: *const c_char,
+        pub origin_path: *const c_char,
+        pub include_depth: c_uint,
+        pub level: c_int,
+        pub free: Option<extern "C" fn(entry: *mut git_config_entry)>,
+    }
+
+    pub type git_config_foreach_cb =
+        extern "C" fn(entry: *const git_config_entry, payload: *mut c_void) -> c_int;
+
+    pub enum git_transaction {}
+
+    pub enum git_submodule {}
+
+    pub type git_submodule_cb =
+        extern "C" fn(sm: *mut git_submodule, name: *const c_char, payload: *mut c_void) -> c_int;
+
+    pub const GIT_SUBMODULE_UPDATE_OPTIONS_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    pub struct git_submodule_update_options {
+        pub version: c_uint,
+        pub checkout_opts: git_checkout_options,
+        pub fetch_opts: git_fetch_options,
+        pub allow_fetch: c_int,
+    }
+
+    pub enum git_worktree {}
+
+    pub const GIT_WORKTREE_ADD_OPTIONS_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    pub struct git_worktree_add_options {
+        pub version: c_uint,
+        pub lock: c_int,
+        pub checkout_existing: c_int,
+        pub ref_: *mut git_reference,
+        pub checkout_options: git_checkout_options,
+    }
+
+    pub const GIT_WORKTREE_PRUNE_OPTIONS_VERSION: c_uint = 1;
+
+    #[repr(C)]
+    pub struct git_worktree_prune_options {
+        pub version: c_uint,
+        pub flags: u32,
+    }
+
+    pub const GIT_EUSER: c_int = -7;
+    pub const GIT_EAUTH: c_int = -16;
+    pub const GIT_ERROR_SSH: c_int = 23;
+    pub const GIT_ERROR_HTTP: c_int = 34;
+    pub const GIT_SUBMODULE_STATUS_IN_CONFIG: c_uint = 1 << 2;
+
+    pub const GIT_REPOSITORY_INIT_OPTIONS_VERSION: c_uint = 1;
+    pub const GIT_REPOSITORY_INIT_MKPATH: u32 = 1 << 4;
+    pub const GIT_REPOSITORY_INIT_EXTERNAL_TEMPLATE: u32 = 1 << 5;
+
+    #[repr(C)]
+    pub struct git_repository_init_options {
+        pub version: c_uint,
+        pub flags: u32,
+        pub mode: u32,
+        pub workdir_path: *const c_char,
+        pub description: *const c_char,
+        pub template_path: *const c_char,
+        pub initial_head: *const c_char,
+        pub origin_url: *const c_char,
+    }
+
+    pub enum git_refdb {}
+
+    pub type git_attr_foreach_cb =
+        extern "C" fn(name: *const c_char, value: *const c_char, payload: *mut c_void) -> c_int;
+
+    pub const GIT_REPOSITORY_OPEN_NO_SEARCH: c_uint = 1 << 0;
+    pub const GIT_REPOSITORY_OPEN_NO_DOTGIT: c_uint = 1 << 3;
+
+    pub type git_repository_mergehead_foreach_cb =
+        extern "C" fn(oid: *const git_oid, payload: *mut c_void) -> c_int;
+
+    pub enum git_filter_source {}
+
+    #[repr(C)]
+    pub struct git_writestream {
+        pub write: extern "C" fn(
+            stream: *mut git_writestream,
+            buffer: *const c_char,
+            len: size_t,
+        ) -> c_int,
+        pub close: extern "C" fn(stream: *mut git_writestream) -> c_int,
+        pub free: extern "C" fn(stream: *mut git_writestream),
+    }
+
+    pub type git_filter_check_fn = extern "C" fn(
+        filter: *mut git_filter,
+        payload: *mut *mut c_void,
+        src: *const git_filter_source,
+        attr_values: *mut *const c_char,
+    ) -> c_int;
+
+    pub type git_filter_stream_fn = extern "C" fn(
+        out: *mut *mut git_writestream,
+        filter: *mut git_filter,
+        payload: *mut *mut c_void,
+        src: *const git_filter_source,
+        next: *mut git_writestream,
+    ) -> c_int;
+
+    #[repr(C)]
+    pub struct git_filter {
+        pub version: c_uint,
+        pub attributes: *const c_char,
+        pub initialize: *mut c_void,
+        pub shutdown: *mut c_void,
+        pub check: Option<git_filter_check_fn>,
+        pub apply: *mut c_void,
+        pub stream: Option<git_filter_stream_fn>,
+        pub cleanup: *mut c_void,
+    }
+
+    pub const GIT_FILTER_VERSION: c_uint = 1;
+    pub const GIT_ERROR_FILTER: c_int = 24;
+
+    pub enum git_blob {}
+    pub enum git_filter_list {}
+
+    pub enum git_odb_object {}
+
+    pub const GIT_EMISMATCH: c_int = -33;
+    pub const GIT_INDEX_ENTRY_INTENT_TO_ADD: u16 = 1 << 13;
 
     #[link(name = "git2")]
     extern "C" {
@@ -55,8 +794,10 @@ pub fn git_status_list_new(
         ) -> c_int;
         pub fn git_status_list_free(list: *mut git_status_list);
         pub fn git_status_list_entrycount(list: *const git_status_list) -> size_t;
-        pub fn git_repository_head(out: *mut *mut git_reference, repo: *mut git_repository)
-            -> c_int;
+        pub fn git_repository_head(
+            out: *mut *mut git_reference,
+            repo: *mut git_repository,
+        ) -> c_int;
         pub fn git_reference_free(ref_: *mut git_reference);
         pub fn git_reference_shorthand(ref_: *const git_reference) -> *const c_char;
         pub fn git_graph_ahead_behind(
@@ -66,104 +807,10860 @@ pub fn git_graph_ahead_behind(
             local: *const git_oid,
             upstream: *const git_oid,
         ) -> c_int;
-    }
-}
-
-// =============================================================================
-// Shim functions
-// =============================================================================
-
-#[no_mangle]
-pub extern "C" fn git2_shim_init() -> c_int {
-    unsafe { raw::git_libgit2_init() }
-}
-
-#[no_mangle]
-pub extern "C" fn git2_shim_shutdown() -> c_int {
-    unsafe { raw::git_libgit2_shutdown() }
-}
-
-#[no_mangle]
-pub unsafe extern "C" fn git2_shim_repository_open(
-    out: *mut *mut raw::git_repository,
-    path: *const c_char,
-) -> c_int {
-    raw::git_repository_open(out, path)
-}
-
-#[no_mangle]
-pub unsafe extern "C" fn git2_shim_repository_free(repo: *mut raw::git_repository) {
-    raw::git_repository_free(repo)
-}
-
-#[no_mangle]
-pub unsafe extern "C" fn git2_shim_repository_is_bare(repo: *mut raw::git_repository) -> c_int {
-    raw::git_repository_is_bare(repo)
-}
-
-#[no_mangle]
-pub unsafe extern "C" fn git2_shim_repository_workdir(
-    repo: *mut raw::git_repository,
-) -> *const c_char {
-    raw::git_repository_workdir(repo)
+        pub fn git_signature_default(
+            out: *mut *mut git_signature,
+            repo: *mut git_repository,
+        ) -> c_int;
+        pub fn git_signature_now(
+            out: *mut *mut git_signature,
+            name: *const c_char,
+            email: *const c_char,
+        ) -> c_int;
+        pub fn git_signature_free(sig: *mut git_signature);
+        pub fn git_stash_save(
+            out: *mut git_oid,
+            repo: *mut git_repository,
+            stasher: *const git_signature,
+            message: *const c_char,
+            flags: u32,
+        ) -> c_int;
+        pub fn git_stash_save_options_init(
+            opts: *mut git_stash_save_options,
+            version: c_uint,
+        ) -> c_int;
+        pub fn git_stash_save_with_opts(
+            out: *mut git_oid,
+            repo: *mut git_repository,
+            opts: *const git_stash_save_options,
+        ) -> c_int;
+        pub fn git_stash_apply_options_init(
+            opts: *mut git_stash_apply_options,
+            version: c_uint,
+        ) -> c_int;
+        pub fn git_stash_apply(
+            repo: *mut git_repository,
+            index: size_t,
+            options: *const git_stash_apply_options,
+        ) -> c_int;
+        pub fn git_stash_pop(
+            repo: *mut git_repository,
+            index: size_t,
+            options: *const git_stash_apply_options,
+        ) -> c_int;
+        pub fn git_stash_drop(repo: *mut git_repository, index: size_t) -> c_int;
+        pub fn git_stash_foreach(
+            repo: *mut git_repository,
+            callback: git_stash_cb,
+            payload: *mut c_void,
+        ) -> c_int;
+        pub fn git_checkout_options_init(opts: *mut git_checkout_options, version: c_uint)
+            -> c_int;
+        pub fn git_merge_options_init(opts: *mut git_merge_options, version: c_uint) -> c_int;
+        pub fn git_annotated_commit_from_ref(
+            out: *mut *mut git_annotated_commit,
+            repo: *mut git_repository,
+            ref_: *const git_reference,
+        ) -> c_int;
+        pub fn git_annotated_commit_lookup(
+            out: *mut *mut git_annotated_commit,
+            repo: *mut git_repository,
+            id: *const git_oid,
+        ) -> c_int;
+        pub fn git_annotated_commit_from_revspec(
+            out: *mut *mut git_annotated_commit,
+            repo: *mut git_repository,
+            revspec: *const c_char,
+        ) -> c_int;
+        pub fn git_annotated_commit_id(commit: *const git_annotated_commit) -> *const git_oid;
+        pub fn git_annotated_commit_ref(commit: *const git_annotated_commit) -> *const c_char;
+        pub fn git_annotated_commit_free(commit: *mut git_annotated_commit);
+        pub fn git_merge(
+            repo: *mut git_repository,
+            their_heads: *mut *const git_annotated_commit,
+            their_heads_len: size_t,
+            merge_opts: *const git_merge_options,
+            checkout_opts: *const git_checkout_options,
+        ) -> c_int;
+        pub fn git_reference_lookup(
+            out: *mut *mut git_reference,
+            repo: *mut git_repository,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_branch_is_checked_out(branch: *const git_reference) -> c_int;
+        pub fn git_merge_analysis(
+            analysis_out: *mut c_uint,
+            preference_out: *mut c_uint,
+            repo: *mut git_repository,
+            their_heads: *mut *const git_annotated_commit,
+            their_heads_len: size_t,
+        ) -> c_int;
+        pub fn git_merge_analysis_for_ref(
+            analysis_out: *mut c_uint,
+            preference_out: *mut c_uint,
+            repo: *mut git_repository,
+            our_ref: *mut git_reference,
+            their_heads: *mut *const git_annotated_commit,
+            their_heads_len: size_t,
+        ) -> c_int;
+        pub fn git_commit_lookup(
+            out: *mut *mut git_commit,
+            repo: *mut git_repository,
+            id: *const git_oid,
+        ) -> c_int;
+        pub fn git_commit_free(commit: *mut git_commit);
+        pub fn git_commit_create(
+            id: *mut git_oid,
+            repo: *mut git_repository,
+            update_ref: *const c_char,
+            author: *const git_signature,
+            committer: *const git_signature,
+            message_encoding: *const c_char,
+            message: *const c_char,
+            tree: *const git_tree,
+            parent_count: size_t,
+            parents: *const *const git_commit,
+        ) -> c_int;
+        pub fn git_commit_tree(tree_out: *mut *mut git_tree, commit: *const git_commit) -> c_int;
+        pub fn git_commit_tree_id(commit: *const git_commit) -> *const git_oid;
+        pub fn git_tree_lookup(
+            out: *mut *mut git_tree,
+            repo: *mut git_repository,
+            id: *const git_oid,
+        ) -> c_int;
+        pub fn git_tree_free(tree: *mut git_tree);
+        pub fn git_index_free(index: *mut git_index);
+        pub fn git_index_entrycount(index: *const git_index) -> size_t;
+        pub fn git_index_has_conflicts(index: *const git_index) -> c_int;
+        pub fn git_index_write_tree_to(
+            out: *mut git_oid,
+            index: *mut git_index,
+            repo: *mut git_repository,
+        ) -> c_int;
+        pub fn git_merge_trees(
+            out: *mut *mut git_index,
+            repo: *mut git_repository,
+            ancestor_tree: *const git_tree,
+            our_tree: *const git_tree,
+            their_tree: *const git_tree,
+            opts: *const git_merge_options,
+        ) -> c_int;
+        pub fn git_merge_commits(
+            out: *mut *mut git_index,
+            repo: *mut git_repository,
+            our_commit: *const git_commit,
+            their_commit: *const git_commit,
+            opts: *const git_merge_options,
+        ) -> c_int;
+        pub fn git_merge_base(
+            out: *mut git_oid,
+            repo: *mut git_repository,
+            one: *const git_oid,
+            two: *const git_oid,
+        ) -> c_int;
+        pub fn git_merge_base_many(
+            out: *mut git_oid,
+            repo: *mut git_repository,
+            length: size_t,
+            input_array: *const git_oid,
+        ) -> c_int;
+        pub fn git_merge_base_octopus(
+            out: *mut git_oid,
+            repo: *mut git_repository,
+            length: size_t,
+            input_array: *const git_oid,
+        ) -> c_int;
+        pub fn git_merge_file_options_init(
+            opts: *mut git_merge_file_options,
+            version: c_uint,
+        ) -> c_int;
+        pub fn git_merge_file(
+            out: *mut git_merge_file_result,
+            ancestor: *const git_merge_file_input,
+            ours: *const git_merge_file_input,
+            theirs: *const git_merge_file_input,
+            opts: *const git_merge_file_options,
+        ) -> c_int;
+        pub fn git_merge_file_from_index(
+            out: *mut git_merge_file_result,
+            repo: *mut git_repository,
+            ancestor: *const git_index_entry,
+            ours: *const git_index_entry,
+            theirs: *const git_index_entry,
+            opts: *const git_merge_file_options,
+        ) -> c_int;
+        pub fn git_merge_file_result_free(result: *mut git_merge_file_result);
+        pub fn git_index_conflict_get(
+            ancestor_out: *mut *const git_index_entry,
+            our_out: *mut *const git_index_entry,
+            their_out: *mut *const git_index_entry,
+            index: *mut git_index,
+            path: *const c_char,
+        ) -> c_int;
+        pub fn git_cherrypick_options_init(
+            opts: *mut git_cherrypick_options,
+            version: c_uint,
+        ) -> c_int;
+        pub fn git_cherrypick(
+            repo: *mut git_repository,
+            commit: *mut git_commit,
+            cherrypick_options: *const git_cherrypick_options,
+        ) -> c_int;
+        pub fn git_cherrypick_commit(
+            out: *mut *mut git_index,
+            repo: *mut git_repository,
+            cherrypick_commit: *mut git_commit,
+            our_commit: *mut git_commit,
+            mainline: c_uint,
+            merge_options: *const git_merge_options,
+        ) -> c_int;
+        pub fn git_revert_options_init(opts: *mut git_revert_options, version: c_uint) -> c_int;
+        pub fn git_revert_commit(
+            out: *mut *mut git_index,
+            repo: *mut git_repository,
+            revert_commit: *mut git_commit,
+            our_commit: *mut git_commit,
+            mainline: c_uint,
+            merge_options: *const git_merge_options,
+        ) -> c_int;
+        pub fn git_revert(
+            repo: *mut git_repository,
+            commit: *mut git_commit,
+            given_opts: *const git_revert_options,
+        ) -> c_int;
+        pub fn git_rebase_options_init(opts: *mut git_rebase_options, version: c_uint) -> c_int;
+        pub fn git_rebase_init(
+            out: *mut *mut git_rebase,
+            repo: *mut git_repository,
+            branch: *const git_annotated_commit,
+            upstream: *const git_annotated_commit,
+            onto: *const git_annotated_commit,
+            opts: *const git_rebase_options,
+        ) -> c_int;
+        pub fn git_rebase_open(
+            out: *mut *mut git_rebase,
+            repo: *mut git_repository,
+            opts: *const git_rebase_options,
+        ) -> c_int;
+        pub fn git_rebase_operation_entrycount(rebase: *mut git_rebase) -> size_t;
+        pub fn git_rebase_operation_current(rebase: *mut git_rebase) -> size_t;
+        pub fn git_rebase_operation_byindex(
+            rebase: *mut git_rebase,
+            idx: size_t,
+        ) -> *mut git_rebase_operation;
+        pub fn git_rebase_next(
+            operation: *mut *mut git_rebase_operation,
+            rebase: *mut git_rebase,
+        ) -> c_int;
+        pub fn git_rebase_commit(
+            id: *mut git_oid,
+            rebase: *mut git_rebase,
+            author: *const git_signature,
+            committer: *const git_signature,
+            message_encoding: *const c_char,
+            message: *const c_char,
+        ) -> c_int;
+        pub fn git_rebase_abort(rebase: *mut git_rebase) -> c_int;
+        pub fn git_rebase_finish(rebase: *mut git_rebase, signature: *const git_signature)
+            -> c_int;
+        pub fn git_rebase_free(rebase: *mut git_rebase);
+        pub fn git_rebase_inmemory_index(
+            index: *mut *mut git_index,
+            rebase: *mut git_rebase,
+        ) -> c_int;
+        pub fn git_buf_dispose(buffer: *mut git_buf);
+        pub fn git_commit_create_buffer(
+            out: *mut git_buf,
+            repo: *mut git_repository,
+            author: *const git_signature,
+            committer: *const git_signature,
+            message_encoding: *const c_char,
+            message: *const c_char,
+            tree: *const git_tree,
+            parent_count: size_t,
+            parents: *const *const git_commit,
+        ) -> c_int;
+        pub fn git_commit_create_with_signature(
+            out: *mut git_oid,
+            repo: *mut git_repository,
+            commit_content: *const c_char,
+            signature: *const c_char,
+            signature_field: *const c_char,
+        ) -> c_int;
+        pub fn git_reference_type(ref_: *const git_reference) -> c_int;
+        pub fn git_reference_symbolic_target(ref_: *const git_reference) -> *const c_char;
+        pub fn git_reference_create_matching(
+            out: *mut *mut git_reference,
+            repo: *mut git_repository,
+            name: *const c_char,
+            id: *const git_oid,
+            force: c_int,
+            current_id: *const git_oid,
+            log_message: *const c_char,
+        ) -> c_int;
+        pub fn git_commit_id(commit: *const git_commit) -> *const git_oid;
+        pub fn git_reference_create(
+            out: *mut *mut git_reference,
+            repo: *mut git_repository,
+            name: *const c_char,
+            id: *const git_oid,
+            force: c_int,
+            log_message: *const c_char,
+        ) -> c_int;
+        pub fn git_blame_file(
+            out: *mut *mut git_blame,
+            repo: *mut git_repository,
+            path: *const c_char,
+            options: *mut git_blame_options,
+        ) -> c_int;
+        pub fn git_blame_buffer(
+            out: *mut *mut git_blame,
+            reference: *mut git_blame,
+            buffer: *const c_char,
+            buffer_len: size_t,
+        ) -> c_int;
+        pub fn git_blame_options_init(opts: *mut git_blame_options, version: c_uint) -> c_int;
+        pub fn git_blame_get_hunk_count(blame: *mut git_blame) -> u32;
+        pub fn git_blame_get_hunk_byindex(
+            blame: *mut git_blame,
+            index: u32,
+        ) -> *const git_blame_hunk;
+        pub fn git_blame_get_hunk_byline(
+            blame: *mut git_blame,
+            lineno: size_t,
+        ) -> *const git_blame_hunk;
+        pub fn git_blame_free(blame: *mut git_blame);
+        pub fn git_revwalk_new(out: *mut *mut git_revwalk, repo: *mut git_repository) -> c_int;
+        pub fn git_revwalk_reset(walk: *mut git_revwalk) -> c_int;
+        pub fn git_revwalk_push(walk: *mut git_revwalk, id: *const git_oid) -> c_int;
+        pub fn git_revwalk_hide(walk: *mut git_revwalk, id: *const git_oid) -> c_int;
+        pub fn git_revwalk_next(out: *mut git_oid, walk: *mut git_revwalk) -> c_int;
+        pub fn git_revwalk_sorting(walk: *mut git_revwalk, sort_mode: c_uint) -> c_int;
+        pub fn git_revwalk_free(walk: *mut git_revwalk);
+        pub fn git_revwalk_push_range(walk: *mut git_revwalk, range: *const c_char) -> c_int;
+        pub fn git_revwalk_push_glob(walk: *mut git_revwalk, glob: *const c_char) -> c_int;
+        pub fn git_revwalk_push_head(walk: *mut git_revwalk) -> c_int;
+        pub fn git_revwalk_push_ref(walk: *mut git_revwalk, refname: *const c_char) -> c_int;
+        pub fn git_revwalk_hide_glob(walk: *mut git_revwalk, glob: *const c_char) -> c_int;
+        pub fn git_revwalk_hide_head(walk: *mut git_revwalk) -> c_int;
+        pub fn git_revwalk_hide_ref(walk: *mut git_revwalk, refname: *const c_char) -> c_int;
+        pub fn git_revwalk_simplify_first_parent(walk: *mut git_revwalk) -> c_int;
+        pub fn git_revwalk_add_hide_cb(
+            walk: *mut git_revwalk,
+            hide_cb: Option<git_revwalk_hide_cb>,
+            payload: *mut c_void,
+        ) -> c_int;
+        pub fn git_object_free(object: *mut git_object);
+        pub fn git_object_id(object: *const git_object) -> *const git_oid;
+        pub fn git_object_type(object: *const git_object) -> c_int;
+        pub fn git_reference_name(ref_: *const git_reference) -> *const c_char;
+        pub fn git_revparse_single(
+            out: *mut *mut git_object,
+            repo: *mut git_repository,
+            spec: *const c_char,
+        ) -> c_int;
+        pub fn git_revparse_ext(
+            object_out: *mut *mut git_object,
+            reference_out: *mut *mut git_reference,
+            repo: *mut git_repository,
+            spec: *const c_char,
+        ) -> c_int;
+        pub fn git_revparse(
+            revspec: *mut git_revspec,
+            repo: *mut git_repository,
+            spec: *const c_char,
+        ) -> c_int;
+        pub fn git_error_last() -> *const git_error;
+        pub fn git_clone_options_init(opts: *mut git_clone_options, version: c_uint) -> c_int;
+        pub fn git_clone(
+            out: *mut *mut git_repository,
+            url: *const c_char,
+            local_path: *const c_char,
+            options: *const git_clone_options,
+        ) -> c_int;
+        pub fn git_fetch_options_init(opts: *mut git_fetch_options, version: c_uint) -> c_int;
+        pub fn git_repository_is_shallow(repo: *mut git_repository) -> c_int;
+        pub fn git_strarray_dispose(array: *mut git_strarray);
+        pub fn git_remote_list(out: *mut git_strarray, repo: *mut git_repository) -> c_int;
+        pub fn git_remote_lookup(
+            out: *mut *mut git_remote,
+            repo: *mut git_repository,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_remote_create(
+            out: *mut *mut git_remote,
+            repo: *mut git_repository,
+            name: *const c_char,
+            url: *const c_char,
+        ) -> c_int;
+        pub fn git_remote_delete(repo: *mut git_repository, name: *const c_char) -> c_int;
+        pub fn git_remote_rename(
+            problems: *mut git_strarray,
+            repo: *mut git_repository,
+            name: *const c_char,
+            new_name: *const c_char,
+        ) -> c_int;
+        pub fn git_remote_free(remote: *mut git_remote);
+        pub fn git_remote_name(remote: *const git_remote) -> *const c_char;
+        pub fn git_remote_url(remote: *const git_remote) -> *const c_char;
+        pub fn git_remote_pushurl(remote: *const git_remote) -> *const c_char;
+        pub fn git_remote_set_url(
+            repo: *mut git_repository,
+            remote: *const c_char,
+            url: *const c_char,
+        ) -> c_int;
+        pub fn git_remote_set_pushurl(
+            repo: *mut git_repository,
+            remote: *const c_char,
+            url: *const c_char,
+        ) -> c_int;
+        pub fn git_proxy_options_init(opts: *mut git_proxy_options, version: c_uint) -> c_int;
+        pub fn git_remote_fetch(
+            remote: *mut git_remote,
+            refspecs: *const git_strarray,
+            opts: *const git_fetch_options,
+            reflog_message: *const c_char,
+        ) -> c_int;
+        pub fn git_push_options_init(opts: *mut git_push_options, version: c_uint) -> c_int;
+        pub fn git_remote_push(
+            remote: *mut git_remote,
+            refspecs: *const git_strarray,
+            opts: *const git_push_options,
+        ) -> c_int;
+        pub fn git_credential_free(cred: *mut git_credential);
+        pub fn git_credential_username_new(
+            out: *mut *mut git_credential,
+            username: *const c_char,
+        ) -> c_int;
+        pub fn git_credential_userpass_plaintext_new(
+            out: *mut *mut git_credential,
+            username: *const c_char,
+            password: *const c_char,
+        ) -> c_int;
+        pub fn git_credential_ssh_key_new(
+            out: *mut *mut git_credential,
+            username: *const c_char,
+            publickey: *const c_char,
+            privatekey: *const c_char,
+            passphrase: *const c_char,
+        ) -> c_int;
+        pub fn git_credential_ssh_key_memory_new(
+            out: *mut *mut git_credential,
+            username: *const c_char,
+            publickey: *const c_char,
+            privatekey: *const c_char,
+            passphrase: *const c_char,
+        ) -> c_int;
+        pub fn git_credential_ssh_key_from_agent(
+            out: *mut *mut git_credential,
+            username: *const c_char,
+        ) -> c_int;
+        pub fn git_credential_default_new(out: *mut *mut git_credential) -> c_int;
+        pub fn git_remote_connect_options_init(
+            opts: *mut git_remote_connect_options,
+            version: c_uint,
+        ) -> c_int;
+        pub fn git_remote_connect_ext(
+            remote: *mut git_remote,
+            direction: c_int,
+            opts: *const git_remote_connect_options,
+        ) -> c_int;
+        pub fn git_remote_connected(remote: *const git_remote) -> c_int;
+        pub fn git_remote_disconnect(remote: *mut git_remote) -> c_int;
+        pub fn git_remote_ls(
+            out: *mut *mut *const git_remote_head,
+            size: *mut size_t,
+            remote: *mut git_remote,
+        ) -> c_int;
+        pub fn git_remote_prune(
+            remote: *mut git_remote,
+            callbacks: *const git_remote_callbacks,
+        ) -> c_int;
+        pub fn git_remote_prune_refs(remote: *const git_remote) -> c_int;
+        pub fn git_refspec_parse(
+            refspec: *mut *mut git_refspec,
+            input: *const c_char,
+            is_fetch: c_int,
+        ) -> c_int;
+        pub fn git_refspec_free(refspec: *mut git_refspec);
+        pub fn git_refspec_src(refspec: *const git_refspec) -> *const c_char;
+        pub fn git_refspec_dst(refspec: *const git_refspec) -> *const c_char;
+        pub fn git_refspec_string(refspec: *const git_refspec) -> *const c_char;
+        pub fn git_refspec_force(refspec: *const git_refspec) -> c_int;
+        pub fn git_refspec_direction(refspec: *const git_refspec) -> c_int;
+        pub fn git_refspec_src_matches(
+            refspec: *const git_refspec,
+            refname: *const c_char,
+        ) -> c_int;
+        pub fn git_refspec_dst_matches(
+            refspec: *const git_refspec,
+            refname: *const c_char,
+        ) -> c_int;
+        pub fn git_refspec_transform(
+            out: *mut git_buf,
+            spec: *const git_refspec,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_refspec_rtransform(
+            out: *mut git_buf,
+            spec: *const git_refspec,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_libgit2_opts(option: c_int, ...) -> c_int;
+        pub fn git_transport_register(
+            prefix: *const c_char,
+            cb: git_transport_cb,
+            param: *mut c_void,
+        ) -> c_int;
+        pub fn git_transport_unregister(prefix: *const c_char) -> c_int;
+        pub fn git_transport_smart(
+            out: *mut *mut git_transport,
+            owner: *mut git_remote,
+            payload: *mut c_void,
+        ) -> c_int;
+        pub fn git_transport_smart_credentials(
+            out: *mut *mut git_credential,
+            transport: *mut git_transport,
+            user: *const c_char,
+            methods: c_int,
+        ) -> c_int;
+        pub fn git_transport_smart_certificate_check(
+            transport: *mut git_transport,
+            cert: *mut git_cert,
+            valid: c_int,
+            hostname: *const c_char,
+        ) -> c_int;
+        pub fn git_repository_fetchhead_foreach(
+            repo: *mut git_repository,
+            callback: git_repository_fetchhead_foreach_cb,
+            payload: *mut c_void,
+        ) -> c_int;
+        pub fn git_remote_download(
+            remote: *mut git_remote,
+            refspecs: *const git_strarray,
+            opts: *const git_fetch_options,
+        ) -> c_int;
+        pub fn git_remote_update_tips(
+            remote: *mut git_remote,
+            callbacks: *const git_remote_callbacks,
+            update_flags: c_uint,
+            download_tags: c_uint,
+            reflog_message: *const c_char,
+        ) -> c_int;
+        pub fn git_remote_create_anonymous(
+            out: *mut *mut git_remote,
+            repo: *mut git_repository,
+            url: *const c_char,
+        ) -> c_int;
+        pub fn git_remote_autotag(remote: *const git_remote) -> c_uint;
+        pub fn git_remote_set_autotag(
+            repo: *mut git_repository,
+            remote: *const c_char,
+            value: c_uint,
+        ) -> c_int;
+        pub fn git_repository_path(repo: *const git_repository) -> *const c_char;
+        pub fn git_error_set_str(error_class: c_int, string: *const c_char) -> c_int;
+        pub fn git_object_lookup(
+            out: *mut *mut git_object,
+            repo: *mut git_repository,
+            id: *const git_oid,
+            kind: c_int,
+        ) -> c_int;
+        pub fn git_tag_target_id(tag: *const git_tag) -> *const git_oid;
+        pub fn git_tree_entrycount(tree: *const git_tree) -> size_t;
+        pub fn git_tree_entry_byindex(tree: *const git_tree, idx: size_t) -> *const git_tree_entry;
+        pub fn git_tree_entry_id(entry: *const git_tree_entry) -> *const git_oid;
+        pub fn git_tree_entry_type(entry: *const git_tree_entry) -> c_int;
+        pub fn git_reference_foreach(
+            repo: *mut git_repository,
+            callback: git_reference_foreach_cb,
+            payload: *mut c_void,
+        ) -> c_int;
+        pub fn git_reference_resolve(
+            out: *mut *mut git_reference,
+            ref_: *const git_reference,
+        ) -> c_int;
+        pub fn git_reference_target(ref_: *const git_reference) -> *const git_oid;
+        pub fn git_reference_name_to_id(
+            out: *mut git_oid,
+            repo: *mut git_repository,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_reflog_read(
+            out: *mut *mut git_reflog,
+            repo: *mut git_repository,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_reflog_entrycount(reflog: *mut git_reflog) -> size_t;
+        pub fn git_reflog_entry_byindex(
+            reflog: *const git_reflog,
+            idx: size_t,
+        ) -> *const git_reflog_entry;
+        pub fn git_reflog_entry_id_old(entry: *const git_reflog_entry) -> *const git_oid;
+        pub fn git_reflog_entry_id_new(entry: *const git_reflog_entry) -> *const git_oid;
+        pub fn git_reflog_free(reflog: *mut git_reflog);
+        pub fn git_repository_index(out: *mut *mut git_index, repo: *mut git_repository) -> c_int;
+        pub fn git_index_get_byindex(index: *mut git_index, n: size_t) -> *const git_index_entry;
+        pub fn git_repository_item_path(
+            out: *mut git_buf,
+            repo: *const git_repository,
+            item: c_int,
+        ) -> c_int;
+        pub fn git_packbuilder_new(
+            out: *mut *mut git_packbuilder,
+            repo: *mut git_repository,
+        ) -> c_int;
+        pub fn git_packbuilder_insert(
+            pb: *mut git_packbuilder,
+            id: *const git_oid,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_packbuilder_write(
+            pb: *mut git_packbuilder,
+            path: *const c_char,
+            mode: c_uint,
+            progress_cb: Option<git_indexer_progress_cb>,
+            progress_cb_payload: *mut c_void,
+        ) -> c_int;
+        pub fn git_packbuilder_name(pb: *mut git_packbuilder) -> *const c_char;
+        pub fn git_packbuilder_free(pb: *mut git_packbuilder);
+        pub fn git_odb_new(out: *mut *mut git_odb) -> c_int;
+        pub fn git_odb_backend_one_pack(
+            out: *mut *mut git_odb_backend,
+            index_file: *const c_char,
+        ) -> c_int;
+        pub fn git_odb_backend_pack(
+            out: *mut *mut git_odb_backend,
+            objects_dir: *const c_char,
+        ) -> c_int;
+        pub fn git_odb_write_multi_pack_index(db: *mut git_odb) -> c_int;
+        pub fn git_odb_add_backend(
+            odb: *mut git_odb,
+            backend: *mut git_odb_backend,
+            priority: c_int,
+        ) -> c_int;
+        pub fn git_odb_foreach(
+            odb: *mut git_odb,
+            callback: git_odb_foreach_cb,
+            payload: *mut c_void,
+        ) -> c_int;
+        pub fn git_odb_free(odb: *mut git_odb);
+        pub fn git_repository_odb(out: *mut *mut git_odb, repo: *mut git_repository) -> c_int;
+        pub fn git_odb_refresh(odb: *mut git_odb) -> c_int;
+        pub fn git_odb_exists(odb: *mut git_odb, id: *const git_oid) -> c_int;
+        pub fn git_reference_dwim(
+            out: *mut *mut git_reference,
+            repo: *mut git_repository,
+            shorthand: *const c_char,
+        ) -> c_int;
+        pub fn git_object_peel(
+            peeled: *mut *mut git_object,
+            object: *const git_object,
+            target_type: c_int,
+        ) -> c_int;
+        pub fn git_commit_parentcount(commit: *const git_commit) -> c_uint;
+        pub fn git_commit_parent_id(commit: *const git_commit, n: c_uint) -> *const git_oid;
+        pub fn git_commit_summary(commit: *mut git_commit) -> *const c_char;
+        pub fn git_graph_descendant_of(
+            repo: *mut git_repository,
+            commit: *const git_oid,
+            ancestor: *const git_oid,
+        ) -> c_int;
+        pub fn git_packbuilder_insert_walk(
+            pb: *mut git_packbuilder,
+            walk: *mut git_revwalk,
+        ) -> c_int;
+        pub fn git_packbuilder_insert_recur(
+            pb: *mut git_packbuilder,
+            id: *const git_oid,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_packbuilder_foreach(
+            pb: *mut git_packbuilder,
+            cb: git_packbuilder_foreach_cb,
+            payload: *mut c_void,
+        ) -> c_int;
+        pub fn git_indexer_new(
+            out: *mut *mut git_indexer,
+            path: *const c_char,
+            mode: c_uint,
+            odb: *mut git_odb,
+            opts: *mut c_void,
+        ) -> c_int;
+        pub fn git_indexer_append(
+            idx: *mut git_indexer,
+            data: *const c_void,
+            size: size_t,
+            stats: *mut git_indexer_progress,
+        ) -> c_int;
+        pub fn git_indexer_commit(idx: *mut git_indexer, stats: *mut git_indexer_progress)
+            -> c_int;
+        pub fn git_indexer_free(idx: *mut git_indexer);
+        pub fn git_repository_config(out: *mut *mut git_config, repo: *mut git_repository)
+            -> c_int;
+        pub fn git_config_free(cfg: *mut git_config);
+        pub fn git_config_get_string_buf(
+            out: *mut git_buf,
+            cfg: *const git_config,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_config_set_string(
+            cfg: *mut git_config,
+            name: *const c_char,
+            value: *const c_char,
+        ) -> c_int;
+        pub fn git_config_get_bool(
+            out: *mut c_int,
+            cfg: *const git_config,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_config_get_int32(
+            out: *mut i32,
+            cfg: *const git_config,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_config_get_int64(
+            out: *mut i64,
+            cfg: *const git_config,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_config_get_path(
+            out: *mut git_buf,
+            cfg: *const git_config,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_config_set_bool(
+            cfg: *mut git_config,
+            name: *const c_char,
+            value: c_int,
+        ) -> c_int;
+        pub fn git_config_set_int32(cfg: *mut git_config, name: *const c_char, value: i32)
+            -> c_int;
+        pub fn git_config_set_int64(cfg: *mut git_config, name: *const c_char, value: i64)
+            -> c_int;
+        pub fn git_config_iterator_new(
+            out: *mut *mut git_config_iterator,
+            cfg: *const git_config,
+        ) -> c_int;
+        pub fn git_config_iterator_glob_new(
+            out: *mut *mut git_config_iterator,
+            cfg: *const git_config,
+            regexp: *const c_char,
+        ) -> c_int;
+        pub fn git_config_next(
+            entry: *mut *mut git_config_entry,
+            iter: *mut git_config_iterator,
+        ) -> c_int;
+        pub fn git_config_iterator_free(iter: *mut git_config_iterator);
+        pub fn git_config_foreach(
+            cfg: *const git_config,
+            callback: git_config_foreach_cb,
+            payload: *mut c_void,
+        ) -> c_int;
+        pub fn git_config_foreach_match(
+            cfg: *const git_config,
+            regexp: *const c_char,
+            callback: git_config_foreach_cb,
+            payload: *mut c_void,
+        ) -> c_int;
+        pub fn git_config_get_multivar_foreach(
+            cfg: *const git_config,
+            name: *const c_char,
+            regexp: *const c_char,
+            callback: git_config_foreach_cb,
+            payload: *mut c_void,
+        ) -> c_int;
+        pub fn git_config_set_multivar(
+            cfg: *mut git_config,
+            name: *const c_char,
+            regexp: *const c_char,
+            value: *const c_char,
+        ) -> c_int;
+        pub fn git_config_open_level(
+            out: *mut *mut git_config,
+            parent: *const git_config,
+            level: c_int,
+        ) -> c_int;
+        pub fn git_config_open_default(out: *mut *mut git_config) -> c_int;
+        pub fn git_config_snapshot(out: *mut *mut git_config, config: *mut git_config) -> c_int;
+        pub fn git_repository_config_snapshot(
+            out: *mut *mut git_config,
+            repo: *mut git_repository,
+        ) -> c_int;
+        pub fn git_config_delete_entry(cfg: *mut git_config, name: *const c_char) -> c_int;
+        pub fn git_config_delete_multivar(
+            cfg: *mut git_config,
+            name: *const c_char,
+            regexp: *const c_char,
+        ) -> c_int;
+        pub fn git_config_lock(tx: *mut *mut git_transaction, cfg: *mut git_config) -> c_int;
+        pub fn git_transaction_commit(tx: *mut git_transaction) -> c_int;
+        pub fn git_transaction_free(tx: *mut git_transaction);
+        pub fn git_repository_set_ident(
+            repo: *mut git_repository,
+            name: *const c_char,
+            email: *const c_char,
+        ) -> c_int;
+        pub fn git_repository_ident(
+            name: *mut *const c_char,
+            email: *mut *const c_char,
+            repo: *const git_repository,
+        ) -> c_int;
+        pub fn git_config_parse_bool(out: *mut c_int, value: *const c_char) -> c_int;
+        pub fn git_config_parse_int32(out: *mut i32, value: *const c_char) -> c_int;
+        pub fn git_config_parse_int64(out: *mut i64, value: *const c_char) -> c_int;
+        pub fn git_config_parse_path(out: *mut git_buf, value: *const c_char) -> c_int;
+        pub fn git_config_new(out: *mut *mut git_config) -> c_int;
+        pub fn git_config_add_file_ondisk(
+            cfg: *mut git_config,
+            path: *const c_char,
+            level: c_int,
+            repo: *const git_repository,
+            force: c_int,
+        ) -> c_int;
+        pub fn git_submodule_lookup(
+            out: *mut *mut git_submodule,
+            repo: *mut git_repository,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_submodule_free(submodule: *mut git_submodule);
+        pub fn git_submodule_foreach(
+            repo: *mut git_repository,
+            callback: git_submodule_cb,
+            payload: *mut c_void,
+        ) -> c_int;
+        pub fn git_submodule_status(
+            status: *mut c_uint,
+            repo: *mut git_repository,
+            name: *const c_char,
+            ignore: c_int,
+        ) -> c_int;
+        pub fn git_submodule_location(
+            location_status: *mut c_uint,
+            submodule: *mut git_submodule,
+        ) -> c_int;
+        pub fn git_submodule_update_options_init(
+            opts: *mut git_submodule_update_options,
+            version: c_uint,
+        ) -> c_int;
+        pub fn git_submodule_init(submodule: *mut git_submodule, overwrite: c_int) -> c_int;
+        pub fn git_submodule_update(
+            submodule: *mut git_submodule,
+            init: c_int,
+            options: *mut git_submodule_update_options,
+        ) -> c_int;
+        pub fn git_submodule_add_setup(
+            out: *mut *mut git_submodule,
+            repo: *mut git_repository,
+            url: *const c_char,
+            path: *const c_char,
+            use_gitlink: c_int,
+        ) -> c_int;
+        pub fn git_submodule_clone(
+            out: *mut *mut git_repository,
+            submodule: *mut git_submodule,
+            opts: *const git_submodule_update_options,
+        ) -> c_int;
+        pub fn git_submodule_add_finalize(submodule: *mut git_submodule) -> c_int;
+        pub fn git_submodule_add_to_index(
+            submodule: *mut git_submodule,
+            write_index: c_int,
+        ) -> c_int;
+        pub fn git_submodule_name(submodule: *mut git_submodule) -> *const c_char;
+        pub fn git_submodule_path(submodule: *mut git_submodule) -> *const c_char;
+        pub fn git_submodule_url(submodule: *mut git_submodule) -> *const c_char;
+        pub fn git_submodule_branch(submodule: *mut git_submodule) -> *const c_char;
+        pub fn git_submodule_head_id(submodule: *mut git_submodule) -> *const git_oid;
+        pub fn git_submodule_index_id(submodule: *mut git_submodule) -> *const git_oid;
+        pub fn git_submodule_wd_id(submodule: *mut git_submodule) -> *const git_oid;
+        pub fn git_submodule_set_url(
+            repo: *mut git_repository,
+            name: *const c_char,
+            url: *const c_char,
+        ) -> c_int;
+        pub fn git_submodule_set_branch(
+            repo: *mut git_repository,
+            name: *const c_char,
+            branch: *const c_char,
+        ) -> c_int;
+        pub fn git_submodule_set_update(
+            repo: *mut git_repository,
+            name: *const c_char,
+            update: c_int,
+        ) -> c_int;
+        pub fn git_submodule_set_ignore(
+            repo: *mut git_repository,
+            name: *const c_char,
+            ignore: c_int,
+        ) -> c_int;
+        pub fn git_submodule_sync(submodule: *mut git_submodule) -> c_int;
+        pub fn git_submodule_open(
+            repo: *mut *mut git_repository,
+            submodule: *mut git_submodule,
+        ) -> c_int;
+        pub fn git_submodule_repo_init(
+            out: *mut *mut git_repository,
+            sm: *const git_submodule,
+            use_gitlink: c_int,
+        ) -> c_int;
+        pub fn git_worktree_list(out: *mut git_strarray, repo: *mut git_repository) -> c_int;
+        pub fn git_worktree_lookup(
+            out: *mut *mut git_worktree,
+            repo: *mut git_repository,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_worktree_open_from_repository(
+            out: *mut *mut git_worktree,
+            repo: *mut git_repository,
+        ) -> c_int;
+        pub fn git_worktree_free(wt: *mut git_worktree);
+        pub fn git_worktree_add_options_init(
+            opts: *mut git_worktree_add_options,
+            version: c_uint,
+        ) -> c_int;
+        pub fn git_worktree_add(
+            out: *mut *mut git_worktree,
+            repo: *mut git_repository,
+            name: *const c_char,
+            path: *const c_char,
+            opts: *const git_worktree_add_options,
+        ) -> c_int;
+        pub fn git_worktree_prune_options_init(
+            opts: *mut git_worktree_prune_options,
+            version: c_uint,
+        ) -> c_int;
+        pub fn git_worktree_validate(wt: *const git_worktree) -> c_int;
+        pub fn git_worktree_lock(wt: *mut git_worktree, reason: *const c_char) -> c_int;
+        pub fn git_worktree_unlock(wt: *mut git_worktree) -> c_int;
+        pub fn git_worktree_is_locked(reason: *mut git_buf, wt: *const git_worktree) -> c_int;
+        pub fn git_worktree_is_prunable(
+            wt: *mut git_worktree,
+            opts: *mut git_worktree_prune_options,
+        ) -> c_int;
+        pub fn git_worktree_prune(
+            wt: *mut git_worktree,
+            opts: *mut git_worktree_prune_options,
+        ) -> c_int;
+        pub fn git_repository_open_from_worktree(
+            out: *mut *mut git_repository,
+            wt: *mut git_worktree,
+        ) -> c_int;
+        pub fn git_repository_is_worktree(repo: *const git_repository) -> c_int;
+        pub fn git_repository_commondir(repo: *const git_repository) -> *const c_char;
+        pub fn git_worktree_name(wt: *const git_worktree) -> *const c_char;
+        pub fn git_worktree_path(wt: *const git_worktree) -> *const c_char;
+        pub fn git_repository_init(
+            out: *mut *mut git_repository,
+            path: *const c_char,
+            is_bare: c_uint,
+        ) -> c_int;
+        pub fn git_repository_init_options_init(
+            opts: *mut git_repository_init_options,
+            version: c_uint,
+        ) -> c_int;
+        pub fn git_repository_init_ext(
+            out: *mut *mut git_repository,
+            repo_path: *const c_char,
+            opts: *mut git_repository_init_options,
+        ) -> c_int;
+        pub fn git_repository_discover(
+            out: *mut git_buf,
+            start_path: *const c_char,
+            across_fs: c_int,
+            ceiling_dirs: *const c_char,
+        ) -> c_int;
+        pub fn git_repository_open_ext(
+            out: *mut *mut git_repository,
+            path: *const c_char,
+            flags: c_uint,
+            ceiling_dirs: *const c_char,
+        ) -> c_int;
+        pub fn git_repository_open_bare(
+            out: *mut *mut git_repository,
+            bare_path: *const c_char,
+        ) -> c_int;
+        pub fn git_repository_message(out: *mut git_buf, repo: *mut git_repository) -> c_int;
+        pub fn git_repository_message_remove(repo: *mut git_repository) -> c_int;
+        pub fn git_repository_set_workdir(
+            repo: *mut git_repository,
+            workdir: *const c_char,
+            update_gitlink: c_int,
+        ) -> c_int;
+        pub fn git_index_read(index: *mut git_index, force: c_int) -> c_int;
+        pub fn git_index_write(index: *mut git_index) -> c_int;
+        pub fn git_repository_refdb(out: *mut *mut git_refdb, repo: *mut git_repository) -> c_int;
+        pub fn git_refdb_compress(refdb: *mut git_refdb) -> c_int;
+        pub fn git_refdb_free(refdb: *mut git_refdb);
+        pub fn git_repository_hashfile(
+            out: *mut git_oid,
+            repo: *mut git_repository,
+            path: *const c_char,
+            kind: c_int,
+            as_path: *const c_char,
+        ) -> c_int;
+        pub fn git_ignore_add_rule(repo: *mut git_repository, rules: *const c_char) -> c_int;
+        pub fn git_ignore_clear_internal_rules(repo: *mut git_repository) -> c_int;
+        pub fn git_ignore_path_is_ignored(
+            ignored: *mut c_int,
+            repo: *mut git_repository,
+            path: *const c_char,
+        ) -> c_int;
+        pub fn git_attr_value(attr: *const c_char) -> c_int;
+        pub fn git_attr_get(
+            value_out: *mut *const c_char,
+            repo: *mut git_repository,
+            flags: u32,
+            path: *const c_char,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_attr_get_many(
+            values_out: *mut *const c_char,
+            repo: *mut git_repository,
+            flags: u32,
+            path: *const c_char,
+            num_attr: size_t,
+            names: *mut *const c_char,
+        ) -> c_int;
+        pub fn git_attr_foreach(
+            repo: *mut git_repository,
+            flags: u32,
+            path: *const c_char,
+            callback: git_attr_foreach_cb,
+            payload: *mut c_void,
+        ) -> c_int;
+        pub fn git_attr_add_macro(
+            repo: *mut git_repository,
+            name: *const c_char,
+            values: *const c_char,
+        ) -> c_int;
+        pub fn git_attr_cache_flush(repo: *mut git_repository) -> c_int;
+        pub fn git_reference_peel(
+            out: *mut *mut git_object,
+            ref_: *const git_reference,
+            kind: c_int,
+        ) -> c_int;
+        pub fn git_checkout_tree(
+            repo: *mut git_repository,
+            treeish: *const git_object,
+            opts: *const git_checkout_options,
+        ) -> c_int;
+        pub fn git_repository_set_head(repo: *mut git_repository, refname: *const c_char) -> c_int;
+        pub fn git_repository_state(repo: *mut git_repository) -> c_int;
+        pub fn git_repository_mergehead_foreach(
+            repo: *mut git_repository,
+            callback: git_repository_mergehead_foreach_cb,
+            payload: *mut c_void,
+        ) -> c_int;
+        pub fn git_rebase_orig_head_name(rebase: *mut git_rebase) -> *const c_char;
+        pub fn git_rebase_orig_head_id(rebase: *mut git_rebase) -> *const git_oid;
+        pub fn git_rebase_onto_name(rebase: *mut git_rebase) -> *const c_char;
+        pub fn git_rebase_onto_id(rebase: *mut git_rebase) -> *const git_oid;
+        pub fn git_filter_register(
+            name: *const c_char,
+            filter: *mut git_filter,
+            priority: c_int,
+        ) -> c_int;
+        pub fn git_filter_unregister(name: *const c_char) -> c_int;
+        pub fn git_filter_source_repo(src: *const git_filter_source) -> *mut git_repository;
+        pub fn git_filter_source_path(src: *const git_filter_source) -> *const c_char;
+        pub fn git_filter_source_filemode(src: *const git_filter_source) -> u16;
+        pub fn git_filter_source_id(src: *const git_filter_source) -> *const git_oid;
+        pub fn git_filter_source_mode(src: *const git_filter_source) -> c_uint;
+        pub fn git_blob_lookup(
+            blob: *mut *mut git_blob,
+            repo: *mut git_repository,
+            id: *const git_oid,
+        ) -> c_int;
+        pub fn git_blob_free(blob: *mut git_blob);
+        pub fn git_filter_list_load(
+            filters: *mut *mut git_filter_list,
+            repo: *mut git_repository,
+            blob: *mut git_blob,
+            path: *const c_char,
+            mode: c_uint,
+            flags: u32,
+        ) -> c_int;
+        pub fn git_filter_list_contains(
+            filters: *mut git_filter_list,
+            name: *const c_char,
+        ) -> c_int;
+        pub fn git_filter_list_apply_to_buffer(
+            out: *mut git_buf,
+            filters: *mut git_filter_list,
+            input: *const c_char,
+            in_len: size_t,
+        ) -> c_int;
+        pub fn git_filter_list_apply_to_blob(
+            out: *mut git_buf,
+            filters: *mut git_filter_list,
+            blob: *mut git_blob,
+        ) -> c_int;
+        pub fn git_filter_list_free(filters: *mut git_filter_list);
+        pub fn git_odb_open(out: *mut *mut git_odb, objects_dir: *const c_char) -> c_int;
+        pub fn git_odb_add_disk_alternate(odb: *mut git_odb, path: *const c_char) -> c_int;
+        pub fn git_repository_wrap_odb(out: *mut *mut git_repository, odb: *mut git_odb) -> c_int;
+        pub fn git_object_type2string(kind: c_int) -> *const c_char;
+        pub fn git_object_string2type(name: *const c_char) -> c_int;
+        pub fn git_object_typeisloose(kind: c_int) -> c_int;
+        pub fn git_odb_read(
+            out: *mut *mut git_odb_object,
+            db: *mut git_odb,
+            id: *const git_oid,
+        ) -> c_int;
+        pub fn git_odb_read_header(
+            len_out: *mut size_t,
+            type_out: *mut c_int,
+            db: *mut git_odb,
+            id: *const git_oid,
+        ) -> c_int;
+        pub fn git_odb_object_free(object: *mut git_odb_object);
+        pub fn git_odb_object_data(object: *mut git_odb_object) -> *const c_void;
+        pub fn git_odb_object_size(object: *mut git_odb_object) -> size_t;
+        pub fn git_odb_object_type(object: *mut git_odb_object) -> c_int;
+        pub fn git_odb_hash(
+            out: *mut git_oid,
+            data: *const c_void,
+            len: size_t,
+            kind: c_int,
+        ) -> c_int;
+        pub fn git_tag_target_type(tag: *const git_tag) -> c_int;
+    }
+}
+
+/// A Zig callback together with its userdata.
+///
+/// Handed to libgit2 as the payload of a trampoline, so the Zig side only
+/// ever sees the shim's callback types and its own userdata pointer.
+#[derive(Clone, Copy)]
+struct Trampoline<F> {
+    callback: F,
+    payload: *mut c_void,
+}
+
+/// Owned copy of a C string list, lent to libgit2 as a `git_strarray`.
+#[derive(Default)]
+struct StrArray {
+    _strings: Vec<CString>,
+    ptrs: Vec<*mut c_char>,
+}
+
+impl StrArray {
+    /// Copy `count` NUL-terminated strings; a NULL `strings` yields an empty list.
+    unsafe fn from_raw(strings: *const *const c_char, count: size_t) -> StrArray {
+        if strings.is_null() {
+            return StrArray::default();
+        }
+        let owned: Vec<CString> = (0..count)
+            .map(|i| CStr::from_ptr(*strings.add(i)).to_owned())
+            .collect();
+        let ptrs = owned.iter().map(|s| s.as_ptr() as *mut c_char).collect();
+        StrArray {
+            _strings: owned,
+            ptrs,
+        }
+    }
+
+    fn as_raw(&mut self) -> raw::git_strarray {
+        raw::git_strarray {
+            strings: if self.ptrs.is_empty() {
+                ptr::null_mut()
+            } else {
+                self.ptrs.as_mut_ptr()
+            },
+            count: self.ptrs.len(),
+        }
+    }
+}
+
+impl Clone for StrArray {
+    fn clone(&self) -> StrArray {
+        let owned = self._strings.clone();
+        let ptrs = owned.iter().map(|s| s.as_ptr() as *mut c_char).collect();
+        StrArray {
+            _strings: owned,
+            ptrs,
+        }
+    }
+}
+
+/// Copy an optional C string so the shim can keep it past the setter call.
+unsafe fn owned_cstring(s: *const c_char) -> Option<CString> {
+    if s.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(s).to_owned())
+    }
+}
+
+fn cstring_ptr(s: &Option<CString>) -> *const c_char {
+    s.as_ref().map_or(ptr::null(), |s| s.as_ptr())
+}
+
+// =============================================================================
+// Shim functions
+// =============================================================================
+
+#[no_mangle]
+pub extern "C" fn git2_shim_init() -> c_int {
+    unsafe { raw::git_libgit2_init() }
+}
+
+#[no_mangle]
+pub extern "C" fn git2_shim_shutdown() -> c_int {
+    unsafe { raw::git_libgit2_shutdown() }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_open(
+    out: *mut *mut raw::git_repository,
+    path: *const c_char,
+) -> c_int {
+    raw::git_repository_open(out, path)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_free(repo: *mut raw::git_repository) {
+    raw::git_repository_free(repo)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_is_bare(repo: *mut raw::git_repository) -> c_int {
+    raw::git_repository_is_bare(repo)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_workdir(
+    repo: *mut raw::git_repository,
+) -> *const c_char {
+    raw::git_repository_workdir(repo)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_status_options_init(
+    opts: *mut raw::git_status_options,
+    version: c_uint,
+) -> c_int {
+    raw::git_status_options_init(opts, version)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_status_list_new(
+    out: *mut *mut raw::git_status_list,
+    repo: *mut raw::git_repository,
+    opts: *const raw::git_status_options,
+) -> c_int {
+    raw::git_status_list_new(out, repo, opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_status_list_free(list: *mut raw::git_status_list) {
+    raw::git_status_list_free(list)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_status_list_entrycount(
+    list: *const raw::git_status_list,
+) -> size_t {
+    raw::git_status_list_entrycount(list)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_head(
+    out: *mut *mut raw::git_reference,
+    repo: *mut raw::git_repository,
+) -> c_int {
+    raw::git_repository_head(out, repo)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_reference_lookup(
+    out: *mut *mut raw::git_reference,
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+) -> c_int {
+    raw::git_reference_lookup(out, repo, name)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_reference_free(ref_: *mut raw::git_reference) {
+    raw::git_reference_free(ref_)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_reference_shorthand(
+    ref_: *const raw::git_reference,
+) -> *const c_char {
+    raw::git_reference_shorthand(ref_)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_graph_ahead_behind(
+    ahead: *mut size_t,
+    behind: *mut size_t,
+    repo: *mut raw::git_repository,
+    local: *const raw::git_oid,
+    upstream: *const raw::git_oid,
+) -> c_int {
+    raw::git_graph_ahead_behind(ahead, behind, repo, local, upstream)
+}
+
+// =============================================================================
+// Signatures
+// =============================================================================
+
+/// The identity set with `git2_shim_repository_set_ident` if there is
+/// one, else `user.name` and `user.email` from the configuration, with
+/// the current time.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_signature_default(
+    out: *mut *mut raw::git_signature,
+    repo: *mut raw::git_repository,
+) -> c_int {
+    let mut name = ptr::null();
+    let mut email = ptr::null();
+    if raw::git_repository_ident(&mut name, &mut email, repo) == 0
+        && !name.is_null()
+        && !email.is_null()
+    {
+        return raw::git_signature_now(out, name, email);
+    }
+    raw::git_signature_default(out, repo)
+}
+
+/// Use `name` and `email` as this handle's identity for reflog entries
+/// and, through `git2_shim_signature_default`, as author and committer,
+/// without touching any configuration file. Both must be set for the
+/// override to apply; NULL for both clears it.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_set_ident(
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+    email: *const c_char,
+) -> c_int {
+    raw::git_repository_set_ident(repo, name, email)
+}
+
+/// Identity set with `git2_shim_repository_set_ident`; each part is NULL
+/// if unset and stays valid until the identity is changed.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_ident(
+    name: *mut *const c_char,
+    email: *mut *const c_char,
+    repo: *const raw::git_repository,
+) -> c_int {
+    raw::git_repository_ident(name, email, repo)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_signature_now(
+    out: *mut *mut raw::git_signature,
+    name: *const c_char,
+    email: *const c_char,
+) -> c_int {
+    raw::git_signature_now(out, name, email)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_signature_free(sig: *mut raw::git_signature) {
+    raw::git_signature_free(sig)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_signature_name(sig: *const raw::git_signature) -> *const c_char {
+    (*sig).name
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_signature_email(
+    sig: *const raw::git_signature,
+) -> *const c_char {
+    (*sig).email
+}
+
+/// Seconds since the epoch.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_signature_time(sig: *const raw::git_signature) -> i64 {
+    (*sig).when.time
+}
+
+/// Timezone offset in minutes.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_signature_offset(sig: *const raw::git_signature) -> c_int {
+    (*sig).when.offset
+}
+
+// =============================================================================
+// Stash
+// =============================================================================
+
+/// Callback invoked once per stash entry, newest first.
+///
+/// Return 0 to continue iterating; any other value stops the walk and is
+/// returned from `git2_shim_stash_foreach`.
+pub type git2_shim_stash_cb = extern "C" fn(
+    index: size_t,
+    message: *const c_char,
+    stash_id: *const raw::git_oid,
+    payload: *mut c_void,
+) -> c_int;
+
+extern "C" fn stash_trampoline(
+    index: size_t,
+    message: *const c_char,
+    stash_id: *const raw::git_oid,
+    payload: *mut c_void,
+) -> c_int {
+    let t = unsafe { &*(payload as *const Trampoline<git2_shim_stash_cb>) };
+    (t.callback)(index, message, stash_id, t.payload)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_stash_save(
+    out: *mut raw::git_oid,
+    repo: *mut raw::git_repository,
+    stasher: *const raw::git_signature,
+    message: *const c_char,
+    flags: u32,
+) -> c_int {
+    raw::git_stash_save(out, repo, stasher, message, flags)
+}
+
+/// Stash save options: flags, message, stasher, and pathspec.
+pub struct StashSaveOptions {
+    raw: raw::git_stash_save_options,
+    message: Option<CString>,
+    paths: StrArray,
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_stash_save_options_new() -> *mut StashSaveOptions {
+    let mut opts = Box::new(StashSaveOptions {
+        raw: std::mem::zeroed(),
+        message: None,
+        paths: StrArray::default(),
+    });
+    if raw::git_stash_save_options_init(&mut opts.raw, raw::GIT_STASH_SAVE_OPTIONS_VERSION) < 0 {
+        return ptr::null_mut();
+    }
+    Box::into_raw(opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_stash_save_options_free(opts: *mut StashSaveOptions) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// Set `GIT_STASH_*` flags (keep-index, include-untracked, ...).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_stash_save_options_set_flags(
+    opts: *mut StashSaveOptions,
+    flags: u32,
+) {
+    (*opts).raw.flags = flags;
+}
+
+/// Set the stash message; NULL lets libgit2 generate one.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_stash_save_options_set_message(
+    opts: *mut StashSaveOptions,
+    message: *const c_char,
+) {
+    (*opts).message = owned_cstring(message);
+}
+
+/// Set the stasher identity; the signature must outlive the save call.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_stash_save_options_set_stasher(
+    opts: *mut StashSaveOptions,
+    stasher: *const raw::git_signature,
+) {
+    (*opts).raw.stasher = stasher;
+}
+
+/// Restrict the stash to the given pathspecs.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_stash_save_options_set_paths(
+    opts: *mut StashSaveOptions,
+    paths: *const *const c_char,
+    count: size_t,
+) {
+    (*opts).paths = StrArray::from_raw(paths, count);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_stash_save_with_opts(
+    out: *mut raw::git_oid,
+    repo: *mut raw::git_repository,
+    opts: *mut StashSaveOptions,
+) -> c_int {
+    let opts = &mut *opts;
+    opts.raw.message = cstring_ptr(&opts.message);
+    opts.raw.paths = opts.paths.as_raw();
+    raw::git_stash_save_with_opts(out, repo, &opts.raw)
+}
+
+/// Progress callback for stash apply/pop; `progress` is a
+/// `GIT_STASH_APPLY_PROGRESS_*` value. Return non-zero to abort.
+pub type git2_shim_stash_apply_progress_cb =
+    extern "C" fn(progress: c_int, payload: *mut c_void) -> c_int;
+
+extern "C" fn stash_apply_progress_trampoline(progress: c_int, payload: *mut c_void) -> c_int {
+    let t = unsafe { &*(payload as *const Trampoline<git2_shim_stash_apply_progress_cb>) };
+    (t.callback)(progress, t.payload)
+}
+
+/// Stash apply options: reinstate-index flag and progress reporting.
+pub struct StashApplyOptions {
+    raw: raw::git_stash_apply_options,
+    progress: Option<Trampoline<git2_shim_stash_apply_progress_cb>>,
+}
+
+impl StashApplyOptions {
+    fn as_raw(&mut self) -> *const raw::git_stash_apply_options {
+        match self.progress.as_mut() {
+            Some(t) => {
+                self.raw.progress_cb = Some(stash_apply_progress_trampoline);
+                self.raw.progress_payload = t as *mut _ as *mut c_void;
+            }
+            None => {
+                self.raw.progress_cb = None;
+                self.raw.progress_payload = ptr::null_mut();
+            }
+        }
+        &self.raw
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_stash_apply_options_new() -> *mut StashApplyOptions {
+    let mut opts = Box::new(StashApplyOptions {
+        raw: std::mem::zeroed(),
+        progress: None,
+    });
+    if raw::git_stash_apply_options_init(&mut opts.raw, raw::GIT_STASH_APPLY_OPTIONS_VERSION) < 0 {
+        return ptr::null_mut();
+    }
+    Box::into_raw(opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_stash_apply_options_free(opts: *mut StashApplyOptions) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// Also restore the stashed index, not just the working tree changes.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_stash_apply_options_set_reinstate_index(
+    opts: *mut StashApplyOptions,
+    enabled: c_int,
+) {
+    if enabled != 0 {
+        (*opts).raw.flags |= raw::GIT_STASH_APPLY_REINSTATE_INDEX;
+    } else {
+        (*opts).raw.flags &= !raw::GIT_STASH_APPLY_REINSTATE_INDEX;
+    }
+}
+
+/// Set or clear (NULL callback) the apply progress callback.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_stash_apply_options_set_progress_cb(
+    opts: *mut StashApplyOptions,
+    callback: Option<git2_shim_stash_apply_progress_cb>,
+    payload: *mut c_void,
+) {
+    (*opts).progress = callback.map(|callback| Trampoline { callback, payload });
+}
+
+/// Apply a stash; `opts` may be NULL for defaults.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_stash_apply(
+    repo: *mut raw::git_repository,
+    index: size_t,
+    opts: *mut StashApplyOptions,
+) -> c_int {
+    let raw_opts = opts.as_mut().map_or(ptr::null(), |o| o.as_raw());
+    raw::git_stash_apply(repo, index, raw_opts)
+}
+
+/// Apply and drop a stash; `opts` may be NULL for defaults.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_stash_pop(
+    repo: *mut raw::git_repository,
+    index: size_t,
+    opts: *mut StashApplyOptions,
+) -> c_int {
+    let raw_opts = opts.as_mut().map_or(ptr::null(), |o| o.as_raw());
+    raw::git_stash_pop(repo, index, raw_opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_stash_drop(
+    repo: *mut raw::git_repository,
+    index: size_t,
+) -> c_int {
+    raw::git_stash_drop(repo, index)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_stash_foreach(
+    repo: *mut raw::git_repository,
+    callback: git2_shim_stash_cb,
+    payload: *mut c_void,
+) -> c_int {
+    let mut t = Trampoline { callback, payload };
+    raw::git_stash_foreach(repo, stash_trampoline, &mut t as *mut _ as *mut c_void)
+}
+
+// =============================================================================
+// Checkout options
+// =============================================================================
+
+/// Progress callback for checkouts: current path and step counters.
+pub type git2_shim_checkout_progress_cb = extern "C" fn(
+    path: *const c_char,
+    completed_steps: size_t,
+    total_steps: size_t,
+    payload: *mut c_void,
+);
+
+extern "C" fn checkout_progress_trampoline(
+    path: *const c_char,
+    completed_steps: size_t,
+    total_steps: size_t,
+    payload: *mut c_void,
+) {
+    let t = unsafe { &*(payload as *const Trampoline<git2_shim_checkout_progress_cb>) };
+    (t.callback)(path, completed_steps, total_steps, t.payload)
+}
+
+/// Checkout options shared by merge, cherry-pick, revert, clone, and friends.
+#[derive(Clone)]
+pub struct CheckoutOptions {
+    raw: raw::git_checkout_options,
+    paths: StrArray,
+    target_directory: Option<CString>,
+    ancestor_label: Option<CString>,
+    our_label: Option<CString>,
+    their_label: Option<CString>,
+    progress: Option<Trampoline<git2_shim_checkout_progress_cb>>,
+}
+
+impl CheckoutOptions {
+    /// Snapshot the options for a libgit2 call; `self` must outlive the call.
+    fn raw_opts(&mut self) -> raw::git_checkout_options {
+        let mut opts = self.raw;
+        opts.paths = self.paths.as_raw();
+        opts.target_directory = cstring_ptr(&self.target_directory);
+        opts.ancestor_label = cstring_ptr(&self.ancestor_label);
+        opts.our_label = cstring_ptr(&self.our_label);
+        opts.their_label = cstring_ptr(&self.their_label);
+        if let Some(t) = self.progress.as_mut() {
+            opts.progress_cb = Some(checkout_progress_trampoline);
+            opts.progress_payload = t as *mut _ as *mut c_void;
+        }
+        opts
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_checkout_options_new() -> *mut CheckoutOptions {
+    let mut opts = Box::new(CheckoutOptions {
+        raw: std::mem::zeroed(),
+        paths: StrArray::default(),
+        target_directory: None,
+        ancestor_label: None,
+        our_label: None,
+        their_label: None,
+        progress: None,
+    });
+    if raw::git_checkout_options_init(&mut opts.raw, raw::GIT_CHECKOUT_OPTIONS_VERSION) < 0 {
+        return ptr::null_mut();
+    }
+    Box::into_raw(opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_checkout_options_free(opts: *mut CheckoutOptions) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// Set `GIT_CHECKOUT_*` strategy flags (default: `GIT_CHECKOUT_SAFE`).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_checkout_options_set_strategy(
+    opts: *mut CheckoutOptions,
+    strategy: c_uint,
+) {
+    (*opts).raw.checkout_strategy = strategy;
+}
+
+/// Restrict the checkout to the given pathspecs.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_checkout_options_set_paths(
+    opts: *mut CheckoutOptions,
+    paths: *const *const c_char,
+    count: size_t,
+) {
+    (*opts).paths = StrArray::from_raw(paths, count);
+}
+
+/// Check out into `path` instead of the repository workdir.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_checkout_options_set_target_directory(
+    opts: *mut CheckoutOptions,
+    path: *const c_char,
+) {
+    (*opts).target_directory = owned_cstring(path);
+}
+
+/// Set the conflict marker labels; NULL keeps libgit2's default for that side.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_checkout_options_set_conflict_labels(
+    opts: *mut CheckoutOptions,
+    ancestor: *const c_char,
+    ours: *const c_char,
+    theirs: *const c_char,
+) {
+    let opts = &mut *opts;
+    opts.ancestor_label = owned_cstring(ancestor);
+    opts.our_label = owned_cstring(ours);
+    opts.their_label = owned_cstring(theirs);
+}
+
+/// Set or clear (NULL callback) the checkout progress callback.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_checkout_options_set_progress_cb(
+    opts: *mut CheckoutOptions,
+    callback: Option<git2_shim_checkout_progress_cb>,
+    payload: *mut c_void,
+) {
+    (*opts).progress = callback.map(|callback| Trampoline { callback, payload });
+}
+
+// =============================================================================
+// Annotated commits
+// =============================================================================
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_annotated_commit_from_ref(
+    out: *mut *mut raw::git_annotated_commit,
+    repo: *mut raw::git_repository,
+    ref_: *const raw::git_reference,
+) -> c_int {
+    raw::git_annotated_commit_from_ref(out, repo, ref_)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_annotated_commit_lookup(
+    out: *mut *mut raw::git_annotated_commit,
+    repo: *mut raw::git_repository,
+    id: *const raw::git_oid,
+) -> c_int {
+    raw::git_annotated_commit_lookup(out, repo, id)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_annotated_commit_from_revspec(
+    out: *mut *mut raw::git_annotated_commit,
+    repo: *mut raw::git_repository,
+    revspec: *const c_char,
+) -> c_int {
+    raw::git_annotated_commit_from_revspec(out, repo, revspec)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_annotated_commit_id(
+    commit: *const raw::git_annotated_commit,
+) -> *const raw::git_oid {
+    raw::git_annotated_commit_id(commit)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_annotated_commit_ref(
+    commit: *const raw::git_annotated_commit,
+) -> *const c_char {
+    raw::git_annotated_commit_ref(commit)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_annotated_commit_free(commit: *mut raw::git_annotated_commit) {
+    raw::git_annotated_commit_free(commit)
+}
+
+// =============================================================================
+// Merge
+// =============================================================================
+
+/// Merge options: flags, file favor, and rename detection.
+#[derive(Clone)]
+pub struct MergeOptions {
+    raw: raw::git_merge_options,
+    default_driver: Option<CString>,
+}
+
+impl MergeOptions {
+    fn raw_opts(&self) -> raw::git_merge_options {
+        let mut opts = self.raw;
+        opts.default_driver = cstring_ptr(&self.default_driver);
+        opts
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_options_new() -> *mut MergeOptions {
+    let mut opts = Box::new(MergeOptions {
+        raw: std::mem::zeroed(),
+        default_driver: None,
+    });
+    if raw::git_merge_options_init(&mut opts.raw, raw::GIT_MERGE_OPTIONS_VERSION) < 0 {
+        return ptr::null_mut();
+    }
+    Box::into_raw(opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_options_free(opts: *mut MergeOptions) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// Set `GIT_MERGE_*` flags (default: `GIT_MERGE_FIND_RENAMES`).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_options_set_flags(opts: *mut MergeOptions, flags: u32) {
+    (*opts).raw.flags = flags;
+}
+
+/// Set the `GIT_MERGE_FILE_FAVOR_*` strategy for conflicting hunks.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_options_set_file_favor(
+    opts: *mut MergeOptions,
+    favor: c_uint,
+) {
+    (*opts).raw.file_favor = favor;
+}
+
+/// Set `GIT_MERGE_FILE_*` flags (conflict style, whitespace handling, ...).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_options_set_file_flags(
+    opts: *mut MergeOptions,
+    flags: u32,
+) {
+    (*opts).raw.file_flags = flags;
+}
+
+/// Set the similarity percentage (0-100) for rename detection.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_options_set_rename_threshold(
+    opts: *mut MergeOptions,
+    threshold: c_uint,
+) {
+    (*opts).raw.rename_threshold = threshold;
+}
+
+/// Cap the number of files inspected for rename detection.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_options_set_target_limit(
+    opts: *mut MergeOptions,
+    limit: c_uint,
+) {
+    (*opts).raw.target_limit = limit;
+}
+
+/// Cap how many virtual merge bases are built for criss-cross merges.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_options_set_recursion_limit(
+    opts: *mut MergeOptions,
+    limit: c_uint,
+) {
+    (*opts).raw.recursion_limit = limit;
+}
+
+/// Set the merge driver used when no gitattributes driver applies.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_options_set_default_driver(
+    opts: *mut MergeOptions,
+    driver: *const c_char,
+) {
+    (*opts).default_driver = owned_cstring(driver);
+}
+
+/// Merge `their_heads` into HEAD, updating index and workdir.
+///
+/// Conflicts are left in the index and working tree, and MERGE_HEAD /
+/// MERGE_MSG are written; committing the result is up to the caller.
+/// Either options pointer may be NULL for defaults.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge(
+    repo: *mut raw::git_repository,
+    their_heads: *const *const raw::git_annotated_commit,
+    their_heads_len: size_t,
+    merge_opts: *const MergeOptions,
+    checkout_opts: *mut CheckoutOptions,
+) -> c_int {
+    let merge_raw = merge_opts.as_ref().map(MergeOptions::raw_opts);
+    let checkout_raw = checkout_opts.as_mut().map(CheckoutOptions::raw_opts);
+    raw::git_merge(
+        repo,
+        their_heads as *mut *const raw::git_annotated_commit,
+        their_heads_len,
+        merge_raw.as_ref().map_or(ptr::null(), |o| o as *const _),
+        checkout_raw.as_ref().map_or(ptr::null(), |o| o as *const _),
+    )
+}
+
+/// Classify merging `their_heads` into HEAD.
+///
+/// `analysis_out` receives `GIT_MERGE_ANALYSIS_*` bits (normal, up-to-date,
+/// fast-forward, unborn) and `preference_out` the `GIT_MERGE_PREFERENCE_*`
+/// bits from the `merge.ff` configuration.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_analysis(
+    analysis_out: *mut c_uint,
+    preference_out: *mut c_uint,
+    repo: *mut raw::git_repository,
+    their_heads: *const *const raw::git_annotated_commit,
+    their_heads_len: size_t,
+) -> c_int {
+    raw::git_merge_analysis(
+        analysis_out,
+        preference_out,
+        repo,
+        their_heads as *mut *const raw::git_annotated_commit,
+        their_heads_len,
+    )
+}
+
+/// Like `git2_shim_merge_analysis`, but against `our_ref` instead of HEAD.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_analysis_for_ref(
+    analysis_out: *mut c_uint,
+    preference_out: *mut c_uint,
+    repo: *mut raw::git_repository,
+    our_ref: *mut raw::git_reference,
+    their_heads: *const *const raw::git_annotated_commit,
+    their_heads_len: size_t,
+) -> c_int {
+    raw::git_merge_analysis_for_ref(
+        analysis_out,
+        preference_out,
+        repo,
+        our_ref,
+        their_heads as *mut *const raw::git_annotated_commit,
+        their_heads_len,
+    )
+}
+
+/// Merge two commits into a new in-memory index without touching HEAD,
+/// the repository index, or any working directory (works on bare repos).
+/// `opts` may be NULL; free the result with `git2_shim_index_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_commits(
+    out: *mut *mut raw::git_index,
+    repo: *mut raw::git_repository,
+    our_commit: *const raw::git_commit,
+    their_commit: *const raw::git_commit,
+    opts: *const MergeOptions,
+) -> c_int {
+    let merge_raw = opts.as_ref().map(MergeOptions::raw_opts);
+    raw::git_merge_commits(
+        out,
+        repo,
+        our_commit,
+        their_commit,
+        merge_raw.as_ref().map_or(ptr::null(), |o| o as *const _),
+    )
+}
+
+/// Three-way merge of trees into a new in-memory index. `ancestor_tree`
+/// may be NULL for a merge without a common base; `opts` may be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_trees(
+    out: *mut *mut raw::git_index,
+    repo: *mut raw::git_repository,
+    ancestor_tree: *const raw::git_tree,
+    our_tree: *const raw::git_tree,
+    their_tree: *const raw::git_tree,
+    opts: *const MergeOptions,
+) -> c_int {
+    let merge_raw = opts.as_ref().map(MergeOptions::raw_opts);
+    raw::git_merge_trees(
+        out,
+        repo,
+        ancestor_tree,
+        our_tree,
+        their_tree,
+        merge_raw.as_ref().map_or(ptr::null(), |o| o as *const _),
+    )
+}
+
+// =============================================================================
+// Commits and trees
+// =============================================================================
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_commit_lookup(
+    out: *mut *mut raw::git_commit,
+    repo: *mut raw::git_repository,
+    id: *const raw::git_oid,
+) -> c_int {
+    raw::git_commit_lookup(out, repo, id)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_commit_free(commit: *mut raw::git_commit) {
+    raw::git_commit_free(commit)
+}
+
+/// Create a commit. `update_ref` (e.g. "HEAD" or "refs/heads/main") may
+/// be NULL to only write the object.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_commit_create(
+    id: *mut raw::git_oid,
+    repo: *mut raw::git_repository,
+    update_ref: *const c_char,
+    author: *const raw::git_signature,
+    committer: *const raw::git_signature,
+    message: *const c_char,
+    tree: *const raw::git_tree,
+    parent_count: size_t,
+    parents: *const *const raw::git_commit,
+) -> c_int {
+    raw::git_commit_create(
+        id,
+        repo,
+        update_ref,
+        author,
+        committer,
+        ptr::null(),
+        message,
+        tree,
+        parent_count,
+        parents,
+    )
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_commit_tree(
+    tree_out: *mut *mut raw::git_tree,
+    commit: *const raw::git_commit,
+) -> c_int {
+    raw::git_commit_tree(tree_out, commit)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_commit_tree_id(
+    commit: *const raw::git_commit,
+) -> *const raw::git_oid {
+    raw::git_commit_tree_id(commit)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_tree_lookup(
+    out: *mut *mut raw::git_tree,
+    repo: *mut raw::git_repository,
+    id: *const raw::git_oid,
+) -> c_int {
+    raw::git_tree_lookup(out, repo, id)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_tree_free(tree: *mut raw::git_tree) {
+    raw::git_tree_free(tree)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blob_lookup(
+    out: *mut *mut raw::git_blob,
+    repo: *mut raw::git_repository,
+    id: *const raw::git_oid,
+) -> c_int {
+    raw::git_blob_lookup(out, repo, id)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blob_free(blob: *mut raw::git_blob) {
+    raw::git_blob_free(blob)
+}
+
+// =============================================================================
+// Index
+// =============================================================================
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_index_free(index: *mut raw::git_index) {
+    raw::git_index_free(index)
+}
+
+/// The repository's own index (`.git/index`, or the worktree's), shared
+/// with every other operation on `repo`; free with `git2_shim_index_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_index(
+    out: *mut *mut raw::git_index,
+    repo: *mut raw::git_repository,
+) -> c_int {
+    raw::git_repository_index(out, repo)
+}
+
+/// Reload the index from disk if another process changed it, or
+/// unconditionally (discarding unsaved changes) when `force` is non-zero.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_index_read(index: *mut raw::git_index, force: c_int) -> c_int {
+    raw::git_index_read(index, force)
+}
+
+/// Write the in-memory index back to its file atomically.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_index_write(index: *mut raw::git_index) -> c_int {
+    raw::git_index_write(index)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_index_entrycount(index: *const raw::git_index) -> size_t {
+    raw::git_index_entrycount(index)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_index_has_conflicts(index: *const raw::git_index) -> c_int {
+    raw::git_index_has_conflicts(index)
+}
+
+/// Write the index as a tree into `repo`'s object database; fails if the
+/// index still has conflicts.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_index_write_tree_to(
+    out: *mut raw::git_oid,
+    index: *mut raw::git_index,
+    repo: *mut raw::git_repository,
+) -> c_int {
+    raw::git_index_write_tree_to(out, index, repo)
+}
+
+// =============================================================================
+// Merge bases
+// =============================================================================
+
+/// Find the best common ancestor of two commits. Returns `GIT_ENOTFOUND`
+/// (-3) when the histories are unrelated.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_base(
+    out: *mut raw::git_oid,
+    repo: *mut raw::git_repository,
+    one: *const raw::git_oid,
+    two: *const raw::git_oid,
+) -> c_int {
+    raw::git_merge_base(out, repo, one, two)
+}
+
+/// Find the best common ancestor of `input_array[0]` and a hypothetical
+/// merge of all the other commits, like `git merge-base A B C`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_base_many(
+    out: *mut raw::git_oid,
+    repo: *mut raw::git_repository,
+    length: size_t,
+    input_array: *const raw::git_oid,
+) -> c_int {
+    raw::git_merge_base_many(out, repo, length, input_array)
+}
+
+/// Find a common ancestor of all the commits, like `git merge-base --octopus`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_base_octopus(
+    out: *mut raw::git_oid,
+    repo: *mut raw::git_repository,
+    length: size_t,
+    input_array: *const raw::git_oid,
+) -> c_int {
+    raw::git_merge_base_octopus(out, repo, length, input_array)
+}
+
+// =============================================================================
+// File-content merge
+// =============================================================================
+
+/// One side of a file-content merge. `ptr`/`size` hold the file contents;
+/// `path` and `mode` are optional (NULL / 0) and only used to pick the
+/// result's path and mode.
+#[repr(C)]
+pub struct git2_shim_merge_file_input {
+    pub ptr: *const c_char,
+    pub size: size_t,
+    pub path: *const c_char,
+    pub mode: c_uint,
+}
+
+impl git2_shim_merge_file_input {
+    fn to_raw(&self) -> raw::git_merge_file_input {
+        raw::git_merge_file_input {
+            version: raw::GIT_MERGE_FILE_INPUT_VERSION,
+            ptr: self.ptr,
+            size: self.size,
+            path: self.path,
+            mode: self.mode,
+        }
+    }
+}
+
+/// File-content merge options: conflict labels, favor, style, marker size.
+pub struct MergeFileOptions {
+    raw: raw::git_merge_file_options,
+    ancestor_label: Option<CString>,
+    our_label: Option<CString>,
+    their_label: Option<CString>,
+}
+
+impl MergeFileOptions {
+    fn raw_opts(&self) -> raw::git_merge_file_options {
+        let mut opts = self.raw;
+        opts.ancestor_label = cstring_ptr(&self.ancestor_label);
+        opts.our_label = cstring_ptr(&self.our_label);
+        opts.their_label = cstring_ptr(&self.their_label);
+        opts
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_file_options_new() -> *mut MergeFileOptions {
+    let mut opts = Box::new(MergeFileOptions {
+        raw: std::mem::zeroed(),
+        ancestor_label: None,
+        our_label: None,
+        their_label: None,
+    });
+    if raw::git_merge_file_options_init(&mut opts.raw, raw::GIT_MERGE_FILE_OPTIONS_VERSION) < 0 {
+        return ptr::null_mut();
+    }
+    Box::into_raw(opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_file_options_free(opts: *mut MergeFileOptions) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// Set the conflict marker labels; NULL keeps libgit2's default for that side.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_file_options_set_labels(
+    opts: *mut MergeFileOptions,
+    ancestor: *const c_char,
+    ours: *const c_char,
+    theirs: *const c_char,
+) {
+    let opts = &mut *opts;
+    opts.ancestor_label = owned_cstring(ancestor);
+    opts.our_label = owned_cstring(ours);
+    opts.their_label = owned_cstring(theirs);
+}
+
+/// Set the `GIT_MERGE_FILE_FAVOR_*` strategy for conflicting hunks.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_file_options_set_favor(
+    opts: *mut MergeFileOptions,
+    favor: c_uint,
+) {
+    (*opts).raw.favor = favor;
+}
+
+/// Set `GIT_MERGE_FILE_*` flags, including the conflict marker style
+/// (`STYLE_MERGE`, `STYLE_DIFF3`, `STYLE_ZDIFF3`).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_file_options_set_flags(
+    opts: *mut MergeFileOptions,
+    flags: u32,
+) {
+    (*opts).raw.flags = flags;
+}
+
+/// Set the conflict marker length (default 7).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_file_options_set_marker_size(
+    opts: *mut MergeFileOptions,
+    marker_size: u16,
+) {
+    (*opts).raw.marker_size = marker_size;
+}
+
+/// Result of a file-content merge; free with `git2_shim_merge_file_result_free`.
+pub struct MergeFileResult {
+    raw: raw::git_merge_file_result,
+}
+
+impl Drop for MergeFileResult {
+    fn drop(&mut self) {
+        unsafe { raw::git_merge_file_result_free(&mut self.raw) }
+    }
+}
+
+unsafe fn merge_file_result(
+    out: *mut *mut MergeFileResult,
+    run: impl FnOnce(*mut raw::git_merge_file_result) -> c_int,
+) -> c_int {
+    let mut result = Box::new(MergeFileResult {
+        raw: std::mem::zeroed(),
+    });
+    let rc = run(&mut result.raw);
+    if rc >= 0 {
+        *out = Box::into_raw(result);
+    }
+    rc
+}
+
+/// Three-way merge of in-memory file contents. `ancestor` may be NULL for
+/// a merge without a base; `opts` may be NULL for defaults.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_file(
+    out: *mut *mut MergeFileResult,
+    ancestor: *const git2_shim_merge_file_input,
+    ours: *const git2_shim_merge_file_input,
+    theirs: *const git2_shim_merge_file_input,
+    opts: *const MergeFileOptions,
+) -> c_int {
+    let ancestor_raw = ancestor.as_ref().map(git2_shim_merge_file_input::to_raw);
+    let ours_raw = (*ours).to_raw();
+    let theirs_raw = (*theirs).to_raw();
+    let opts_raw = opts.as_ref().map(MergeFileOptions::raw_opts);
+    merge_file_result(out, |result| {
+        raw::git_merge_file(
+            result,
+            ancestor_raw.as_ref().map_or(ptr::null(), |a| a as *const _),
+            &ours_raw,
+            &theirs_raw,
+            opts_raw.as_ref().map_or(ptr::null(), |o| o as *const _),
+        )
+    })
+}
+
+/// Three-way merge of conflicted index entries, e.g. from
+/// `git2_shim_index_conflict_get`. `ancestor` may be NULL (add/add).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_file_from_index(
+    out: *mut *mut MergeFileResult,
+    repo: *mut raw::git_repository,
+    ancestor: *const raw::git_index_entry,
+    ours: *const raw::git_index_entry,
+    theirs: *const raw::git_index_entry,
+    opts: *const MergeFileOptions,
+) -> c_int {
+    let opts_raw = opts.as_ref().map(MergeFileOptions::raw_opts);
+    merge_file_result(out, |result| {
+        raw::git_merge_file_from_index(
+            result,
+            repo,
+            ancestor,
+            ours,
+            theirs,
+            opts_raw.as_ref().map_or(ptr::null(), |o| o as *const _),
+        )
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_file_result_free(result: *mut MergeFileResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result));
+    }
+}
+
+/// Non-zero when the merge produced no conflicts.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_file_result_automergeable(
+    result: *const MergeFileResult,
+) -> c_int {
+    ((*result).raw.automergeable != 0) as c_int
+}
+
+/// Merged contents (with conflict markers if not automergeable).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_file_result_content(
+    result: *const MergeFileResult,
+    len_out: *mut size_t,
+) -> *const c_char {
+    *len_out = (*result).raw.len;
+    (*result).raw.ptr
+}
+
+/// Path chosen for the result, or NULL if the sides disagree on a rename.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_file_result_path(
+    result: *const MergeFileResult,
+) -> *const c_char {
+    (*result).raw.path
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_merge_file_result_mode(
+    result: *const MergeFileResult,
+) -> c_uint {
+    (*result).raw.mode
+}
+
+/// Look up the conflict entries for `path`; any side may come back NULL.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_index_conflict_get(
+    ancestor_out: *mut *const raw::git_index_entry,
+    our_out: *mut *const raw::git_index_entry,
+    their_out: *mut *const raw::git_index_entry,
+    index: *mut raw::git_index,
+    path: *const c_char,
+) -> c_int {
+    raw::git_index_conflict_get(ancestor_out, our_out, their_out, index, path)
+}
+
+// =============================================================================
+// Cherry-pick
+// =============================================================================
+
+/// Cherry-pick `commit` onto HEAD, updating index and workdir.
+///
+/// `mainline` is the 1-based parent to diff against when `commit` is a
+/// merge (0 for ordinary commits). Like the git CLI with `--no-commit`,
+/// conflicts are left in place and CHERRY_PICK_HEAD is written.
+/// Either options pointer may be NULL for defaults.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_cherrypick(
+    repo: *mut raw::git_repository,
+    commit: *mut raw::git_commit,
+    mainline: c_uint,
+    merge_opts: *const MergeOptions,
+    checkout_opts: *mut CheckoutOptions,
+) -> c_int {
+    let mut opts: raw::git_cherrypick_options = std::mem::zeroed();
+    let rc = raw::git_cherrypick_options_init(&mut opts, raw::GIT_CHERRYPICK_OPTIONS_VERSION);
+    if rc < 0 {
+        return rc;
+    }
+    opts.mainline = mainline;
+    if let Some(merge) = merge_opts.as_ref() {
+        opts.merge_opts = merge.raw_opts();
+    }
+    if let Some(checkout) = checkout_opts.as_mut() {
+        opts.checkout_opts = checkout.raw_opts();
+    }
+    raw::git_cherrypick(repo, commit, &opts)
+}
+
+/// Cherry-pick `cherrypick_commit` against `our_commit` into a new
+/// in-memory index, leaving HEAD, the repository index, and any workdir
+/// untouched. `merge_opts` may be NULL; free the result with
+/// `git2_shim_index_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_cherrypick_commit(
+    out: *mut *mut raw::git_index,
+    repo: *mut raw::git_repository,
+    cherrypick_commit: *mut raw::git_commit,
+    our_commit: *mut raw::git_commit,
+    mainline: c_uint,
+    merge_opts: *const MergeOptions,
+) -> c_int {
+    let merge_raw = merge_opts.as_ref().map(MergeOptions::raw_opts);
+    raw::git_cherrypick_commit(
+        out,
+        repo,
+        cherrypick_commit,
+        our_commit,
+        mainline,
+        merge_raw.as_ref().map_or(ptr::null(), |o| o as *const _),
+    )
+}
+
+// =============================================================================
+// Revert
+// =============================================================================
+
+/// Revert `commit` on HEAD, updating index and workdir and writing
+/// REVERT_HEAD and MERGE_MSG; conflicts are left in place.
+///
+/// `mainline` is the 1-based parent whose side is kept when reverting a
+/// merge commit (0 for ordinary commits). Either options pointer may be
+/// NULL for defaults.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revert(
+    repo: *mut raw::git_repository,
+    commit: *mut raw::git_commit,
+    mainline: c_uint,
+    merge_opts: *const MergeOptions,
+    checkout_opts: *mut CheckoutOptions,
+) -> c_int {
+    let mut opts: raw::git_revert_options = std::mem::zeroed();
+    let rc = raw::git_revert_options_init(&mut opts, raw::GIT_REVERT_OPTIONS_VERSION);
+    if rc < 0 {
+        return rc;
+    }
+    opts.mainline = mainline;
+    if let Some(merge) = merge_opts.as_ref() {
+        opts.merge_opts = merge.raw_opts();
+    }
+    if let Some(checkout) = checkout_opts.as_mut() {
+        opts.checkout_opts = checkout.raw_opts();
+    }
+    raw::git_revert(repo, commit, &opts)
+}
+
+/// Revert `revert_commit` against `our_commit` into a new in-memory index.
+/// `merge_opts` may be NULL; free the result with `git2_shim_index_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revert_commit(
+    out: *mut *mut raw::git_index,
+    repo: *mut raw::git_repository,
+    revert_commit: *mut raw::git_commit,
+    our_commit: *mut raw::git_commit,
+    mainline: c_uint,
+    merge_opts: *const MergeOptions,
+) -> c_int {
+    let merge_raw = merge_opts.as_ref().map(MergeOptions::raw_opts);
+    raw::git_revert_commit(
+        out,
+        repo,
+        revert_commit,
+        our_commit,
+        mainline,
+        merge_raw.as_ref().map_or(ptr::null(), |o| o as *const _),
+    )
+}
+
+// =============================================================================
+// Rebase
+// =============================================================================
+
+/// Rebase options. libgit2 keeps referring to these for the whole rebase,
+/// so the handle must outlive the `git_rebase` it was passed to.
+pub struct RebaseOptions {
+    raw: raw::git_rebase_options,
+    rewrite_notes_ref: Option<CString>,
+    merge: Option<MergeOptions>,
+    checkout: Option<CheckoutOptions>,
+    signing: Option<Trampoline<git2_shim_commit_signing_cb>>,
+    repo: *mut raw::git_repository,
+}
+
+extern "C" fn rebase_commit_create_trampoline(
+    out: *mut raw::git_oid,
+    author: *const raw::git_signature,
+    committer: *const raw::git_signature,
+    message_encoding: *const c_char,
+    message: *const c_char,
+    tree: *const raw::git_tree,
+    parent_count: size_t,
+    parents: *const *const raw::git_commit,
+    payload: *mut c_void,
+) -> c_int {
+    let opts = unsafe { &*(payload as *const RebaseOptions) };
+    match opts.signing.as_ref() {
+        Some(signing) => unsafe {
+            create_signed_commit(
+                out,
+                opts.repo,
+                author,
+                committer,
+                message_encoding,
+                message,
+                tree,
+                parent_count,
+                parents,
+                signing,
+            )
+        },
+        None => raw::GIT_PASSTHROUGH,
+    }
+}
+
+impl RebaseOptions {
+    fn raw_opts(&mut self, repo: *mut raw::git_repository) -> *const raw::git_rebase_options {
+        self.repo = repo;
+        if self.signing.is_some() {
+            self.raw.commit_create_cb = Some(rebase_commit_create_trampoline);
+            self.raw.payload = self as *mut _ as *mut c_void;
+        } else {
+            self.raw.commit_create_cb = None;
+            self.raw.payload = ptr::null_mut();
+        }
+        self.raw.rewrite_notes_ref = cstring_ptr(&self.rewrite_notes_ref);
+        if let Some(merge) = self.merge.as_ref() {
+            self.raw.merge_options = merge.raw_opts();
+        }
+        if let Some(checkout) = self.checkout.as_mut() {
+            self.raw.checkout_options = checkout.raw_opts();
+        }
+        &self.raw
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_options_new() -> *mut RebaseOptions {
+    let mut opts = Box::new(RebaseOptions {
+        raw: std::mem::zeroed(),
+        rewrite_notes_ref: None,
+        merge: None,
+        checkout: None,
+        signing: None,
+        repo: ptr::null_mut(),
+    });
+    if raw::git_rebase_options_init(&mut opts.raw, raw::GIT_REBASE_OPTIONS_VERSION) < 0 {
+        return ptr::null_mut();
+    }
+    Box::into_raw(opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_options_free(opts: *mut RebaseOptions) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// Suppress the informational output some git implementations print.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_options_set_quiet(
+    opts: *mut RebaseOptions,
+    quiet: c_int,
+) {
+    (*opts).raw.quiet = quiet;
+}
+
+/// Rebase without touching the working directory or the repository index.
+///
+/// Each `git2_shim_rebase_next` leaves its result in an in-memory index
+/// (see `git2_shim_rebase_inmemory_index`), `git2_shim_rebase_commit` writes
+/// commits without moving any ref, and finishing is a no-op: the caller
+/// points a ref at the last commit. Works on bare repositories.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_options_set_inmemory(
+    opts: *mut RebaseOptions,
+    inmemory: c_int,
+) {
+    (*opts).raw.inmemory = inmemory;
+}
+
+/// Notes ref to rewrite on finish; NULL uses `notes.rewriteRef` from config.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_options_set_rewrite_notes_ref(
+    opts: *mut RebaseOptions,
+    notes_ref: *const c_char,
+) {
+    (*opts).rewrite_notes_ref = owned_cstring(notes_ref);
+}
+
+/// Copy `merge` into the rebase options; NULL restores the defaults.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_options_set_merge_options(
+    opts: *mut RebaseOptions,
+    merge: *const MergeOptions,
+) {
+    (*opts).merge = merge.as_ref().cloned();
+}
+
+/// Copy `checkout` into the rebase options; NULL restores the defaults.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_options_set_checkout_options(
+    opts: *mut RebaseOptions,
+    checkout: *const CheckoutOptions,
+) {
+    (*opts).checkout = checkout.as_ref().cloned();
+}
+
+/// Sign every commit the rebase writes; NULL callback clears it.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_options_set_signing_cb(
+    opts: *mut RebaseOptions,
+    callback: Option<git2_shim_commit_signing_cb>,
+    payload: *mut c_void,
+) {
+    (*opts).signing = callback.map(|callback| Trampoline { callback, payload });
+}
+
+unsafe fn rebase_raw_opts(
+    opts: *mut RebaseOptions,
+    repo: *mut raw::git_repository,
+) -> *const raw::git_rebase_options {
+    opts.as_mut().map_or(ptr::null(), |o| o.raw_opts(repo))
+}
+
+/// Start rebasing `branch` (NULL: HEAD) onto `onto` (NULL: `upstream`).
+/// `opts` may be NULL; otherwise it must outlive the returned rebase.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_init(
+    out: *mut *mut raw::git_rebase,
+    repo: *mut raw::git_repository,
+    branch: *const raw::git_annotated_commit,
+    upstream: *const raw::git_annotated_commit,
+    onto: *const raw::git_annotated_commit,
+    opts: *mut RebaseOptions,
+) -> c_int {
+    raw::git_rebase_init(
+        out,
+        repo,
+        branch,
+        upstream,
+        onto,
+        rebase_raw_opts(opts, repo),
+    )
+}
+
+/// Reopen a rebase in progress (e.g. one stopped for conflicts).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_open(
+    out: *mut *mut raw::git_rebase,
+    repo: *mut raw::git_repository,
+    opts: *mut RebaseOptions,
+) -> c_int {
+    raw::git_rebase_open(out, repo, rebase_raw_opts(opts, repo))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_operation_entrycount(
+    rebase: *mut raw::git_rebase,
+) -> size_t {
+    raw::git_rebase_operation_entrycount(rebase)
+}
+
+/// Index of the operation being applied, or `SIZE_MAX` before the first
+/// `git2_shim_rebase_next`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_operation_current(
+    rebase: *mut raw::git_rebase,
+) -> size_t {
+    raw::git_rebase_operation_current(rebase)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_operation_byindex(
+    rebase: *mut raw::git_rebase,
+    idx: size_t,
+) -> *mut raw::git_rebase_operation {
+    raw::git_rebase_operation_byindex(rebase, idx)
+}
+
+/// Apply the next operation, leaving its changes in the index (and workdir,
+/// unless in-memory). Returns `GIT_ITEROVER` (-31) when all are done.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_next(
+    operation: *mut *mut raw::git_rebase_operation,
+    rebase: *mut raw::git_rebase,
+) -> c_int {
+    raw::git_rebase_next(operation, rebase)
+}
+
+/// Commit the current operation. NULL `author` keeps the original author;
+/// NULL `message` keeps the original message.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_commit(
+    id: *mut raw::git_oid,
+    rebase: *mut raw::git_rebase,
+    author: *const raw::git_signature,
+    committer: *const raw::git_signature,
+    message: *const c_char,
+) -> c_int {
+    raw::git_rebase_commit(id, rebase, author, committer, ptr::null(), message)
+}
+
+/// Index produced by the current operation of an in-memory rebase, e.g. to
+/// inspect or resolve conflicts before committing. Free with
+/// `git2_shim_index_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_inmemory_index(
+    index: *mut *mut raw::git_index,
+    rebase: *mut raw::git_rebase,
+) -> c_int {
+    raw::git_rebase_inmemory_index(index, rebase)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_abort(rebase: *mut raw::git_rebase) -> c_int {
+    raw::git_rebase_abort(rebase)
+}
+
+/// Finish the rebase, moving the branch to the rewritten commits.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_finish(
+    rebase: *mut raw::git_rebase,
+    signature: *const raw::git_signature,
+) -> c_int {
+    raw::git_rebase_finish(rebase, signature)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_free(rebase: *mut raw::git_rebase) {
+    raw::git_rebase_free(rebase)
+}
+
+/// `GIT_REBASE_OPERATION_*` kind (pick, reword, edit, squash, fixup, exec).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_operation_type(
+    operation: *const raw::git_rebase_operation,
+) -> c_uint {
+    (*operation).type_
+}
+
+/// Commit being applied; zeroed for exec operations.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_operation_id(
+    operation: *const raw::git_rebase_operation,
+) -> *const raw::git_oid {
+    &(*operation).id
+}
+
+/// Command to run for exec operations, otherwise NULL.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_operation_exec(
+    operation: *const raw::git_rebase_operation,
+) -> *const c_char {
+    (*operation).exec
+}
+
+/// Full name of the branch being rebased (e.g. `refs/heads/feature`), or
+/// NULL if the rebase started from a detached HEAD. Owned by `rebase`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_orig_head_name(
+    rebase: *mut raw::git_rebase,
+) -> *const c_char {
+    raw::git_rebase_orig_head_name(rebase)
+}
+
+/// Commit the rebased branch pointed at before the rebase started.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_orig_head_id(
+    rebase: *mut raw::git_rebase,
+) -> *const raw::git_oid {
+    raw::git_rebase_orig_head_id(rebase)
+}
+
+/// What the branch is being rebased onto: the reference name it was given
+/// as (e.g. `refs/heads/main`), otherwise the commit id in hex. Owned by
+/// `rebase`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_onto_name(rebase: *mut raw::git_rebase) -> *const c_char {
+    raw::git_rebase_onto_name(rebase)
+}
+
+/// Commit the rewritten operations are applied on top of.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_rebase_onto_id(
+    rebase: *mut raw::git_rebase,
+) -> *const raw::git_oid {
+    raw::git_rebase_onto_id(rebase)
+}
+
+// =============================================================================
+// Commit signing
+// =============================================================================
+
+/// Where a signing callback hands back its signature.
+#[derive(Default)]
+pub struct SignatureSink {
+    signature: Option<CString>,
+    field: Option<CString>,
+}
+
+/// Signing callback: sign the `content_len` bytes of `commit_content` and
+/// pass the signature to `git2_shim_signature_sink_set`. Leaving the sink
+/// empty creates the commit unsigned; a non-zero return aborts the commit.
+pub type git2_shim_commit_signing_cb = extern "C" fn(
+    sink: *mut SignatureSink,
+    commit_content: *const c_char,
+    content_len: size_t,
+    payload: *mut c_void,
+) -> c_int;
+
+/// Store the signature for the commit being created. `field` names the
+/// header it goes in; NULL means "gpgsig" (used for both GPG and SSH).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_signature_sink_set(
+    sink: *mut SignatureSink,
+    signature: *const c_char,
+    field: *const c_char,
+) {
+    (*sink).signature = owned_cstring(signature);
+    (*sink).field = owned_cstring(field);
+}
+
+/// Build the commit buffer, let the Zig side sign it, and write the result.
+#[allow(clippy::too_many_arguments)]
+unsafe fn create_signed_commit(
+    out: *mut raw::git_oid,
+    repo: *mut raw::git_repository,
+    author: *const raw::git_signature,
+    committer: *const raw::git_signature,
+    message_encoding: *const c_char,
+    message: *const c_char,
+    tree: *const raw::git_tree,
+    parent_count: size_t,
+    parents: *const *const raw::git_commit,
+    signing: &Trampoline<git2_shim_commit_signing_cb>,
+) -> c_int {
+    let mut buf: raw::git_buf = std::mem::zeroed();
+    let mut rc = raw::git_commit_create_buffer(
+        &mut buf,
+        repo,
+        author,
+        committer,
+        message_encoding,
+        message,
+        tree,
+        parent_count,
+        parents,
+    );
+    if rc < 0 {
+        return rc;
+    }
+
+    let mut sink = SignatureSink::default();
+    rc = (signing.callback)(&mut sink, buf.ptr, buf.size, signing.payload);
+    if rc == 0 {
+        rc = raw::git_commit_create_with_signature(
+            out,
+            repo,
+            buf.ptr,
+            cstring_ptr(&sink.signature),
+            cstring_ptr(&sink.field),
+        );
+    }
+    raw::git_buf_dispose(&mut buf);
+    rc
+}
+
+/// Point `update_ref` at the new commit `id`, following it first if it is
+/// symbolic (HEAD), as `git_commit_create` would: the ref must still be at
+/// the first parent (or unborn, for a root commit), else `GIT_EMODIFIED`,
+/// and the move is logged as `commit: <summary>`.
+unsafe fn update_commit_ref(
+    repo: *mut raw::git_repository,
+    update_ref: *const c_char,
+    id: *const raw::git_oid,
+    parent_count: size_t,
+    parents: *const *const raw::git_commit,
+) -> c_int {
+    let mut target = CStr::from_ptr(update_ref).to_owned();
+    let mut existing: *mut raw::git_reference = ptr::null_mut();
+    if raw::git_reference_lookup(&mut existing, repo, update_ref) == 0 {
+        if raw::git_reference_type(existing) == raw::GIT_REFERENCE_SYMBOLIC {
+            target = CStr::from_ptr(raw::git_reference_symbolic_target(existing)).to_owned();
+        }
+        raw::git_reference_free(existing);
+    }
+
+    let mut commit = ptr::null_mut();
+    let rc = raw::git_commit_lookup(&mut commit, repo, id);
+    if rc < 0 {
+        return rc;
+    }
+    let kind = match parent_count {
+        0 => " (initial)",
+        1 => "",
+        _ => " (merge)",
+    };
+    let summary = raw::git_commit_summary(commit);
+    let summary = if summary.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(summary).to_string_lossy().into_owned()
+    };
+    raw::git_commit_free(commit);
+    let message = CString::new(format!("commit{kind}: {summary}")).unwrap_or_default();
+
+    // A zero id only matches a ref that does not exist yet.
+    let current = if parent_count == 0 {
+        raw::git_oid {
+            id: [0; raw::GIT_OID_RAWSZ],
+        }
+    } else {
+        raw::git_oid {
+            id: (*raw::git_commit_id(*parents)).id,
+        }
+    };
+    let mut updated: *mut raw::git_reference = ptr::null_mut();
+    let rc = raw::git_reference_create_matching(
+        &mut updated,
+        repo,
+        target.as_ptr(),
+        id,
+        1,
+        &current,
+        message.as_ptr(),
+    );
+    if rc == 0 {
+        raw::git_reference_free(updated);
+    }
+    rc
+}
+
+/// Like `git2_shim_commit_create`, but `sign_cb` is given the commit buffer
+/// to sign (GPG, SSH, ...) before the commit object is written.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_commit_create_signed(
+    id: *mut raw::git_oid,
+    repo: *mut raw::git_repository,
+    update_ref: *const c_char,
+    author: *const raw::git_signature,
+    committer: *const raw::git_signature,
+    message: *const c_char,
+    tree: *const raw::git_tree,
+    parent_count: size_t,
+    parents: *const *const raw::git_commit,
+    sign_cb: git2_shim_commit_signing_cb,
+    payload: *mut c_void,
+) -> c_int {
+    let signing = Trampoline {
+        callback: sign_cb,
+        payload,
+    };
+    let rc = create_signed_commit(
+        id,
+        repo,
+        author,
+        committer,
+        ptr::null(),
+        message,
+        tree,
+        parent_count,
+        parents,
+        &signing,
+    );
+    if rc < 0 || update_ref.is_null() {
+        return rc;
+    }
+    update_commit_ref(repo, update_ref, id, parent_count, parents)
+}
+
+// =============================================================================
+// Blame
+// =============================================================================
+
+/// Blame options: line range, commit range, and copy tracking.
+pub struct BlameOptions {
+    raw: raw::git_blame_options,
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_options_new() -> *mut BlameOptions {
+    let mut opts = Box::new(BlameOptions {
+        raw: std::mem::zeroed(),
+    });
+    if raw::git_blame_options_init(&mut opts.raw, raw::GIT_BLAME_OPTIONS_VERSION) < 0 {
+        return ptr::null_mut();
+    }
+    Box::into_raw(opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_options_free(opts: *mut BlameOptions) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// Set `GIT_BLAME_*` flags: the `TRACK_COPIES_*` levels, `FIRST_PARENT`,
+/// `USE_MAILMAP`, and `IGNORE_WHITESPACE`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_options_set_flags(opts: *mut BlameOptions, flags: u32) {
+    (*opts).raw.flags = flags;
+}
+
+/// Minimum characters a moved or copied line must match to be attributed
+/// to its source (default 20).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_options_set_min_match_characters(
+    opts: *mut BlameOptions,
+    count: u16,
+) {
+    (*opts).raw.min_match_characters = count;
+}
+
+/// Restrict blame to the 1-based, inclusive line range; 0 leaves that end
+/// unbounded. Limiting to the visible viewport keeps large files fast.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_options_set_line_range(
+    opts: *mut BlameOptions,
+    min_line: size_t,
+    max_line: size_t,
+) {
+    (*opts).raw.min_line = min_line;
+    (*opts).raw.max_line = max_line;
+}
+
+/// Blame as of `id` instead of HEAD; NULL restores HEAD.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_options_set_newest_commit(
+    opts: *mut BlameOptions,
+    id: *const raw::git_oid,
+) {
+    (*opts).raw.newest_commit =
+        id.as_ref()
+            .map_or(raw::git_oid { id: [0; 20] }, |id| raw::git_oid {
+                id: id.id,
+            });
+}
+
+/// Stop at `id`, marking hunks that reach it as boundaries; NULL searches
+/// the whole history.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_options_set_oldest_commit(
+    opts: *mut BlameOptions,
+    id: *const raw::git_oid,
+) {
+    (*opts).raw.oldest_commit =
+        id.as_ref()
+            .map_or(raw::git_oid { id: [0; 20] }, |id| raw::git_oid {
+                id: id.id,
+            });
+}
+
+/// Blame `path` (relative to the workdir); `opts` may be NULL to blame the
+/// whole file as of HEAD.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_file(
+    out: *mut *mut raw::git_blame,
+    repo: *mut raw::git_repository,
+    path: *const c_char,
+    opts: *mut BlameOptions,
+) -> c_int {
+    let raw_opts = opts
+        .as_mut()
+        .map_or(ptr::null_mut(), |o| &mut o.raw as *mut _);
+    raw::git_blame_file(out, repo, path, raw_opts)
+}
+
+/// Blame in-memory `buffer` contents (e.g. an unsaved editor buffer) on top
+/// of `reference`, a blame of the same file. Changed lines are attributed
+/// to no commit; the result must be freed separately from `reference`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_buffer(
+    out: *mut *mut raw::git_blame,
+    reference: *mut raw::git_blame,
+    buffer: *const c_char,
+    buffer_len: size_t,
+) -> c_int {
+    raw::git_blame_buffer(out, reference, buffer, buffer_len)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_free(blame: *mut raw::git_blame) {
+    raw::git_blame_free(blame)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_get_hunk_count(blame: *mut raw::git_blame) -> u32 {
+    raw::git_blame_get_hunk_count(blame)
+}
+
+/// Hunk at `index`, or NULL if out of range. Owned by the blame.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_get_hunk_byindex(
+    blame: *mut raw::git_blame,
+    index: u32,
+) -> *const raw::git_blame_hunk {
+    raw::git_blame_get_hunk_byindex(blame, index)
+}
+
+/// Hunk containing the 1-based line `lineno`, or NULL if out of range.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_get_hunk_byline(
+    blame: *mut raw::git_blame,
+    lineno: size_t,
+) -> *const raw::git_blame_hunk {
+    raw::git_blame_get_hunk_byline(blame, lineno)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_hunk_lines(hunk: *const raw::git_blame_hunk) -> size_t {
+    (*hunk).lines_in_hunk
+}
+
+/// Commit that last changed the hunk's lines.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_hunk_final_commit_id(
+    hunk: *const raw::git_blame_hunk,
+) -> *const raw::git_oid {
+    &(*hunk).final_commit_id
+}
+
+/// 1-based first line of the hunk in the blamed file.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_hunk_final_start_line(
+    hunk: *const raw::git_blame_hunk,
+) -> size_t {
+    (*hunk).final_start_line_number
+}
+
+/// Author of the final commit; may be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_hunk_final_signature(
+    hunk: *const raw::git_blame_hunk,
+) -> *const raw::git_signature {
+    (*hunk).final_signature
+}
+
+/// Commit where the lines were originally introduced (differs from the
+/// final commit when copies are tracked).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_hunk_orig_commit_id(
+    hunk: *const raw::git_blame_hunk,
+) -> *const raw::git_oid {
+    &(*hunk).orig_commit_id
+}
+
+/// Path of the file in the original commit.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_hunk_orig_path(
+    hunk: *const raw::git_blame_hunk,
+) -> *const c_char {
+    (*hunk).orig_path
+}
+
+/// 1-based first line of the hunk in the original file.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_hunk_orig_start_line(
+    hunk: *const raw::git_blame_hunk,
+) -> size_t {
+    (*hunk).orig_start_line_number
+}
+
+/// Author of the original commit; may be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_hunk_orig_signature(
+    hunk: *const raw::git_blame_hunk,
+) -> *const raw::git_signature {
+    (*hunk).orig_signature
+}
+
+/// Non-zero if the hunk reached the oldest commit searched (a boundary).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_blame_hunk_is_boundary(
+    hunk: *const raw::git_blame_hunk,
+) -> c_int {
+    ((*hunk).boundary != 0) as c_int
+}
+
+// =============================================================================
+// Revision walking
+// =============================================================================
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revwalk_new(
+    out: *mut *mut raw::git_revwalk,
+    repo: *mut raw::git_repository,
+) -> c_int {
+    raw::git_revwalk_new(out, repo)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revwalk_free(walk: *mut raw::git_revwalk) {
+    raw::git_revwalk_free(walk);
+    if let Some(table) = hide_callbacks().as_mut() {
+        table.0.remove(&(walk as usize));
+    }
+}
+
+/// Clear all pushed and hidden commits so the walker can be reused.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revwalk_reset(walk: *mut raw::git_revwalk) -> c_int {
+    raw::git_revwalk_reset(walk)
+}
+
+/// Start the walk from commit `id`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revwalk_push(
+    walk: *mut raw::git_revwalk,
+    id: *const raw::git_oid,
+) -> c_int {
+    raw::git_revwalk_push(walk, id)
+}
+
+/// Exclude commit `id` and its ancestors from the walk.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revwalk_hide(
+    walk: *mut raw::git_revwalk,
+    id: *const raw::git_oid,
+) -> c_int {
+    raw::git_revwalk_hide(walk, id)
+}
+
+/// Next commit in the walk; returns `GIT_ITEROVER` when done.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revwalk_next(
+    out: *mut raw::git_oid,
+    walk: *mut raw::git_revwalk,
+) -> c_int {
+    raw::git_revwalk_next(out, walk)
+}
+
+/// Set `GIT_SORT_*` flags (TOPOLOGICAL 1, TIME 2, REVERSE 4); resets the
+/// walk.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revwalk_sorting(
+    walk: *mut raw::git_revwalk,
+    sort_mode: c_uint,
+) -> c_int {
+    raw::git_revwalk_sorting(walk, sort_mode)
+}
+
+/// Push and hide in one step from a range such as `"main..feature"`.
+/// Symmetric (`...`) ranges are not supported.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revwalk_push_range(
+    walk: *mut raw::git_revwalk,
+    range: *const c_char,
+) -> c_int {
+    raw::git_revwalk_push_range(walk, range)
+}
+
+/// Push every reference matching `glob`, e.g. `"refs/tags/*"`. A glob
+/// without wildcards gets an implicit `/*` appended.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revwalk_push_glob(
+    walk: *mut raw::git_revwalk,
+    glob: *const c_char,
+) -> c_int {
+    raw::git_revwalk_push_glob(walk, glob)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revwalk_push_head(walk: *mut raw::git_revwalk) -> c_int {
+    raw::git_revwalk_push_head(walk)
+}
+
+/// Push the commit `refname` points to, e.g. `"refs/heads/main"`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revwalk_push_ref(
+    walk: *mut raw::git_revwalk,
+    refname: *const c_char,
+) -> c_int {
+    raw::git_revwalk_push_ref(walk, refname)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revwalk_hide_glob(
+    walk: *mut raw::git_revwalk,
+    glob: *const c_char,
+) -> c_int {
+    raw::git_revwalk_hide_glob(walk, glob)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revwalk_hide_head(walk: *mut raw::git_revwalk) -> c_int {
+    raw::git_revwalk_hide_head(walk)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revwalk_hide_ref(
+    walk: *mut raw::git_revwalk,
+    refname: *const c_char,
+) -> c_int {
+    raw::git_revwalk_hide_ref(walk, refname)
+}
+
+/// Follow only the first parent of each commit, like `git log --first-parent`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revwalk_simplify_first_parent(
+    walk: *mut raw::git_revwalk,
+) -> c_int {
+    raw::git_revwalk_simplify_first_parent(walk)
+}
+
+/// Hide callback: return non-zero to hide `commit_id` and its ancestors.
+pub type git2_shim_revwalk_hide_cb =
+    extern "C" fn(commit_id: *const raw::git_oid, payload: *mut c_void) -> c_int;
+
+extern "C" fn revwalk_hide_trampoline(
+    commit_id: *const raw::git_oid,
+    payload: *mut c_void,
+) -> c_int {
+    let t = unsafe { &*(payload as *const Trampoline<git2_shim_revwalk_hide_cb>) };
+    (t.callback)(commit_id, t.payload)
+}
+
+/// Hide-callback trampolines, keyed by walker address. libgit2 keeps the
+/// payload for the life of the walker, so each one lives here until it is
+/// replaced or the walker is freed.
+struct HideCallbacks(HashMap<usize, Box<Trampoline<git2_shim_revwalk_hide_cb>>>);
+
+// The payloads are only handed back to the callback, never dereferenced here.
+unsafe impl Send for HideCallbacks {}
+
+static HIDE_CALLBACKS: Mutex<Option<HideCallbacks>> = Mutex::new(None);
+
+fn hide_callbacks() -> MutexGuard<'static, Option<HideCallbacks>> {
+    HIDE_CALLBACKS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Install, replace, or (with a NULL `callback`) remove the hide callback.
+/// `payload` must stay valid until the walker is freed or the callback
+/// replaced.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revwalk_add_hide_cb(
+    walk: *mut raw::git_revwalk,
+    callback: Option<git2_shim_revwalk_hide_cb>,
+    payload: *mut c_void,
+) -> c_int {
+    let Some(callback) = callback else {
+        let rc = raw::git_revwalk_add_hide_cb(walk, None, ptr::null_mut());
+        if rc >= 0 {
+            if let Some(table) = hide_callbacks().as_mut() {
+                table.0.remove(&(walk as usize));
+            }
+        }
+        return rc;
+    };
+    let mut t = Box::new(Trampoline { callback, payload });
+    let rc = raw::git_revwalk_add_hide_cb(
+        walk,
+        Some(revwalk_hide_trampoline),
+        &mut *t as *mut _ as *mut c_void,
+    );
+    if rc >= 0 {
+        hide_callbacks()
+            .get_or_insert_with(|| HideCallbacks(HashMap::new()))
+            .0
+            .insert(walk as usize, t);
+    }
+    rc
+}
+
+// =============================================================================
+// Objects and revparse
+// =============================================================================
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_object_free(object: *mut raw::git_object) {
+    raw::git_object_free(object)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_object_id(
+    object: *const raw::git_object,
+) -> *const raw::git_oid {
+    raw::git_object_id(object)
+}
+
+/// `GIT_OBJECT_*` type of the object (commit 1, tree 2, blob 3, tag 4).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_object_type(object: *const raw::git_object) -> c_int {
+    raw::git_object_type(object)
+}
+
+/// Name of a `GIT_OBJECT_*` type as git writes it in object headers and
+/// `git cat-file -t` output ("commit", "tree", "blob", "tag", plus the pack
+/// delta types "OFS_DELTA" and "REF_DELTA"); empty for unknown types.
+/// Static; do not free.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_object_type2string(kind: c_int) -> *const c_char {
+    raw::git_object_type2string(kind)
+}
+
+/// `GIT_OBJECT_*` type named by `name`, the inverse of
+/// `git2_shim_object_type2string`; `GIT_OBJECT_INVALID` (-1) if unknown.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_object_string2type(name: *const c_char) -> c_int {
+    raw::git_object_string2type(name)
+}
+
+/// 1 if `kind` can be stored as a loose object (commit, tree, blob or tag),
+/// otherwise 0.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_object_typeisloose(kind: c_int) -> c_int {
+    raw::git_object_typeisloose(kind)
+}
+
+/// Full name of the reference, e.g. `"refs/heads/main"`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_reference_name(
+    ref_: *const raw::git_reference,
+) -> *const c_char {
+    raw::git_reference_name(ref_)
+}
+
+/// Resolve a single revision such as `"HEAD~3"` or `"main@{yesterday}"`
+/// exactly as `git rev-parse` does.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revparse_single(
+    out: *mut *mut raw::git_object,
+    repo: *mut raw::git_repository,
+    spec: *const c_char,
+) -> c_int {
+    raw::git_revparse_single(out, repo, spec)
+}
+
+/// Like `git2_shim_revparse_single`, also returning the reference the spec
+/// went through (e.g. `refs/heads/main` for `"main~2"`). `reference_out` is
+/// set to NULL when the spec names no reference.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revparse_ext(
+    object_out: *mut *mut raw::git_object,
+    reference_out: *mut *mut raw::git_reference,
+    repo: *mut raw::git_repository,
+    spec: *const c_char,
+) -> c_int {
+    raw::git_revparse_ext(object_out, reference_out, repo, spec)
+}
+
+/// Parse a single revision or a range (`"a..b"`, `"a...b"`). For a single
+/// revision only `from` is set; the caller frees both objects.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_revparse(
+    revspec: *mut raw::git_revspec,
+    repo: *mut raw::git_repository,
+    spec: *const c_char,
+) -> c_int {
+    raw::git_revparse(revspec, repo, spec)
+}
+
+// =============================================================================
+// Errors
+// =============================================================================
+
+/// Message of the last error raised on this thread, e.g. the HTTP status or
+/// SSH failure behind a failed clone. NULL if there is none; valid until the
+/// next libgit2 call on this thread.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_error_last_message() -> *const c_char {
+    match raw::git_error_last().as_ref() {
+        Some(err) if err.klass != 0 => err.message,
+        _ => ptr::null(),
+    }
+}
+
+/// `GIT_ERROR_*` class of the last error on this thread (e.g. 2 for OS,
+/// 12 for network, 16 for SSL, 23 for SSH, 34 for HTTP); 0 if there is
+/// none.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_error_last_class() -> c_int {
+    raw::git_error_last().as_ref().map_or(0, |err| err.klass)
+}
+
+// =============================================================================
+// Remote callbacks
+// =============================================================================
+
+/// Host certificate check. `valid` is libgit2's own verdict (always 0 for
+/// SSH host keys). Return 0 to accept, negative to reject, or positive to
+/// defer to `valid`.
+pub type git2_shim_certificate_check_cb = extern "C" fn(
+    cert: *mut raw::git_cert,
+    valid: c_int,
+    host: *const c_char,
+    payload: *mut c_void,
+) -> c_int;
+
+/// Fetch/clone download progress. `stats` is libgit2's
+/// `git_indexer_progress` struct; objects are received, then indexed, then
+/// deltas resolved. Return negative to cancel.
+pub type git2_shim_transfer_progress_cb =
+    extern "C" fn(stats: *const raw::git_indexer_progress, payload: *mut c_void) -> c_int;
+
+/// Progress text from the server ("Counting objects...", hook output),
+/// `len` bytes, not NUL-terminated and possibly ending in `\r`. Return
+/// negative to cancel.
+pub type git2_shim_sideband_progress_cb =
+    extern "C" fn(str: *const c_char, len: c_int, payload: *mut c_void) -> c_int;
+
+/// A local ref moved from `old_id` to `new_id`. A zero `new_id` means the
+/// ref was deleted (e.g. pruned); a zero `old_id` means it was created.
+pub type git2_shim_update_tips_cb = extern "C" fn(
+    refname: *const c_char,
+    old_id: *const raw::git_oid,
+    new_id: *const raw::git_oid,
+    payload: *mut c_void,
+) -> c_int;
+
+/// Per-ref push result: `status` is NULL if `refname` was updated, otherwise
+/// the server's rejection message (e.g. "non-fast-forward").
+pub type git2_shim_push_update_reference_cb =
+    extern "C" fn(refname: *const c_char, status: *const c_char, payload: *mut c_void) -> c_int;
+
+/// Push upload progress: objects sent of `total`, and bytes sent.
+pub type git2_shim_push_transfer_progress_cb =
+    extern "C" fn(current: c_uint, total: c_uint, bytes: size_t, payload: *mut c_void) -> c_int;
+
+/// The ref updates a push is about to send, called once before the pack is
+/// built. `updates` holds `len` pointers to `git_push_update` (source and
+/// destination ref names and ids). Return negative to abort the push.
+pub type git2_shim_push_negotiation_cb = extern "C" fn(
+    updates: *mut *const raw::git_push_update,
+    len: size_t,
+    payload: *mut c_void,
+) -> c_int;
+
+/// Credential request: produce a credential of one of the `allowed_types`
+/// (`GIT_CREDENTIAL_*` bits) in `out`, e.g. with
+/// `git2_shim_credential_username_new`. Return 0 on success, a positive value
+/// if no credential is available, `GIT2_SHIM_CREDENTIAL_ASK_GIT` to fall back
+/// to git's credential helpers, or negative to abort.
+pub type git2_shim_credential_acquire_cb = extern "C" fn(
+    out: *mut *mut raw::git_credential,
+    url: *const c_char,
+    username_from_url: *const c_char,
+    allowed_types: c_uint,
+    payload: *mut c_void,
+) -> c_int;
+
+/// Network callbacks, copied into fetch and push options. The owning
+/// options handle is libgit2's payload; each trampoline forwards to the
+/// matching Zig callback with its own userdata.
+#[derive(Clone, Default)]
+pub struct RemoteCallbacks {
+    sideband_progress: Option<Trampoline<git2_shim_sideband_progress_cb>>,
+    credentials: Option<Trampoline<git2_shim_credential_acquire_cb>>,
+    certificate_check: Option<Trampoline<git2_shim_certificate_check_cb>>,
+    transfer_progress: Option<Trampoline<git2_shim_transfer_progress_cb>>,
+    update_tips: Option<Trampoline<git2_shim_update_tips_cb>>,
+    push_update_reference: Option<Trampoline<git2_shim_push_update_reference_cb>>,
+    push_transfer_progress: Option<Trampoline<git2_shim_push_transfer_progress_cb>>,
+    push_negotiation: Option<Trampoline<git2_shim_push_negotiation_cb>>,
+    helper: CredentialHelper,
+}
+
+impl RemoteCallbacks {
+    /// Point `raw` at the trampolines for the callbacks that are set;
+    /// `self` must outlive the libgit2 call.
+    fn fill(&mut self, raw: &mut raw::git_remote_callbacks) {
+        self.helper.filled = None;
+        raw.payload = self as *mut _ as *mut c_void;
+        raw.sideband_progress = self
+            .sideband_progress
+            .map(|_| sideband_progress_trampoline as raw::git_transport_message_cb);
+        raw.credentials = self
+            .credentials
+            .map(|_| credentials_trampoline as raw::git_credential_acquire_cb);
+        raw.certificate_check = self
+            .certificate_check
+            .map(|_| certificate_check_trampoline as raw::git_transport_certificate_check_cb);
+        raw.transfer_progress = self
+            .transfer_progress
+            .map(|_| transfer_progress_trampoline as raw::git_indexer_progress_cb);
+        raw.update_tips = self
+            .update_tips
+            .map(|_| update_tips_trampoline as raw::git_update_tips_cb);
+        raw.push_update_reference = self
+            .push_update_reference
+            .map(|_| push_update_reference_trampoline as raw::git_push_update_reference_cb);
+        raw.push_transfer_progress = self
+            .push_transfer_progress
+            .map(|_| push_transfer_progress_trampoline as raw::git_push_transfer_progress_cb);
+        raw.push_negotiation = self
+            .push_negotiation
+            .map(|_| push_negotiation_trampoline as raw::git_push_negotiation);
+    }
+}
+
+/// Standalone `git_remote_callbacks` for calls that take them directly;
+/// `callbacks` may be NULL and must outlive the libgit2 call.
+unsafe fn raw_remote_callbacks(callbacks: *mut RemoteCallbacks) -> raw::git_remote_callbacks {
+    let mut raw_callbacks: raw::git_remote_callbacks = std::mem::zeroed();
+    raw_callbacks.version = raw::GIT_REMOTE_CALLBACKS_VERSION;
+    if let Some(callbacks) = callbacks.as_mut() {
+        callbacks.fill(&mut raw_callbacks);
+    }
+    raw_callbacks
+}
+
+unsafe fn remote_callbacks<'a>(payload: *mut c_void) -> &'a RemoteCallbacks {
+    &*(payload as *const RemoteCallbacks)
+}
+
+extern "C" fn sideband_progress_trampoline(
+    str: *const c_char,
+    len: c_int,
+    payload: *mut c_void,
+) -> c_int {
+    match unsafe { remote_callbacks(payload) }.sideband_progress {
+        Some(t) => (t.callback)(str, len, t.payload),
+        None => 0,
+    }
+}
+
+extern "C" fn credentials_trampoline(
+    out: *mut *mut raw::git_credential,
+    url: *const c_char,
+    username_from_url: *const c_char,
+    allowed_types: c_uint,
+    payload: *mut c_void,
+) -> c_int {
+    let callbacks = unsafe { &mut *(payload as *mut RemoteCallbacks) };
+    acquire_credential(
+        callbacks.credentials,
+        &mut callbacks.helper,
+        out,
+        url,
+        username_from_url,
+        allowed_types,
+    )
+}
+
+extern "C" fn certificate_check_trampoline(
+    cert: *mut raw::git_cert,
+    valid: c_int,
+    host: *const c_char,
+    payload: *mut c_void,
+) -> c_int {
+    match unsafe { remote_callbacks(payload) }.certificate_check {
+        Some(t) => (t.callback)(cert, valid, host, t.payload),
+        None => raw::GIT_PASSTHROUGH,
+    }
+}
+
+extern "C" fn transfer_progress_trampoline(
+    stats: *const raw::git_indexer_progress,
+    payload: *mut c_void,
+) -> c_int {
+    match unsafe { remote_callbacks(payload) }.transfer_progress {
+        Some(t) => (t.callback)(stats, t.payload),
+        None => 0,
+    }
+}
+
+extern "C" fn update_tips_trampoline(
+    refname: *const c_char,
+    old_id: *const raw::git_oid,
+    new_id: *const raw::git_oid,
+    payload: *mut c_void,
+) -> c_int {
+    match unsafe { remote_callbacks(payload) }.update_tips {
+        Some(t) => (t.callback)(refname, old_id, new_id, t.payload),
+        None => 0,
+    }
+}
+
+extern "C" fn push_update_reference_trampoline(
+    refname: *const c_char,
+    status: *const c_char,
+    payload: *mut c_void,
+) -> c_int {
+    match unsafe { remote_callbacks(payload) }.push_update_reference {
+        Some(t) => (t.callback)(refname, status, t.payload),
+        None => 0,
+    }
+}
+
+extern "C" fn push_transfer_progress_trampoline(
+    current: c_uint,
+    total: c_uint,
+    bytes: size_t,
+    payload: *mut c_void,
+) -> c_int {
+    match unsafe { remote_callbacks(payload) }.push_transfer_progress {
+        Some(t) => (t.callback)(current, total, bytes, t.payload),
+        None => 0,
+    }
+}
+
+extern "C" fn push_negotiation_trampoline(
+    updates: *mut *const raw::git_push_update,
+    len: size_t,
+    payload: *mut c_void,
+) -> c_int {
+    match unsafe { remote_callbacks(payload) }.push_negotiation {
+        Some(t) => (t.callback)(updates, len, t.payload),
+        None => 0,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_callbacks_new() -> *mut RemoteCallbacks {
+    Box::into_raw(Box::default())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_callbacks_free(callbacks: *mut RemoteCallbacks) {
+    if !callbacks.is_null() {
+        drop(Box::from_raw(callbacks));
+    }
+}
+
+/// Called with server-side progress and hook messages during fetch and
+/// push; NULL clears it.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_callbacks_set_sideband_progress_cb(
+    callbacks: *mut RemoteCallbacks,
+    callback: Option<git2_shim_sideband_progress_cb>,
+    payload: *mut c_void,
+) {
+    (*callbacks).sideband_progress = callback.map(|callback| Trampoline { callback, payload });
+}
+
+/// Called whenever the remote asks for authentication, possibly several
+/// times if a credential is rejected; NULL clears it.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_callbacks_set_credentials_cb(
+    callbacks: *mut RemoteCallbacks,
+    callback: Option<git2_shim_credential_acquire_cb>,
+    payload: *mut c_void,
+) {
+    (*callbacks).credentials = callback.map(|callback| Trampoline { callback, payload });
+}
+
+/// Repository whose config names the credential helpers run for
+/// `GIT2_SHIM_CREDENTIAL_ASK_GIT`; NULL (the default) uses the global, XDG
+/// and system config only. It must outlive every operation using these
+/// callbacks.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_callbacks_set_repository(
+    callbacks: *mut RemoteCallbacks,
+    repo: *mut raw::git_repository,
+) {
+    (*callbacks).helper.repo = repo;
+}
+
+/// Called after connecting with the server's TLS certificate or SSH host
+/// key; NULL clears it.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_callbacks_set_certificate_check_cb(
+    callbacks: *mut RemoteCallbacks,
+    callback: Option<git2_shim_certificate_check_cb>,
+    payload: *mut c_void,
+) {
+    (*callbacks).certificate_check = callback.map(|callback| Trampoline { callback, payload });
+}
+
+/// Called repeatedly while a fetch or clone downloads and indexes objects;
+/// NULL clears it.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_callbacks_set_transfer_progress_cb(
+    callbacks: *mut RemoteCallbacks,
+    callback: Option<git2_shim_transfer_progress_cb>,
+    payload: *mut c_void,
+) {
+    (*callbacks).transfer_progress = callback.map(|callback| Trampoline { callback, payload });
+}
+
+/// Called for every local ref a fetch or prune creates, moves, or
+/// deletes; NULL clears it.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_callbacks_set_update_tips_cb(
+    callbacks: *mut RemoteCallbacks,
+    callback: Option<git2_shim_update_tips_cb>,
+    payload: *mut c_void,
+) {
+    (*callbacks).update_tips = callback.map(|callback| Trampoline { callback, payload });
+}
+
+/// Called once per pushed ref with the server's verdict; NULL clears it.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_callbacks_set_push_update_reference_cb(
+    callbacks: *mut RemoteCallbacks,
+    callback: Option<git2_shim_push_update_reference_cb>,
+    payload: *mut c_void,
+) {
+    (*callbacks).push_update_reference = callback.map(|callback| Trampoline { callback, payload });
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_callbacks_set_push_transfer_progress_cb(
+    callbacks: *mut RemoteCallbacks,
+    callback: Option<git2_shim_push_transfer_progress_cb>,
+    payload: *mut c_void,
+) {
+    (*callbacks).push_transfer_progress = callback.map(|callback| Trampoline { callback, payload });
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_callbacks_set_push_negotiation_cb(
+    callbacks: *mut RemoteCallbacks,
+    callback: Option<git2_shim_push_negotiation_cb>,
+    payload: *mut c_void,
+) {
+    (*callbacks).push_negotiation = callback.map(|callback| Trampoline { callback, payload });
+}
+
+// =============================================================================
+// Proxy options
+// =============================================================================
+
+/// Proxy settings, copied into fetch and push options. Credentials and
+/// certificate checks for the proxy itself use the same callback types as
+/// the remote's.
+#[derive(Clone)]
+pub struct ProxyOptions {
+    raw: raw::git_proxy_options,
+    url: Option<CString>,
+    credentials: Option<Trampoline<git2_shim_credential_acquire_cb>>,
+    certificate_check: Option<Trampoline<git2_shim_certificate_check_cb>>,
+    helper: CredentialHelper,
+}
+
+impl ProxyOptions {
+    /// Snapshot the options for a libgit2 call; `self` must outlive the call.
+    fn raw_opts(&mut self) -> raw::git_proxy_options {
+        self.helper.filled = None;
+        let mut opts = self.raw;
+        opts.url = cstring_ptr(&self.url);
+        opts.payload = self as *mut _ as *mut c_void;
+        opts.credentials = self
+            .credentials
+            .map(|_| proxy_credentials_trampoline as raw::git_credential_acquire_cb);
+        opts.certificate_check = self
+            .certificate_check
+            .map(|_| proxy_certificate_check_trampoline as raw::git_transport_certificate_check_cb);
+        opts
+    }
+}
+
+extern "C" fn proxy_credentials_trampoline(
+    out: *mut *mut raw::git_credential,
+    url: *const c_char,
+    username_from_url: *const c_char,
+    allowed_types: c_uint,
+    payload: *mut c_void,
+) -> c_int {
+    let opts = unsafe { &mut *(payload as *mut ProxyOptions) };
+    acquire_credential(
+        opts.credentials,
+        &mut opts.helper,
+        out,
+        url,
+        username_from_url,
+        allowed_types,
+    )
+}
+
+extern "C" fn proxy_certificate_check_trampoline(
+    cert: *mut raw::git_cert,
+    valid: c_int,
+    host: *const c_char,
+    payload: *mut c_void,
+) -> c_int {
+    let opts = unsafe { &*(payload as *const ProxyOptions) };
+    match opts.certificate_check {
+        Some(t) => (t.callback)(cert, valid, host, t.payload),
+        None => raw::GIT_PASSTHROUGH,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_proxy_options_new() -> *mut ProxyOptions {
+    let mut opts = Box::new(ProxyOptions {
+        raw: std::mem::zeroed(),
+        url: None,
+        credentials: None,
+        certificate_check: None,
+        helper: CredentialHelper::default(),
+    });
+    if raw::git_proxy_options_init(&mut opts.raw, raw::GIT_PROXY_OPTIONS_VERSION) < 0 {
+        return ptr::null_mut();
+    }
+    Box::into_raw(opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_proxy_options_free(opts: *mut ProxyOptions) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// `GIT_PROXY_NONE` (0, the default), `GIT_PROXY_AUTO` (1, from git config
+/// and the environment), or `GIT_PROXY_SPECIFIED` (2, use the URL below).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_proxy_options_set_type(opts: *mut ProxyOptions, type_: c_uint) {
+    (*opts).raw.type_ = type_;
+}
+
+/// Proxy URL for `GIT_PROXY_SPECIFIED`, e.g. `"http://proxy.corp:3128"`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_proxy_options_set_url(
+    opts: *mut ProxyOptions,
+    url: *const c_char,
+) {
+    (*opts).url = owned_cstring(url);
+}
+
+/// Credentials for an authenticating proxy; NULL clears it.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_proxy_options_set_credentials_cb(
+    opts: *mut ProxyOptions,
+    callback: Option<git2_shim_credential_acquire_cb>,
+    payload: *mut c_void,
+) {
+    (*opts).credentials = callback.map(|callback| Trampoline { callback, payload });
+}
+
+/// Certificate check for a TLS proxy; NULL clears it.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_proxy_options_set_certificate_check_cb(
+    opts: *mut ProxyOptions,
+    callback: Option<git2_shim_certificate_check_cb>,
+    payload: *mut c_void,
+) {
+    (*opts).certificate_check = callback.map(|callback| Trampoline { callback, payload });
+}
+
+// =============================================================================
+// Fetch options
+// =============================================================================
+
+/// Fetch options, shared by fetches and clones.
+#[derive(Clone)]
+pub struct FetchOptions {
+    raw: raw::git_fetch_options,
+    callbacks: RemoteCallbacks,
+    proxy: Option<ProxyOptions>,
+    custom_headers: StrArray,
+}
+
+impl FetchOptions {
+    /// Snapshot the options for a libgit2 call; `self` must outlive the call.
+    fn raw_opts(&mut self) -> raw::git_fetch_options {
+        let mut opts = self.raw;
+        self.callbacks.fill(&mut opts.callbacks);
+        opts.custom_headers = self.custom_headers.as_raw();
+        if let Some(proxy) = self.proxy.as_mut() {
+            opts.proxy_opts = proxy.raw_opts();
+        }
+        opts
+    }
+
+    /// Settle the credentials the call returning `rc` used; returns `rc`.
+    unsafe fn settle(&mut self, rc: c_int) -> c_int {
+        self.callbacks.helper.settle(rc);
+        if let Some(proxy) = self.proxy.as_mut() {
+            proxy.helper.settle(rc);
+        }
+        rc
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_fetch_options_new() -> *mut FetchOptions {
+    let mut opts = Box::new(FetchOptions {
+        raw: std::mem::zeroed(),
+        callbacks: RemoteCallbacks::default(),
+        proxy: None,
+
+        custom_headers: StrArray::default(),
+    });
+    if raw::git_fetch_options_init(&mut opts.raw, raw::GIT_FETCH_OPTIONS_VERSION) < 0 {
+        return ptr::null_mut();
+    }
+    Box::into_raw(opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_fetch_options_free(opts: *mut FetchOptions) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// Callbacks for the fetch; copied. NULL clears them.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_fetch_options_set_callbacks(
+    opts: *mut FetchOptions,
+    callbacks: *const RemoteCallbacks,
+) {
+    (*opts).callbacks = callbacks.as_ref().cloned().unwrap_or_default();
+}
+
+/// Proxy to fetch through; copied. NULL connects directly.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_fetch_options_set_proxy_options(
+    opts: *mut FetchOptions,
+    proxy: *const ProxyOptions,
+) {
+    (*opts).proxy = proxy.as_ref().cloned();
+}
+
+/// Extra HTTP headers (e.g. `"Authorization: Bearer <token>"`) sent with every
+/// request of the fetch; copied. NULL sends none.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_fetch_options_set_custom_headers(
+    opts: *mut FetchOptions,
+    headers: *const *const c_char,
+    count: size_t,
+) {
+    (*opts).custom_headers = StrArray::from_raw(headers, count);
+}
+
+/// Limit history to `depth` commits from each fetched tip; 0 fetches full
+/// history and `GIT_FETCH_DEPTH_UNSHALLOW` (2147483647) converts a shallow
+/// repository into a complete one.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_fetch_options_set_depth(opts: *mut FetchOptions, depth: c_int) {
+    (*opts).raw.depth = depth;
+}
+
+/// `GIT_FETCH_PRUNE_UNSPECIFIED` (0, use `remote.<name>.prune` / `fetch.prune`),
+/// `GIT_FETCH_PRUNE` (1), or `GIT_FETCH_NO_PRUNE` (2).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_fetch_options_set_prune(opts: *mut FetchOptions, prune: c_uint) {
+    (*opts).raw.prune = prune;
+}
+
+/// `GIT_REMOTE_DOWNLOAD_TAGS_UNSPECIFIED` (0, use `remote.<name>.tagOpt`),
+/// `GIT_REMOTE_DOWNLOAD_TAGS_AUTO` (1, tags pointing into fetched history),
+/// `GIT_REMOTE_DOWNLOAD_TAGS_NONE` (2), or `GIT_REMOTE_DOWNLOAD_TAGS_ALL` (3).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_fetch_options_set_download_tags(
+    opts: *mut FetchOptions,
+    download_tags: c_uint,
+) {
+    (*opts).raw.download_tags = download_tags;
+}
+
+/// Non-zero if fetches and clones can take an object filter (partial
+/// clone, e.g. `blob:none`). libgit2 (through 1.8) has no fetch filter
+/// option, so this is 0 and callers should fall back to a full or shallow
+/// fetch.
+#[no_mangle]
+pub extern "C" fn git2_shim_fetch_filter_supported() -> c_int {
+    0
+}
+
+/// Request a partial fetch with a `git rev-list --filter` spec such as
+/// `"blob:none"` or `"tree:0"`; NULL fetches every object. Fails with
+/// `GIT_ENOTSUPPORTED` for any other value unless
+/// `git2_shim_fetch_filter_supported` reports support. `opts` is
+/// intentionally left untouched: libgit2 has no field to carry a filter.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_fetch_options_set_filter(
+    _opts: *mut FetchOptions,
+    filter: *const c_char,
+) -> c_int {
+    if filter.is_null() {
+        return 0;
+    }
+    raw::git_error_set_str(
+        raw::GIT_ERROR_INVALID,
+        c"object filters (partial clone) are not supported by this libgit2".as_ptr(),
+    );
+    raw::GIT_ENOTSUPPORTED
+}
+
+/// `GIT_REMOTE_REDIRECT_NONE` (1), `GIT_REMOTE_REDIRECT_INITIAL` (2, only on
+/// the first request; the default), or `GIT_REMOTE_REDIRECT_ALL` (4); 0 uses
+/// `http.followRedirects`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_fetch_options_set_follow_redirects(
+    opts: *mut FetchOptions,
+    follow_redirects: c_uint,
+) {
+    (*opts).raw.follow_redirects = follow_redirects;
+}
+
+/// Non-zero if the repository was created by a shallow clone or fetch.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_is_shallow(repo: *mut raw::git_repository) -> c_int {
+    raw::git_repository_is_shallow(repo)
+}
+
+// =============================================================================
+// Clone
+// =============================================================================
+
+/// Clone options; defaults to a full, non-bare clone of the remote HEAD.
+pub struct CloneOptions {
+    raw: raw::git_clone_options,
+    checkout_branch: Option<CString>,
+    checkout: Option<CheckoutOptions>,
+    fetch: Option<FetchOptions>,
+}
+
+impl CloneOptions {
+    fn raw_opts(&mut self) -> *const raw::git_clone_options {
+        self.raw.checkout_branch = cstring_ptr(&self.checkout_branch);
+        if let Some(checkout) = self.checkout.as_mut() {
+            self.raw.checkout_opts = checkout.raw_opts();
+        }
+        if let Some(fetch) = self.fetch.as_mut() {
+            self.raw.fetch_opts = fetch.raw_opts();
+        }
+        &self.raw
+    }
+
+    unsafe fn settle(&mut self, rc: c_int) -> c_int {
+        self.fetch.as_mut().map_or(rc, |fetch| fetch.settle(rc))
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_clone_options_new() -> *mut CloneOptions {
+    let mut opts = Box::new(CloneOptions {
+        raw: std::mem::zeroed(),
+        checkout_branch: None,
+        checkout: None,
+        fetch: None,
+    });
+    if raw::git_clone_options_init(&mut opts.raw, raw::GIT_CLONE_OPTIONS_VERSION) < 0 {
+        return ptr::null_mut();
+    }
+    Box::into_raw(opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_clone_options_free(opts: *mut CloneOptions) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// Non-zero creates a bare repository with no working directory.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_clone_options_set_bare(opts: *mut CloneOptions, bare: c_int) {
+    (*opts).raw.bare = bare;
+}
+
+/// Branch to check out instead of the remote's default; NULL restores the
+/// default.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_clone_options_set_checkout_branch(
+    opts: *mut CloneOptions,
+    branch: *const c_char,
+) {
+    (*opts).checkout_branch = owned_cstring(branch);
+}
+
+/// How to clone from a local path: `GIT_CLONE_LOCAL_AUTO` (0, the default;
+/// local transport for plain paths but not `file://` URLs), `LOCAL` (1,
+/// hardlink objects where possible), `NO_LOCAL` (2, always use the regular
+/// transport), or `LOCAL_NO_LINKS` (3, copy instead of hardlinking).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_clone_options_set_local(opts: *mut CloneOptions, local: c_uint) {
+    (*opts).raw.local = local;
+}
+
+/// Checkout options for the initial checkout; copied. NULL restores the
+/// default safe checkout.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_clone_options_set_checkout_options(
+    opts: *mut CloneOptions,
+    checkout: *const CheckoutOptions,
+) {
+    (*opts).checkout = checkout.as_ref().cloned();
+    if (*opts).checkout.is_none() {
+        let mut defaults: raw::git_clone_options = std::mem::zeroed();
+        raw::git_clone_options_init(&mut defaults, raw::GIT_CLONE_OPTIONS_VERSION);
+        (*opts).raw.checkout_opts = defaults.checkout_opts;
+    }
+}
+
+/// Fetch options for the initial fetch, e.g. a shallow depth; copied. NULL
+/// restores the defaults.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_clone_options_set_fetch_options(
+    opts: *mut CloneOptions,
+    fetch: *const FetchOptions,
+) {
+    (*opts).fetch = fetch.as_ref().cloned();
+    if (*opts).fetch.is_none() {
+        let mut defaults: raw::git_clone_options = std::mem::zeroed();
+        raw::git_clone_options_init(&mut defaults, raw::GIT_CLONE_OPTIONS_VERSION);
+        (*opts).raw.fetch_opts = defaults.fetch_opts;
+    }
+}
+
+/// Clone `url` into `local_path` and open the result; free it with
+/// `git2_shim_repository_free`. `opts` may be NULL. On failure, see
+/// `git2_shim_error_last_message` for the network or filesystem error.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_clone(
+    out: *mut *mut raw::git_repository,
+    url: *const c_char,
+    local_path: *const c_char,
+    opts: *mut CloneOptions,
+) -> c_int {
+    let raw_opts = opts.as_mut().map_or(ptr::null(), |o| o.raw_opts());
+    let rc = raw::git_clone(out, url, local_path, raw_opts);
+    opts.as_mut().map_or(rc, |o| o.settle(rc))
+}
+
+// =============================================================================
+// Remotes
+// =============================================================================
+
+/// Free the strings of a `git_strarray` filled in by libgit2.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_strarray_dispose(array: *mut raw::git_strarray) {
+    raw::git_strarray_dispose(array)
+}
+
+/// Names of all configured remotes; dispose with `git2_shim_strarray_dispose`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_list(
+    out: *mut raw::git_strarray,
+    repo: *mut raw::git_repository,
+) -> c_int {
+    raw::git_remote_list(out, repo)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_lookup(
+    out: *mut *mut raw::git_remote,
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+) -> c_int {
+    raw::git_remote_lookup(out, repo, name)
+}
+
+/// Add remote `name` with the default fetch refspec
+/// (`+refs/heads/*:refs/remotes/<name>/*`).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_create(
+    out: *mut *mut raw::git_remote,
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+    url: *const c_char,
+) -> c_int {
+    raw::git_remote_create(out, repo, name, url)
+}
+
+/// In-memory remote for a one-off fetch or push to `url`; nothing is
+/// written to the repository configuration. Fetches through it only update
+/// FETCH_HEAD unless explicit refspecs with destinations are given.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_create_anonymous(
+    out: *mut *mut raw::git_remote,
+    repo: *mut raw::git_repository,
+    url: *const c_char,
+) -> c_int {
+    raw::git_remote_create_anonymous(out, repo, url)
+}
+
+/// Remove the remote, its remote-tracking branches, and its config.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_delete(
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+) -> c_int {
+    raw::git_remote_delete(repo, name)
+}
+
+/// Rename a remote and its remote-tracking branches. Non-default fetch
+/// refspecs that could not be updated are returned in `problems`, which
+/// must be disposed with `git2_shim_strarray_dispose`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_rename(
+    problems: *mut raw::git_strarray,
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+    new_name: *const c_char,
+) -> c_int {
+    raw::git_remote_rename(problems, repo, name, new_name)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_free(remote: *mut raw::git_remote) {
+    raw::git_remote_free(remote)
+}
+
+/// Name of the remote; NULL for an anonymous remote.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_name(remote: *const raw::git_remote) -> *const c_char {
+    raw::git_remote_name(remote)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_url(remote: *const raw::git_remote) -> *const c_char {
+    raw::git_remote_url(remote)
+}
+
+/// Separate push URL; NULL when pushes use the fetch URL.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_pushurl(remote: *const raw::git_remote) -> *const c_char {
+    raw::git_remote_pushurl(remote)
+}
+
+/// Set the URL of the remote called `name` in the configuration. Already
+/// loaded `git_remote` handles keep their old URL.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_set_url(
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+    url: *const c_char,
+) -> c_int {
+    raw::git_remote_set_url(repo, name, url)
+}
+
+/// Set the push URL of remote `name` in the configuration; NULL removes it
+/// so pushes use the fetch URL again.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_set_pushurl(
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+    url: *const c_char,
+) -> c_int {
+    raw::git_remote_set_pushurl(repo, name, url)
+}
+
+/// Tag-following setting of the remote (`remote.<name>.tagOpt`), one of the
+/// `GIT_REMOTE_DOWNLOAD_TAGS_*` values; fetch options override it.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_autotag(remote: *const raw::git_remote) -> c_uint {
+    raw::git_remote_autotag(remote)
+}
+
+/// Store the tag-following setting of remote `name` in the configuration;
+/// loaded remotes keep their old setting.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_set_autotag(
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+    value: c_uint,
+) -> c_int {
+    raw::git_remote_set_autotag(repo, name, value)
+}
+
+/// Fetch from `remote` and update its remote-tracking refs. `refspecs` may
+/// be NULL to use the configured fetch refspecs; `opts` and
+/// `reflog_message` may be NULL (the latter defaults to "fetch").
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_fetch(
+    remote: *mut raw::git_remote,
+    refspecs: *const *const c_char,
+    refspec_count: size_t,
+    opts: *mut FetchOptions,
+    reflog_message: *const c_char,
+) -> c_int {
+    let mut specs = StrArray::from_raw(refspecs, refspec_count);
+    let raw_specs = specs.as_raw();
+    let raw_opts = opts.as_mut().map(|o| o.raw_opts());
+    let rc = raw::git_remote_fetch(
+        remote,
+        if refspecs.is_null() {
+            ptr::null()
+        } else {
+            &raw_specs
+        },
+        raw_opts.as_ref().map_or(ptr::null(), |o| o as *const _),
+        reflog_message,
+    );
+    opts.as_mut().map_or(rc, |o| o.settle(rc))
+}
+
+/// Download the packfile for `refspecs` (NULL for the configured ones)
+/// without touching any refs; follow with `git2_shim_remote_update_tips`.
+/// Connects first if `remote` is not connected.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_download(
+    remote: *mut raw::git_remote,
+    refspecs: *const *const c_char,
+    refspec_count: size_t,
+    opts: *mut FetchOptions,
+) -> c_int {
+    let mut specs = StrArray::from_raw(refspecs, refspec_count);
+    let raw_specs = specs.as_raw();
+    let raw_opts = opts.as_mut().map(|o| o.raw_opts());
+    let rc = raw::git_remote_download(
+        remote,
+        if refspecs.is_null() {
+            ptr::null()
+        } else {
+            &raw_specs
+        },
+        raw_opts.as_ref().map_or(ptr::null(), |o| o as *const _),
+    );
+    opts.as_mut().map_or(rc, |o| o.settle(rc))
+}
+
+/// Move the remote-tracking refs to what the last download fetched,
+/// reporting each one through the `update_tips` callback of `callbacks`
+/// (may be NULL). `update_flags` combines `GIT_REMOTE_UPDATE_FETCHHEAD` (1)
+/// and `GIT_REMOTE_UPDATE_REPORT_UNCHANGED` (2); `download_tags` must match
+/// the download's fetch options.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_update_tips(
+    remote: *mut raw::git_remote,
+    callbacks: *mut RemoteCallbacks,
+    update_flags: c_uint,
+    download_tags: c_uint,
+    reflog_message: *const c_char,
+) -> c_int {
+    let raw_callbacks = raw_remote_callbacks(callbacks);
+    raw::git_remote_update_tips(
+        remote,
+        &raw_callbacks,
+        update_flags,
+        download_tags,
+        reflog_message,
+    )
+}
+
+// =============================================================================
+// Push
+// =============================================================================
+
+/// Push options.
+#[derive(Clone)]
+pub struct PushOptions {
+    raw: raw::git_push_options,
+    callbacks: RemoteCallbacks,
+    proxy: Option<ProxyOptions>,
+    custom_headers: StrArray,
+    remote_push_options: StrArray,
+}
+
+impl PushOptions {
+    /// Snapshot the options for a libgit2 call; `self` must outlive the call.
+    fn raw_opts(&mut self) -> raw::git_push_options {
+        let mut opts = self.raw;
+        self.callbacks.fill(&mut opts.callbacks);
+        opts.custom_headers = self.custom_headers.as_raw();
+        opts.remote_push_options = self.remote_push_options.as_raw();
+        if let Some(proxy) = self.proxy.as_mut() {
+            opts.proxy_opts = proxy.raw_opts();
+        }
+        opts
+    }
+
+    /// Settle the credentials the call returning `rc` used; returns `rc`.
+    unsafe fn settle(&mut self, rc: c_int) -> c_int {
+        self.callbacks.helper.settle(rc);
+        if let Some(proxy) = self.proxy.as_mut() {
+            proxy.helper.settle(rc);
+        }
+        rc
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_push_options_new() -> *mut PushOptions {
+    let mut opts = Box::new(PushOptions {
+        raw: std::mem::zeroed(),
+        callbacks: RemoteCallbacks::default(),
+        proxy: None,
+
+        custom_headers: StrArray::default(),
+        remote_push_options: StrArray::default(),
+    });
+    if raw::git_push_options_init(&mut opts.raw, raw::GIT_PUSH_OPTIONS_VERSION) < 0 {
+        return ptr::null_mut();
+    }
+    Box::into_raw(opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_push_options_free(opts: *mut PushOptions) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// Callbacks for the push; copied. NULL clears them.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_push_options_set_callbacks(
+    opts: *mut PushOptions,
+    callbacks: *const RemoteCallbacks,
+) {
+    (*opts).callbacks = callbacks.as_ref().cloned().unwrap_or_default();
+}
+
+/// Proxy to push through; copied. NULL connects directly.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_push_options_set_proxy_options(
+    opts: *mut PushOptions,
+    proxy: *const ProxyOptions,
+) {
+    (*opts).proxy = proxy.as_ref().cloned();
+}
+
+/// Extra HTTP headers (e.g. `"Authorization: Bearer <token>"`) sent with every
+/// request of the push; copied. NULL sends none.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_push_options_set_custom_headers(
+    opts: *mut PushOptions,
+    headers: *const *const c_char,
+    count: size_t,
+) {
+    (*opts).custom_headers = StrArray::from_raw(headers, count);
+}
+
+/// Threads used to build the packfile; 0 auto-detects, 1 (the default)
+/// keeps it single-threaded.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_push_options_set_pb_parallelism(
+    opts: *mut PushOptions,
+    threads: c_uint,
+) {
+    (*opts).raw.pb_parallelism = threads;
+}
+
+/// `GIT_REMOTE_REDIRECT_NONE` (1), `GIT_REMOTE_REDIRECT_INITIAL` (2, only on
+/// the first request; the default), or `GIT_REMOTE_REDIRECT_ALL` (4); 0 uses
+/// `http.followRedirects`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_push_options_set_follow_redirects(
+    opts: *mut PushOptions,
+    follow_redirects: c_uint,
+) {
+    (*opts).raw.follow_redirects = follow_redirects;
+}
+
+/// Push options (`git push -o`) such as `"ci.skip"` or
+/// `"merge_request.create"`, handed to the server's hooks; copied. The
+/// server must advertise `push-options`. NULL sends none.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_push_options_set_remote_push_options(
+    opts: *mut PushOptions,
+    options: *const *const c_char,
+    count: size_t,
+) {
+    (*opts).remote_push_options = StrArray::from_raw(options, count);
+}
+
+/// Push `refspecs` (e.g. `"refs/heads/main"`, `"+refs/tags/v1:refs/tags/v1"`)
+/// to `remote`; NULL uses the configured push refspecs. Fails with
+/// `GIT_ENONFASTFORWARD` when an unforced update would lose commits. Refs
+/// the server itself rejects do not fail the push.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_push(
+    remote: *mut raw::git_remote,
+    refspecs: *const *const c_char,
+    refspec_count: size_t,
+    opts: *mut PushOptions,
+) -> c_int {
+    let mut specs = StrArray::from_raw(refspecs, refspec_count);
+    let raw_specs = specs.as_raw();
+    let raw_opts = opts.as_mut().map(|o| o.raw_opts());
+    let rc = raw::git_remote_push(
+        remote,
+        if refspecs.is_null() {
+            ptr::null()
+        } else {
+            &raw_specs
+        },
+        raw_opts.as_ref().map_or(ptr::null(), |o| o as *const _),
+    );
+    opts.as_mut().map_or(rc, |o| o.settle(rc))
+}
+
+// =============================================================================
+// Credentials
+// =============================================================================
+
+/// Free a credential that was not handed back to libgit2. Credentials
+/// returned from a credential callback are owned and freed by libgit2.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_credential_free(cred: *mut raw::git_credential) {
+    raw::git_credential_free(cred)
+}
+
+/// Username-only credential, for `GIT_CREDENTIAL_USERNAME` requests (SSH
+/// asks for the user before trying keys when the URL has none).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_credential_username_new(
+    out: *mut *mut raw::git_credential,
+    username: *const c_char,
+) -> c_int {
+    raw::git_credential_username_new(out, username)
+}
+
+/// Plaintext username and password for HTTPS. Hosts that take tokens
+/// (GitHub PATs, GitLab deploy tokens) accept the token as the password;
+/// the username is then often ignored but must be non-empty.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_credential_userpass_plaintext_new(
+    out: *mut *mut raw::git_credential,
+    username: *const c_char,
+    password: *const c_char,
+) -> c_int {
+    raw::git_credential_userpass_plaintext_new(out, username, password)
+}
+
+/// SSH key pair read from files. `publickey` may be NULL to derive it from
+/// the private key; `passphrase` is NULL for unencrypted keys.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_credential_ssh_key_new(
+    out: *mut *mut raw::git_credential,
+    username: *const c_char,
+    publickey: *const c_char,
+    privatekey: *const c_char,
+    passphrase: *const c_char,
+) -> c_int {
+    raw::git_credential_ssh_key_new(out, username, publickey, privatekey, passphrase)
+}
+
+/// SSH key pair held in memory (PEM/OpenSSH text), e.g. from a secret
+/// store, so the key never touches disk. Same NULL rules as
+/// `git2_shim_credential_ssh_key_new`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_credential_ssh_key_memory_new(
+    out: *mut *mut raw::git_credential,
+    username: *const c_char,
+    publickey: *const c_char,
+    privatekey: *const c_char,
+    passphrase: *const c_char,
+) -> c_int {
+    raw::git_credential_ssh_key_memory_new(out, username, publickey, privatekey, passphrase)
+}
+
+/// Authenticate as `username` with whatever keys the running ssh-agent
+/// (`SSH_AUTH_SOCK`, or Pageant on Windows) offers.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_credential_ssh_key_from_agent(
+    out: *mut *mut raw::git_credential,
+    username: *const c_char,
+) -> c_int {
+    raw::git_credential_ssh_key_from_agent(out, username)
+}
+
+/// Use the current user's platform credentials (NTLM/Negotiate/Kerberos),
+/// for `GIT_CREDENTIAL_DEFAULT` requests from enterprise servers and proxies.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_credential_default_new(
+    out: *mut *mut raw::git_credential,
+) -> c_int {
+    raw::git_credential_default_new(out)
+}
+
+/// Return value of a credentials callback asking the shim to consult git's
+/// credential helpers instead, as `git credential fill` would.
+pub const GIT2_SHIM_CREDENTIAL_ASK_GIT: c_int = 2;
+
+/// A username and password a credential helper supplied for `url`.
+#[derive(Clone)]
+struct HelperCredential {
+    url: String,
+    username: String,
+    password: String,
+}
+
+/// Helper state kept by whoever owns a credentials callback: the repository
+/// whose config names the helpers, and the credential handed out during the
+/// current libgit2 call. Being asked again for that URL means it was
+/// rejected, so it is reported to the helpers instead of retried forever;
+/// otherwise `settle` reports it once the call returns.
+#[derive(Clone)]
+struct CredentialHelper {
+    repo: *mut raw::git_repository,
+    filled: Option<HelperCredential>,
+}
+
+impl Default for CredentialHelper {
+    fn default() -> CredentialHelper {
+        CredentialHelper {
+            repo: ptr::null_mut(),
+            filled: None,
+        }
+    }
+}
+
+impl CredentialHelper {
+    /// Report how the libgit2 call that may have used the credential
+    /// handed out ended: `git credential approve` after success, so the
+    /// helpers can store it, and `reject` after an authentication failure.
+    /// Other failures (the network, a refused push) say nothing about the
+    /// credential. Returns `rc`.
+    unsafe fn settle(&mut self, rc: c_int) -> c_int {
+        let Some(credential) = self.filled.take() else {
+            return rc;
+        };
+        let auth_failed = rc == raw::GIT_EAUTH
+            || (rc < 0
+                && matches!(
+                    git2_shim_error_last_class(),
+                    raw::GIT_ERROR_HTTP | raw::GIT_ERROR_SSH
+                ));
+        if rc >= 0 {
+            report_helper_credential(self.repo, "approve", &credential);
+        } else if auth_failed {
+            report_helper_credential(self.repo, "reject", &credential);
+        }
+        rc
+    }
+}
+
+extern "C" fn note_config_entry(
+    _entry: *const raw::git_config_entry,
+    payload: *mut c_void,
+) -> c_int {
+    unsafe { *(payload as *mut bool) = true };
+    1
+}
+
+/// Whether `repo`'s config (or, if NULL, the global, XDG and system files)
+/// sets `credential.helper`, for every URL or for some URL.
+unsafe fn helper_configured(repo: *mut raw::git_repository) -> Result<bool, c_int> {
+    let mut config = ptr::null_mut();
+    let rc = if repo.is_null() {
+        raw::git_config_open_default(&mut config)
+    } else {
+        raw::git_repository_config(&mut config, repo)
+    };
+    if rc < 0 {
+        return Err(rc);
+    }
+    let mut found = false;
+    let rc = raw::git_config_foreach_match(
+        config,
+        c"^credential\\.(.+\\.)?helper$".as_ptr(),
+        note_config_entry,
+        &mut found as *mut _ as *mut c_void,
+    );
+    raw::git_config_free(config);
+    if rc < 0 && !found {
+        return Err(rc);
+    }
+    Ok(found)
+}
+
+/// The `key=value` lines git's credential protocol uses to describe `url`,
+/// or None if it has no `scheme://host` part or a value would span lines.
+fn credential_request(url: &str, username: Option<&str>) -> Option<String> {
+    let (protocol, rest) = url.split_once("://")?;
+    let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let (user_info, host) = match authority.rsplit_once('@') {
+        Some((user_info, host)) => (Some(user_info), host),
+        None => (None, authority),
+    };
+    let username = username.or_else(|| user_info.map(|u| u.split(':').next().unwrap_or(u)));
+    let fields = [
+        ("protocol", Some(protocol)),
+        ("host", Some(host)),
+        ("path", Some(path)),
+        ("username", username),
+    ];
+    let mut request = String::new();
+    for (key, value) in fields {
+        match value {
+            Some(value) if value.contains(['\n', '\0']) => return None,
+            Some(value) if !value.is_empty() => request.push_str(&format!("{key}={value}\n")),
+            _ => {}
+        }
+    }
+    (!protocol.is_empty() && !host.is_empty()).then_some(request)
+}
+
+/// Run `git credential <action>` on `request` for `repo` (or outside any
+/// repository if NULL) and return what it printed, or None if it failed.
+/// Terminal prompts are disabled, so only helpers and askpass programs can
+/// answer.
+unsafe fn run_git_credential(
+    repo: *mut raw::git_repository,
+    action: &str,
+    request: &str,
+) -> Option<String> {
+    let mut command = std::process::Command::new("git");
+    if repo.is_null() {
+        command.current_dir(std::env::temp_dir());
+    } else {
+        let git_dir = CStr::from_ptr(raw::git_repository_path(repo))
+            .to_str()
+            .ok()?;
+        command.arg("--git-dir").arg(git_dir);
+    }
+    let mut child = command
+        .args(["credential", action])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .ok()?;
+    let written = child
+        .stdin
+        .take()
+        .and_then(|mut stdin| stdin.write_all(format!("{request}\n").as_bytes()).ok());
+    let output = child.wait_with_output().ok()?;
+    (written.is_some() && output.status.success())
+        .then(|| String::from_utf8(output.stdout).ok())
+        .flatten()
+}
+
+/// Ask the configured helpers for a username and password for `url`.
+unsafe fn fill_from_helper(
+    repo: *mut raw::git_repository,
+    url: &str,
+    username: Option<&str>,
+) -> Result<Option<HelperCredential>, c_int> {
+    if !helper_configured(repo)? {
+        return Ok(None);
+    }
+    let Some(request) = credential_request(url, username) else {
+        return Ok(None);
+    };
+    let Some(response) = run_git_credential(repo, "fill", &request) else {
+        return Ok(None);
+    };
+    let mut credential = HelperCredential {
+        url: url.to_owned(),
+        username: String::new(),
+        password: String::new(),
+    };
+    for line in response.lines() {
+        match line.split_once('=') {
+            Some(("username", value)) => credential.username = value.to_owned(),
+            Some(("password", value)) => credential.password = value.to_owned(),
+            _ => {}
+        }
+    }
+    Ok((!credential.username.is_empty() && !credential.password.is_empty()).then_some(credential))
+}
+
+/// Tell the helpers to store (`approve`) or forget (`reject`) a credential.
+unsafe fn report_helper_credential(
+    repo: *mut raw::git_repository,
+    action: &str,
+    credential: &HelperCredential,
+) {
+    if let Some(request) = credential_request(&credential.url, Some(&credential.username)) {
+        let request = format!("{request}password={}\n", credential.password);
+        run_git_credential(repo, action, &request);
+    }
+}
+
+unsafe fn userpass_credential(
+    out: *mut *mut raw::git_credential,
+    credential: &HelperCredential,
+) -> c_int {
+    let (Ok(username), Ok(password)) = (
+        CString::new(credential.username.as_str()),
+        CString::new(credential.password.as_str()),
+    ) else {
+        return 1;
+    };
+    raw::git_credential_userpass_plaintext_new(out, username.as_ptr(), password.as_ptr())
+}
+
+/// Ask the helpers configured in `credential.helper` for a username and
+/// password for `url` by running `git credential fill`, reading the
+/// configuration of `repo` (or, if NULL, the global, XDG and system files).
+/// Returns 0 with a plaintext credential in `out`, or 1 if no helper
+/// supplied both.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_credential_helper_fill(
+    out: *mut *mut raw::git_credential,
+    repo: *mut raw::git_repository,
+    url: *const c_char,
+    username_from_url: *const c_char,
+) -> c_int {
+    let Ok(url) = CStr::from_ptr(url).to_str() else {
+        return 1;
+    };
+    let username = if username_from_url.is_null() {
+        None
+    } else {
+        CStr::from_ptr(username_from_url).to_str().ok()
+    };
+    match fill_from_helper(repo, url, username) {
+        Ok(Some(credential)) => userpass_credential(out, &credential),
+        Ok(None) => 1,
+        Err(rc) => rc,
+    }
+}
+
+/// Forward a credentials request to `t`, running git's credential helpers
+/// when it answers `GIT2_SHIM_CREDENTIAL_ASK_GIT`. A second request for the
+/// URL the helpers just answered means their credential was rejected: it is
+/// passed to `git credential reject` and the request fails. A credential
+/// that is not asked about again is approved or rejected by
+/// `CredentialHelper::settle` when the operation ends.
+fn acquire_credential(
+    t: Option<Trampoline<git2_shim_credential_acquire_cb>>,
+    helper: &mut CredentialHelper,
+    out: *mut *mut raw::git_credential,
+    url: *const c_char,
+    username_from_url: *const c_char,
+    allowed_types: c_uint,
+) -> c_int {
+    let Some(t) = t else {
+        return raw::GIT_PASSTHROUGH;
+    };
+    let rc = (t.callback)(out, url, username_from_url, allowed_types, t.payload);
+    if rc != GIT2_SHIM_CREDENTIAL_ASK_GIT {
+        return rc;
+    }
+    if allowed_types & raw::GIT_CREDENTIAL_USERPASS_PLAINTEXT == 0 {
+        return 1;
+    }
+    let Ok(url) = unsafe { CStr::from_ptr(url) }.to_str() else {
+        return 1;
+    };
+    if let Some(rejected) = helper.filled.take() {
+        if rejected.url == url {
+            unsafe { report_helper_credential(helper.repo, "reject", &rejected) };
+            return 1;
+        }
+    }
+    let username = if username_from_url.is_null() {
+        None
+    } else {
+        unsafe { CStr::from_ptr(username_from_url) }.to_str().ok()
+    };
+    match unsafe { fill_from_helper(helper.repo, url, username) } {
+        Ok(Some(credential)) => {
+            let rc = unsafe { userpass_credential(out, &credential) };
+            if rc == 0 {
+                helper.filled = Some(credential);
+            }
+            rc
+        }
+        Ok(None) => 1,
+        Err(rc) => rc,
+    }
+}
+
+// =============================================================================
+// Certificates
+// =============================================================================
+
+/// `GIT_CERT_*` kind: X509 (1) for HTTPS, HOSTKEY_LIBSSH2 (2) for SSH.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_cert_type(cert: *const raw::git_cert) -> c_int {
+    (*cert).cert_type
+}
+
+/// DER-encoded X.509 certificate; NULL if `cert` is not X.509.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_cert_x509_data(
+    cert: *const raw::git_cert,
+    len_out: *mut size_t,
+) -> *const u8 {
+    if (*cert).cert_type != raw::GIT_CERT_X509 {
+        return ptr::null();
+    }
+    let x509 = &*(cert as *const raw::git_cert_x509);
+    *len_out = x509.len;
+    x509.data as *const u8
+}
+
+unsafe fn cert_hostkey<'a>(cert: *const raw::git_cert) -> Option<&'a raw::git_cert_hostkey> {
+    if (*cert).cert_type != raw::GIT_CERT_HOSTKEY_LIBSSH2 {
+        return None;
+    }
+    Some(&*(cert as *const raw::git_cert_hostkey))
+}
+
+/// Host key hash of the kind selected by `GIT_CERT_SSH_MD5` (16 bytes),
+/// `SHA1` (20), or `SHA256` (32); NULL if `cert` is not an SSH host key or
+/// that hash is unavailable.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_cert_hostkey_hash(
+    cert: *const raw::git_cert,
+    kind: c_uint,
+) -> *const u8 {
+    let Some(hostkey) = cert_hostkey(cert) else {
+        return ptr::null();
+    };
+    if hostkey.type_ & kind == 0 {
+        return ptr::null();
+    }
+    match kind {
+        raw::GIT_CERT_SSH_MD5 => hostkey.hash_md5.as_ptr(),
+        raw::GIT_CERT_SSH_SHA1 => hostkey.hash_sha1.as_ptr(),
+        raw::GIT_CERT_SSH_SHA256 => hostkey.hash_sha256.as_ptr(),
+        _ => ptr::null(),
+    }
+}
+
+/// Raw host key blob, as in `known_hosts`, with its
+/// `GIT_CERT_SSH_RAW_TYPE_*` in `type_out`; NULL if unavailable.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_cert_hostkey_raw(
+    cert: *const raw::git_cert,
+    len_out: *mut size_t,
+    type_out: *mut c_uint,
+) -> *const c_char {
+    match cert_hostkey(cert) {
+        Some(hostkey) if hostkey.type_ & raw::GIT_CERT_SSH_RAW != 0 => {
+            *len_out = hostkey.hostkey_len;
+            *type_out = hostkey.raw_type;
+            hostkey.hostkey
+        }
+        _ => ptr::null(),
+    }
+}
+
+// =============================================================================
+// Remote connections
+// =============================================================================
+
+/// Options for `git2_shim_remote_connect`.
+#[derive(Clone)]
+pub struct ConnectOptions {
+    raw: raw::git_remote_connect_options,
+    callbacks: RemoteCallbacks,
+    proxy: Option<ProxyOptions>,
+    custom_headers: StrArray,
+}
+
+impl ConnectOptions {
+    /// Snapshot the options for a libgit2 call; `self` must outlive the call.
+    fn raw_opts(&mut self) -> raw::git_remote_connect_options {
+        let mut opts = self.raw;
+        self.callbacks.fill(&mut opts.callbacks);
+        opts.custom_headers = self.custom_headers.as_raw();
+        if let Some(proxy) = self.proxy.as_mut() {
+            opts.proxy_opts = proxy.raw_opts();
+        }
+        opts
+    }
+
+    /// Settle the credentials the call returning `rc` used; returns `rc`.
+    unsafe fn settle(&mut self, rc: c_int) -> c_int {
+        self.callbacks.helper.settle(rc);
+        if let Some(proxy) = self.proxy.as_mut() {
+            proxy.helper.settle(rc);
+        }
+        rc
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_connect_options_new() -> *mut ConnectOptions {
+    let mut opts = Box::new(ConnectOptions {
+        raw: std::mem::zeroed(),
+        callbacks: RemoteCallbacks::default(),
+        proxy: None,
+        custom_headers: StrArray::default(),
+    });
+    if raw::git_remote_connect_options_init(&mut opts.raw, raw::GIT_REMOTE_CONNECT_OPTIONS_VERSION)
+        < 0
+    {
+        return ptr::null_mut();
+    }
+    Box::into_raw(opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_connect_options_free(opts: *mut ConnectOptions) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// Callbacks for the connection; copied. NULL clears them.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_connect_options_set_callbacks(
+    opts: *mut ConnectOptions,
+    callbacks: *const RemoteCallbacks,
+) {
+    (*opts).callbacks = callbacks.as_ref().cloned().unwrap_or_default();
+}
+
+/// Proxy to connect through; copied. NULL connects directly.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_connect_options_set_proxy_options(
+    opts: *mut ConnectOptions,
+    proxy: *const ProxyOptions,
+) {
+    (*opts).proxy = proxy.as_ref().cloned();
+}
+
+/// Extra HTTP headers sent with every request of the connection; copied.
+/// NULL sends none.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_connect_options_set_custom_headers(
+    opts: *mut ConnectOptions,
+    headers: *const *const c_char,
+    count: size_t,
+) {
+    (*opts).custom_headers = StrArray::from_raw(headers, count);
+}
+
+/// Same values as `git2_shim_fetch_options_set_follow_redirects`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_connect_options_set_follow_redirects(
+    opts: *mut ConnectOptions,
+    follow_redirects: c_uint,
+) {
+    (*opts).raw.follow_redirects = follow_redirects;
+}
+
+/// Open a connection in `direction` (`GIT_DIRECTION_FETCH` 0 or `PUSH` 1)
+/// without transferring anything. `opts` may be NULL for the defaults;
+/// libgit2 keeps calling its callbacks until `git2_shim_remote_disconnect`,
+/// so it must stay alive until then.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_connect(
+    remote: *mut raw::git_remote,
+    direction: c_int,
+    opts: *mut ConnectOptions,
+) -> c_int {
+    let raw_opts = opts.as_mut().map(|o| o.raw_opts());
+    let rc = raw::git_remote_connect_ext(
+        remote,
+        direction,
+        raw_opts.as_ref().map_or(ptr::null(), |o| o as *const _),
+    );
+    opts.as_mut().map_or(rc, |o| o.settle(rc))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_connected(remote: *const raw::git_remote) -> c_int {
+    raw::git_remote_connected(remote)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_disconnect(remote: *mut raw::git_remote) -> c_int {
+    raw::git_remote_disconnect(remote)
+}
+
+/// Delete remote-tracking refs whose branch no longer exists on the
+/// connected remote. Each deletion is reported through the `update_tips`
+/// callback of `callbacks` (may be NULL) with a zero new id.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_prune(
+    remote: *mut raw::git_remote,
+    callbacks: *mut RemoteCallbacks,
+) -> c_int {
+    let raw_callbacks = raw_remote_callbacks(callbacks);
+    raw::git_remote_prune(remote, &raw_callbacks)
+}
+
+/// Non-zero if `remote.<name>.prune` asks fetches to prune by default.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_prune_refs(remote: *const raw::git_remote) -> c_int {
+    raw::git_remote_prune_refs(remote)
+}
+
+/// Refs advertised by the connected remote, like `git ls-remote`. The array
+/// and heads are owned by the remote and valid until it disconnects.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_ls(
+    out: *mut *mut *const raw::git_remote_head,
+    size: *mut size_t,
+    remote: *mut raw::git_remote,
+) -> c_int {
+    raw::git_remote_ls(out, size, remote)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_head_name(
+    head: *const raw::git_remote_head,
+) -> *const c_char {
+    (*head).name
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_head_oid(
+    head: *const raw::git_remote_head,
+) -> *const raw::git_oid {
+    &(*head).oid
+}
+
+/// Target of a symbolic ref such as `HEAD`, if the server advertised it.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_remote_head_symref_target(
+    head: *const raw::git_remote_head,
+) -> *const c_char {
+    (*head).symref_target
+}
+
+// =============================================================================
+// Refspecs
+// =============================================================================
+
+/// Free the contents of a `git_buf` filled in by libgit2.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_buf_dispose(buf: *mut raw::git_buf) {
+    raw::git_buf_dispose(buf)
+}
+
+/// Parse `input` (e.g. `"+refs/heads/*:refs/remotes/origin/*"`) as a fetch
+/// refspec if `is_fetch` is non-zero, otherwise as a push refspec.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_refspec_parse(
+    out: *mut *mut raw::git_refspec,
+    input: *const c_char,
+    is_fetch: c_int,
+) -> c_int {
+    raw::git_refspec_parse(out, input, is_fetch)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_refspec_free(refspec: *mut raw::git_refspec) {
+    raw::git_refspec_free(refspec)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_refspec_src(refspec: *const raw::git_refspec) -> *const c_char {
+    raw::git_refspec_src(refspec)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_refspec_dst(refspec: *const raw::git_refspec) -> *const c_char {
+    raw::git_refspec_dst(refspec)
+}
+
+/// The refspec as originally written.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_refspec_string(
+    refspec: *const raw::git_refspec,
+) -> *const c_char {
+    raw::git_refspec_string(refspec)
+}
+
+/// Non-zero for a forced (`+`) refspec.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_refspec_force(refspec: *const raw::git_refspec) -> c_int {
+    raw::git_refspec_force(refspec)
+}
+
+/// `GIT_DIRECTION_FETCH` (0) or `GIT_DIRECTION_PUSH` (1).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_refspec_direction(refspec: *const raw::git_refspec) -> c_int {
+    raw::git_refspec_direction(refspec)
+}
+
+/// Non-zero if `refname` matches the source side.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_refspec_src_matches(
+    refspec: *const raw::git_refspec,
+    refname: *const c_char,
+) -> c_int {
+    raw::git_refspec_src_matches(refspec, refname)
+}
+
+/// Non-zero if `refname` matches the destination side.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_refspec_dst_matches(
+    refspec: *const raw::git_refspec,
+    refname: *const c_char,
+) -> c_int {
+    raw::git_refspec_dst_matches(refspec, refname)
+}
+
+/// Map a source ref to its destination, e.g. `refs/heads/foo` to
+/// `refs/remotes/origin/foo`. Dispose `out` with `git2_shim_buf_dispose`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_refspec_transform(
+    out: *mut raw::git_buf,
+    refspec: *const raw::git_refspec,
+    name: *const c_char,
+) -> c_int {
+    raw::git_refspec_transform(out, refspec, name)
+}
+
+/// Map a destination ref back to its source.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_refspec_rtransform(
+    out: *mut raw::git_buf,
+    refspec: *const raw::git_refspec,
+    name: *const c_char,
+) -> c_int {
+    raw::git_refspec_rtransform(out, refspec, name)
+}
+
+// =============================================================================
+// Library settings
+// =============================================================================
+
+/// Process-wide limit, in milliseconds, on establishing a connection to a
+/// remote server; 0 uses the system default, typically about 75 seconds,
+/// which most systems will not let it exceed.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_set_server_connect_timeout(timeout_ms: c_int) -> c_int {
+    raw::git_libgit2_opts(raw::GIT_OPT_SET_SERVER_CONNECT_TIMEOUT, timeout_ms)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_get_server_connect_timeout(timeout_ms: *mut c_int) -> c_int {
+    raw::git_libgit2_opts(raw::GIT_OPT_GET_SERVER_CONNECT_TIMEOUT, timeout_ms)
+}
+
+/// Process-wide limit, in milliseconds, on waiting for a connected server to
+/// send or accept data; 0 waits forever. A stalled fetch or push then fails
+/// with a timeout error instead of hanging.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_set_server_timeout(timeout_ms: c_int) -> c_int {
+    raw::git_libgit2_opts(raw::GIT_OPT_SET_SERVER_TIMEOUT, timeout_ms)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_get_server_timeout(timeout_ms: *mut c_int) -> c_int {
+    raw::git_libgit2_opts(raw::GIT_OPT_GET_SERVER_TIMEOUT, timeout_ms)
+}
+
+/// Process-wide directories searched for the configuration file of `level`
+/// (1 programdata, 2 system, 3 XDG, 4 global), and for the shared
+/// attributes and ignore files found alongside it. `path` lists
+/// directories separated by `:` (`;` on Windows), may include `$PATH` for
+/// the previous value, and NULL restores the default derived from the
+/// environment. Affects configurations opened afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_set_search_path(level: c_int, path: *const c_char) -> c_int {
+    raw::git_libgit2_opts(raw::GIT_OPT_SET_SEARCH_PATH, level, path)
+}
+
+/// Current search path for `level`; dispose `out` with
+/// `git2_shim_buf_dispose`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_get_search_path(level: c_int, out: *mut raw::git_buf) -> c_int {
+    raw::git_libgit2_opts(raw::GIT_OPT_GET_SEARCH_PATH, level, out)
+}
+
+// =============================================================================
+// Custom transports
+// =============================================================================
+
+/// Transport factory: store a new `git_transport` for `owner` in `out`.
+/// The transport is a C vtable (`struct git_transport` in
+/// `git2/sys/transport.h`, `GIT_TRANSPORT_VERSION` 1) that the caller lays
+/// out and implements itself; libgit2 destroys it through its `free` slot.
+pub type git2_shim_transport_cb = raw::git_transport_cb;
+
+/// Route URLs starting with `prefix` (e.g. `"ipc://"`) to `callback`.
+/// Process-wide; `param` is passed to every call and must stay valid until
+/// the prefix is unregistered. Fails with `GIT_EEXISTS` if the prefix is
+/// already taken.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_transport_register(
+    prefix: *const c_char,
+    callback: git2_shim_transport_cb,
+    param: *mut c_void,
+) -> c_int {
+    raw::git_transport_register(prefix, callback, param)
+}
+
+/// Remove a transport added with `git2_shim_transport_register`; fails with
+/// `GIT_ENOTFOUND` if none is registered for `prefix`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_transport_unregister(prefix: *const c_char) -> c_int {
+    raw::git_transport_unregister(prefix)
+}
+
+// =============================================================================
+// Smart subtransports
+// =============================================================================
+
+/// Subtransport factory: store a new `git_smart_subtransport` (the vtable in
+/// `git2/sys/transport.h`) for the smart transport `owner` in `out`.
+pub type git2_shim_smart_subtransport_cb = raw::git_smart_subtransport_cb;
+
+/// A subtransport to register under a URL prefix. libgit2 speaks the git
+/// protocol itself and only asks the subtransport for byte streams.
+pub struct SmartSubtransport {
+    raw: raw::git_smart_subtransport_definition,
+}
+
+/// `rpc` is non-zero for a stateless protocol, where every request opens a
+/// fresh stream (like HTTP), and zero for one long-lived stream (like
+/// `git://` or SSH). `param` is passed to every `callback` call.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_smart_subtransport_new(
+    callback: git2_shim_smart_subtransport_cb,
+    rpc: c_uint,
+    param: *mut c_void,
+) -> *mut SmartSubtransport {
+    Box::into_raw(Box::new(SmartSubtransport {
+        raw: raw::git_smart_subtransport_definition {
+            callback,
+            rpc,
+            param,
+        },
+    }))
+}
+
+/// Only free after unregistering its prefix with
+/// `git2_shim_transport_unregister`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_smart_subtransport_free(subtransport: *mut SmartSubtransport) {
+    if !subtransport.is_null() {
+        drop(Box::from_raw(subtransport));
+    }
+}
+
+extern "C" fn smart_transport_trampoline(
+    out: *mut *mut raw::git_transport,
+    owner: *mut raw::git_remote,
+    param: *mut c_void,
+) -> c_int {
+    unsafe { raw::git_transport_smart(out, owner, param) }
+}
+
+/// Run the smart protocol over `subtransport` for URLs starting with
+/// `prefix`; libgit2 borrows `subtransport` until the prefix is
+/// unregistered.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_smart_subtransport_register(
+    prefix: *const c_char,
+    subtransport: *mut SmartSubtransport,
+) -> c_int {
+    raw::git_transport_register(
+        prefix,
+        smart_transport_trampoline,
+        &mut (*subtransport).raw as *mut _ as *mut c_void,
+    )
+}
+
+/// Ask the remote's credentials callback for a credential of one of the
+/// `methods` (`GIT_CREDENTIAL_*` bits), on behalf of a subtransport whose
+/// stream needs authentication. `transport` is the factory's `owner`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_transport_smart_credentials(
+    out: *mut *mut raw::git_credential,
+    transport: *mut raw::git_transport,
+    user: *const c_char,
+    methods: c_int,
+) -> c_int {
+    raw::git_transport_smart_credentials(out, transport, user, methods)
+}
+
+/// Run the remote's certificate check for a subtransport that established
+/// its own TLS or SSH session; `valid` is the subtransport's own verdict.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_transport_smart_certificate_check(
+    transport: *mut raw::git_transport,
+    cert: *mut raw::git_cert,
+    valid: c_int,
+    hostname: *const c_char,
+) -> c_int {
+    raw::git_transport_smart_certificate_check(transport, cert, valid, hostname)
+}
+
+// =============================================================================
+// FETCH_HEAD
+// =============================================================================
+
+/// Callback invoked once per FETCH_HEAD line, in file order. `ref_name`
+/// may be NULL (the line for a fetched HEAD has no ref name), and so may
+/// `remote_url` (older FETCH_HEAD files). `is_merge` is non-zero for the
+/// refs a following `git pull` would merge.
+///
+/// Return 0 to continue iterating; any other value stops the walk and is
+/// returned from `git2_shim_repository_fetchhead_foreach`.
+pub type git2_shim_fetchhead_cb = extern "C" fn(
+    ref_name: *const c_char,
+    remote_url: *const c_char,
+    oid: *const raw::git_oid,
+    is_merge: c_uint,
+    payload: *mut c_void,
+) -> c_int;
+
+extern "C" fn fetchhead_trampoline(
+    ref_name: *const c_char,
+    remote_url: *const c_char,
+    oid: *const raw::git_oid,
+    is_merge: c_uint,
+    payload: *mut c_void,
+) -> c_int {
+    let t = unsafe { &*(payload as *const Trampoline<git2_shim_fetchhead_cb>) };
+    (t.callback)(ref_name, remote_url, oid, is_merge, t.payload)
+}
+
+/// Walk the refs recorded by the last fetch; fails with `GIT_ENOTFOUND`
+/// if there is no FETCH_HEAD.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_fetchhead_foreach(
+    repo: *mut raw::git_repository,
+    callback: git2_shim_fetchhead_cb,
+    payload: *mut c_void,
+) -> c_int {
+    let mut t = Trampoline { callback, payload };
+    raw::git_repository_fetchhead_foreach(
+        repo,
+        fetchhead_trampoline,
+        &mut t as *mut _ as *mut c_void,
+    )
+}
+
+// =============================================================================
+// Maintenance
+// =============================================================================
+
+/// What `git2_shim_maintenance_run` does. Both steps are on by default, and
+/// the prune cutoff defaults to two weeks before the run starts, like git's
+/// `gc.pruneExpire`.
+#[derive(Clone)]
+pub struct MaintenanceOptions {
+    repack: bool,
+    prune: bool,
+    prune_expire: Option<SystemTime>,
+}
+
+/// Counters filled in by `git2_shim_maintenance_run`.
+#[repr(C)]
+#[derive(Default)]
+pub struct git2_shim_maintenance_stats {
+    /// Reachable objects written to the new pack.
+    pub objects_packed: size_t,
+    /// Old packs deleted because the new pack holds all of their objects.
+    pub packs_removed: size_t,
+    /// Loose objects deleted because the new pack holds them.
+    pub loose_removed: size_t,
+    /// Unreachable loose objects deleted for being older than the cutoff.
+    pub loose_pruned: size_t,
+}
+
+#[no_mangle]
+pub extern "C" fn git2_shim_maintenance_options_new() -> *mut MaintenanceOptions {
+    Box::into_raw(Box::new(MaintenanceOptions::default()))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_maintenance_options_free(opts: *mut MaintenanceOptions) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// Pack every reachable object into one new pack, then delete the loose
+/// copies and any older pack (without a `.keep` file) it makes redundant.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_maintenance_options_set_repack(
+    opts: *mut MaintenanceOptions,
+    repack: c_int,
+) {
+    (*opts).repack = repack != 0;
+}
+
+/// Delete unreachable loose objects last modified before the cutoff.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_maintenance_options_set_prune(
+    opts: *mut MaintenanceOptions,
+    prune: c_int,
+) {
+    (*opts).prune = prune != 0;
+}
+
+/// Prune cutoff as seconds since the Unix epoch. Keeping a margin protects
+/// objects that a concurrent writer has created but not yet referenced.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_maintenance_options_set_prune_expire(
+    opts: *mut MaintenanceOptions,
+    expire: i64,
+) {
+    (*opts).prune_expire = Some(if expire >= 0 {
+        UNIX_EPOCH + Duration::from_secs(expire as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(expire.unsigned_abs())
+    });
+}
+
+impl Default for MaintenanceOptions {
+    fn default() -> MaintenanceOptions {
+        MaintenanceOptions {
+            repack: true,
+            prune: true,
+            prune_expire: None,
+        }
+    }
+}
+
+type ObjectId = [u8; raw::GIT_OID_RAWSZ];
+
+/// Objects reachable from refs, HEAD, reflogs and the index, in the order
+/// they were found.
+#[derive(Default)]
+struct Reachable {
+    seen: HashSet<ObjectId>,
+    ids: Vec<ObjectId>,
+}
+
+impl Reachable {
+    /// Record `id`; false if it was already known.
+    fn insert(&mut self, id: ObjectId) -> bool {
+        let new = self.seen.insert(id);
+        if new {
+            self.ids.push(id);
+        }
+        new
+    }
+}
+
+/// Ref tips and names gathered by `collect_ref`.
+#[derive(Default)]
+struct RefScan {
+    tips: Vec<ObjectId>,
+    names: Vec<CString>,
+}
+
+extern "C" fn collect_ref(reference: *mut raw::git_reference, payload: *mut c_void) -> c_int {
+    let scan = unsafe { &mut *(payload as *mut RefScan) };
+    unsafe {
+        scan.names
+            .push(CStr::from_ptr(raw::git_reference_name(reference)).to_owned());
+        let mut resolved = ptr::null_mut();
+        if raw::git_reference_resolve(&mut resolved, reference) == 0 {
+            if let Some(id) = raw::git_reference_target(resolved).as_ref() {
+                scan.tips.push(id.id);
+            }
+            raw::git_reference_free(resolved);
+        }
+        raw::git_reference_free(reference);
+    }
+    0
+}
+
+extern "C" fn collect_odb_id(id: *const raw::git_oid, payload: *mut c_void) -> c_int {
+    let ids = unsafe { &mut *(payload as *mut Vec<ObjectId>) };
+    ids.push(unsafe { (*id).id });
+    0
+}
+
+/// Push the ids recorded in `name`'s reflog onto `tips`; a missing reflog
+/// adds nothing.
+unsafe fn push_reflog_ids(repo: *mut raw::git_repository, name: &CStr, tips: &mut Vec<ObjectId>) {
+    let mut reflog = ptr::null_mut();
+    if raw::git_reflog_read(&mut reflog, repo, name.as_ptr()) < 0 {
+        return;
+    }
+    for i in 0..raw::git_reflog_entrycount(reflog) {
+        let entry = raw::git_reflog_entry_byindex(reflog, i);
+        for id in [
+            raw::git_reflog_entry_id_old(entry),
+            raw::git_reflog_entry_id_new(entry),
+        ] {
+            if (*id).id != [0; raw::GIT_OID_RAWSZ] {
+                tips.push((*id).id);
+            }
+        }
+    }
+    raw::git_reflog_free(reflog);
+}
+
+/// Roots private to `repo`'s worktree: its HEAD, the HEAD reflog and the
+/// blobs and trees staged in its index.
+unsafe fn add_worktree_roots(
+    repo: *mut raw::git_repository,
+    tips: &mut Vec<ObjectId>,
+    reachable: &mut Reachable,
+) {
+    let mut head = raw::git_oid {
+        id: [0; raw::GIT_OID_RAWSZ],
+    };
+    if raw::git_reference_name_to_id(&mut head, repo, c"HEAD".as_ptr()) == 0 {
+        tips.push(head.id);
+    }
+    push_reflog_ids(repo, c"HEAD", tips);
+
+    let mut index = ptr::null_mut();
+    let mut odb = ptr::null_mut();
+    if raw::git_repository_index(&mut index, repo) == 0 {
+        if raw::git_repository_odb(&mut odb, repo) == 0 {
+            for i in 0..raw::git_index_entrycount(index) {
+                let entry = &*raw::git_index_get_byindex(index, i);
+                // Intent-to-add entries name blobs that were never written.
+                if entry.mode != raw::GIT_FILEMODE_COMMIT
+                    && raw::git_odb_exists(odb, &entry.id) != 0
+                {
+                    reachable.insert(entry.id.id);
+                }
+            }
+            raw::git_odb_free(odb);
+        }
+        raw::git_index_free(index);
+    }
+}
+
+/// Worktree roots of every other checkout sharing `repo`'s object store:
+/// each linked worktree and, when `repo` is itself linked, the main one.
+/// Linked worktrees that fail `git_worktree_validate` (e.g. their directory
+/// was deleted) are skipped; any other that cannot be opened is an error,
+/// since its roots would be lost.
+unsafe fn add_other_worktree_roots(
+    repo: *mut raw::git_repository,
+    tips: &mut Vec<ObjectId>,
+    reachable: &mut Reachable,
+) -> Result<(), c_int> {
+    if raw::git_repository_is_worktree(repo) == 1 {
+        let mut main = ptr::null_mut();
+        let rc = raw::git_repository_open(&mut main, raw::git_repository_commondir(repo));
+        if rc < 0 {
+            return Err(rc);
+        }
+        add_worktree_roots(main, tips, reachable);
+        raw::git_repository_free(main);
+    }
+
+    let mut names: raw::git_strarray = std::mem::zeroed();
+    let rc = raw::git_worktree_list(&mut names, repo);
+    if rc < 0 {
+        return Err(rc);
+    }
+    let mut result = Ok(());
+    for i in 0..names.count {
+        let mut worktree = ptr::null_mut();
+        let mut rc = raw::git_worktree_lookup(&mut worktree, repo, *names.strings.add(i));
+        let mut linked = ptr::null_mut();
+        if rc == 0 {
+            if raw::git_worktree_validate(worktree) < 0 {
+                raw::git_worktree_free(worktree);
+                continue;
+            }
+            rc = raw::git_repository_open_from_worktree(&mut linked, worktree);
+            raw::git_worktree_free(worktree);
+        }
+        if rc < 0 {
+            result = Err(rc);
+            break;
+        }
+        add_worktree_roots(linked, tips, reachable);
+        raw::git_repository_free(linked);
+    }
+    raw::git_strarray_dispose(&mut names);
+    result
+}
+
+/// Every object id that must survive maintenance. Reflog entries and the
+/// index count as roots, as they do for `git gc`, and so do the HEAD and
+/// index of every worktree sharing the object store.
+unsafe fn find_reachable(repo: *mut raw::git_repository) -> Result<Reachable, c_int> {
+    let mut scan = RefScan::default();
+    let rc = raw::git_reference_foreach(repo, collect_ref, &mut scan as *mut _ as *mut c_void);
+    if rc < 0 {
+        return Err(rc);
+    }
+    for name in &scan.names {
+        push_reflog_ids(repo, name, &mut scan.tips);
+    }
+
+    let mut reachable = Reachable::default();
+    add_worktree_roots(repo, &mut scan.tips, &mut reachable);
+    add_other_worktree_roots(repo, &mut scan.tips, &mut reachable)?;
+
+    let mut walk = ptr::null_mut();
+    let rc = raw::git_revwalk_new(&mut walk, repo);
+    if rc < 0 {
+        return Err(rc);
+    }
+    let result = mark_reachable(repo, walk, &scan.tips, &mut reachable);
+    raw::git_revwalk_free(walk);
+    result.map(|()| reachable)
+}
+
+/// Mark `tips` and everything they lead to: tag targets, commit history,
+/// and the trees and blobs of every commit. Submodule commits are skipped.
+unsafe fn mark_reachable(
+    repo: *mut raw::git_repository,
+    walk: *mut raw::git_revwalk,
+    tips: &[ObjectId],
+    reachable: &mut Reachable,
+) -> Result<(), c_int> {
+    let mut trees = Vec::new();
+    for tip in tips {
+        let mut id = raw::git_oid { id: *tip };
+        loop {
+            let mut object = ptr::null_mut();
+            if raw::git_object_lookup(&mut object, repo, &id, raw::GIT_OBJECT_ANY) < 0 {
+                break;
+            }
+            let kind = raw::git_object_type(object);
+            let mut peeled = None;
+            match kind {
+                raw::GIT_OBJECT_TAG if reachable.insert(id.id) => {
+                    peeled = Some((*raw::git_tag_target_id(object as *const raw::git_tag)).id);
+                }
+                raw::GIT_OBJECT_COMMIT => {
+                    let rc = raw::git_revwalk_push(walk, &id);
+                    if rc < 0 {
+                        raw::git_object_free(object);
+                        return Err(rc);
+                    }
+                }
+                raw::GIT_OBJECT_TREE => trees.push(id.id),
+                raw::GIT_OBJECT_BLOB => {
+                    reachable.insert(id.id);
+                }
+                _ => {}
+            }
+            raw::git_object_free(object);
+            match peeled {
+                Some(target) => id.id = target,
+                None => break,
+            }
+        }
+    }
+
+    let mut id = raw::git_oid {
+        id: [0; raw::GIT_OID_RAWSZ],
+    };
+    loop {
+        let rc = raw::git_revwalk_next(&mut id, walk);
+        if rc == raw::GIT_ITEROVER {
+            break;
+        }
+        if rc < 0 {
+            return Err(rc);
+        }
+        reachable.insert(id.id);
+        let mut commit = ptr::null_mut();
+        let rc = raw::git_commit_lookup(&mut commit, repo, &id);
+        if rc < 0 {
+            return Err(rc);
+        }
+        trees.push((*raw::git_commit_tree_id(commit)).id);
+        raw::git_commit_free(commit);
+    }
+
+    while let Some(tree_id) = trees.pop() {
+        if !reachable.insert(tree_id) {
+            continue;
+        }
+        let mut tree = ptr::null_mut();
+        let rc = raw::git_tree_lookup(&mut tree, repo, &raw::git_oid { id: tree_id });
+        if rc < 0 {
+            return Err(rc);
+        }
+        for i in 0..raw::git_tree_entrycount(tree) {
+            let entry = raw::git_tree_entry_byindex(tree, i);
+            let entry_id = (*raw::git_tree_entry_id(entry)).id;
+            match raw::git_tree_entry_type(entry) {
+                raw::GIT_OBJECT_TREE => trees.push(entry_id),
+                raw::GIT_OBJECT_BLOB => {
+                    reachable.insert(entry_id);
+                }
+                _ => {}
+            }
+        }
+        raw::git_tree_free(tree);
+    }
+    Ok(())
+}
+
+/// Record an I/O failure as this thread's libgit2 error.
+fn os_error(err: std::io::Error) -> c_int {
+    let message = CString::new(err.to_string()).unwrap_or_default();
+    unsafe { raw::git_error_set_str(raw::GIT_ERROR_OS, message.as_ptr()) };
+    -1
+}
+
+unsafe fn objects_dir(repo: *mut raw::git_repository) -> Result<PathBuf, c_int> {
+    let mut buf: raw::git_buf = std::mem::zeroed();
+    let rc = raw::git_repository_item_path(&mut buf, repo, raw::GIT_REPOSITORY_ITEM_OBJECTS);
+    if rc < 0 {
+        return Err(rc);
+    }
+    let path = CStr::from_ptr(buf.ptr).to_str().map(PathBuf::from);
+    raw::git_buf_dispose(&mut buf);
+    path.map_err(|_| os_error(std::io::Error::other("objects path is not UTF-8")))
+}
+
+/// Ids of every object in the pack indexed by `idx`, or None if it cannot
+/// be read.
+unsafe fn pack_contents(idx: &Path) -> Option<Vec<ObjectId>> {
+    let idx = CString::new(idx.to_str()?).ok()?;
+    let mut odb = ptr::null_mut();
+    if raw::git_odb_new(&mut odb) < 0 {
+        return None;
+    }
+    let mut ids = Vec::new();
+    let mut backend = ptr::null_mut();
+    let ok = raw::git_odb_backend_one_pack(&mut backend, idx.as_ptr()) == 0
+        && add_odb_backend(odb, backend, 1) == 0
+        && raw::git_odb_foreach(odb, collect_odb_id, &mut ids as *mut _ as *mut c_void) == 0;
+    raw::git_odb_free(odb);
+    ok.then_some(ids)
+}
+
+/// Hand `backend` over to `odb`, freeing it if `odb` does not take it.
+unsafe fn add_odb_backend(
+    odb: *mut raw::git_odb,
+    backend: *mut raw::git_odb_backend,
+    priority: c_int,
+) -> c_int {
+    let rc = raw::git_odb_add_backend(odb, backend, priority);
+    if rc < 0 {
+        if let Some(free) = (*backend).free {
+            free(backend);
+        }
+    }
+    rc
+}
+
+fn remove_if_exists(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+/// Write one pack holding every reachable object; returns its
+/// `pack-<hash>` file stem, or None when there was nothing to pack.
+unsafe fn write_pack(
+    repo: *mut raw::git_repository,
+    reachable: &Reachable,
+) -> Result<Option<String>, c_int> {
+    if reachable.ids.is_empty() {
+        return Ok(None);
+    }
+    let mut pb = ptr::null_mut();
+    let rc = raw::git_packbuilder_new(&mut pb, repo);
+    if rc < 0 {
+        return Err(rc);
+    }
+    let mut rc = 0;
+    for id in &reachable.ids {
+        rc = raw::git_packbuilder_insert(pb, &raw::git_oid { id: *id }, ptr::null());
+        if rc < 0 {
+            break;
+        }
+    }
+    if rc >= 0 {
+        rc = raw::git_packbuilder_write(pb, ptr::null(), 0, None, ptr::null_mut());
+    }
+    let name = if rc >= 0 {
+        Some(format!(
+            "pack-{}",
+            CStr::from_ptr(raw::git_packbuilder_name(pb)).to_string_lossy()
+        ))
+    } else {
+        None
+    };
+    raw::git_packbuilder_free(pb);
+    if rc < 0 {
+        return Err(rc);
+    }
+    Ok(name)
+}
+
+/// Delete packs other than `new_pack` whose objects it all contains.
+/// Packs with a `.keep` file or unreachable objects are left alone.
+unsafe fn remove_redundant_packs(
+    pack_dir: &Path,
+    new_pack: &str,
+    reachable: &Reachable,
+) -> Result<size_t, c_int> {
+    let mut removed = 0;
+    for entry in std::fs::read_dir(pack_dir).map_err(os_error)? {
+        let idx = entry.map_err(os_error)?.path();
+        if idx.extension().is_none_or(|ext| ext != "idx")
+            || idx.file_stem().is_some_and(|stem| stem == new_pack)
+            || idx.with_extension("keep").exists()
+        {
+            continue;
+        }
+        let Some(ids) = pack_contents(&idx) else {
+            continue;
+        };
+        if ids.iter().all(|id| reachable.seen.contains(id)) {
+            for ext in ["idx", "pack", "rev", "bitmap", "mtimes"] {
+                remove_if_exists(&idx.with_extension(ext)).map_err(os_error)?;
+            }
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Replace `objects/pack/multi-pack-index`, if there is one, with an index
+/// of the packs left on disk. The old file names deleted packs, so it goes
+/// first; a fresh odb is used because the repository's caches those packs.
+unsafe fn rewrite_multi_pack_index(objects: &Path) -> Result<(), c_int> {
+    let midx = objects.join("pack").join("multi-pack-index");
+    if !midx.exists() {
+        return Ok(());
+    }
+    remove_if_exists(&midx).map_err(os_error)?;
+    let objects = objects
+        .to_str()
+        .and_then(|path| CString::new(path).ok())
+        .ok_or_else(|| os_error(std::io::Error::other("objects path is not UTF-8")))?;
+    let mut odb = ptr::null_mut();
+    let rc = raw::git_odb_new(&mut odb);
+    if rc < 0 {
+        return Err(rc);
+    }
+    let mut backend = ptr::null_mut();
+    let mut rc = raw::git_odb_backend_pack(&mut backend, objects.as_ptr());
+    if rc == 0 {
+        rc = add_odb_backend(odb, backend, 1);
+    }
+    if rc == 0 {
+        rc = raw::git_odb_write_multi_pack_index(odb);
+    }
+    raw::git_odb_free(odb);
+    if rc < 0 {
+        return Err(rc);
+    }
+    Ok(())
+}
+
+/// Object id from its 40-digit hex form.
+fn parse_object_id(hex: &str) -> Option<ObjectId> {
+    if hex.len() != raw::GIT_OID_RAWSZ * 2 {
+        return None;
+    }
+    let mut id = [0; raw::GIT_OID_RAWSZ];
+    for (i, byte) in id.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(id)
+}
+
+/// Delete loose objects now stored in the new pack (`packed`) and, if
+/// `prune_before` is set, unreachable ones last modified before it.
+fn clean_loose_objects(
+    objects: &Path,
+    reachable: &Reachable,
+    packed: bool,
+    prune_before: Option<SystemTime>,
+    stats: &mut git2_shim_maintenance_stats,
+) -> Result<(), c_int> {
+    for dir in std::fs::read_dir(objects).map_err(os_error)? {
+        let dir = dir.map_err(os_error)?;
+        let dir_name = dir.file_name();
+        let Some(dir_name) = dir_name.to_str().filter(|name| name.len() == 2) else {
+            continue;
+        };
+        let Ok(files) = std::fs::read_dir(dir.path()) else {
+            continue;
+        };
+        for file in files {
+            let file = file.map_err(os_error)?;
+            let Some(id) = file
+                .file_name()
+                .to_str()
+                .and_then(|f| parse_object_id(&format!("{dir_name}{f}")))
+            else {
+                continue;
+            };
+            let remove = if reachable.seen.contains(&id) {
+                packed
+            } else {
+                let modified = file.metadata().and_then(|m| m.modified());
+                prune_before.is_some_and(|cutoff| modified.is_ok_and(|t| t < cutoff))
+            };
+            if remove {
+                remove_if_exists(&file.path()).map_err(os_error)?;
+                if reachable.seen.contains(&id) {
+                    stats.loose_removed += 1;
+                } else {
+                    stats.loose_pruned += 1;
+                }
+            }
+        }
+        let _ = std::fs::remove_dir(dir.path());
+    }
+    Ok(())
+}
+
+unsafe fn run_maintenance(
+    repo: *mut raw::git_repository,
+    opts: &MaintenanceOptions,
+    stats: &mut git2_shim_maintenance_stats,
+) -> Result<(), c_int> {
+    if !opts.repack && !opts.prune {
+        return Ok(());
+    }
+    let objects = objects_dir(repo)?;
+    let reachable = find_reachable(repo)?;
+
+    let mut packed = false;
+    if opts.repack {
+        if let Some(new_pack) = write_pack(repo, &reachable)? {
+            stats.objects_packed = reachable.ids.len();
+            stats.packs_removed =
+                remove_redundant_packs(&objects.join("pack"), &new_pack, &reachable)?;
+            if stats.packs_removed > 0 {
+                rewrite_multi_pack_index(&objects)?;
+            }
+            packed = true;
+        }
+    }
+    let prune_before = opts.prune.then(|| {
+        opts.prune_expire
+            .unwrap_or_else(|| SystemTime::now() - Duration::from_secs(14 * 24 * 60 * 60))
+    });
+    clean_loose_objects(&objects, &reachable, packed, prune_before, stats)?;
+
+    let mut odb = ptr::null_mut();
+    if raw::git_repository_odb(&mut odb, repo) == 0 {
+        raw::git_odb_refresh(odb);
+        raw::git_odb_free(odb);
+    }
+    Ok(())
+}
+
+/// Repack and prune the repository's object store, like a daemon-friendly
+/// `git gc`: see `git2_shim_maintenance_options_set_repack` and `_set_prune`.
+/// `opts` may be NULL for the defaults and `stats` NULL to skip the counters.
+/// The HEADs and indexes of linked worktrees are roots too; invalid ones
+/// (see `git2_shim_worktree_validate`) are skipped, and this fails without
+/// touching anything if a valid one cannot be opened.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_maintenance_run(
+    repo: *mut raw::git_repository,
+    opts: *const MaintenanceOptions,
+    stats: *mut git2_shim_maintenance_stats,
+) -> c_int {
+    let opts = opts.as_ref().cloned().unwrap_or_default();
+    let mut counters = git2_shim_maintenance_stats::default();
+    let rc = match run_maintenance(repo, &opts, &mut counters) {
+        Ok(()) => 0,
+        Err(rc) => rc,
+    };
+    if let Some(stats) = stats.as_mut() {
+        *stats = counters;
+    }
+    rc
+}
+
+// =============================================================================
+// Bundles
+// =============================================================================
+
+const BUNDLE_V2_SIGNATURE: &str = "# v2 git bundle";
+const BUNDLE_V3_SIGNATURE: &str = "# v3 git bundle";
+
+fn object_id_hex(id: &ObjectId) -> String {
+    id.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Record a bundle problem as this thread's libgit2 error.
+fn bundle_error(message: &str) -> c_int {
+    let message = CString::new(message).unwrap_or_default();
+    unsafe { raw::git_error_set_str(raw::GIT_ERROR_INVALID, message.as_ptr()) };
+    -1
+}
+
+/// A ref named on the command line of `git bundle create`: its full name,
+/// the object it points at, the tags peeled on the way to its final target.
+struct BundleRef {
+    name: String,
+    id: ObjectId,
+    tags: Vec<ObjectId>,
+    target: ObjectId,
+    commit: bool,
+}
+
+unsafe fn resolve_bundle_ref(
+    repo: *mut raw::git_repository,
+    shorthand: *const c_char,
+) -> Result<BundleRef, c_int> {
+    let mut reference = ptr::null_mut();
+    // Full names such as "HEAD" are kept as given rather than resolved.
+    let mut rc = raw::git_reference_lookup(&mut reference, repo, shorthand);
+    if rc < 0 {
+        rc = raw::git_reference_dwim(&mut reference, repo, shorthand);
+    }
+    if rc < 0 {
+        return Err(rc);
+    }
+    let mut resolved = ptr::null_mut();
+    let rc = raw::git_reference_resolve(&mut resolved, reference);
+    let name = CStr::from_ptr(raw::git_reference_name(reference))
+        .to_string_lossy()
+        .into_owned();
+    raw::git_reference_free(reference);
+    if rc < 0 {
+        return Err(rc);
+    }
+    let id = (*raw::git_reference_target(resolved)).id;
+    raw::git_reference_free(resolved);
+
+    let mut bundle_ref = BundleRef {
+        name,
+        id,
+        tags: Vec::new(),
+        target: id,
+        commit: false,
+    };
+    loop {
+        let mut object = ptr::null_mut();
+        let rc = raw::git_object_lookup(
+            &mut object,
+            repo,
+            &raw::git_oid {
+                id: bundle_ref.target,
+            },
+            raw::GIT_OBJECT_ANY,
+        );
+        if rc < 0 {
+            return Err(rc);
+        }
+        let kind = raw::git_object_type(object);
+        if kind == raw::GIT_OBJECT_TAG {
+            bundle_ref.tags.push(bundle_ref.target);
+            bundle_ref.target = (*raw::git_tag_target_id(object as *const raw::git_tag)).id;
+        }
+        raw::git_object_free(object);
+        if kind != raw::GIT_OBJECT_TAG {
+            bundle_ref.commit = kind == raw::GIT_OBJECT_COMMIT;
+            return Ok(bundle_ref);
+        }
+    }
+}
+
+/// Commit a `basis` revspec excludes, with everything it can reach.
+unsafe fn resolve_bundle_basis(
+    repo: *mut raw::git_repository,
+    spec: *const c_char,
+) -> Result<ObjectId, c_int> {
+    let mut object = ptr::null_mut();
+    let rc = raw::git_revparse_single(&mut object, repo, spec);
+    if rc < 0 {
+        return Err(rc);
+    }
+    let mut commit = ptr::null_mut();
+    let rc = raw::git_object_peel(&mut commit, object, raw::GIT_OBJECT_COMMIT);
+    raw::git_object_free(object);
+    if rc < 0 {
+        return Err(rc);
+    }
+    let id = (*raw::git_object_id(commit)).id;
+    raw::git_object_free(commit);
+    Ok(id)
+}
+
+unsafe fn bundle_walk(
+    repo: *mut raw::git_repository,
+    refs: &[&BundleRef],
+    basis: &[ObjectId],
+) -> Result<*mut raw::git_revwalk, c_int> {
+    let mut walk = ptr::null_mut();
+    let rc = raw::git_revwalk_new(&mut walk, repo);
+    if rc < 0 {
+        return Err(rc);
+    }
+    let tips = refs.iter().filter(|r| r.commit).map(|r| (r.target, false));
+    for (id, hide) in tips.chain(basis.iter().map(|id| (*id, true))) {
+        let oid = raw::git_oid { id };
+        let rc = if hide {
+            raw::git_revwalk_hide(walk, &oid)
+        } else {
+            raw::git_revwalk_push(walk, &oid)
+        };
+        if rc < 0 {
+            raw::git_revwalk_free(walk);
+            return Err(rc);
+        }
+    }
+    Ok(walk)
+}
+
+/// Commits a bundle carries, and its prerequisites: commits just outside
+/// it, with their summaries, that the receiving repository must have.
+struct BundleCommits {
+    included: HashSet<ObjectId>,
+    prerequisites: Vec<(ObjectId, String)>,
+}
+
+unsafe fn bundle_commits(
+    repo: *mut raw::git_repository,
+    refs: &[&BundleRef],
+    basis: &[ObjectId],
+) -> Result<BundleCommits, c_int> {
+    let walk = bundle_walk(repo, refs, basis)?;
+    let mut commits = Vec::new();
+    let mut oid: raw::git_oid = std::mem::zeroed();
+    let mut rc;
+    loop {
+        rc = raw::git_revwalk_next(&mut oid, walk);
+        if rc < 0 {
+            break;
+        }
+        commits.push(oid.id);
+    }
+    raw::git_revwalk_free(walk);
+    if rc != raw::GIT_ITEROVER {
+        return Err(rc);
+    }
+    let included: HashSet<ObjectId> = commits.iter().copied().collect();
+
+    let mut seen = HashSet::new();
+    let mut prerequisites = Vec::new();
+    for id in &commits {
+        let mut commit = ptr::null_mut();
+        let rc = raw::git_commit_lookup(&mut commit, repo, &raw::git_oid { id: *id });
+        if rc < 0 {
+            return Err(rc);
+        }
+        for n in 0..raw::git_commit_parentcount(commit) {
+            let parent = (*raw::git_commit_parent_id(commit, n)).id;
+            if included.contains(&parent) || !seen.insert(parent) {
+                continue;
+            }
+            let mut parent_commit = ptr::null_mut();
+            let mut summary = String::new();
+            if raw::git_commit_lookup(&mut parent_commit, repo, &raw::git_oid { id: parent }) == 0 {
+                let text = raw::git_commit_summary(parent_commit);
+                if !text.is_null() {
+                    summary = CStr::from_ptr(text).to_string_lossy().into_owned();
+                }
+                raw::git_commit_free(parent_commit);
+            }
+            prerequisites.push((parent, summary));
+        }
+        raw::git_commit_free(commit);
+    }
+    Ok(BundleCommits {
+        included,
+        prerequisites,
+    })
+}
+
+unsafe fn fill_bundle_pack(
+    repo: *mut raw::git_repository,
+    pb: *mut raw::git_packbuilder,
+    refs: &[&BundleRef],
+    basis: &[ObjectId],
+) -> Result<(), c_int> {
+    let walk = bundle_walk(repo, refs, basis)?;
+    let rc = raw::git_packbuilder_insert_walk(pb, walk);
+    raw::git_revwalk_free(walk);
+    if rc < 0 {
+        return Err(rc);
+    }
+    for bundle_ref in refs {
+        for tag in &bundle_ref.tags {
+            let rc = raw::git_packbuilder_insert(pb, &raw::git_oid { id: *tag }, ptr::null());
+            if rc < 0 {
+                return Err(rc);
+            }
+        }
+        if !bundle_ref.commit {
+            let target = raw::git_oid {
+                id: bundle_ref.target,
+            };
+            let rc = raw::git_packbuilder_insert_recur(pb, &target, ptr::null());
+            if rc < 0 {
+                return Err(rc);
+            }
+        }
+    }
+    Ok(())
+}
+
+struct BundleWriter {
+    out: BufWriter<std::fs::File>,
+    error: Option<std::io::Error>,
+}
+
+extern "C" fn bundle_write_cb(buf: *mut c_void, size: size_t, payload: *mut c_void) -> c_int {
+    let writer = unsafe { &mut *(payload as *mut BundleWriter) };
+    let data = unsafe { std::slice::from_raw_parts(buf as *const u8, size) };
+    match writer.out.write_all(data) {
+        Ok(()) => 0,
+        Err(err) => {
+            writer.error = Some(err);
+            -1
+        }
+    }
+}
+
+/// Write the header and the pack built in `pb` to `file`.
+unsafe fn write_bundle(
+    file: std::fs::File,
+    pb: *mut raw::git_packbuilder,
+    refs: &[&BundleRef],
+    prerequisites: &[(ObjectId, String)],
+) -> Result<(), c_int> {
+    let mut header = format!("{BUNDLE_V2_SIGNATURE}\n");
+    for (id, summary) in prerequisites {
+        header.push('-');
+        header.push_str(&object_id_hex(id));
+        if !summary.is_empty() {
+            header.push(' ');
+            header.push_str(summary);
+        }
+        header.push('\n');
+    }
+    for bundle_ref in refs {
+        header.push_str(&format!(
+            "{} {}\n",
+            object_id_hex(&bundle_ref.id),
+            bundle_ref.name
+        ));
+    }
+    header.push('\n');
+
+    let mut writer = BundleWriter {
+        out: BufWriter::new(file),
+        error: None,
+    };
+    writer.out.write_all(header.as_bytes()).map_err(os_error)?;
+    let rc =
+        raw::git_packbuilder_foreach(pb, bundle_write_cb, &mut writer as *mut _ as *mut c_void);
+    if let Some(err) = writer.error.take() {
+        return Err(os_error(err));
+    }
+    if rc < 0 {
+        return Err(rc);
+    }
+    writer
+        .out
+        .into_inner()
+        .map_err(|err| os_error(err.into_error()))?
+        .sync_all()
+        .map_err(os_error)
+}
+
+unsafe fn create_bundle(
+    repo: *mut raw::git_repository,
+    path: &Path,
+    refs: &[BundleRef],
+    basis: &[ObjectId],
+) -> Result<(), c_int> {
+    let mut names = HashSet::new();
+    let all: Vec<&BundleRef> = refs.iter().filter(|r| names.insert(&r.name)).collect();
+    let commits = bundle_commits(repo, &all, basis)?;
+    // Like git, leave out refs the basis already covers.
+    let kept: Vec<&BundleRef> = all
+        .into_iter()
+        .filter(|r| !r.commit || commits.included.contains(&r.target))
+        .collect();
+    if kept.is_empty() {
+        return Err(bundle_error("refusing to create an empty bundle"));
+    }
+
+    let mut pb = ptr::null_mut();
+    let rc = raw::git_packbuilder_new(&mut pb, repo);
+    if rc < 0 {
+        return Err(rc);
+    }
+    let mut lock = path.as_os_str().to_owned();
+    lock.push(".lock");
+    let lock = PathBuf::from(lock);
+    let result = fill_bundle_pack(repo, pb, &kept, basis).and_then(|()| {
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock)
+            .map_err(os_error)?;
+        write_bundle(file, pb, &kept, &commits.prerequisites)
+            .and_then(|()| std::fs::rename(&lock, path).map_err(os_error))
+            .inspect_err(|_| {
+                let _ = std::fs::remove_file(&lock);
+            })
+    });
+    raw::git_packbuilder_free(pb);
+    result
+}
+
+/// Write a `git bundle`-compatible file at `path` holding `refs` (names as
+/// `git rev-parse` would resolve them, e.g. "main", "refs/tags/v1", "HEAD")
+/// and everything they reach, minus the history of the `basis` revspecs
+/// (e.g. "origin/main", "v1.0"). Commits the bundle builds on are listed as
+/// prerequisites, so the receiver must already have them. Refs the basis
+/// fully covers are left out; with nothing left the call fails. The file is
+/// written through `<path>.lock` and only appears once complete.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_bundle_create(
+    repo: *mut raw::git_repository,
+    path: *const c_char,
+    refs: *const *const c_char,
+    ref_count: size_t,
+    basis: *const *const c_char,
+    basis_count: size_t,
+) -> c_int {
+    let Ok(path) = CStr::from_ptr(path).to_str() else {
+        return bundle_error("bundle path is not UTF-8");
+    };
+    let mut bundle_refs = Vec::with_capacity(ref_count);
+    for i in 0..ref_count {
+        match resolve_bundle_ref(repo, *refs.add(i)) {
+            Ok(bundle_ref) => bundle_refs.push(bundle_ref),
+            Err(rc) => return rc,
+        }
+    }
+    let mut hidden = Vec::with_capacity(basis_count);
+    for i in 0..basis_count {
+        match resolve_bundle_basis(repo, *basis.add(i)) {
+            Ok(id) => hidden.push(id),
+            Err(rc) => return rc,
+        }
+    }
+    match create_bundle(repo, Path::new(path), &bundle_refs, &hidden) {
+        Ok(()) => 0,
+        Err(rc) => rc,
+    }
+}
+
+#[derive(Default)]
+struct BundleHeader {
+    prerequisites: Vec<ObjectId>,
+    refs: Vec<(ObjectId, String)>,
+}
+
+fn read_bundle_line(input: &mut impl BufRead) -> Result<Option<String>, c_int> {
+    let mut line = Vec::new();
+    if input.read_until(b'\n', &mut line).map_err(os_error)? == 0 {
+        return Ok(None);
+    }
+    if line.last() == Some(&b'\n') {
+        line.pop();
+    }
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|_| bundle_error("bundle header is not UTF-8"))
+}
+
+/// Parse a v2 or v3 bundle header, leaving `input` at the start of the pack.
+fn read_bundle_header(input: &mut impl BufRead) -> Result<BundleHeader, c_int> {
+    let v3 = match read_bundle_line(input)?.as_deref() {
+        Some(BUNDLE_V2_SIGNATURE) => false,
+        Some(BUNDLE_V3_SIGNATURE) => true,
+        _ => return Err(bundle_error("not a git bundle")),
+    };
+    let mut header = BundleHeader::default();
+    loop {
+        let Some(line) = read_bundle_line(input)? else {
+            return Err(bundle_error("truncated bundle header"));
+        };
+        if line.is_empty() {
+            return Ok(header);
+        }
+        if let Some(capability) = line.strip_prefix('@') {
+            if !v3 || capability != "object-format=sha1" {
+                return Err(bundle_error(&format!(
+                    "unsupported bundle capability '{capability}'"
+                )));
+            }
+            continue;
+        }
+        let (prerequisite, entry) = match line.strip_prefix('-') {
+            Some(entry) => (true, entry),
+            None => (false, line.as_str()),
+        };
+        let (hex, name) = entry.split_once(' ').unwrap_or((entry, ""));
+        match parse_object_id(hex) {
+            Some(id) if prerequisite => header.prerequisites.push(id),
+            Some(id) if !name.is_empty() => header.refs.push((id, name.to_string())),
+            _ => {
+                return Err(bundle_error(&format!(
+                    "malformed bundle header line '{line}'"
+                )))
+            }
+        }
+    }
+}
+
+/// Stream the pack following the header into a new pack in the repository,
+/// resolving thin-pack bases against `odb`.
+unsafe fn index_bundle_pack(
+    odb: *mut raw::git_odb,
+    pack_dir: &Path,
+    input: &mut impl Read,
+) -> Result<(), c_int> {
+    let Some(dir) = pack_dir.to_str().and_then(|dir| CString::new(dir).ok()) else {
+        return Err(bundle_error("pack directory path is not UTF-8"));
+    };
+    let mut indexer = ptr::null_mut();
+    let rc = raw::git_indexer_new(&mut indexer, dir.as_ptr(), 0, odb, ptr::null_mut());
+    if rc < 0 {
+        return Err(rc);
+    }
+    let mut stats: raw::git_indexer_progress = std::mem::zeroed();
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut result = loop {
+        let len = match input.read(&mut buf) {
+            Ok(0) => break Ok(()),
+            Ok(len) => len,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(err) => break Err(os_error(err)),
+        };
+        let rc = raw::git_indexer_append(indexer, buf.as_ptr() as *const c_void, len, &mut stats);
+        if rc < 0 {
+            break Err(rc);
+        }
+    };
+    if result.is_ok() {
+        let rc = raw::git_indexer_commit(indexer, &mut stats);
+        if rc < 0 {
+            result = Err(rc);
+        }
+    }
+    raw::git_indexer_free(indexer);
+    result
+}
+
+/// Create or move the local ref each bundle ref maps to. Unforced updates
+/// that are not fast-forwards are skipped and fail the call at the end.
+unsafe fn store_bundle_refs(
+    repo: *mut raw::git_repository,
+    refs: &[(ObjectId, String)],
+    spec: *const raw::git_refspec,
+    callback: Option<git2_shim_update_tips_cb>,
+    payload: *mut c_void,
+) -> Result<(), c_int> {
+    // The first ref left alone, with the error and the reason.
+    let mut rejected = None;
+    let bare = raw::git_repository_is_bare(repo) != 0;
+    for (id, name) in refs {
+        let Ok(name) = CString::new(name.as_str()) else {
+            return Err(bundle_error("bundle ref name contains NUL"));
+        };
+        let (dst, force) = if spec.is_null() {
+            if name.as_bytes() == b"HEAD" {
+                continue;
+            }
+            (name, false)
+        } else {
+            if raw::git_refspec_src_matches(spec, name.as_ptr()) == 0 {
+                continue;
+            }
+            let mut buf: raw::git_buf = std::mem::zeroed();
+            let rc = raw::git_refspec_transform(&mut buf, spec, name.as_ptr());
+            if rc < 0 {
+                return Err(rc);
+            }
+            let dst = CStr::from_ptr(buf.ptr).to_owned();
+            raw::git_buf_dispose(&mut buf);
+            (dst, raw::git_refspec_force(spec) != 0)
+        };
+
+        // Like `git fetch`, never move a branch out from under a worktree.
+        if !bare && dst.as_bytes().starts_with(b"refs/heads/") {
+            let mut existing = ptr::null_mut();
+            if raw::git_reference_lookup(&mut existing, repo, dst.as_ptr()) == 0 {
+                let checked_out = raw::git_branch_is_checked_out(existing);
+                raw::git_reference_free(existing);
+                if checked_out == 1 {
+                    rejected.get_or_insert((dst, -1, "it is checked out"));
+                    continue;
+                }
+            }
+        }
+
+        let new_id = raw::git_oid { id: *id };
+        let mut old_id: raw::git_oid = std::mem::zeroed();
+        match raw::git_reference_name_to_id(&mut old_id, repo, dst.as_ptr()) {
+            0 if old_id.id == *id => continue,
+            0 if !force && raw::git_graph_descendant_of(repo, &new_id, &old_id) != 1 => {
+                rejected.get_or_insert((dst, raw::GIT_ENONFASTFORWARD, "not a fast-forward"));
+                continue;
+            }
+            0 => {}
+            raw::GIT_ENOTFOUND => old_id = std::mem::zeroed(),
+            rc => return Err(rc),
+        }
+        let mut reference = ptr::null_mut();
+        let rc = raw::git_reference_create(
+            &mut reference,
+            repo,
+            dst.as_ptr(),
+            &new_id,
+            1,
+            c"unbundle: storing head".as_ptr(),
+        );
+        if rc < 0 {
+            return Err(rc);
+        }
+        raw::git_reference_free(reference);
+        if let Some(callback) = callback {
+            let rc = callback(dst.as_ptr(), &old_id, &new_id, payload);
+            if rc != 0 {
+                return Err(rc);
+            }
+        }
+    }
+    match rejected {
+        Some((dst, rc, reason)) => {
+            bundle_error(&format!(
+                "refusing to update '{}': {reason}",
+                dst.to_string_lossy()
+            ));
+            Err(rc)
+        }
+        None => Ok(()),
+    }
+}
+
+unsafe fn unbundle(
+    repo: *mut raw::git_repository,
+    odb: *mut raw::git_odb,
+    path: &Path,
+    spec: *const raw::git_refspec,
+    callback: Option<git2_shim_update_tips_cb>,
+    payload: *mut c_void,
+) -> Result<(), c_int> {
+    let mut input = BufReader::new(std::fs::File::open(path).map_err(os_error)?);
+    let header = read_bundle_header(&mut input)?;
+    for id in &header.prerequisites {
+        if raw::git_odb_exists(odb, &raw::git_oid { id: *id }) == 0 {
+            bundle_error(&format!(
+                "repository lacks prerequisite commit {}",
+                object_id_hex(id)
+            ));
+            return Err(raw::GIT_ENOTFOUND);
+        }
+    }
+    index_bundle_pack(odb, &objects_dir(repo)?.join("pack"), &mut input)?;
+    let rc = raw::git_odb_refresh(odb);
+    if rc < 0 {
+        return Err(rc);
+    }
+    store_bundle_refs(repo, &header.refs, spec, callback, payload)
+}
+
+/// Import the bundle at `path`: check the repository has its
+/// prerequisites (`GIT_ENOTFOUND` if not), add its pack to the object
+/// store, then write its refs. `refspec` (e.g. "+refs/heads/*:refs/remotes/
+/// bundle/*") maps bundle refs to local ones, as for a fetch; NULL writes
+/// them under their own names, except HEAD, without forcing. Unforced
+/// updates that would lose commits are skipped and the call then fails with
+/// `GIT_ENONFASTFORWARD`; in a non-bare repository, a branch checked out in
+/// any worktree is never touched, and the call then fails with -1. Each ref written is reported through `callback`
+/// (may be NULL) with a zero `old_id` when it was created.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_bundle_unbundle(
+    repo: *mut raw::git_repository,
+    path: *const c_char,
+    refspec: *const c_char,
+    callback: Option<git2_shim_update_tips_cb>,
+    payload: *mut c_void,
+) -> c_int {
+    let Ok(path) = CStr::from_ptr(path).to_str() else {
+        return bundle_error("bundle path is not UTF-8");
+    };
+    let mut spec = ptr::null_mut();
+    if !refspec.is_null() {
+        let rc = raw::git_refspec_parse(&mut spec, refspec, 1);
+        if rc < 0 {
+            return rc;
+        }
+    }
+    let mut odb = ptr::null_mut();
+    let mut rc = raw::git_repository_odb(&mut odb, repo);
+    if rc == 0 {
+        rc = match unbundle(repo, odb, Path::new(path), spec, callback, payload) {
+            Ok(()) => 0,
+            Err(rc) => rc,
+        };
+        raw::git_odb_free(odb);
+    }
+    if !spec.is_null() {
+        raw::git_refspec_free(spec);
+    }
+    rc
+}
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// The repository's configuration: its `.git/config` layered over the
+/// worktree, global, XDG and system files. Free with `git2_shim_config_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_config(
+    out: *mut *mut raw::git_config,
+    repo: *mut raw::git_repository,
+) -> c_int {
+    raw::git_repository_config(out, repo)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_free(cfg: *mut raw::git_config) {
+    raw::git_config_free(cfg)
+}
+
+/// Value of `name` (e.g. "user.email") from the highest-priority file that
+/// sets it; `GIT_ENOTFOUND` if none does. Dispose `out` with
+/// `git2_shim_buf_dispose`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_get_string_buf(
+    out: *mut raw::git_buf,
+    cfg: *const raw::git_config,
+    name: *const c_char,
+) -> c_int {
+    raw::git_config_get_string_buf(out, cfg, name)
+}
+
+/// Set `name` (e.g. "remote.origin.url") in the highest-priority writable
+/// file, normally the repository's own `.git/config`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_set_string(
+    cfg: *mut raw::git_config,
+    name: *const c_char,
+    value: *const c_char,
+) -> c_int {
+    raw::git_config_set_string(cfg, name, value)
+}
+
+/// `name` read as a boolean the way git does: "true", "yes", "on", a
+/// bare key or a non-zero number are true; "false", "no", "off", "" or
+/// zero are false. Anything else fails.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_get_bool(
+    out: *mut c_int,
+    cfg: *const raw::git_config,
+    name: *const c_char,
+) -> c_int {
+    raw::git_config_get_bool(out, cfg, name)
+}
+
+/// `name` read as an integer, accepting git's `k`, `m` and `g` suffixes
+/// (powers of 1024). Fails if the value does not fit in 32 bits.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_get_int32(
+    out: *mut i32,
+    cfg: *const raw::git_config,
+    name: *const c_char,
+) -> c_int {
+    raw::git_config_get_int32(out, cfg, name)
+}
+
+/// Like `git2_shim_config_get_int32`, for 64-bit values such as
+/// `core.bigFileThreshold`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_get_int64(
+    out: *mut i64,
+    cfg: *const raw::git_config,
+    name: *const c_char,
+) -> c_int {
+    raw::git_config_get_int64(out, cfg, name)
+}
+
+/// `name` read as a path, with a leading `~/` expanded to the home
+/// directory. Dispose `out` with `git2_shim_buf_dispose`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_get_path(
+    out: *mut raw::git_buf,
+    cfg: *const raw::git_config,
+    name: *const c_char,
+) -> c_int {
+    raw::git_config_get_path(out, cfg, name)
+}
+
+/// Write `name` as "true" or "false". Paths are written with
+/// `git2_shim_config_set_string`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_set_bool(
+    cfg: *mut raw::git_config,
+    name: *const c_char,
+    value: c_int,
+) -> c_int {
+    raw::git_config_set_bool(cfg, name, value)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_set_int32(
+    cfg: *mut raw::git_config,
+    name: *const c_char,
+    value: i32,
+) -> c_int {
+    raw::git_config_set_int32(cfg, name, value)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_set_int64(
+    cfg: *mut raw::git_config,
+    name: *const c_char,
+    value: i64,
+) -> c_int {
+    raw::git_config_set_int64(cfg, name, value)
+}
+
+/// Callback invoked once per configuration entry. `level` is the file it
+/// came from: 1 programdata, 2 system, 3 XDG, 4 global, 5 local,
+/// 6 worktree, 7 app. `value` is NULL for a bare key such as `[core] bare`.
+///
+/// Return 0 to continue iterating; any other value stops the walk and is
+/// returned from the foreach call.
+pub type git2_shim_config_entry_cb = extern "C" fn(
+    name: *const c_char,
+    value: *const c_char,
+    level: c_int,
+    payload: *mut c_void,
+) -> c_int;
+
+extern "C" fn config_entry_trampoline(
+    entry: *const raw::git_config_entry,
+    payload: *mut c_void,
+) -> c_int {
+    let t = unsafe { &*(payload as *const Trampoline<git2_shim_config_entry_cb>) };
+    let entry = unsafe { &*entry };
+    (t.callback)(entry.name, entry.value, entry.level, t.payload)
+}
+
+/// Walk every entry, lowest-priority file first, or only those whose name
+/// matches the regular expression `regexp` (e.g. `"^remote\\..*\\.url$"`)
+/// when it is not NULL.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_foreach_match(
+    cfg: *const raw::git_config,
+    regexp: *const c_char,
+    callback: git2_shim_config_entry_cb,
+    payload: *mut c_void,
+) -> c_int {
+    let mut t = Trampoline { callback, payload };
+    let payload = &mut t as *mut _ as *mut c_void;
+    if regexp.is_null() {
+        raw::git_config_foreach(cfg, config_entry_trampoline, payload)
+    } else {
+        raw::git_config_foreach_match(cfg, regexp, config_entry_trampoline, payload)
+    }
+}
+
+/// Iterator over the entries `git2_shim_config_foreach_match` would visit.
+/// Free with `git2_shim_config_iterator_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_iterator_new(
+    out: *mut *mut raw::git_config_iterator,
+    cfg: *const raw::git_config,
+    regexp: *const c_char,
+) -> c_int {
+    if regexp.is_null() {
+        raw::git_config_iterator_new(out, cfg)
+    } else {
+        raw::git_config_iterator_glob_new(out, cfg, regexp)
+    }
+}
+
+/// Advance to the next entry; `GIT_ITEROVER` (-31) once there are none.
+/// `name` and `value` stay valid until the next call or until the
+/// iterator is freed.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_next(
+    name: *mut *const c_char,
+    value: *mut *const c_char,
+    level: *mut c_int,
+    iter: *mut raw::git_config_iterator,
+) -> c_int {
+    let mut entry = ptr::null_mut();
+    let rc = raw::git_config_next(&mut entry, iter);
+    if rc < 0 {
+        return rc;
+    }
+    let entry = &*entry;
+    *name = entry.name;
+    *value = entry.value;
+    *level = entry.level;
+    0
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_iterator_free(iter: *mut raw::git_config_iterator) {
+    raw::git_config_iterator_free(iter)
+}
+
+/// Walk each value of the multi-valued key `name` (e.g.
+/// "remote.origin.fetch"), or only the values matching `regexp` when it is
+/// not NULL. `GIT_ENOTFOUND` if the key is unset.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_get_multivar_foreach(
+    cfg: *const raw::git_config,
+    name: *const c_char,
+    regexp: *const c_char,
+    callback: git2_shim_config_entry_cb,
+    payload: *mut c_void,
+) -> c_int {
+    let mut t = Trampoline { callback, payload };
+    raw::git_config_get_multivar_foreach(
+        cfg,
+        name,
+        regexp,
+        config_entry_trampoline,
+        &mut t as *mut _ as *mut c_void,
+    )
+}
+
+/// Replace the values of `name` matching `regexp` with `value` in the
+/// highest-priority writable file. A pattern that matches nothing, such as
+/// `"$^"`, appends `value` and leaves the existing values alone.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_set_multivar(
+    cfg: *mut raw::git_config,
+    name: *const c_char,
+    regexp: *const c_char,
+    value: *const c_char,
+) -> c_int {
+    raw::git_config_set_multivar(cfg, name, regexp, value)
+}
+
+/// Only the file at `level` (2 system, 3 XDG, 4 global, 5 local,
+/// 6 worktree; see `git2_shim_config_entry_cb`) of `parent`, so reads see
+/// just that file and writes land in it, like `git config --global`.
+/// `GIT_ENOTFOUND` if `parent` has no file at that level.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_open_level(
+    out: *mut *mut raw::git_config,
+    parent: *const raw::git_config,
+    level: c_int,
+) -> c_int {
+    raw::git_config_open_level(out, parent, level)
+}
+
+/// The global, XDG and system configuration without any repository, as
+/// `git config` sees it outside a work tree.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_open_default(out: *mut *mut raw::git_config) -> c_int {
+    raw::git_config_open_default(out)
+}
+
+/// Read-only copy of `config` frozen at this moment: later reads neither
+/// see concurrent edits nor re-check the files for changes. Free with
+/// `git2_shim_config_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_snapshot(
+    out: *mut *mut raw::git_config,
+    config: *mut raw::git_config,
+) -> c_int {
+    raw::git_config_snapshot(out, config)
+}
+
+/// Snapshot of the repository's configuration; like
+/// `git2_shim_config_snapshot` on `git2_shim_repository_config` without
+/// the intermediate handle.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_config_snapshot(
+    out: *mut *mut raw::git_config,
+    repo: *mut raw::git_repository,
+) -> c_int {
+    raw::git_repository_config_snapshot(out, repo)
+}
+
+/// Remove `name` from the highest-priority writable file;
+/// `GIT_ENOTFOUND` if it is not set there. Fails for multi-valued keys,
+/// which need `git2_shim_config_delete_multivar`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_delete_entry(
+    cfg: *mut raw::git_config,
+    name: *const c_char,
+) -> c_int {
+    raw::git_config_delete_entry(cfg, name)
+}
+
+/// Remove the values of `name` matching `regexp` (`"."` for all of them)
+/// from the highest-priority writable file.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_delete_multivar(
+    cfg: *mut raw::git_config,
+    name: *const c_char,
+    regexp: *const c_char,
+) -> c_int {
+    raw::git_config_delete_multivar(cfg, name, regexp)
+}
+
+/// Lock the highest-priority writable file of `cfg` for a batch of
+/// related changes, e.g. a remote's URL and its fetch refspecs. Writes
+/// through `cfg` are held back until `git2_shim_transaction_commit` writes
+/// them all in one atomic rename; freeing the transaction uncommitted
+/// discards them. Other writers fail while the lock is held.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_lock(
+    tx: *mut *mut raw::git_transaction,
+    cfg: *mut raw::git_config,
+) -> c_int {
+    raw::git_config_lock(tx, cfg)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_transaction_commit(tx: *mut raw::git_transaction) -> c_int {
+    raw::git_transaction_commit(tx)
+}
+
+/// Release the lock; changes not yet committed are dropped.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_transaction_free(tx: *mut raw::git_transaction) {
+    raw::git_transaction_free(tx)
+}
+
+/// Parse `value` with the rules of `git2_shim_config_get_bool`, for values
+/// that come from somewhere other than a config file.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_parse_bool(
+    out: *mut c_int,
+    value: *const c_char,
+) -> c_int {
+    raw::git_config_parse_bool(out, value)
+}
+
+/// Parse `value` with the rules of `git2_shim_config_get_int32`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_parse_int32(
+    out: *mut i32,
+    value: *const c_char,
+) -> c_int {
+    raw::git_config_parse_int32(out, value)
+}
+
+/// Parse `value` with the rules of `git2_shim_config_get_int64`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_parse_int64(
+    out: *mut i64,
+    value: *const c_char,
+) -> c_int {
+    raw::git_config_parse_int64(out, value)
+}
+
+/// Expand a leading `~/` in `value` like `git2_shim_config_get_path`;
+/// dispose `out` with `git2_shim_buf_dispose`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_parse_path(
+    out: *mut raw::git_buf,
+    value: *const c_char,
+) -> c_int {
+    raw::git_config_parse_path(out, value)
+}
+
+/// Empty configuration with no files, to be stacked up with
+/// `git2_shim_config_add_file_ondisk`. Free with `git2_shim_config_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_new(out: *mut *mut raw::git_config) -> c_int {
+    raw::git_config_new(out)
+}
+
+/// Add the file at `path` to `cfg` at priority `level` (1 lowest to 7
+/// highest; 7 is for application-provided files, e.g. a CI config layered
+/// over a repository configuration). A missing file is created on first
+/// write. `repo` (may be NULL) lets `includeIf` conditions see the
+/// repository. Fails with `GIT_EEXISTS` if `level` is taken unless `force`
+/// is non-zero, which replaces the file there.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_config_add_file_ondisk(
+    cfg: *mut raw::git_config,
+    path: *const c_char,
+    level: c_int,
+    repo: *const raw::git_repository,
+    force: c_int,
+) -> c_int {
+    raw::git_config_add_file_ondisk(cfg, path, level, repo, force)
+}
+
+// =============================================================================
+// Submodules
+// =============================================================================
+
+/// Look up a submodule by name or by its path in the work tree; fails
+/// with `GIT_ENOTFOUND` if there is none, or `GIT_EEXISTS` for a plain
+/// repository cloned into the work tree without being added as a
+/// submodule. Free with `git2_shim_submodule_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_lookup(
+    out: *mut *mut raw::git_submodule,
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+) -> c_int {
+    raw::git_submodule_lookup(out, repo, name)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_free(submodule: *mut raw::git_submodule) {
+    raw::git_submodule_free(submodule)
+}
+
+/// Callback invoked once per submodule known to `.gitmodules`, the index,
+/// HEAD or the configuration. `submodule` is only valid during the call.
+///
+/// Return 0 to continue iterating; any other value stops the walk and is
+/// returned from `git2_shim_submodule_foreach`.
+pub type git2_shim_submodule_cb = extern "C" fn(
+    submodule: *mut raw::git_submodule,
+    name: *const c_char,
+    payload: *mut c_void,
+) -> c_int;
+
+extern "C" fn submodule_trampoline(
+    submodule: *mut raw::git_submodule,
+    name: *const c_char,
+    payload: *mut c_void,
+) -> c_int {
+    let t = unsafe { &*(payload as *const Trampoline<git2_shim_submodule_cb>) };
+    (t.callback)(submodule, name, t.payload)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_foreach(
+    repo: *mut raw::git_repository,
+    callback: git2_shim_submodule_cb,
+    payload: *mut c_void,
+) -> c_int {
+    let mut t = Trampoline { callback, payload };
+    raw::git_submodule_foreach(repo, submodule_trampoline, &mut t as *mut _ as *mut c_void)
+}
+
+/// Status of submodule `name` as `GIT_SUBMODULE_STATUS_*` bits: where it
+/// is recorded (1 HEAD, 2 index, 4 config, 8 work tree), how the index and
+/// work tree differ from HEAD (0x10 added, 0x20 deleted, 0x40 modified in
+/// the index; 0x80 uninitialized, 0x100 added, 0x200 deleted, 0x400
+/// modified in the work tree) and whether its checkout is dirty (0x800
+/// index changes, 0x1000 tracked file changes, 0x2000 untracked files).
+/// `ignore` limits how deep the work tree is examined: -1 follows the
+/// submodule's `ignore` setting, 1 none, 2 untracked, 3 dirty, 4 all.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_status(
+    status: *mut c_uint,
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+    ignore: c_int,
+) -> c_int {
+    raw::git_submodule_status(status, repo, name, ignore)
+}
+
+/// Only the location bits (HEAD, index, config, work tree) of
+/// `git2_shim_submodule_status`, without examining the work tree.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_location(
+    location_status: *mut c_uint,
+    submodule: *mut raw::git_submodule,
+) -> c_int {
+    raw::git_submodule_location(location_status, submodule)
+}
+
+/// Copy the submodule's URL and update settings from `.gitmodules` into
+/// `.git/config`, like `git submodule init`. Entries already in the
+/// configuration are kept unless `overwrite` is non-zero.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_init(
+    submodule: *mut raw::git_submodule,
+    overwrite: c_int,
+) -> c_int {
+    raw::git_submodule_init(submodule, overwrite)
+}
+
+/// Submodule update options; defaults to a safe checkout, fetching when
+/// the pinned commit is missing.
+pub struct SubmoduleUpdateOptions {
+    raw: raw::git_submodule_update_options,
+    checkout: Option<CheckoutOptions>,
+    fetch: Option<FetchOptions>,
+}
+
+impl SubmoduleUpdateOptions {
+    fn raw_opts(&mut self) -> *mut raw::git_submodule_update_options {
+        if let Some(checkout) = self.checkout.as_mut() {
+            self.raw.checkout_opts = checkout.raw_opts();
+        }
+        if let Some(fetch) = self.fetch.as_mut() {
+            self.raw.fetch_opts = fetch.raw_opts();
+        }
+        &mut self.raw
+    }
+
+    unsafe fn settle(&mut self, rc: c_int) -> c_int {
+        self.fetch.as_mut().map_or(rc, |fetch| fetch.settle(rc))
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_update_options_new() -> *mut SubmoduleUpdateOptions {
+    let mut opts = Box::new(SubmoduleUpdateOptions {
+        raw: std::mem::zeroed(),
+        checkout: None,
+        fetch: None,
+    });
+    if raw::git_submodule_update_options_init(
+        &mut opts.raw,
+        raw::GIT_SUBMODULE_UPDATE_OPTIONS_VERSION,
+    ) < 0
+    {
+        return ptr::null_mut();
+    }
+    Box::into_raw(opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_update_options_free(
+    opts: *mut SubmoduleUpdateOptions,
+) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// Checkout options for the submodule's work tree; copied. NULL restores
+/// the default safe checkout.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_update_options_set_checkout_options(
+    opts: *mut SubmoduleUpdateOptions,
+    checkout: *const CheckoutOptions,
+) {
+    (*opts).checkout = checkout.as_ref().cloned();
+    if (*opts).checkout.is_none() {
+        let mut defaults: raw::git_submodule_update_options = std::mem::zeroed();
+        raw::git_submodule_update_options_init(
+            &mut defaults,
+            raw::GIT_SUBMODULE_UPDATE_OPTIONS_VERSION,
+        );
+        (*opts).raw.checkout_opts = defaults.checkout_opts;
+    }
+}
+
+/// Fetch options, including the credential callbacks private submodules
+/// need, for cloning or fetching the submodule; copied. NULL restores the
+/// defaults.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_update_options_set_fetch_options(
+    opts: *mut SubmoduleUpdateOptions,
+    fetch: *const FetchOptions,
+) {
+    (*opts).fetch = fetch.as_ref().cloned();
+    if (*opts).fetch.is_none() {
+        let mut defaults: raw::git_submodule_update_options = std::mem::zeroed();
+        raw::git_submodule_update_options_init(
+            &mut defaults,
+            raw::GIT_SUBMODULE_UPDATE_OPTIONS_VERSION,
+        );
+        (*opts).raw.fetch_opts = defaults.fetch_opts;
+    }
+}
+
+/// Zero fails the update instead of fetching when the pinned commit is
+/// not in the submodule's repository.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_update_options_set_allow_fetch(
+    opts: *mut SubmoduleUpdateOptions,
+    allow_fetch: c_int,
+) {
+    (*opts).raw.allow_fetch = allow_fetch;
+}
+
+/// Clone the submodule if needed and check out the commit the superproject
+/// pins, like `git submodule update`; a non-zero `init` also initializes
+/// it first (`--init`). Fails for uninitialized submodules otherwise.
+/// `opts` may be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_update(
+    submodule: *mut raw::git_submodule,
+    init: c_int,
+    opts: *mut SubmoduleUpdateOptions,
+) -> c_int {
+    let raw_opts = opts.as_mut().map_or(ptr::null_mut(), |o| o.raw_opts());
+    let rc = raw::git_submodule_update(submodule, init, raw_opts);
+    opts.as_mut().map_or(rc, |o| o.settle(rc))
+}
+
+/// First step of `git submodule add`: record `url` for a new submodule at
+/// `path` in `.gitmodules` and `.git/config` and create its empty
+/// repository. A non-zero `use_gitlink` keeps that repository in
+/// `.git/modules/` with a `.git` file in `path`, as git does. Follow with
+/// `git2_shim_submodule_clone` and `git2_shim_submodule_add_finalize`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_add_setup(
+    out: *mut *mut raw::git_submodule,
+    repo: *mut raw::git_repository,
+    url: *const c_char,
+    path: *const c_char,
+    use_gitlink: c_int,
+) -> c_int {
+    raw::git_submodule_add_setup(out, repo, url, path, use_gitlink)
+}
+
+/// Fetch and check out a submodule set up by
+/// `git2_shim_submodule_add_setup`, opening its repository in `out`; free
+/// it with `git2_shim_repository_free`. `opts` may be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_clone(
+    out: *mut *mut raw::git_repository,
+    submodule: *mut raw::git_submodule,
+    opts: *mut SubmoduleUpdateOptions,
+) -> c_int {
+    let raw_opts = opts.as_mut().map_or(ptr::null_mut(), |o| o.raw_opts());
+    let rc = raw::git_submodule_clone(out, submodule, raw_opts);
+    opts.as_mut().map_or(rc, |o| o.settle(rc))
+}
+
+/// Last step of `git submodule add`: stage `.gitmodules` and the
+/// submodule's checked-out commit in the superproject index.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_add_finalize(
+    submodule: *mut raw::git_submodule,
+) -> c_int {
+    raw::git_submodule_add_finalize(submodule)
+}
+
+/// Stage the submodule's current HEAD commit in the superproject index,
+/// writing the index to disk if `write_index` is non-zero.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_add_to_index(
+    submodule: *mut raw::git_submodule,
+    write_index: c_int,
+) -> c_int {
+    raw::git_submodule_add_to_index(submodule, write_index)
+}
+
+/// Name from `.gitmodules`, often but not always equal to the path. The
+/// accessors below return strings and ids owned by `submodule`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_name(
+    submodule: *mut raw::git_submodule,
+) -> *const c_char {
+    raw::git_submodule_name(submodule)
+}
+
+/// Path of the submodule relative to the superproject's work tree.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_path(
+    submodule: *mut raw::git_submodule,
+) -> *const c_char {
+    raw::git_submodule_path(submodule)
+}
+
+/// URL from the configuration or `.gitmodules`; NULL if neither sets one.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_url(
+    submodule: *mut raw::git_submodule,
+) -> *const c_char {
+    raw::git_submodule_url(submodule)
+}
+
+/// Branch to track from `submodule.<name>.branch`, or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_branch(
+    submodule: *mut raw::git_submodule,
+) -> *const c_char {
+    raw::git_submodule_branch(submodule)
+}
+
+/// Commit pinned by the superproject's HEAD tree; NULL if not in HEAD.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_head_id(
+    submodule: *mut raw::git_submodule,
+) -> *const raw::git_oid {
+    raw::git_submodule_head_id(submodule)
+}
+
+/// Commit staged in the superproject's index; NULL if not in the index.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_index_id(
+    submodule: *mut raw::git_submodule,
+) -> *const raw::git_oid {
+    raw::git_submodule_index_id(submodule)
+}
+
+/// HEAD of the submodule's checked-out repository; NULL if it is not
+/// checked out. Cached when the submodule was loaded.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_wd_id(
+    submodule: *mut raw::git_submodule,
+) -> *const raw::git_oid {
+    raw::git_submodule_wd_id(submodule)
+}
+
+/// Set the submodule's URL in `.gitmodules`, e.g. to redirect it to a
+/// mirror. Like the other setters this edits the work tree file, to be
+/// committed, and does not change loaded submodule handles; look the
+/// submodule up again and `git2_shim_submodule_sync` it to apply the URL
+/// to `.git/config` and the cloned submodule.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_set_url(
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+    url: *const c_char,
+) -> c_int {
+    raw::git_submodule_set_url(repo, name, url)
+}
+
+/// Set the branch the submodule tracks in `.gitmodules`; NULL removes it.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_set_branch(
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+    branch: *const c_char,
+) -> c_int {
+    raw::git_submodule_set_branch(repo, name, branch)
+}
+
+/// Set the submodule's update strategy in `.gitmodules`: 1 checkout,
+/// 2 rebase, 3 merge or 4 none.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_set_update(
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+    update: c_int,
+) -> c_int {
+    raw::git_submodule_set_update(repo, name, update)
+}
+
+/// Set the submodule's `ignore` level in `.gitmodules`, one of those taken
+/// by `git2_shim_submodule_status` (1 none to 4 all).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_set_ignore(
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+    ignore: c_int,
+) -> c_int {
+    raw::git_submodule_set_ignore(repo, name, ignore)
+}
+
+/// Copy the submodule's URL from `.gitmodules` into `.git/config` and into
+/// the checked-out submodule's remote, like `git submodule sync`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_sync(submodule: *mut raw::git_submodule) -> c_int {
+    raw::git_submodule_sync(submodule)
+}
+
+/// Open the checked-out submodule's repository as a regular handle, so
+/// status, fetch and checkout shims work on it too; fails if it is not
+/// checked out. Free it with `git2_shim_repository_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_open(
+    repo: *mut *mut raw::git_repository,
+    submodule: *mut raw::git_submodule,
+) -> c_int {
+    raw::git_submodule_open(repo, submodule)
+}
+
+/// Create the submodule's repository, with its remote set from the
+/// configured URL, ready to be fetched into by hand. A non-zero
+/// `use_gitlink` places it under `.git/modules/` with a `.git` file in the
+/// submodule's path. Free `out` with `git2_shim_repository_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_repo_init(
+    out: *mut *mut raw::git_repository,
+    submodule: *const raw::git_submodule,
+    use_gitlink: c_int,
+) -> c_int {
+    raw::git_submodule_repo_init(out, submodule, use_gitlink)
+}
+
+/// Outcome of one submodule in `git2_shim_submodule_update_recursive`.
+/// `path` is relative to the top-level work tree (e.g. "libs/a/vendor/b")
+/// and `depth` is 1 for direct submodules. `error` is 0 on success,
+/// otherwise the libgit2 error code with `message` describing it (NULL on
+/// success).
+///
+/// Return 0 to continue; any other value stops the update.
+pub type git2_shim_submodule_update_cb = extern "C" fn(
+    path: *const c_char,
+    depth: c_uint,
+    error: c_int,
+    message: *const c_char,
+    payload: *mut c_void,
+) -> c_int;
+
+struct SubmoduleRecursion {
+    init: c_int,
+    max_depth: c_uint,
+    opts: *mut SubmoduleUpdateOptions,
+    callback: Option<git2_shim_submodule_update_cb>,
+    payload: *mut c_void,
+    failures: c_int,
+}
+
+impl SubmoduleRecursion {
+    /// Tell the callback how the submodule at `path` fared, counting a
+    /// failure; `Err(GIT_EUSER)` if the callback stopped the update.
+    unsafe fn report(&mut self, path: &[u8], depth: c_uint, rc: c_int) -> Result<(), c_int> {
+        if rc < 0 {
+            self.failures += 1;
+        }
+        let Some(callback) = self.callback else {
+            return Ok(());
+        };
+        let path = CString::new(path).unwrap_or_default();
+        let message = if rc < 0 {
+            git2_shim_error_last_message()
+        } else {
+            ptr::null()
+        };
+        if callback(path.as_ptr(), depth, rc, message, self.payload) != 0 {
+            return Err(raw::GIT_EUSER);
+        }
+        Ok(())
+    }
+}
+
+extern "C" fn submodule_name_collector(
+    _submodule: *mut raw::git_submodule,
+    name: *const c_char,
+    payload: *mut c_void,
+) -> c_int {
+    let names = unsafe { &mut *(payload as *mut Vec<CString>) };
+    names.push(unsafe { CStr::from_ptr(name) }.to_owned());
+    0
+}
+
+/// Whether `git submodule init` has copied the submodule's URL into the
+/// repository configuration.
+unsafe fn submodule_is_initialized(repo: *mut raw::git_repository, name: &CStr) -> bool {
+    let mut config = ptr::null_mut();
+    if raw::git_repository_config_snapshot(&mut config, repo) < 0 {
+        return false;
+    }
+    let mut key = b"submodule.".to_vec();
+    key.extend_from_slice(name.to_bytes());
+    key.extend_from_slice(b".url");
+    let key = CString::new(key).unwrap_or_default();
+
+    let mut url: raw::git_buf = std::mem::zeroed();
+    let found = raw::git_config_get_string_buf(&mut url, config, key.as_ptr()) == 0;
+    raw::git_buf_dispose(&mut url);
+    raw::git_config_free(config);
+    found
+}
+
+/// Update every submodule of `repo`, then recurse into the checked-out
+/// ones. Returns `GIT_EUSER` if the callback stopped the walk.
+unsafe fn submodule_update_level(
+    state: &mut SubmoduleRecursion,
+    repo: *mut raw::git_repository,
+    prefix: &[u8],
+    depth: c_uint,
+) -> c_int {
+    let mut names: Vec<CString> = Vec::new();
+    let rc = raw::git_submodule_foreach(
+        repo,
+        submodule_name_collector,
+        &mut names as *mut _ as *mut c_void,
+    );
+    if rc < 0 {
+        return rc;
+    }
+
+    for name in &names {
+        // Entries only in .gitmodules have nothing checked out to update.
+        let mut status: c_uint = 0;
+        if raw::git_submodule_status(&mut status, repo, name.as_ptr(), -1) == 0
+            && status == raw::GIT_SUBMODULE_STATUS_IN_CONFIG
+        {
+            continue;
+        }
+        // Like `git submodule update --recursive`, leave uninitialized
+        // submodules alone unless asked to initialize them.
+        if state.init == 0 && !submodule_is_initialized(repo, name) {
+            continue;
+        }
+
+        let mut submodule = ptr::null_mut();
+        let mut rc = raw::git_submodule_lookup(&mut submodule, repo, name.as_ptr());
+        let mut path = prefix.to_vec();
+        path.extend_from_slice(if submodule.is_null() {
+            name.as_bytes()
+        } else {
+            CStr::from_ptr(raw::git_submodule_path(submodule)).to_bytes()
+        });
+
+        let mut child = ptr::null_mut();
+        if rc == 0 {
+            rc = git2_shim_submodule_update(submodule, state.init, state.opts);
+        }
+        if rc == 0 && (state.max_depth == 0 || depth < state.max_depth) {
+            rc = raw::git_submodule_open(&mut child, submodule);
+        }
+        if !submodule.is_null() {
+            raw::git_submodule_free(submodule);
+        }
+
+        if let Err(rc) = state.report(&path, depth, rc) {
+            if !child.is_null() {
+                raw::git_repository_free(child);
+            }
+            return rc;
+        }
+
+        if !child.is_null() {
+            let mut child_prefix = path.clone();
+            child_prefix.push(b'/');
+            let rc = submodule_update_level(state, child, &child_prefix, depth + 1);
+            raw::git_repository_free(child);
+            if rc == raw::GIT_EUSER {
+                return rc;
+            }
+            // The submodule's own submodules could not be listed.
+            if rc < 0 {
+                if let Err(rc) = state.report(&path, depth, rc) {
+                    return rc;
+                }
+            }
+        }
+    }
+    0
+}
+
+/// `git submodule update --recursive` in one call: update each submodule
+/// of `repo` (initializing it first when `init` is non-zero, otherwise
+/// skipping uninitialized ones), then descend into it, up to `max_depth`
+/// levels (0 for no limit). A failing submodule does not stop the others
+/// but is not descended into; `callback` (may be NULL) hears about every
+/// submodule, and about it a second time, with the error, if its own
+/// submodules cannot be listed. `opts` (may be NULL) applies at every
+/// level.
+///
+/// Returns the number of submodules that failed, or a negative error if
+/// the top-level submodules could not be listed or the callback stopped
+/// the update (`GIT_EUSER`).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_submodule_update_recursive(
+    repo: *mut raw::git_repository,
+    init: c_int,
+    max_depth: c_uint,
+    opts: *mut SubmoduleUpdateOptions,
+    callback: Option<git2_shim_submodule_update_cb>,
+    payload: *mut c_void,
+) -> c_int {
+    let mut state = SubmoduleRecursion {
+        init,
+        max_depth,
+        opts,
+        callback,
+        payload,
+        failures: 0,
+    };
+    let rc = submodule_update_level(&mut state, repo, b"", 1);
+    if rc < 0 {
+        return rc;
+    }
+    state.failures
+}
+
+// =============================================================================
+// Worktrees
+// =============================================================================
+
+/// Names of the repository's linked worktrees (not the main one); dispose
+/// with `git2_shim_strarray_dispose`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_list(
+    out: *mut raw::git_strarray,
+    repo: *mut raw::git_repository,
+) -> c_int {
+    raw::git_worktree_list(out, repo)
+}
+
+/// Linked worktree `name` of `repo`; free with `git2_shim_worktree_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_lookup(
+    out: *mut *mut raw::git_worktree,
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+) -> c_int {
+    raw::git_worktree_lookup(out, repo, name)
+}
+
+/// The worktree that `repo` was opened from; fails if `repo` is not a
+/// linked worktree.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_open_from_repository(
+    out: *mut *mut raw::git_worktree,
+    repo: *mut raw::git_repository,
+) -> c_int {
+    raw::git_worktree_open_from_repository(out, repo)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_free(wt: *mut raw::git_worktree) {
+    raw::git_worktree_free(wt)
+}
+
+/// Worktree creation options; by default a new branch named after the
+/// worktree is created at HEAD and checked out safely.
+pub struct WorktreeAddOptions {
+    raw: raw::git_worktree_add_options,
+    checkout: Option<CheckoutOptions>,
+    branch: Option<CString>,
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_add_options_new() -> *mut WorktreeAddOptions {
+    let mut opts = Box::new(WorktreeAddOptions {
+        raw: std::mem::zeroed(),
+        checkout: None,
+        branch: None,
+    });
+    if raw::git_worktree_add_options_init(&mut opts.raw, raw::GIT_WORKTREE_ADD_OPTIONS_VERSION) < 0
+    {
+        return ptr::null_mut();
+    }
+    Box::into_raw(opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_add_options_free(opts: *mut WorktreeAddOptions) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// Non-zero locks the new worktree straight away, so `prune` leaves it
+/// alone until it is unlocked.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_add_options_set_lock(
+    opts: *mut WorktreeAddOptions,
+    lock: c_int,
+) {
+    (*opts).raw.lock = lock;
+}
+
+/// Non-zero checks out an existing local branch named after the worktree
+/// instead of failing because it already exists.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_add_options_set_checkout_existing(
+    opts: *mut WorktreeAddOptions,
+    checkout_existing: c_int,
+) {
+    (*opts).raw.checkout_existing = checkout_existing;
+}
+
+/// Existing local branch (short name, e.g. "main") to check out in the new
+/// worktree; it must not be checked out elsewhere. NULL restores creating
+/// a branch named after the worktree.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_add_options_set_branch(
+    opts: *mut WorktreeAddOptions,
+    branch: *const c_char,
+) {
+    (*opts).branch = (!branch.is_null()).then(|| {
+        let mut name = b"refs/heads/".to_vec();
+        name.extend_from_slice(CStr::from_ptr(branch).to_bytes());
+        CString::new(name).unwrap_or_default()
+    });
+}
+
+/// Checkout options for the new work tree; copied. NULL restores the
+/// default safe checkout.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_add_options_set_checkout_options(
+    opts: *mut WorktreeAddOptions,
+    checkout: *const CheckoutOptions,
+) {
+    (*opts).checkout = checkout.as_ref().cloned();
+    if (*opts).checkout.is_none() {
+        let mut defaults: raw::git_worktree_add_options = std::mem::zeroed();
+        raw::git_worktree_add_options_init(&mut defaults, raw::GIT_WORKTREE_ADD_OPTIONS_VERSION);
+        (*opts).raw.checkout_options = defaults.checkout_options;
+    }
+}
+
+/// Create linked worktree `name` with its work tree at `path`, like
+/// `git worktree add`; free the result with `git2_shim_worktree_free`.
+/// `opts` may be NULL. Fails with `GIT_ENOTFOUND` if the requested branch
+/// does not exist.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_add(
+    out: *mut *mut raw::git_worktree,
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+    path: *const c_char,
+    opts: *mut WorktreeAddOptions,
+) -> c_int {
+    let Some(opts) = opts.as_mut() else {
+        return raw::git_worktree_add(out, repo, name, path, ptr::null());
+    };
+
+    let mut reference = ptr::null_mut();
+    if let Some(branch) = opts.branch.as_ref() {
+        let rc = raw::git_reference_lookup(&mut reference, repo, branch.as_ptr());
+        if rc < 0 {
+            return rc;
+        }
+    }
+    if let Some(checkout) = opts.checkout.as_mut() {
+        opts.raw.checkout_options = checkout.raw_opts();
+    }
+    opts.raw.ref_ = reference;
+
+    let rc = raw::git_worktree_add(out, repo, name, path, &opts.raw);
+
+    opts.raw.ref_ = ptr::null_mut();
+    if !reference.is_null() {
+        raw::git_reference_free(reference);
+    }
+    rc
+}
+
+/// Name of the worktree, as used with `git2_shim_worktree_lookup`; valid
+/// until the worktree is freed.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_name(wt: *const raw::git_worktree) -> *const c_char {
+    raw::git_worktree_name(wt)
+}
+
+/// Absolute path of the worktree's work tree; valid until the worktree is
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_path(wt: *const raw::git_worktree) -> *const c_char {
+    raw::git_worktree_path(wt)
+}
+
+/// Zero if the worktree's administrative files and gitdir are intact;
+/// an error (with the reason in the last error) once the work tree has
+/// been removed or moved behind libgit2's back.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_validate(wt: *const raw::git_worktree) -> c_int {
+    raw::git_worktree_validate(wt)
+}
+
+/// Lock the worktree against pruning, recording `reason` (may be NULL);
+/// fails if it is already locked.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_lock(
+    wt: *mut raw::git_worktree,
+    reason: *const c_char,
+) -> c_int {
+    raw::git_worktree_lock(wt, reason)
+}
+
+/// 0 once unlocked, 1 if the worktree was not locked.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_unlock(wt: *mut raw::git_worktree) -> c_int {
+    raw::git_worktree_unlock(wt)
+}
+
+/// 0 if unlocked, positive if locked, with the lock reason written to
+/// `reason` (may be NULL; dispose with `git2_shim_buf_dispose`).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_is_locked(
+    reason: *mut raw::git_buf,
+    wt: *const raw::git_worktree,
+) -> c_int {
+    raw::git_worktree_is_locked(reason, wt)
+}
+
+unsafe fn worktree_prune_options(flags: u32) -> raw::git_worktree_prune_options {
+    let mut opts: raw::git_worktree_prune_options = std::mem::zeroed();
+    raw::git_worktree_prune_options_init(&mut opts, raw::GIT_WORKTREE_PRUNE_OPTIONS_VERSION);
+    opts.flags = flags;
+    opts
+}
+
+/// 1 if `git2_shim_worktree_prune` with the same `flags` would prune the
+/// worktree, 0 if not. `flags` is a `GIT_WORKTREE_PRUNE_*` mask: 1 also
+/// prunes valid worktrees, 2 locked ones, 4 removes the work tree itself.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_is_prunable(
+    wt: *mut raw::git_worktree,
+    flags: u32,
+) -> c_int {
+    let mut opts = worktree_prune_options(flags);
+    raw::git_worktree_is_prunable(wt, &mut opts)
+}
+
+/// Remove the worktree's administrative files, and its work tree too when
+/// `flags` has 4 set, like `git worktree prune`/`remove`. Without flags
+/// only stale (invalid), unlocked worktrees are pruned.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_worktree_prune(wt: *mut raw::git_worktree, flags: u32) -> c_int {
+    let mut opts = worktree_prune_options(flags);
+    raw::git_worktree_prune(wt, &mut opts)
+}
+
+/// Open the repository checked out in a linked worktree; its gitdir is
+/// the worktree's own `.git/worktrees/<name>` directory.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_open_from_worktree(
+    out: *mut *mut raw::git_repository,
+    wt: *mut raw::git_worktree,
+) -> c_int {
+    raw::git_repository_open_from_worktree(out, wt)
+}
+
+/// 1 if `repo` was opened through a linked worktree, 0 otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_is_worktree(
+    repo: *const raw::git_repository,
+) -> c_int {
+    raw::git_repository_is_worktree(repo)
+}
+
+/// The gitdir of `repo`: `.git/worktrees/<name>/` for a linked worktree,
+/// holding its HEAD and index.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_path(
+    repo: *const raw::git_repository,
+) -> *const c_char {
+    raw::git_repository_path(repo)
+}
+
+/// The directory shared by all worktrees, holding objects, refs and
+/// config; the same as the gitdir outside linked worktrees.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_commondir(
+    repo: *const raw::git_repository,
+) -> *const c_char {
+    raw::git_repository_commondir(repo)
+}
+
+// =============================================================================
+// Repository initialization, discovery and opening
+// =============================================================================
+
+/// Create (or reinitialize) a repository at `path`, creating missing
+/// directories, like `git init [--bare]`; free with
+/// `git2_shim_repository_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_init(
+    out: *mut *mut raw::git_repository,
+    path: *const c_char,
+    is_bare: c_uint,
+) -> c_int {
+    raw::git_repository_init(out, path, is_bare)
+}
+
+/// Options for `git2_shim_repository_init_ext`; defaults to creating
+/// missing directories, as `git2_shim_repository_init` does.
+pub struct RepositoryInitOptions {
+    raw: raw::git_repository_init_options,
+    workdir_path: Option<CString>,
+    description: Option<CString>,
+    template_path: Option<CString>,
+    initial_head: Option<CString>,
+    origin_url: Option<CString>,
+}
+
+impl RepositoryInitOptions {
+    fn raw_opts(&mut self) -> *mut raw::git_repository_init_options {
+        self.raw.workdir_path = cstring_ptr(&self.workdir_path);
+        self.raw.description = cstring_ptr(&self.description);
+        self.raw.template_path = cstring_ptr(&self.template_path);
+        self.raw.initial_head = cstring_ptr(&self.initial_head);
+        self.raw.origin_url = cstring_ptr(&self.origin_url);
+        if self.template_path.is_some() {
+            self.raw.flags |= raw::GIT_REPOSITORY_INIT_EXTERNAL_TEMPLATE;
+        }
+        &mut self.raw
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_init_options_new() -> *mut RepositoryInitOptions {
+    let mut opts = Box::new(RepositoryInitOptions {
+        raw: std::mem::zeroed(),
+        workdir_path: None,
+        description: None,
+        template_path: None,
+        initial_head: None,
+        origin_url: None,
+    });
+    if raw::git_repository_init_options_init(
+        &mut opts.raw,
+        raw::GIT_REPOSITORY_INIT_OPTIONS_VERSION,
+    ) < 0
+    {
+        return ptr::null_mut();
+    }
+    opts.raw.flags = raw::GIT_REPOSITORY_INIT_MKPATH;
+    Box::into_raw(opts)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_init_options_free(opts: *mut RepositoryInitOptions) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// Replace the `GIT_REPOSITORY_INIT_*` flags: 1 bare, 2 fail if the
+/// repository exists, 4 no `.git` subdirectory, 8 create the repository
+/// directory, 16 create missing parents too, 32 copy an external template
+/// instead of the built-in one, 64 relative gitlink.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_init_options_set_flags(
+    opts: *mut RepositoryInitOptions,
+    flags: u32,
+) {
+    (*opts).raw.flags = flags;
+}
+
+/// Permissions to share the repository with: 0 for the umask (default),
+/// 0o2775 for the group, 0o2777 for everyone, or a custom mode.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_init_options_set_mode(
+    opts: *mut RepositoryInitOptions,
+    mode: u32,
+) {
+    (*opts).raw.mode = mode;
+}
+
+/// Work tree to use instead of the repository path's parent; a `.git`
+/// gitlink file pointing at the repository is written there. Relative
+/// paths are taken from the repository path.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_init_options_set_workdir_path(
+    opts: *mut RepositoryInitOptions,
+    path: *const c_char,
+) {
+    (*opts).workdir_path = owned_cstring(path);
+}
+
+/// Contents of the `description` file, instead of the template's.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_init_options_set_description(
+    opts: *mut RepositoryInitOptions,
+    description: *const c_char,
+) {
+    (*opts).description = owned_cstring(description);
+}
+
+/// Template directory to copy hooks, info and description from, like
+/// `git init --template`; setting one implies flag 32. With NULL, flag 32
+/// picks `init.templateDir` or else git's installed templates, and without
+/// it the built-in template is used.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_init_options_set_template_path(
+    opts: *mut RepositoryInitOptions,
+    path: *const c_char,
+) {
+    (*opts).template_path = owned_cstring(path);
+}
+
+/// Branch HEAD points at, e.g. "main" or "refs/heads/main"; NULL uses
+/// `init.defaultBranch`, falling back to "master".
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_init_options_set_initial_head(
+    opts: *mut RepositoryInitOptions,
+    head: *const c_char,
+) {
+    (*opts).initial_head = owned_cstring(head);
+}
+
+/// Add an "origin" remote with this URL once the repository is created.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_init_options_set_origin_url(
+    opts: *mut RepositoryInitOptions,
+    url: *const c_char,
+) {
+    (*opts).origin_url = owned_cstring(url);
+}
+
+/// Create (or reinitialize) a repository at `repo_path` with extended
+/// options; `opts` may be NULL for the defaults. Free the result with
+/// `git2_shim_repository_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_init_ext(
+    out: *mut *mut raw::git_repository,
+    repo_path: *const c_char,
+    opts: *mut RepositoryInitOptions,
+) -> c_int {
+    match opts.as_mut() {
+        Some(opts) => raw::git_repository_init_ext(out, repo_path, opts.raw_opts()),
+        None => {
+            let mut defaults: raw::git_repository_init_options = std::mem::zeroed();
+            raw::git_repository_init_options_init(
+                &mut defaults,
+                raw::GIT_REPOSITORY_INIT_OPTIONS_VERSION,
+            );
+            defaults.flags = raw::GIT_REPOSITORY_INIT_MKPATH;
+            raw::git_repository_init_ext(out, repo_path, &mut defaults)
+        }
+    }
+}
+
+/// Find the repository containing `start_path`, walking up through parent
+/// directories like git does, and write the path of its git directory to
+/// `out` (dispose with `git2_shim_buf_dispose`). The walk stops at any of
+/// the `ceiling_dirs` (a `:`-separated list, `;` on Windows; may be NULL)
+/// and, unless `across_fs` is non-zero, at filesystem boundaries. Fails
+/// with `GIT_ENOTFOUND` when no repository encloses `start_path`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_discover(
+    out: *mut raw::git_buf,
+    start_path: *const c_char,
+    across_fs: c_int,
+    ceiling_dirs: *const c_char,
+) -> c_int {
+    raw::git_repository_discover(out, start_path, across_fs, ceiling_dirs)
+}
+
+/// Open a repository with control over how it is located; free with
+/// `git2_shim_repository_free`. `flags` is a `GIT_REPOSITORY_OPEN_*`
+/// mask: 1 only looks at `path` itself instead of walking up parent
+/// directories, 2 lets the walk cross filesystem boundaries, 4 opens the
+/// repository as bare, 8 does not try appending `/.git`, and 16 follows
+/// `GIT_DIR`, `GIT_CEILING_DIRECTORIES` and friends like the git CLI,
+/// ignoring the other flags and `ceiling_dirs` (`path` may then be NULL).
+/// `ceiling_dirs` is a `:`-separated (`;` on Windows) list of directories
+/// the walk stops at; may be NULL. Pass NULL for `out` to only test
+/// whether a repository would be found.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_open_ext(
+    out: *mut *mut raw::git_repository,
+    path: *const c_char,
+    flags: c_uint,
+    ceiling_dirs: *const c_char,
+) -> c_int {
+    raw::git_repository_open_ext(out, path, flags, ceiling_dirs)
+}
+
+/// Open the git directory at `bare_path` directly, with no search and no
+/// work tree or index setup; the cheapest way to open a bare repository.
+/// Free with `git2_shim_repository_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_open_bare(
+    out: *mut *mut raw::git_repository,
+    bare_path: *const c_char,
+) -> c_int {
+    raw::git_repository_open_bare(out, bare_path)
+}
+
+/// Open the git directory `gitdir` exactly as given, with `workdir` as its
+/// work tree, whatever `core.worktree` or a `.git` file say; for detached
+/// git directories and exported work trees. Nothing is written to disk;
+/// free with `git2_shim_repository_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_open_with_workdir(
+    out: *mut *mut raw::git_repository,
+    gitdir: *const c_char,
+    workdir: *const c_char,
+) -> c_int {
+    let mut repo = ptr::null_mut();
+    let flags = raw::GIT_REPOSITORY_OPEN_NO_SEARCH | raw::GIT_REPOSITORY_OPEN_NO_DOTGIT;
+    let rc = raw::git_repository_open_ext(&mut repo, gitdir, flags, ptr::null());
+    if rc < 0 {
+        return rc;
+    }
+    let rc = raw::git_repository_set_workdir(repo, workdir, 0);
+    if rc < 0 {
+        raw::git_repository_free(repo);
+        return rc;
+    }
+    *out = repo;
+    0
+}
+
+// =============================================================================
+// Repository paths and state
+// =============================================================================
+
+/// Write the location of a repository item to `out` (dispose with
+/// `git2_shim_buf_dispose`), accounting for linked worktrees, `gitdir:`
+/// files and shared common directories. `item` is a
+/// `GIT_REPOSITORY_ITEM_*` value: 0 gitdir, 1 workdir, 2 commondir,
+/// 3 index, 4 objects, 5 refs, 6 packed-refs, 7 remotes, 8 config,
+/// 9 info, 10 hooks, 11 logs, 12 modules, 13 worktrees, 14 worktree
+/// config. Directories end in a slash. Fails with `GIT_ENOTFOUND` when
+/// the item cannot exist, e.g. the workdir of a bare repository.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_item_path(
+    out: *mut raw::git_buf,
+    repo: *const raw::git_repository,
+    item: c_int,
+) -> c_int {
+    raw::git_repository_item_path(out, repo, item)
+}
+
+/// Write the prepared commit message left in `MERGE_MSG` by a merge,
+/// revert, cherry-pick or squash to `out` (dispose with
+/// `git2_shim_buf_dispose`); `GIT_ENOTFOUND` if there is none.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_message(
+    out: *mut raw::git_buf,
+    repo: *mut raw::git_repository,
+) -> c_int {
+    raw::git_repository_message(out, repo)
+}
+
+/// Delete `MERGE_MSG`, as committing or aborting does; fails if there is
+/// none.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_message_remove(
+    repo: *mut raw::git_repository,
+) -> c_int {
+    raw::git_repository_message_remove(repo)
+}
+
+/// A commit whose parents are missing from a shallow repository; history
+/// walks stop there as if it were a root commit.
+///
+/// Return 0 to continue iterating; any other value stops the walk and is
+/// returned from `git2_shim_repository_shallow_foreach`.
+pub type git2_shim_shallow_cb =
+    extern "C" fn(id: *const raw::git_oid, payload: *mut c_void) -> c_int;
+
+/// Visit the boundary commits of a shallow clone or fetch, as listed in the
+/// `shallow` file of the common directory. Non-shallow repositories visit
+/// nothing. Ahead/behind counts and merge bases computed across these
+/// commits can be wrong, since their real ancestry is unknown locally.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_shallow_foreach(
+    repo: *mut raw::git_repository,
+    callback: git2_shim_shallow_cb,
+    payload: *mut c_void,
+) -> c_int {
+    let Ok(commondir) = CStr::from_ptr(raw::git_repository_commondir(repo)).to_str() else {
+        return os_error(std::io::Error::other("common directory is not UTF-8"));
+    };
+    let file = match std::fs::File::open(Path::new(commondir).join("shallow")) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return 0,
+        Err(err) => return os_error(err),
+    };
+
+    for line in BufReader::new(file).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => return os_error(err),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some(id) = parse_object_id(line) else {
+            let message =
+                CString::new(format!("invalid shallow entry '{line}'")).unwrap_or_default();
+            raw::git_error_set_str(raw::GIT_ERROR_INVALID, message.as_ptr());
+            return -1;
+        };
+        let rc = callback(&raw::git_oid { id }, payload);
+        if rc != 0 {
+            return rc;
+        }
+    }
+    0
+}
+
+/// Operation in progress, as a `GIT_REPOSITORY_STATE_*` value: 0 none,
+/// 1 merge, 2 revert, 3 revert sequence, 4 cherry-pick, 5 cherry-pick
+/// sequence, 6 bisect, 7 rebase, 8 interactive rebase, 9 merge rebase,
+/// 10 `git am`, 11 `git am` or rebase.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_state(repo: *mut raw::git_repository) -> c_int {
+    raw::git_repository_state(repo)
+}
+
+/// Commit being cherry-picked, from `CHERRY_PICK_HEAD`; `GIT_ENOTFOUND` if
+/// no cherry-pick is stopped.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_cherrypick_head(
+    out: *mut raw::git_oid,
+    repo: *mut raw::git_repository,
+) -> c_int {
+    raw::git_reference_name_to_id(out, repo, c"CHERRY_PICK_HEAD".as_ptr())
+}
+
+/// Commit being reverted, from `REVERT_HEAD`; `GIT_ENOTFOUND` if no revert
+/// is stopped.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_revert_head(
+    out: *mut raw::git_oid,
+    repo: *mut raw::git_repository,
+) -> c_int {
+    raw::git_reference_name_to_id(out, repo, c"REVERT_HEAD".as_ptr())
+}
+
+/// Callback invoked once per commit being merged into HEAD.
+///
+/// Return 0 to continue iterating; any other value stops the walk and is
+/// returned from `git2_shim_repository_mergehead_foreach`.
+pub type git2_shim_mergehead_cb =
+    extern "C" fn(id: *const raw::git_oid, payload: *mut c_void) -> c_int;
+
+/// Visit the commits listed in `MERGE_HEAD`, one per merged branch (several
+/// for an octopus merge); fails with `GIT_ENOTFOUND` if no merge is stopped.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_mergehead_foreach(
+    repo: *mut raw::git_repository,
+    callback: git2_shim_mergehead_cb,
+    payload: *mut c_void,
+) -> c_int {
+    raw::git_repository_mergehead_foreach(repo, callback, payload)
+}
+
+/// Attach `repo` to the work tree at `workdir`, e.g. after moving a
+/// checkout or to give a detached gitdir a work tree. A non-zero
+/// `update_gitlink` also records the association on disk: a `.git` file
+/// pointing at the repository is written in `workdir`, `core.worktree` is
+/// set and `core.bare` cleared, as `git init --separate-git-dir` does.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_set_workdir(
+    repo: *mut raw::git_repository,
+    workdir: *const c_char,
+    update_gitlink: c_int,
+) -> c_int {
+    raw::git_repository_set_workdir(repo, workdir, update_gitlink)
+}
+
+// =============================================================================
+// Object and reference databases
+// =============================================================================
+
+/// The repository's object database, shared with every other operation on
+/// `repo`; free with `git2_shim_odb_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_odb(
+    out: *mut *mut raw::git_odb,
+    repo: *mut raw::git_repository,
+) -> c_int {
+    raw::git_repository_odb(out, repo)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_odb_free(odb: *mut raw::git_odb) {
+    raw::git_odb_free(odb)
+}
+
+/// 1 if object `id` is in the database, 0 if not.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_odb_exists(
+    odb: *mut raw::git_odb,
+    id: *const raw::git_oid,
+) -> c_int {
+    raw::git_odb_exists(odb, id)
+}
+
+/// Rescan the backends for packs written by other processes since the
+/// database was opened.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_odb_refresh(odb: *mut raw::git_odb) -> c_int {
+    raw::git_odb_refresh(odb)
+}
+
+/// An object database with no backends, to fill with
+/// `git2_shim_odb_add_pack` and `git2_shim_odb_add_disk_alternate`; free
+/// with `git2_shim_odb_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_odb_new(out: *mut *mut raw::git_odb) -> c_int {
+    raw::git_odb_new(out)
+}
+
+/// Open the loose objects and packs under `objects_dir` (a `.git/objects`
+/// directory), following its `info/alternates`, without a repository
+/// around it; free with `git2_shim_odb_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_odb_open(
+    out: *mut *mut raw::git_odb,
+    objects_dir: *const c_char,
+) -> c_int {
+    raw::git_odb_open(out, objects_dir)
+}
+
+/// Read objects from another objects directory as well, as an
+/// `info/alternates` entry would; lookups try it after the existing
+/// backends.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_odb_add_disk_alternate(
+    odb: *mut raw::git_odb,
+    path: *const c_char,
+) -> c_int {
+    raw::git_odb_add_disk_alternate(odb, path)
+}
+
+/// Read objects from the single pack whose index is `index_path` (the
+/// `.idx` next to its `.pack`), wherever it lives on disk.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_odb_add_pack(
+    odb: *mut raw::git_odb,
+    index_path: *const c_char,
+) -> c_int {
+    let mut backend = ptr::null_mut();
+    let rc = raw::git_odb_backend_one_pack(&mut backend, index_path);
+    if rc < 0 {
+        return rc;
+    }
+    add_odb_backend(odb, backend, 1)
+}
+
+/// A repository with `odb` as its object database and nothing else: no
+/// gitdir, work tree, references or configuration, so only object lookups,
+/// revwalks from explicit ids and similar object-level operations work.
+/// `odb` is shared, so it may be freed right away. Free with
+/// `git2_shim_repository_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_wrap_odb(
+    out: *mut *mut raw::git_repository,
+    odb: *mut raw::git_odb,
+) -> c_int {
+    raw::git_repository_wrap_odb(out, odb)
+}
+
+/// The repository's reference database; free with `git2_shim_refdb_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_refdb(
+    out: *mut *mut raw::git_refdb,
+    repo: *mut raw::git_repository,
+) -> c_int {
+    raw::git_repository_refdb(out, repo)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_refdb_free(refdb: *mut raw::git_refdb) {
+    raw::git_refdb_free(refdb)
+}
+
+/// Pack loose references into `packed-refs`, like `git pack-refs --all`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_refdb_compress(refdb: *mut raw::git_refdb) -> c_int {
+    raw::git_refdb_compress(refdb)
+}
+
+/// Id the file at `path` (relative paths start at the work tree) would get
+/// as an object of type `kind` (3 for a blob) after the clean filters,
+/// `.gitattributes` and CRLF conversion that `git add` applies, so it can
+/// be compared with index and tree entries. Filters are chosen by
+/// `as_path` when it is non-NULL, by `path` otherwise, and skipped
+/// entirely when `as_path` is the empty string.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_repository_hashfile(
+    out: *mut raw::git_oid,
+    repo: *mut raw::git_repository,
+    path: *const c_char,
+    kind: c_int,
+    as_path: *const c_char,
+) -> c_int {
+    raw::git_repository_hashfile(out, repo, path, kind, as_path)
+}
+
+// =============================================================================
+// Ignore rules
+// =============================================================================
+
+/// Add in-memory ignore rules to `repo`, in `.gitignore` syntax with one
+/// rule per line (e.g. "build/\n*.o"). They apply on top of the files on
+/// disk for as long as this repository handle lives and are never written
+/// out.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_ignore_add_rule(
+    repo: *mut raw::git_repository,
+    rules: *const c_char,
+) -> c_int {
+    raw::git_ignore_add_rule(repo, rules)
+}
+
+/// Drop every rule added with `git2_shim_ignore_add_rule`, restoring the
+/// default internal rules (".", ".." and ".git").
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_ignore_clear_internal_rules(
+    repo: *mut raw::git_repository,
+) -> c_int {
+    raw::git_ignore_clear_internal_rules(repo)
+}
+
+/// Set `ignored` to 1 if `path` (relative to the work tree) would be
+/// ignored by the `.gitignore` files, `info/exclude`, `core.excludesFile`
+/// and internal rules, 0 otherwise. Unlike status, this does not care
+/// whether the file is tracked; a trailing slash tests `path` as a
+/// directory.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_ignore_path_is_ignored(
+    ignored: *mut c_int,
+    repo: *mut raw::git_repository,
+    path: *const c_char,
+) -> c_int {
+    raw::git_ignore_path_is_ignored(ignored, repo, path)
+}
+
+// =============================================================================
+// Attributes
+// =============================================================================
+
+/// Classify a value returned by the attribute lookups: 0 unspecified,
+/// 1 set (`attr`), 2 unset (`-attr`), 3 a string (`attr=value`). Set and
+/// unset values are sentinels, not printable strings, so always check
+/// this before reading one.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_attr_value(value: *const c_char) -> c_int {
+    raw::git_attr_value(value)
+}
+
+/// Value of attribute `name` for `path` (relative to the work tree; the
+/// file need not exist), resolved through `.gitattributes`, `info/attributes`,
+/// `core.attributesFile` and macros exactly as git would. `flags` picks the
+/// sources: 0 work tree then index, 1 index then work tree, 2 index only;
+/// add 4 to skip the system file and 8 to also read `.gitattributes` from
+/// HEAD. The value is owned by the attribute cache; copy it before the next
+/// attribute call.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_attr_get(
+    value_out: *mut *const c_char,
+    repo: *mut raw::git_repository,
+    flags: u32,
+    path: *const c_char,
+    name: *const c_char,
+) -> c_int {
+    raw::git_attr_get(value_out, repo, flags, path, name)
+}
+
+/// `git2_shim_attr_get` for `num_attr` names at once, filling `values_out`
+/// (room for `num_attr` pointers) in the same order.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_attr_get_many(
+    values_out: *mut *const c_char,
+    repo: *mut raw::git_repository,
+    flags: u32,
+    path: *const c_char,
+    num_attr: size_t,
+    names: *mut *const c_char,
+) -> c_int {
+    raw::git_attr_get_many(values_out, repo, flags, path, num_attr, names)
+}
+
+/// An attribute that applies to the path given to `git2_shim_attr_foreach`;
+/// classify `value` with `git2_shim_attr_value`.
+///
+/// Return 0 to continue iterating; any other value stops the walk and is
+/// returned from `git2_shim_attr_foreach`.
+pub type git2_shim_attr_foreach_cb =
+    extern "C" fn(name: *const c_char, value: *const c_char, payload: *mut c_void) -> c_int;
+
+extern "C" fn attr_foreach_trampoline(
+    name: *const c_char,
+    value: *const c_char,
+    payload: *mut c_void,
+) -> c_int {
+    let t = unsafe { &*(payload as *const Trampoline<git2_shim_attr_foreach_cb>) };
+    (t.callback)(name, value, t.payload)
+}
+
+/// Visit every attribute set or unset for `path`, once each; `flags` as
+/// for `git2_shim_attr_get`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_attr_foreach(
+    repo: *mut raw::git_repository,
+    flags: u32,
+    path: *const c_char,
+    callback: git2_shim_attr_foreach_cb,
+    payload: *mut c_void,
+) -> c_int {
+    let mut t = Trampoline { callback, payload };
+    raw::git_attr_foreach(
+        repo,
+        flags,
+        path,
+        attr_foreach_trampoline,
+        &mut t as *mut _ as *mut c_void,
+    )
+}
+
+/// Define macro attribute `name` as `values` (e.g. "binary" as
+/// "-diff -merge -text"), as a `[attr]` line would, for this repository
+/// handle.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_attr_add_macro(
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+    values: *const c_char,
+) -> c_int {
+    raw::git_attr_add_macro(repo, name, values)
+}
+
+/// Forget cached attribute files, and macros added with
+/// `git2_shim_attr_add_macro`, so the next lookup rereads them from disk.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_attr_cache_flush(repo: *mut raw::git_repository) -> c_int {
+    raw::git_attr_cache_flush(repo)
+}
+
+// =============================================================================
+// Branch checkout
+// =============================================================================
+
+/// Switch to local branch `name` ("main" or "refs/heads/main") like
+/// `git switch`: check out the branch's tree, then point HEAD at it. Work
+/// tree changes are carried over where they do not collide; `opts` may be
+/// NULL for the default safe checkout, and a forced strategy discards
+/// local changes instead. HEAD only moves once the checkout succeeded.
+///
+/// Fails with `GIT_ENOTFOUND` if there is no such branch and with
+/// `GIT_ECONFLICT` if uncommitted changes would be overwritten, leaving
+/// the work tree, index and HEAD untouched.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_checkout_branch(
+    repo: *mut raw::git_repository,
+    name: *const c_char,
+    opts: *mut CheckoutOptions,
+) -> c_int {
+    let name = CStr::from_ptr(name).to_bytes();
+    let mut refname = if name.starts_with(b"refs/heads/") {
+        Vec::new()
+    } else {
+        b"refs/heads/".to_vec()
+    };
+    refname.extend_from_slice(name);
+    let Ok(refname) = CString::new(refname) else {
+        return raw::GIT_ENOTFOUND;
+    };
+
+    let mut branch = ptr::null_mut();
+    let rc = raw::git_reference_lookup(&mut branch, repo, refname.as_ptr());
+    if rc < 0 {
+        return rc;
+    }
+    let mut commit = ptr::null_mut();
+    let rc = raw::git_reference_peel(&mut commit, branch, raw::GIT_OBJECT_COMMIT);
+    raw::git_reference_free(branch);
+    if rc < 0 {
+        return rc;
+    }
+
+    // A NULL options pointer would make libgit2 do a dry run.
+    let raw_opts = match opts.as_mut() {
+        Some(opts) => opts.raw_opts(),
+        None => {
+            let mut defaults: raw::git_checkout_options = std::mem::zeroed();
+            raw::git_checkout_options_init(&mut defaults, raw::GIT_CHECKOUT_OPTIONS_VERSION);
+            defaults
+        }
+    };
+    let mut rc = raw::git_checkout_tree(repo, commit, &raw_opts);
+    raw::git_object_free(commit);
+    if rc == 0 {
+        rc = raw::git_repository_set_head(repo, refname.as_ptr());
+    }
+    rc
+}
+
+// =============================================================================
+// Filters
+// =============================================================================
+
+/// Where a filter's apply callback writes the filtered content.
+#[derive(Default)]
+pub struct FilterSink {
+    output: Vec<u8>,
+}
+
+/// Append `len` bytes to the filtered content; may be called repeatedly.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_filter_sink_write(
+    sink: *mut FilterSink,
+    data: *const c_char,
+    len: size_t,
+) {
+    if len > 0 {
+        let data = std::slice::from_raw_parts(data as *const u8, len);
+        (*sink).output.extend_from_slice(data);
+    }
+}
+
+/// Decide whether the filter runs for `src`. `attr_values` holds one value
+/// per name in the filter's attribute list: NULL if unspecified, while set
+/// and unset are sentinel strings that `git2_shim_attr_value` tells apart
+/// from real values. Return 0 to apply the filter,
+/// `GIT_PASSTHROUGH` (-30) to skip this file, or a negative error to fail
+/// the checkout or add.
+pub type git2_shim_filter_check_cb = extern "C" fn(
+    src: *const raw::git_filter_source,
+    attr_values: *const *const c_char,
+    payload: *mut c_void,
+) -> c_int;
+
+/// Filter the `len` bytes at `input` for `src` and write the result to
+/// `sink`. Return 0 to use the sink's content, `GIT_PASSTHROUGH` (-30) to
+/// keep the input unchanged, or a negative error to fail the checkout or
+/// add.
+pub type git2_shim_filter_apply_cb = extern "C" fn(
+    sink: *mut FilterSink,
+    input: *const c_char,
+    len: size_t,
+    src: *const raw::git_filter_source,
+    payload: *mut c_void,
+) -> c_int;
+
+/// A filter to register with `git2_shim_filter_register`. The libgit2
+/// vtable comes first so callbacks can recover the filter from it.
+#[repr(C)]
+pub struct Filter {
+    raw: raw::git_filter,
+    attributes: Option<CString>,
+    check: Option<git2_shim_filter_check_cb>,
+    apply: git2_shim_filter_apply_cb,
+    payload: *mut c_void,
+}
+
+/// Build a filter from callbacks. `attributes` selects the files it sees,
+/// as a whitespace-separated list: a bare name (e.g. `"text"`) is only
+/// looked up and passed to `check`, while `name=value` (e.g.
+/// `"filter=crypt"`) must match, with `*` matching any set value. A NULL
+/// `attributes` runs the filter on every file, and a NULL `check` applies
+/// it whenever the attributes match. `payload` is passed to every call.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_filter_new(
+    attributes: *const c_char,
+    check: Option<git2_shim_filter_check_cb>,
+    apply: git2_shim_filter_apply_cb,
+    payload: *mut c_void,
+) -> *mut Filter {
+    let attributes = owned_cstring(attributes);
+    Box::into_raw(Box::new(Filter {
+        raw: raw::git_filter {
+            version: raw::GIT_FILTER_VERSION,
+            attributes: cstring_ptr(&attributes),
+            initialize: ptr::null_mut(),
+            shutdown: ptr::null_mut(),
+            check: Some(filter_check_trampoline),
+            apply: ptr::null_mut(),
+            stream: Some(filter_stream_trampoline),
+            cleanup: ptr::null_mut(),
+        },
+        attributes,
+        check,
+        apply,
+        payload,
+    }))
+}
+
+/// Only free after unregistering it with `git2_shim_filter_unregister`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_filter_free(filter: *mut Filter) {
+    if !filter.is_null() {
+        drop(Box::from_raw(filter));
+    }
+}
+
+/// Apply `filter` under `name` wherever libgit2 converts content: smudging
+/// on checkout and blob filtering, cleaning on add and hashing. Filters run
+/// in ascending `priority` when smudging and descending when cleaning; the
+/// built-in crlf and ident filters have priorities 0 and 100, and git's
+/// `filter.<driver>` attribute convention uses 200. libgit2 borrows
+/// `filter` until unregistered. Fails with `GIT_EEXISTS` if `name` is
+/// taken. Not thread-safe: register during setup, before other threads use
+/// libgit2.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_filter_register(
+    name: *const c_char,
+    filter: *mut Filter,
+    priority: c_int,
+) -> c_int {
+    raw::git_filter_register(name, &mut (*filter).raw, priority)
+}
+
+/// Remove a filter added with `git2_shim_filter_register`; the built-in
+/// filters cannot be removed.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_filter_unregister(name: *const c_char) -> c_int {
+    raw::git_filter_unregister(name)
+}
+
+extern "C" fn filter_check_trampoline(
+    filter: *mut raw::git_filter,
+    _payload: *mut *mut c_void,
+    src: *const raw::git_filter_source,
+    attr_values: *mut *const c_char,
+) -> c_int {
+    let filter = unsafe { &*(filter as *const Filter) };
+    match filter.check {
+        Some(check) => check(src, attr_values, filter.payload),
+        None => 0,
+    }
+}
+
+/// Buffers a file's content, runs the filter's apply callback on close and
+/// forwards the result to the next stream in the chain.
+#[repr(C)]
+struct FilterStream {
+    parent: raw::git_writestream,
+    filter: *const Filter,
+    src: *const raw::git_filter_source,
+    next: *mut raw::git_writestream,
+    input: Vec<u8>,
+}
+
+extern "C" fn filter_stream_trampoline(
+    out: *mut *mut raw::git_writestream,
+    filter: *mut raw::git_filter,
+    _payload: *mut *mut c_void,
+    src: *const raw::git_filter_source,
+    next: *mut raw::git_writestream,
+) -> c_int {
+    let stream = Box::new(FilterStream {
+        parent: raw::git_writestream {
+            write: filter_stream_write,
+            close: filter_stream_close,
+            free: filter_stream_free,
+        },
+        filter: filter as *const Filter,
+        src,
+        next,
+        input: Vec::new(),
+    });
+    unsafe { *out = Box::into_raw(stream) as *mut raw::git_writestream };
+    0
+}
+
+extern "C" fn filter_stream_write(
+    stream: *mut raw::git_writestream,
+    buffer: *const c_char,
+    len: size_t,
+) -> c_int {
+    let stream = unsafe { &mut *(stream as *mut FilterStream) };
+    if len > 0 {
+        let data = unsafe { std::slice::from_raw_parts(buffer as *const u8, len) };
+        stream.input.extend_from_slice(data);
+    }
+    0
+}
+
+extern "C" fn filter_stream_close(stream: *mut raw::git_writestream) -> c_int {
+    let stream = unsafe { &mut *(stream as *mut FilterStream) };
+    let filter = unsafe { &*stream.filter };
+    let next = unsafe { &mut *stream.next };
+
+    let mut sink = FilterSink::default();
+    let rc = (filter.apply)(
+        &mut sink,
+        stream.input.as_ptr() as *const c_char,
+        stream.input.len(),
+        stream.src,
+        filter.payload,
+    );
+    let output = match rc {
+        0 => &sink.output,
+        raw::GIT_PASSTHROUGH => &stream.input,
+        _ => {
+            // Close the rest of the chain without clobbering the error
+            // the filter reported.
+            let message = unsafe { owned_cstring(git2_shim_error_last_message()) }
+                .unwrap_or_else(|| c"filter callback failed".to_owned());
+            (next.close)(next);
+            unsafe { raw::git_error_set_str(raw::GIT_ERROR_FILTER, message.as_ptr()) };
+            return rc;
+        }
+    };
+
+    let rc = (next.write)(next, output.as_ptr() as *const c_char, output.len());
+    if rc != 0 {
+        return rc;
+    }
+    (next.close)(next)
+}
+
+extern "C" fn filter_stream_free(stream: *mut raw::git_writestream) {
+    drop(unsafe { Box::from_raw(stream as *mut FilterStream) });
+}
+
+/// Path of the file being filtered, relative to the working directory.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_filter_source_path(
+    src: *const raw::git_filter_source,
+) -> *const c_char {
+    raw::git_filter_source_path(src)
+}
+
+/// 0 when smudging (to the working directory), 1 when cleaning (to the
+/// object database).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_filter_source_mode(
+    src: *const raw::git_filter_source,
+) -> c_uint {
+    raw::git_filter_source_mode(src)
+}
+
+/// Blob being filtered, or NULL if not known (e.g. when cleaning a file
+/// that has not been hashed yet).
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_filter_source_id(
+    src: *const raw::git_filter_source,
+) -> *const raw::git_oid {
+    raw::git_filter_source_id(src)
 }
 
+/// File mode of the file being filtered, or 0 if not known.
 #[no_mangle]
-pub unsafe extern "C" fn git2_shim_status_options_init(
-    opts: *mut raw::git_status_options,
-    version: c_uint,
-) -> c_int {
-    raw::git_status_options_init(opts, version)
+pub unsafe extern "C" fn git2_shim_filter_source_filemode(
+    src: *const raw::git_filter_source,
+) -> u16 {
+    raw::git_filter_source_filemode(src)
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn git2_shim_status_list_new(
-    out: *mut *mut raw::git_status_list,
+pub unsafe extern "C" fn git2_shim_filter_source_repo(
+    src: *const raw::git_filter_source,
+) -> *mut raw::git_repository {
+    raw::git_filter_source_repo(src)
+}
+
+/// Load the filters that apply to `path` in direction `mode` (0 smudge, 1
+/// clean), as decided by its attributes and the registered filters.
+/// `blob` (may be NULL) is the content about to be filtered, which the
+/// ident filter needs. `flags` are `GIT_FILTER_*` bits: 1 allow unsafe
+/// CRLF conversion, 2 skip system attributes, 4 read `.gitattributes` from
+/// HEAD instead of the working directory. Stores NULL in `out` when no
+/// filter applies; the apply functions accept that and copy the content
+/// through. Free with `git2_shim_filter_list_free`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_filter_list_load(
+    out: *mut *mut raw::git_filter_list,
     repo: *mut raw::git_repository,
-    opts: *const raw::git_status_options,
+    blob: *mut raw::git_blob,
+    path: *const c_char,
+    mode: c_uint,
+    flags: u32,
 ) -> c_int {
-    raw::git_status_list_new(out, repo, opts)
+    raw::git_filter_list_load(out, repo, blob, path, mode, flags)
 }
 
+/// 1 if the filter registered as `name` (or built-in "crlf" or "ident")
+/// is in `filters`, otherwise 0.
 #[no_mangle]
-pub unsafe extern "C" fn git2_shim_status_list_free(list: *mut raw::git_status_list) {
-    raw::git_status_list_free(list)
+pub unsafe extern "C" fn git2_shim_filter_list_contains(
+    filters: *mut raw::git_filter_list,
+    name: *const c_char,
+) -> c_int {
+    raw::git_filter_list_contains(filters, name)
 }
 
+/// Run the `len` bytes at `input` through `filters` into `out` (dispose with
+/// `git2_shim_buf_dispose`).
 #[no_mangle]
-pub unsafe extern "C" fn git2_shim_status_list_entrycount(
-    list: *const raw::git_status_list,
-) -> size_t {
-    raw::git_status_list_entrycount(list)
+pub unsafe extern "C" fn git2_shim_filter_list_apply_to_buffer(
+    out: *mut raw::git_buf,
+    filters: *mut raw::git_filter_list,
+    input: *const c_char,
+    len: size_t,
+) -> c_int {
+    raw::git_filter_list_apply_to_buffer(out, filters, input, len)
 }
 
+/// Run the content of `blob` through `filters` into `out` (dispose with
+/// `git2_shim_buf_dispose`), e.g. to show a blob as checkout would write it.
 #[no_mangle]
-pub unsafe extern "C" fn git2_shim_repository_head(
-    out: *mut *mut raw::git_reference,
-    repo: *mut raw::git_repository,
+pub unsafe extern "C" fn git2_shim_filter_list_apply_to_blob(
+    out: *mut raw::git_buf,
+    filters: *mut raw::git_filter_list,
+    blob: *mut raw::git_blob,
 ) -> c_int {
-    raw::git_repository_head(out, repo)
+    raw::git_filter_list_apply_to_blob(out, filters, blob)
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn git2_shim_reference_free(ref_: *mut raw::git_reference) {
-    raw::git_reference_free(ref_)
+pub unsafe extern "C" fn git2_shim_filter_list_free(filters: *mut raw::git_filter_list) {
+    raw::git_filter_list_free(filters)
+}
+
+// =============================================================================
+// Integrity checks
+// =============================================================================
+
+/// What `git2_shim_fsck` checks. By default it behaves like `git fsck`:
+/// every stored object is read and hash-checked, and reflog entries count
+/// as roots alongside refs, HEAD and the index.
+#[derive(Clone)]
+pub struct FsckOptions {
+    connectivity_only: bool,
+    unreachable: bool,
+    reflogs: bool,
+}
+
+impl Default for FsckOptions {
+    fn default() -> FsckOptions {
+        FsckOptions {
+            connectivity_only: false,
+            unreachable: false,
+            reflogs: true,
+        }
+    }
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn git2_shim_reference_shorthand(
-    ref_: *const raw::git_reference,
-) -> *const c_char {
-    raw::git_reference_shorthand(ref_)
+pub extern "C" fn git2_shim_fsck_options_new() -> *mut FsckOptions {
+    Box::into_raw(Box::new(FsckOptions::default()))
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn git2_shim_graph_ahead_behind(
-    ahead: *mut size_t,
-    behind: *mut size_t,
+pub unsafe extern "C" fn git2_shim_fsck_options_free(opts: *mut FsckOptions) {
+    if !opts.is_null() {
+        drop(Box::from_raw(opts));
+    }
+}
+
+/// Only check that reachable objects exist and link up, like
+/// `git fsck --connectivity-only`: blobs are only looked up, not read, and
+/// unreachable objects are not examined. Commits, trees and tags are still
+/// read to follow their links, so libgit2's own hash verification of those
+/// reads can still report them as mismatched.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_fsck_options_set_connectivity_only(
+    opts: *mut FsckOptions,
+    enabled: c_int,
+) {
+    (*opts).connectivity_only = enabled != 0;
+}
+
+/// Also report every stored object that nothing reaches, like
+/// `git fsck --unreachable`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_fsck_options_set_unreachable(
+    opts: *mut FsckOptions,
+    enabled: c_int,
+) {
+    (*opts).unreachable = enabled != 0;
+}
+
+/// Whether reflog entries count as roots (the default); disable to treat
+/// commits only reachable from reflogs as unreachable, like
+/// `git fsck --no-reflogs`.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_fsck_options_set_reflogs(
+    opts: *mut FsckOptions,
+    enabled: c_int,
+) {
+    (*opts).reflogs = enabled != 0;
+}
+
+const FSCK_MISSING: c_int = 1;
+const FSCK_CORRUPT: c_int = 2;
+const FSCK_HASH_MISMATCH: c_int = 3;
+const FSCK_UNREACHABLE: c_int = 4;
+
+/// Called once per problem found by `git2_shim_fsck`. `problem` is 1 for a
+/// missing object, 2 for one that cannot be read or parsed or has the
+/// wrong type for its link, 3 for one whose content does not hash to its
+/// id, and 4 for an unreachable object. `kind` is the object's
+/// `GIT_OBJECT_*` type, or the type it was expected to have when it is
+/// missing (-2 if unknown). `referrer` names what links to the object: a
+/// ref, a reflog entry such as `HEAD@{2}`, `:<path>` for an index entry,
+/// or the hex id of the commit, tree or tag (NULL for stored objects
+/// nothing reached). `detail` describes problems 2 and 3, usually with
+/// libgit2's error message, and is otherwise NULL.
+///
+/// Return 0 to continue; any other value stops the check.
+pub type git2_shim_fsck_cb = extern "C" fn(
+    problem: c_int,
+    id: *const raw::git_oid,
+    kind: c_int,
+    referrer: *const c_char,
+    detail: *const c_char,
+    payload: *mut c_void,
+) -> c_int;
+
+/// An object still to be checked, with the type its referrer expects.
+struct FsckLink {
+    id: ObjectId,
+    kind: c_int,
+    referrer: String,
+}
+
+struct Fsck {
     repo: *mut raw::git_repository,
-    local: *const raw::git_oid,
-    upstream: *const raw::git_oid,
+    odb: *mut raw::git_odb,
+    objects: PathBuf,
+    opts: FsckOptions,
+    callback: Option<git2_shim_fsck_cb>,
+    payload: *mut c_void,
+    seen: HashSet<ObjectId>,
+    pending: Vec<FsckLink>,
+    problems: c_int,
+}
+
+impl Fsck {
+    fn report(
+        &mut self,
+        problem: c_int,
+        id: &ObjectId,
+        kind: c_int,
+        referrer: Option<&str>,
+        detail: Option<&str>,
+    ) -> Result<(), c_int> {
+        self.problems += 1;
+        let Some(callback) = self.callback else {
+            return Ok(());
+        };
+        let referrer = referrer.and_then(|text| CString::new(text).ok());
+        let detail = detail.and_then(|text| CString::new(text).ok());
+        let rc = callback(
+            problem,
+            &raw::git_oid { id: *id },
+            kind,
+            cstring_ptr(&referrer),
+            cstring_ptr(&detail),
+            self.payload,
+        );
+        if rc != 0 {
+            return Err(raw::GIT_EUSER);
+        }
+        Ok(())
+    }
+
+    /// Report `id` as corrupt with libgit2's last error as the detail.
+    fn report_error(
+        &mut self,
+        problem: c_int,
+        id: &ObjectId,
+        kind: c_int,
+        referrer: Option<&str>,
+    ) -> Result<(), c_int> {
+        let detail = unsafe { owned_cstring(git2_shim_error_last_message()) };
+        let detail = detail.as_ref().map(|text| text.to_string_lossy());
+        self.report(problem, id, kind, referrer, detail.as_deref())
+    }
+
+    /// Where `id` would be stored as a loose object.
+    fn loose_path(&self, id: &ObjectId) -> PathBuf {
+        let hex = object_id_hex(id);
+        self.objects.join(&hex[..2]).join(&hex[2..])
+    }
+
+    fn queue(&mut self, id: ObjectId, kind: c_int, referrer: String) {
+        if id != [0; raw::GIT_OID_RAWSZ] {
+            self.pending.push(FsckLink { id, kind, referrer });
+        }
+    }
+
+    /// Queue refs, HEAD, reflog entries and index entries.
+    unsafe fn collect_roots(&mut self) -> Result<(), c_int> {
+        let mut scan = Vec::<(CString, Option<ObjectId>)>::new();
+        let rc = raw::git_reference_foreach(
+            self.repo,
+            collect_fsck_ref,
+            &mut scan as *mut _ as *mut c_void,
+        );
+        if rc < 0 {
+            return Err(rc);
+        }
+        for (name, target) in &scan {
+            if let Some(id) = target {
+                self.queue(
+                    *id,
+                    raw::GIT_OBJECT_ANY,
+                    name.to_string_lossy().into_owned(),
+                );
+            }
+        }
+
+        let mut head = raw::git_oid {
+            id: [0; raw::GIT_OID_RAWSZ],
+        };
+        if raw::git_reference_name_to_id(&mut head, self.repo, c"HEAD".as_ptr()) == 0 {
+            self.queue(head.id, raw::GIT_OBJECT_ANY, "HEAD".to_owned());
+        }
+        scan.push((c"HEAD".to_owned(), None));
+
+        if self.opts.reflogs {
+            for (name, _) in &scan {
+                let mut reflog = ptr::null_mut();
+                if raw::git_reflog_read(&mut reflog, self.repo, name.as_ptr()) < 0 {
+                    continue;
+                }
+                let name = name.to_string_lossy();
+                for i in 0..raw::git_reflog_entrycount(reflog) {
+                    let entry = raw::git_reflog_entry_byindex(reflog, i);
+                    for id in [
+                        raw::git_reflog_entry_id_old(entry),
+                        raw::git_reflog_entry_id_new(entry),
+                    ] {
+                        self.queue((*id).id, raw::GIT_OBJECT_COMMIT, format!("{name}@{{{i}}}"));
+                    }
+                }
+                raw::git_reflog_free(reflog);
+            }
+        }
+
+        let mut index = ptr::null_mut();
+        if raw::git_repository_index(&mut index, self.repo) == 0 {
+            for i in 0..raw::git_index_entrycount(index) {
+                let entry = &*raw::git_index_get_byindex(index, i);
+                // Intent-to-add entries name blobs that were never written,
+                // and gitlinks point into the submodule's repository.
+                if entry.flags_extended & raw::GIT_INDEX_ENTRY_INTENT_TO_ADD != 0
+                    || entry.mode == raw::GIT_FILEMODE_COMMIT
+                {
+                    continue;
+                }
+                let path = CStr::from_ptr(entry.path).to_string_lossy();
+                self.queue(entry.id.id, raw::GIT_OBJECT_BLOB, format!(":{path}"));
+            }
+            raw::git_index_free(index);
+        }
+        Ok(())
+    }
+
+    /// Check that `id` exists, reads back intact and has the expected
+    /// type; with `follow`, queue the objects it links to.
+    unsafe fn check(
+        &mut self,
+        id: &ObjectId,
+        expected: c_int,
+        referrer: Option<&str>,
+        follow: bool,
+    ) -> Result<(), c_int> {
+        let oid = raw::git_oid { id: *id };
+        if self.opts.connectivity_only && expected == raw::GIT_OBJECT_BLOB {
+            if raw::git_odb_exists(self.odb, &oid) == 0 {
+                self.report(FSCK_MISSING, id, expected, referrer, None)?;
+            }
+            return Ok(());
+        }
+
+        // libgit2 1.8 never returns from reading a truncated loose object.
+        if let Err(detail) = inflate_loose_object(&self.loose_path(id)) {
+            return self.report(FSCK_CORRUPT, id, expected, referrer, Some(&detail));
+        }
+
+        let mut object = ptr::null_mut();
+        match raw::git_odb_read(&mut object, self.odb, &oid) {
+            0 => {}
+            raw::GIT_ENOTFOUND => return self.report(FSCK_MISSING, id, expected, referrer, None),
+            raw::GIT_EMISMATCH => {
+                return self.report_error(FSCK_HASH_MISMATCH, id, expected, referrer)
+            }
+            _ => return self.report_error(FSCK_CORRUPT, id, expected, referrer),
+        }
+        let kind = raw::git_odb_object_type(object);
+        let mut hashed = raw::git_oid {
+            id: [0; raw::GIT_OID_RAWSZ],
+        };
+        // libgit2 normally verifies reads itself, unless strict hash
+        // verification has been turned off process-wide.
+        let mismatch = !self.opts.connectivity_only
+            && raw::git_odb_hash(
+                &mut hashed,
+                raw::git_odb_object_data(object),
+                raw::git_odb_object_size(object),
+                kind,
+            ) == 0
+            && hashed.id != *id;
+        raw::git_odb_object_free(object);
+        if mismatch {
+            let detail = format!("object hashes to {}", object_id_hex(&hashed.id));
+            return self.report(FSCK_HASH_MISMATCH, id, kind, referrer, Some(&detail));
+        }
+        if expected != raw::GIT_OBJECT_ANY && kind != expected {
+            let expected_name = CStr::from_ptr(raw::git_object_type2string(expected));
+            let kind_name = CStr::from_ptr(raw::git_object_type2string(kind));
+            let detail = format!(
+                "expected {}, found {}",
+                expected_name.to_string_lossy(),
+                kind_name.to_string_lossy()
+            );
+            return self.report(FSCK_CORRUPT, id, kind, referrer, Some(&detail));
+        }
+        if !follow || kind == raw::GIT_OBJECT_BLOB {
+            return Ok(());
+        }
+
+        let mut parsed = ptr::null_mut();
+        if raw::git_object_lookup(&mut parsed, self.repo, &oid, kind) < 0 {
+            return self.report_error(FSCK_CORRUPT, id, kind, referrer);
+        }
+        let hex = object_id_hex(id);
+        match kind {
+            raw::GIT_OBJECT_COMMIT => {
+                let commit = parsed as *const raw::git_commit;
+                let tree = (*raw::git_commit_tree_id(commit)).id;
+                self.queue(tree, raw::GIT_OBJECT_TREE, hex.clone());
+                for i in 0..raw::git_commit_parentcount(commit) {
+                    let parent = (*raw::git_commit_parent_id(commit, i)).id;
+                    self.queue(parent, raw::GIT_OBJECT_COMMIT, hex.clone());
+                }
+            }
+            raw::GIT_OBJECT_TREE => {
+                let tree = parsed as *const raw::git_tree;
+                for i in 0..raw::git_tree_entrycount(tree) {
+                    let entry = raw::git_tree_entry_byindex(tree, i);
+                    let entry_kind = raw::git_tree_entry_type(entry);
+                    // Gitlinks point into a submodule's repository.
+                    if entry_kind == raw::GIT_OBJECT_TREE || entry_kind == raw::GIT_OBJECT_BLOB {
+                        let entry_id = (*raw::git_tree_entry_id(entry)).id;
+                        self.queue(entry_id, entry_kind, hex.clone());
+                    }
+                }
+            }
+            raw::GIT_OBJECT_TAG => {
+                let tag = parsed as *const raw::git_tag;
+                let target = (*raw::git_tag_target_id(tag)).id;
+                self.queue(target, raw::git_tag_target_type(tag), hex);
+            }
+            _ => {}
+        }
+        raw::git_object_free(parsed);
+        Ok(())
+    }
+
+    unsafe fn run(&mut self) -> Result<(), c_int> {
+        self.collect_roots()?;
+        while let Some(link) = self.pending.pop() {
+            if self.seen.insert(link.id) {
+                self.check(&link.id, link.kind, Some(&link.referrer), true)?;
+            }
+        }
+        if self.opts.connectivity_only && !self.opts.unreachable {
+            return Ok(());
+        }
+
+        let mut stored = Vec::new();
+        let rc = raw::git_odb_foreach(
+            self.odb,
+            collect_odb_id,
+            &mut stored as *mut _ as *mut c_void,
+        );
+        if rc < 0 {
+            return Err(rc);
+        }
+        for id in stored {
+            if !self.seen.insert(id) {
+                continue;
+            }
+            if !self.opts.connectivity_only {
+                self.check(&id, raw::GIT_OBJECT_ANY, None, false)?;
+            }
+            if self.opts.unreachable {
+                let mut len = 0;
+                let mut kind = raw::GIT_OBJECT_ANY;
+                if inflate_loose_object(&self.loose_path(&id)).is_ok() {
+                    raw::git_odb_read_header(&mut len, &mut kind, self.odb, &raw::git_oid { id });
+                }
+                self.report(FSCK_UNREACHABLE, &id, kind, None, None)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+unsafe extern "C" fn zlib_alloc(_: *mut c_void, items: c_uint, size: c_uint) -> *mut c_void {
+    libc::calloc(items as size_t, size as size_t)
+}
+
+unsafe extern "C" fn zlib_free(_: *mut c_void, address: *mut c_void) {
+    libc::free(address)
+}
+
+/// Inflate the loose object file at `path`, if there is one, checking that
+/// its zlib stream ends and holds a valid header followed by exactly the
+/// number of bytes the header gives. Returns a description of the problem
+/// otherwise.
+fn inflate_loose_object(path: &Path) -> Result<(), String> {
+    let data = match std::fs::read(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.to_string()),
+    };
+    let mut stream = libz_sys::z_stream {
+        next_in: data.as_ptr() as *mut _,
+        avail_in: data
+            .len()
+            .try_into()
+            .map_err(|_| "loose object is too large")?,
+        total_in: 0,
+        next_out: ptr::null_mut(),
+        avail_out: 0,
+        total_out: 0,
+        msg: ptr::null_mut(),
+        state: ptr::null_mut(),
+        zalloc: zlib_alloc,
+        zfree: zlib_free,
+        opaque: ptr::null_mut(),
+        data_type: 0,
+        adler: 0,
+        reserved: 0,
+    };
+    let rc = unsafe {
+        libz_sys::inflateInit_(
+            &mut stream,
+            libz_sys::zlibVersion(),
+            std::mem::size_of::<libz_sys::z_stream>() as c_int,
+        )
+    };
+    if rc != libz_sys::Z_OK {
+        return Err(format!("cannot inflate loose object: zlib error {rc}"));
+    }
+
+    // Everything up to the NUL ending the "<type> <size>" header, then the
+    // size it declares once that has been seen.
+    let mut header = Vec::new();
+    let mut remaining: Option<u64> = None;
+    let mut chunk = [0u8; 16384];
+    let result = loop {
+        stream.next_out = chunk.as_mut_ptr();
+        stream.avail_out = chunk.len() as c_uint;
+        let rc = unsafe { libz_sys::inflate(&mut stream, libz_sys::Z_NO_FLUSH) };
+        let mut out = &chunk[..chunk.len() - stream.avail_out as usize];
+        if remaining.is_none() {
+            let end = out.iter().position(|&b| b == 0);
+            header.extend_from_slice(&out[..end.unwrap_or(out.len())]);
+            if let Some(end) = end {
+                let size = std::str::from_utf8(&header)
+                    .ok()
+                    .and_then(|text| text.split_once(' '))
+                    .filter(|(kind, _)| ["commit", "tree", "blob", "tag"].contains(kind))
+                    .and_then(|(_, size)| size.parse().ok());
+                let Some(size) = size else {
+                    break Err("loose object has an invalid header".to_owned());
+                };
+                remaining = Some(size);
+                out = &out[end + 1..];
+            } else if header.len() > 64 {
+                break Err("loose object has an invalid header".to_owned());
+            }
+        }
+        if let Some(left) = remaining.as_mut() {
+            match left.checked_sub(out.len() as u64) {
+                Some(rest) => *left = rest,
+                None => break Err("loose object is longer than its header says".to_owned()),
+            }
+        }
+        match rc {
+            libz_sys::Z_STREAM_END if remaining == Some(0) => break Ok(()),
+            libz_sys::Z_STREAM_END => {
+                break Err("loose object is shorter than its header says".to_owned())
+            }
+            // With all of the file as input and room for output, no
+            // progress means the stream stops early.
+            libz_sys::Z_BUF_ERROR => break Err("loose object is truncated".to_owned()),
+            libz_sys::Z_OK => {}
+            _ => {
+                let message = unsafe { owned_cstring(stream.msg) };
+                let message = message.map_or_else(
+                    || format!("zlib error {rc}"),
+                    |text| text.to_string_lossy().into_owned(),
+                );
+                break Err(format!("cannot inflate loose object: {message}"));
+            }
+        }
+    };
+    unsafe { libz_sys::inflateEnd(&mut stream) };
+    result
+}
+
+extern "C" fn collect_fsck_ref(reference: *mut raw::git_reference, payload: *mut c_void) -> c_int {
+    let scan = unsafe { &mut *(payload as *mut Vec<(CString, Option<ObjectId>)>) };
+    unsafe {
+        let name = CStr::from_ptr(raw::git_reference_name(reference)).to_owned();
+        // Symbolic refs are checked through the refs they point at.
+        let target = raw::git_reference_target(reference)
+            .as_ref()
+            .map(|id| id.id);
+        scan.push((name, target));
+        raw::git_reference_free(reference);
+    }
+    0
+}
+
+/// Verify the repository's integrity: walk from every ref, HEAD, the
+/// reflogs and the index through commits, trees, tags and blobs, checking
+/// that each object exists, reads back intact and has the type its link
+/// claims, then (unless connectivity-only) verify every other stored
+/// object too. Each problem goes to `callback`, which may be NULL; `opts`
+/// may be NULL for the defaults. Submodule commits are not followed.
+/// Returns the number of problems found, `GIT_EUSER` if the callback
+/// stopped the check, or another negative error if refs or the object
+/// database could not be listed.
+#[no_mangle]
+pub unsafe extern "C" fn git2_shim_fsck(
+    repo: *mut raw::git_repository,
+    opts: *const FsckOptions,
+    callback: Option<git2_shim_fsck_cb>,
+    payload: *mut c_void,
 ) -> c_int {
-    raw::git_graph_ahead_behind(ahead, behind, repo, local, upstream)
+    let objects = match objects_dir(repo) {
+        Ok(path) => path,
+        Err(rc) => return rc,
+    };
+    let mut odb = ptr::null_mut();
+    let rc = raw::git_repository_odb(&mut odb, repo);
+    if rc < 0 {
+        return rc;
+    }
+    let mut fsck = Fsck {
+        repo,
+        odb,
+        objects,
+        opts: opts.as_ref().cloned().unwrap_or_default(),
+        callback,
+        payload,
+        seen: HashSet::new(),
+        pending: Vec::new(),
+        problems: 0,
+    };
+    let result = fsck.run();
+    raw::git_odb_free(odb);
+    match result {
+        Ok(()) => fsck.problems,
+        Err(rc) => rc,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Only the tests build objects and trees by hand.
+    #[link(name = "git2")]
+    extern "C" {
+        fn git_index_add_from_buffer(
+            index: *mut raw::git_index,
+            entry: *const raw::git_index_entry,
+            buffer: *const c_void,
+            len: size_t,
+        ) -> c_int;
+        fn git_blob_create_from_buffer(
+            id: *mut raw::git_oid,
+            repo: *mut raw::git_repository,
+            buffer: *const c_void,
+            len: size_t,
+        ) -> c_int;
+        fn git_repository_set_head_detached(
+            repo: *mut raw::git_repository,
+            id: *const raw::git_oid,
+        ) -> c_int;
+    }
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn check(rc: c_int) {
+        assert!(rc >= 0, "libgit2 error {rc}: {:?}", unsafe {
+            CStr::from_ptr(git2_shim_error_last_message())
+        });
+    }
+
+    fn cstr(s: &str) -> CString {
+        CString::new(s).unwrap()
+    }
+
+    /// A fresh non-bare repository in its own temporary directory, removed
+    /// again on drop.
+    struct TestRepo {
+        dir: PathBuf,
+        repo: *mut raw::git_repository,
+    }
+
+    impl TestRepo {
+        fn new() -> TestRepo {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            let dir = std::env::temp_dir().join(format!(
+                "git2-shim-test-{}-{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            ));
+            let _ = std::fs::remove_dir_all(&dir);
+            git2_shim_init();
+            let path = cstr(dir.join("repo").to_str().unwrap());
+            let mut repo = ptr::null_mut();
+            check(unsafe { git2_shim_repository_init(&mut repo, path.as_ptr(), 0) });
+            TestRepo { dir, repo }
+        }
+
+        /// A second handle on the repository with nothing cached yet;
+        /// free it with `git_repository_free`.
+        fn reopen(&self) -> *mut raw::git_repository {
+            let path = cstr(self.dir.join("repo").to_str().unwrap());
+            let mut repo = ptr::null_mut();
+            check(unsafe { raw::git_repository_open(&mut repo, path.as_ptr()) });
+            repo
+        }
+
+        fn objects(&self) -> PathBuf {
+            unsafe { objects_dir(self.repo) }.unwrap()
+        }
+
+        fn blob(&self, content: &str) -> ObjectId {
+            blob(self.repo, content)
+        }
+
+        fn has(&self, id: ObjectId) -> bool {
+            has(self.repo, id)
+        }
+    }
+
+    impl Drop for TestRepo {
+        fn drop(&mut self) {
+            unsafe { raw::git_repository_free(self.repo) };
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn blob(repo: *mut raw::git_repository, content: &str) -> ObjectId {
+        let mut id = raw::git_oid {
+            id: [0; raw::GIT_OID_RAWSZ],
+        };
+        check(unsafe {
+            git_blob_create_from_buffer(&mut id, repo, content.as_ptr().cast(), content.len())
+        });
+        id.id
+    }
+
+    /// Whether the object store holds `id`, re-reading it from disk first.
+    fn has(repo: *mut raw::git_repository, id: ObjectId) -> bool {
+        unsafe {
+            let mut odb = ptr::null_mut();
+            check(raw::git_repository_odb(&mut odb, repo));
+            check(raw::git_odb_refresh(odb));
+            let found = raw::git_odb_exists(odb, &raw::git_oid { id }) != 0;
+            raw::git_odb_free(odb);
+            found
+        }
+    }
+
+    /// Write `content` as `path` into the index of `repo` and save it.
+    fn stage(repo: *mut raw::git_repository, path: &str, content: &str) {
+        let path = cstr(path);
+        unsafe {
+            let mut index = ptr::null_mut();
+            check(raw::git_repository_index(&mut index, repo));
+            let mut entry: raw::git_index_entry = std::mem::zeroed();
+            entry.mode = 0o100644;
+            entry.path = path.as_ptr();
+            check(git_index_add_from_buffer(
+                index,
+                &entry,
+                content.as_ptr().cast(),
+                content.len(),
+            ));
+            check(raw::git_index_write(index));
+            raw::git_index_free(index);
+        }
+    }
+
+    /// Commit the index of `repo` on top of `parents`, moving `update_ref`
+    /// (when given) to the new commit.
+    fn commit(
+        repo: *mut raw::git_repository,
+        update_ref: Option<&str>,
+        parents: &[ObjectId],
+        message: &str,
+    ) -> ObjectId {
+        let update_ref = update_ref.map(cstr);
+        let message = cstr(message);
+        unsafe {
+            let mut index = ptr::null_mut();
+            check(raw::git_repository_index(&mut index, repo));
+            let mut tree_id = raw::git_oid {
+                id: [0; raw::GIT_OID_RAWSZ],
+            };
+            check(raw::git_index_write_tree_to(&mut tree_id, index, repo));
+            raw::git_index_free(index);
+            let mut tree = ptr::null_mut();
+            check(raw::git_tree_lookup(&mut tree, repo, &tree_id));
+            let mut parent_commits = Vec::new();
+            for parent in parents {
+                let mut commit = ptr::null_mut();
+                check(raw::git_commit_lookup(
+                    &mut commit,
+                    repo,
+                    &raw::git_oid { id: *parent },
+                ));
+                parent_commits.push(commit as *const raw::git_commit);
+            }
+            let mut sig = ptr::null_mut();
+            check(raw::git_signature_now(
+                &mut sig,
+                c"Test".as_ptr(),
+                c"test@example.com".as_ptr(),
+            ));
+            let mut id = raw::git_oid {
+                id: [0; raw::GIT_OID_RAWSZ],
+            };
+            check(raw::git_commit_create(
+                &mut id,
+                repo,
+                cstring_ptr(&update_ref),
+                sig,
+                sig,
+                ptr::null(),
+                message.as_ptr(),
+                tree,
+                parent_commits.len(),
+                parent_commits.as_ptr(),
+            ));
+            raw::git_signature_free(sig);
+            for commit in parent_commits {
+                raw::git_commit_free(commit as *mut raw::git_commit);
+            }
+            raw::git_tree_free(tree);
+            id.id
+        }
+    }
+
+    fn set_ref(repo: *mut raw::git_repository, name: &str, id: ObjectId) {
+        let name = cstr(name);
+        unsafe {
+            let mut reference = ptr::null_mut();
+            check(raw::git_reference_create(
+                &mut reference,
+                repo,
+                name.as_ptr(),
+                &raw::git_oid { id },
+                1,
+                c"test: move".as_ptr(),
+            ));
+            raw::git_reference_free(reference);
+        }
+    }
+
+    fn loose_path(objects: &Path, id: ObjectId) -> PathBuf {
+        let hex = object_id_hex(&id);
+        objects.join(&hex[..2]).join(&hex[2..])
+    }
+
+    /// Backdate every loose object, so only reachability protects them.
+    fn age_loose_objects(objects: &Path, age: Duration) {
+        let when = SystemTime::now() - age;
+        for dir in std::fs::read_dir(objects).unwrap() {
+            let dir = dir.unwrap();
+            if dir.file_name().len() != 2 {
+                continue;
+            }
+            for file in std::fs::read_dir(dir.path()).unwrap() {
+                let file = std::fs::File::open(file.unwrap().path()).unwrap();
+                file.set_modified(when).unwrap();
+            }
+        }
+    }
+
+    fn pack_stems(objects: &Path) -> Vec<String> {
+        let mut stems: Vec<String> = std::fs::read_dir(objects.join("pack"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "pack"))
+            .map(|path| path.file_stem().unwrap().to_string_lossy().into_owned())
+            .collect();
+        stems.sort();
+        stems
+    }
+
+    fn maintain(repo: *mut raw::git_repository) -> git2_shim_maintenance_stats {
+        let mut stats = git2_shim_maintenance_stats::default();
+        check(unsafe { git2_shim_maintenance_run(repo, ptr::null(), &mut stats) });
+        stats
+    }
+
+    fn set_config(repo: *mut raw::git_repository, name: &str, value: &str) {
+        let (name, value) = (cstr(name), cstr(value));
+        unsafe {
+            let mut config = ptr::null_mut();
+            check(raw::git_repository_config(&mut config, repo));
+            check(raw::git_config_set_string(
+                config,
+                name.as_ptr(),
+                value.as_ptr(),
+            ));
+            raw::git_config_free(config);
+        }
+    }
+
+    extern "C" fn ask_git(
+        _out: *mut *mut raw::git_credential,
+        _url: *const c_char,
+        _username_from_url: *const c_char,
+        _allowed_types: c_uint,
+        _payload: *mut c_void,
+    ) -> c_int {
+        GIT2_SHIM_CREDENTIAL_ASK_GIT
+    }
+
+    extern "C" fn fake_sign(
+        sink: *mut SignatureSink,
+        _commit_content: *const c_char,
+        _content_len: size_t,
+        _payload: *mut c_void,
+    ) -> c_int {
+        unsafe { git2_shim_signature_sink_set(sink, c"fake signature".as_ptr(), ptr::null()) };
+        0
+    }
+
+    /// Sign-commit the index of `repo` onto `parents`, moving HEAD.
+    fn signed_commit(
+        repo: *mut raw::git_repository,
+        parents: &[ObjectId],
+        message: &str,
+    ) -> Result<ObjectId, c_int> {
+        let message = cstr(message);
+        unsafe {
+            let mut index = ptr::null_mut();
+            check(raw::git_repository_index(&mut index, repo));
+            let mut tree_id = raw::git_oid {
+                id: [0; raw::GIT_OID_RAWSZ],
+            };
+            check(raw::git_index_write_tree_to(&mut tree_id, index, repo));
+            raw::git_index_free(index);
+            let mut tree = ptr::null_mut();
+            check(raw::git_tree_lookup(&mut tree, repo, &tree_id));
+            let mut parent_commits = Vec::new();
+            for parent in parents {
+                let mut commit = ptr::null_mut();
+                check(raw::git_commit_lookup(
+                    &mut commit,
+                    repo,
+                    &raw::git_oid { id: *parent },
+                ));
+                parent_commits.push(commit as *const raw::git_commit);
+            }
+            let mut sig = ptr::null_mut();
+            check(raw::git_signature_now(
+                &mut sig,
+                c"Test".as_ptr(),
+                c"test@example.com".as_ptr(),
+            ));
+            let mut id = raw::git_oid {
+                id: [0; raw::GIT_OID_RAWSZ],
+            };
+            let rc = git2_shim_commit_create_signed(
+                &mut id,
+                repo,
+                c"HEAD".as_ptr(),
+                sig,
+                sig,
+                message.as_ptr(),
+                tree,
+                parent_commits.len(),
+                parent_commits.as_ptr(),
+                fake_sign,
+                ptr::null_mut(),
+            );
+            raw::git_signature_free(sig);
+            for commit in parent_commits {
+                raw::git_commit_free(commit as *mut raw::git_commit);
+            }
+            raw::git_tree_free(tree);
+            if rc < 0 {
+                Err(rc)
+            } else {
+                Ok(id.id)
+            }
+        }
+    }
+
+    fn head(repo: *mut raw::git_repository) -> ObjectId {
+        let mut id = raw::git_oid {
+            id: [0; raw::GIT_OID_RAWSZ],
+        };
+        check(unsafe { raw::git_reference_name_to_id(&mut id, repo, c"HEAD".as_ptr()) });
+        id.id
+    }
+
+    #[test]
+    fn signed_commit_moves_head_only_from_its_first_parent() {
+        let t = TestRepo::new();
+        stage(t.repo, "a", "one\n");
+        let first = signed_commit(t.repo, &[], "first\n\nbody").unwrap();
+        assert_eq!(head(t.repo), first);
+        // A root commit cannot replace an existing branch (GIT_EMODIFIED).
+        assert_eq!(signed_commit(t.repo, &[], "again"), Err(-15));
+
+        stage(t.repo, "b", "two\n");
+        let second = signed_commit(t.repo, &[first], "second").unwrap();
+        assert_eq!(head(t.repo), second);
+        // HEAD has moved on, so `first` is no longer the tip to replace.
+        assert_eq!(signed_commit(t.repo, &[first], "stale"), Err(-15));
+        assert_eq!(head(t.repo), second);
+
+        let log = std::fs::read_to_string(t.dir.join("repo/.git/logs/refs/heads/master")).unwrap();
+        let messages: Vec<&str> = log
+            .lines()
+            .map(|line| line.split_once('\t').unwrap().1)
+            .collect();
+        assert_eq!(messages, ["commit (initial): first", "commit: second"]);
+    }
+
+    #[test]
+    fn credential_request_describes_the_url() {
+        assert_eq!(
+            credential_request("https://bob:pw@example.com:8443/team/repo.git", None).unwrap(),
+            "protocol=https\nhost=example.com:8443\npath=team/repo.git\nusername=bob\n"
+        );
+        assert_eq!(
+            credential_request("http://example.com", Some("alice")).unwrap(),
+            "protocol=http\nhost=example.com\nusername=alice\n"
+        );
+        assert!(credential_request("git@example.com:repo.git", None).is_none());
+        assert!(credential_request("https://example.com/a\nb", None).is_none());
+    }
+
+    #[test]
+    fn ask_git_uses_repository_helpers_and_rejects_on_retry() {
+        let t = TestRepo::new();
+        let erased = t.dir.join("erased");
+        set_config(
+            t.repo,
+            "credential.https://example.com.helper",
+            &format!(
+                "!f() {{ case $1 in get) echo username=alice; echo password=secret;; \
+                 erase) cat > '{}';; esac; }}; f",
+                erased.display()
+            ),
+        );
+        let url = c"https://example.com/repo.git";
+        unsafe {
+            let callbacks = git2_shim_remote_callbacks_new();
+            git2_shim_remote_callbacks_set_credentials_cb(
+                callbacks,
+                Some(ask_git),
+                ptr::null_mut(),
+            );
+            git2_shim_remote_callbacks_set_repository(callbacks, t.repo);
+            // Start of a libgit2 call, as a fetch would make it.
+            let _raw = raw_remote_callbacks(callbacks);
+            let ask = |url: &CStr| {
+                let mut cred = ptr::null_mut();
+                let rc = credentials_trampoline(
+                    &mut cred,
+                    url.as_ptr(),
+                    ptr::null(),
+                    raw::GIT_CREDENTIAL_USERPASS_PLAINTEXT,
+                    callbacks.cast(),
+                );
+                if !cred.is_null() {
+                    raw::git_credential_free(cred);
+                }
+                rc
+            };
+            assert_eq!(ask(url), 0);
+            assert!(!erased.exists());
+            assert_eq!(ask(url), 1);
+            let erased = std::fs::read_to_string(&erased).unwrap();
+            assert!(erased.contains("username=alice\n"));
+            assert!(erased.contains("password=secret\n"));
+            // No helper is configured for other hosts.
+            assert_eq!(ask(c"https://elsewhere.example/repo.git"), 1);
+            git2_shim_remote_callbacks_free(callbacks);
+        }
+    }
+
+    #[test]
+    fn helper_credentials_are_approved_or_rejected_by_the_outcome() {
+        let t = TestRepo::new();
+        let log = t.dir.join("helper.log");
+        set_config(
+            t.repo,
+            "credential.helper",
+            &format!(
+                "!f() {{ case $1 in get) echo username=alice; echo password=secret;; \
+                 *) echo $1 >> '{}';; esac; }}; f",
+                log.display()
+            ),
+        );
+        let actions = || std::fs::read_to_string(&log).unwrap_or_default();
+        unsafe {
+            let callbacks = git2_shim_remote_callbacks_new();
+            git2_shim_remote_callbacks_set_credentials_cb(
+                callbacks,
+                Some(ask_git),
+                ptr::null_mut(),
+            );
+            git2_shim_remote_callbacks_set_repository(callbacks, t.repo);
+            let outcome = |rc: c_int| {
+                let _raw = raw_remote_callbacks(callbacks);
+                let mut cred = ptr::null_mut();
+                assert_eq!(
+                    credentials_trampoline(
+                        &mut cred,
+                        c"https://example.com/repo.git".as_ptr(),
+                        ptr::null(),
+                        raw::GIT_CREDENTIAL_USERPASS_PLAINTEXT,
+                        callbacks.cast(),
+                    ),
+                    0
+                );
+                raw::git_credential_free(cred);
+                (*callbacks).helper.settle(rc);
+            };
+            outcome(0);
+            assert_eq!(actions(), "store\n");
+            outcome(raw::GIT_EAUTH);
+            assert_eq!(actions(), "store\nerase\n");
+            // A network failure says nothing about the credential.
+            raw::git_error_set_str(raw::GIT_ERROR_OS, c"connection reset".as_ptr());
+            outcome(-1);
+            assert_eq!(actions(), "store\nerase\n");
+            git2_shim_remote_callbacks_free(callbacks);
+        }
+    }
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(git2_shim_error_last_message()) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn ref_id(repo: *mut raw::git_repository, name: &str) -> Option<ObjectId> {
+        let name = cstr(name);
+        let mut id = raw::git_oid {
+            id: [0; raw::GIT_OID_RAWSZ],
+        };
+        (unsafe { raw::git_reference_name_to_id(&mut id, repo, name.as_ptr()) } == 0)
+            .then_some(id.id)
+    }
+
+    fn parse_header(text: &str) -> Result<BundleHeader, c_int> {
+        read_bundle_header(&mut text.as_bytes())
+    }
+
+    #[test]
+    fn bundle_header_versions() {
+        let a = "1".repeat(40);
+        let b = "2".repeat(40);
+        let text = format!("{BUNDLE_V2_SIGNATURE}\n-{a} base commit\n{b} refs/heads/main\n\nPACK");
+        let mut input = text.as_bytes();
+        let header = read_bundle_header(&mut input).unwrap();
+        assert_eq!(header.prerequisites, [[0x11; 20]]);
+        assert_eq!(header.refs, [([0x22; 20], "refs/heads/main".to_string())]);
+        assert_eq!(input, b"PACK");
+
+        let text = format!("{BUNDLE_V3_SIGNATURE}\n@object-format=sha1\n{b} HEAD\n\n");
+        let header = parse_header(&text).unwrap();
+        assert!(header.prerequisites.is_empty());
+        assert_eq!(header.refs, [([0x22; 20], "HEAD".to_string())]);
+    }
+
+    #[test]
+    fn bundle_header_rejects_unknown_input() {
+        let b = "2".repeat(40);
+        let text = format!("{BUNDLE_V3_SIGNATURE}\n@filter=blob:none\n{b} HEAD\n\n");
+        assert!(parse_header(&text).is_err());
+        assert!(last_error().contains("unsupported bundle capability 'filter=blob:none'"));
+        // Capabilities only exist from v3 on.
+        let text = format!("{BUNDLE_V2_SIGNATURE}\n@object-format=sha1\n{b} HEAD\n\n");
+        assert!(parse_header(&text).is_err());
+
+        // A ref without a name, a bad id, and a truncated prerequisite.
+        for line in [b.as_str(), "xyz refs/heads/main", "-12345 short"] {
+            let text = format!("{BUNDLE_V2_SIGNATURE}\n{line}\n\n");
+            assert!(parse_header(&text).is_err(), "{line}");
+            assert!(last_error().contains("malformed bundle header line"));
+        }
+        assert!(parse_header("# v4 git bundle\n\n").is_err());
+        assert!(parse_header(&format!("{BUNDLE_V2_SIGNATURE}\n{b} HEAD\n")).is_err());
+    }
+
+    extern "C" fn collect_tip(
+        refname: *const c_char,
+        _old_id: *const raw::git_oid,
+        _new_id: *const raw::git_oid,
+        payload: *mut c_void,
+    ) -> c_int {
+        let names = unsafe { &mut *(payload as *mut Vec<String>) };
+        names.push(
+            unsafe { CStr::from_ptr(refname) }
+                .to_string_lossy()
+                .into_owned(),
+        );
+        0
+    }
+
+    fn create_bundle_file(t: &TestRepo, file: &str, refs: &[&str], basis: &[&str]) -> PathBuf {
+        let path = t.dir.join(file);
+        let path_z = cstr(path.to_str().unwrap());
+        let refs: Vec<CString> = refs.iter().map(|r| cstr(r)).collect();
+        let refs: Vec<*const c_char> = refs.iter().map(|r| r.as_ptr()).collect();
+        let basis: Vec<CString> = basis.iter().map(|b| cstr(b)).collect();
+        let basis: Vec<*const c_char> = basis.iter().map(|b| b.as_ptr()).collect();
+        check(unsafe {
+            git2_shim_bundle_create(
+                t.repo,
+                path_z.as_ptr(),
+                refs.as_ptr(),
+                refs.len(),
+                basis.as_ptr(),
+                basis.len(),
+            )
+        });
+        path
+    }
+
+    fn unbundle_file(repo: *mut raw::git_repository, path: &Path, tips: &mut Vec<String>) -> c_int {
+        let path = cstr(path.to_str().unwrap());
+        unsafe {
+            git2_shim_bundle_unbundle(
+                repo,
+                path.as_ptr(),
+                ptr::null(),
+                Some(collect_tip),
+                tips as *mut _ as *mut c_void,
+            )
+        }
+    }
+
+    #[test]
+    fn bundle_round_trip_with_basis() {
+        let src = TestRepo::new();
+        stage(src.repo, "a", "one\n");
+        let first = commit(src.repo, Some("HEAD"), &[], "one");
+        let full = create_bundle_file(&src, "full.bundle", &["master"], &[]);
+
+        let dst = TestRepo::new();
+        let mut tips = Vec::new();
+        assert_eq!(unbundle_file(dst.repo, &full, &mut tips), 0);
+        assert_eq!(tips, ["refs/heads/master"]);
+        assert_eq!(ref_id(dst.repo, "refs/heads/master"), Some(first));
+        // The receiver's own work on `feature` must not be overwritten.
+        stage(dst.repo, "local", "local\n");
+        let local = commit(dst.repo, Some("refs/heads/feature"), &[first], "local");
+
+        stage(src.repo, "b", "two\n");
+        let second = commit(src.repo, Some("HEAD"), &[first], "two");
+        let feature = commit(src.repo, Some("refs/heads/feature"), &[first], "feature");
+        let basis = object_id_hex(&first);
+        let thin = create_bundle_file(&src, "thin.bundle", &["master", "feature"], &[&basis]);
+        let mut input = BufReader::new(std::fs::File::open(&thin).unwrap());
+        let header = read_bundle_header(&mut input).unwrap();
+        assert_eq!(header.prerequisites, [first]);
+        assert_eq!(
+            header.refs,
+            [
+                (second, "refs/heads/master".to_string()),
+                (feature, "refs/heads/feature".to_string())
+            ]
+        );
+
+        let fresh = TestRepo::new();
+        assert_eq!(
+            unbundle_file(fresh.repo, &thin, &mut Vec::new()),
+            raw::GIT_ENOTFOUND
+        );
+        assert!(last_error().contains(&basis));
+
+        // master is checked out, so not even a fast-forward may move it.
+        let mut tips = Vec::new();
+        assert_eq!(unbundle_file(dst.repo, &thin, &mut tips), -1);
+        assert!(last_error().contains("refs/heads/master"));
+        assert_eq!(tips, Vec::<String>::new());
+        assert_eq!(ref_id(dst.repo, "refs/heads/master"), Some(first));
+
+        check(unsafe { git_repository_set_head_detached(dst.repo, &raw::git_oid { id: first }) });
+        let mut tips = Vec::new();
+        assert_eq!(
+            unbundle_file(dst.repo, &thin, &mut tips),
+            raw::GIT_ENONFASTFORWARD
+        );
+        assert!(last_error().contains("refs/heads/feature"));
+        assert_eq!(tips, ["refs/heads/master"]);
+        assert_eq!(ref_id(dst.repo, "refs/heads/master"), Some(second));
+        assert_eq!(ref_id(dst.repo, "refs/heads/feature"), Some(local));
+        assert!(dst.has(feature));
+    }
+
+    /// Problems reported by `git2_shim_fsck`: problem, id, kind, referrer.
+    type FsckReport = Vec<(c_int, ObjectId, c_int, Option<String>)>;
+
+    extern "C" fn collect_problem(
+        problem: c_int,
+        id: *const raw::git_oid,
+        kind: c_int,
+        referrer: *const c_char,
+        _detail: *const c_char,
+        payload: *mut c_void,
+    ) -> c_int {
+        let problems = unsafe { &mut *(payload as *mut FsckReport) };
+        let referrer = unsafe { referrer.as_ref() }.map(|_| {
+            unsafe { CStr::from_ptr(referrer) }
+                .to_string_lossy()
+                .into_owned()
+        });
+        problems.push((problem, unsafe { (*id).id }, kind, referrer));
+        0
+    }
+
+    fn fsck(repo: *mut raw::git_repository, connectivity_only: bool) -> FsckReport {
+        let mut problems = FsckReport::new();
+        unsafe {
+            let opts = git2_shim_fsck_options_new();
+            git2_shim_fsck_options_set_connectivity_only(opts, connectivity_only as c_int);
+            let rc = git2_shim_fsck(
+                repo,
+                opts,
+                Some(collect_problem),
+                &mut problems as *mut _ as *mut c_void,
+            );
+            git2_shim_fsck_options_free(opts);
+            assert_eq!(rc as usize, problems.len());
+        }
+        problems.sort();
+        problems
+    }
+
+    fn tree_of(repo: *mut raw::git_repository, commit: ObjectId) -> ObjectId {
+        unsafe {
+            let mut object = ptr::null_mut();
+            check(raw::git_commit_lookup(
+                &mut object,
+                repo,
+                &raw::git_oid { id: commit },
+            ));
+            let tree = (*raw::git_commit_tree_id(object)).id;
+            raw::git_commit_free(object);
+            tree
+        }
+    }
+
+    #[test]
+    fn fsck_reports_missing_and_corrupt_objects() {
+        let t = TestRepo::new();
+        stage(t.repo, "a", "one\n");
+        let first = commit(t.repo, Some("HEAD"), &[], "one");
+        stage(t.repo, "b", "two\n");
+        let second = commit(t.repo, Some("HEAD"), &[first], "two");
+        assert_eq!(fsck(t.repo, false), []);
+
+        let objects = t.objects();
+        let missing = t.blob("two\n");
+        std::fs::remove_file(loose_path(&objects, missing)).unwrap();
+        // A valid loose object stored under the wrong name.
+        let swapped = tree_of(t.repo, first);
+        let path = loose_path(&objects, swapped);
+        std::fs::remove_file(&path).unwrap();
+        std::fs::copy(loose_path(&objects, t.blob("one\n")), &path).unwrap();
+        // Not zlib data at all.
+        let garbled = tree_of(t.repo, second);
+        let path = loose_path(&objects, garbled);
+        std::fs::remove_file(&path).unwrap();
+        std::fs::write(&path, "not an object").unwrap();
+        // A zlib stream cut short, which libgit2 1.8 alone would spin on
+        // forever; only the full check reads unreachable objects.
+        let truncated = t.blob("cut short\n");
+        let path = loose_path(&objects, truncated);
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        std::fs::write(&path, &data[..data.len() / 2]).unwrap();
+
+        let mut expected = vec![
+            (
+                FSCK_MISSING,
+                missing,
+                raw::GIT_OBJECT_BLOB,
+                Some(":b".to_string()),
+            ),
+            (
+                FSCK_CORRUPT,
+                garbled,
+                raw::GIT_OBJECT_TREE,
+                Some(object_id_hex(&second)),
+            ),
+            (
+                FSCK_HASH_MISMATCH,
+                swapped,
+                raw::GIT_OBJECT_TREE,
+                Some(object_id_hex(&first)),
+            ),
+            (FSCK_CORRUPT, truncated, raw::GIT_OBJECT_ANY, None),
+        ];
+        expected.sort();
+        // The open handle still has the intact objects cached.
+        let repo = t.reopen();
+        assert_eq!(fsck(repo, false), expected);
+        // Connectivity-only still reads trees, so the same problems show up
+        // apart from the unreachable blob.
+        expected.retain(|problem| problem.1 != truncated);
+        assert_eq!(fsck(repo, true), expected);
+        unsafe { raw::git_repository_free(repo) };
+    }
+
+    /// Collects visited ids; stops the walk once it has `payload.1` of them.
+    extern "C" fn collect_shallow(id: *const raw::git_oid, payload: *mut c_void) -> c_int {
+        let (ids, limit) = unsafe { &mut *(payload as *mut (Vec<ObjectId>, usize)) };
+        ids.push(unsafe { (*id).id });
+        if ids.len() == *limit {
+            42
+        } else {
+            0
+        }
+    }
+
+    fn shallow_ids(repo: *mut raw::git_repository, limit: usize) -> (c_int, Vec<ObjectId>) {
+        let mut seen = (Vec::new(), limit);
+        let rc = unsafe {
+            git2_shim_repository_shallow_foreach(
+                repo,
+                collect_shallow,
+                &mut seen as *mut _ as *mut c_void,
+            )
+        };
+        (rc, seen.0)
+    }
+
+    #[test]
+    fn shallow_foreach_reads_the_shallow_file() {
+        let t = TestRepo::new();
+        assert_eq!(shallow_ids(t.repo, 0), (0, vec![]));
+
+        let shallow = t.dir.join("repo/.git/shallow");
+        let (a, b) = ("a".repeat(40), "b".repeat(40));
+        std::fs::write(&shallow, format!("{a}\n\n  \n{b}\n")).unwrap();
+        assert_eq!(shallow_ids(t.repo, 0), (0, vec![[0xaa; 20], [0xbb; 20]]));
+        // A non-zero callback result stops the walk and is returned.
+        assert_eq!(shallow_ids(t.repo, 1), (42, vec![[0xaa; 20]]));
+
+        std::fs::write(&shallow, format!("{a}\nnot-an-id\n{b}\n")).unwrap();
+        assert_eq!(shallow_ids(t.repo, 0), (-1, vec![[0xaa; 20]]));
+        assert_eq!(
+            unsafe { git2_shim_error_last_class() },
+            raw::GIT_ERROR_INVALID
+        );
+        assert!(last_error().contains("invalid shallow entry 'not-an-id'"));
+    }
+
+    #[test]
+    fn maintenance_prunes_only_old_unreachable_objects() {
+        let t = TestRepo::new();
+        stage(t.repo, "a", "first\n");
+        let first = commit(t.repo, Some("HEAD"), &[], "first");
+        stage(t.repo, "b", "second\n");
+        let second = commit(t.repo, Some("HEAD"), &[first], "second");
+        // `second` is now only in the branch and HEAD reflogs.
+        set_ref(t.repo, "refs/heads/master", first);
+        stage(t.repo, "c", "staged only\n");
+        let staged = t.blob("staged only\n");
+        let stale = t.blob("stale orphan\n");
+        age_loose_objects(&t.objects(), 30 * DAY);
+        let fresh = t.blob("fresh orphan\n");
+
+        let stats = maintain(t.repo);
+        assert_eq!(stats.loose_pruned, 1);
+        assert!(stats.objects_packed > 0);
+        assert!(!t.has(stale));
+        assert!(t.has(fresh));
+        assert!(t.has(first));
+        assert!(t.has(second));
+        assert!(t.has(staged));
+        assert!(!loose_path(&t.objects(), first).exists());
+    }
+
+    #[test]
+    fn maintenance_prune_expire_moves_the_cutoff() {
+        let t = TestRepo::new();
+        stage(t.repo, "a", "kept\n");
+        let head = commit(t.repo, Some("HEAD"), &[], "kept");
+        let orphan = t.blob("young orphan\n");
+        unsafe {
+            let opts = git2_shim_maintenance_options_new();
+            git2_shim_maintenance_options_set_repack(opts, 0);
+            let tomorrow = SystemTime::now() + DAY;
+            let expire = tomorrow.duration_since(UNIX_EPOCH).unwrap().as_secs();
+            git2_shim_maintenance_options_set_prune_expire(opts, expire as i64);
+            let mut stats = git2_shim_maintenance_stats::default();
+            check(git2_shim_maintenance_run(t.repo, opts, &mut stats));
+            git2_shim_maintenance_options_free(opts);
+            assert_eq!(stats.loose_pruned, 1);
+            assert_eq!(stats.objects_packed, 0);
+        }
+        assert!(!t.has(orphan));
+        assert!(loose_path(&t.objects(), head).exists());
+    }
+
+    #[test]
+    fn maintenance_leaves_kept_packs_alone() {
+        let t = TestRepo::new();
+        stage(t.repo, "a", "one\n");
+        let first = commit(t.repo, Some("HEAD"), &[], "one");
+        maintain(t.repo);
+        let kept = pack_stems(&t.objects());
+        assert_eq!(kept.len(), 1);
+        let pack = t.objects().join("pack");
+        std::fs::write(pack.join(&kept[0]).with_extension("keep"), "").unwrap();
+
+        stage(t.repo, "b", "two\n");
+        commit(t.repo, Some("HEAD"), &[first], "two");
+        let stats = maintain(t.repo);
+        assert_eq!(stats.packs_removed, 0);
+        assert_eq!(pack_stems(&t.objects()).len(), 2);
+        assert!(pack.join(&kept[0]).with_extension("idx").exists());
+    }
+
+    #[test]
+    fn maintenance_removes_redundant_packs_and_rewrites_the_midx() {
+        let t = TestRepo::new();
+        stage(t.repo, "a", "one\n");
+        let first = commit(t.repo, Some("HEAD"), &[], "one");
+        maintain(t.repo);
+        unsafe {
+            let mut odb = ptr::null_mut();
+            check(raw::git_repository_odb(&mut odb, t.repo));
+            check(raw::git_odb_write_multi_pack_index(odb));
+            raw::git_odb_free(odb);
+        }
+        let midx = t.objects().join("pack").join("multi-pack-index");
+        let old = pack_stems(&t.objects()).remove(0);
+
+        stage(t.repo, "b", "two\n");
+        let second = commit(t.repo, Some("HEAD"), &[first], "two");
+        let stats = maintain(t.repo);
+        assert_eq!(stats.packs_removed, 1);
+        let new = pack_stems(&t.objects()).remove(0);
+        assert_ne!(new, old);
+        // The index lists its packs by name; only the new one may remain.
+        let names = String::from_utf8_lossy(&std::fs::read(&midx).unwrap()).into_owned();
+        assert!(names.contains(&new));
+        assert!(!names.contains(&old));
+
+        let path = cstr(t.dir.join("repo").to_str().unwrap());
+        unsafe {
+            let mut reopened = ptr::null_mut();
+            check(raw::git_repository_open(&mut reopened, path.as_ptr()));
+            for id in [first, second] {
+                let mut commit = ptr::null_mut();
+                check(raw::git_commit_lookup(
+                    &mut commit,
+                    reopened,
+                    &raw::git_oid { id },
+                ));
+                raw::git_commit_free(commit);
+            }
+            raw::git_repository_free(reopened);
+        }
+    }
+
+    #[test]
+    fn maintenance_keeps_linked_worktree_roots() {
+        let t = TestRepo::new();
+        stage(t.repo, "a", "main\n");
+        let base = commit(t.repo, Some("HEAD"), &[], "main");
+        let name = cstr("linked");
+        let path = cstr(t.dir.join("linked").to_str().unwrap());
+        let linked = unsafe {
+            let mut worktree = ptr::null_mut();
+            check(raw::git_worktree_add(
+                &mut worktree,
+                t.repo,
+                name.as_ptr(),
+                path.as_ptr(),
+                ptr::null(),
+            ));
+            let mut linked = ptr::null_mut();
+            check(raw::git_repository_open_from_worktree(
+                &mut linked,
+                worktree,
+            ));
+            raw::git_worktree_free(worktree);
+            linked
+        };
+        stage(linked, "b", "detached\n");
+        let detached = commit(linked, None, &[base], "detached");
+        check(unsafe { git_repository_set_head_detached(linked, &raw::git_oid { id: detached }) });
+        stage(linked, "c", "linked index\n");
+        let staged = blob(linked, "linked index\n");
+        // Only the detached HEAD's reflog is still pointing at it.
+        std::fs::remove_dir_all(t.dir.join("repo/.git/worktrees/linked/logs")).unwrap();
+        age_loose_objects(&t.objects(), 30 * DAY);
+
+        let stats = maintain(t.repo);
+        assert_eq!(stats.loose_pruned, 0);
+        assert!(t.has(detached));
+        assert!(t.has(staged));
+
+        // Run from the linked worktree, the main worktree's index counts.
+        stage(t.repo, "d", "main index\n");
+        let main_staged = t.blob("main index\n");
+        age_loose_objects(&t.objects(), 30 * DAY);
+        let stats = maintain(linked);
+        assert_eq!(stats.loose_pruned, 0);
+        assert!(has(linked, main_staged));
+        unsafe { raw::git_repository_free(linked) };
+
+        // A worktree whose directory is gone is skipped, not an error.
+        std::fs::remove_dir_all(t.dir.join("linked")).unwrap();
+        maintain(t.repo);
+    }
+
+    type FetchHeadLine = (Option<String>, Option<String>, ObjectId, bool);
+
+    extern "C" fn collect_fetchhead(
+        ref_name: *const c_char,
+        remote_url: *const c_char,
+        oid: *const raw::git_oid,
+        is_merge: c_uint,
+        payload: *mut c_void,
+    ) -> c_int {
+        let text = |s: *const c_char| {
+            (!s.is_null()).then(|| unsafe { CStr::from_ptr(s) }.to_string_lossy().into_owned())
+        };
+        let lines = unsafe { &mut *(payload as *mut Vec<FetchHeadLine>) };
+        lines.push((
+            text(ref_name),
+            text(remote_url),
+            unsafe { (*oid).id },
+            is_merge != 0,
+        ));
+        0
+    }
+
+    #[test]
+    fn fetchhead_line_for_head_has_no_ref_name() {
+        let src = TestRepo::new();
+        stage(src.repo, "a", "one\n");
+        let tip = commit(src.repo, Some("HEAD"), &[], "one");
+
+        let dst = TestRepo::new();
+        let url = cstr(src.dir.join("repo").to_str().unwrap());
+        let mut remote = ptr::null_mut();
+        check(unsafe { git2_shim_remote_create_anonymous(&mut remote, dst.repo, url.as_ptr()) });
+        // An anonymous remote has no refspecs, so this fetches HEAD alone.
+        check(unsafe {
+            git2_shim_remote_fetch(remote, ptr::null(), 0, ptr::null_mut(), ptr::null())
+        });
+        unsafe { raw::git_remote_free(remote) };
+
+        let mut lines: Vec<FetchHeadLine> = Vec::new();
+        check(unsafe {
+            git2_shim_repository_fetchhead_foreach(
+                dst.repo,
+                collect_fetchhead,
+                &mut lines as *mut _ as *mut c_void,
+            )
+        });
+        assert_eq!(lines.len(), 1, "{lines:?}");
+        let (name, remote_url, id, is_merge) = &lines[0];
+        assert_eq!(name, &None);
+        assert!(remote_url.is_some());
+        assert_eq!(*id, tip);
+        assert!(*is_merge);
+    }
+
+    extern "C" fn check_upper(
+        _src: *const raw::git_filter_source,
+        attr_values: *const *const c_char,
+        _payload: *mut c_void,
+    ) -> c_int {
+        // `filter=upper` only matches files with exactly that value.
+        let value = unsafe { *attr_values };
+        assert_eq!(unsafe { git2_shim_attr_value(value) }, 3);
+        assert_eq!(unsafe { CStr::from_ptr(value) }, c"upper");
+        0
+    }
+
+    extern "C" fn smudge_upper(
+        sink: *mut FilterSink,
+        input: *const c_char,
+        len: size_t,
+        src: *const raw::git_filter_source,
+        _payload: *mut c_void,
+    ) -> c_int {
+        // Mode 0 is GIT_FILTER_TO_WORKTREE.
+        if unsafe { git2_shim_filter_source_mode(src) } != 0 {
+            return raw::GIT_PASSTHROUGH;
+        }
+        let input = unsafe { std::slice::from_raw_parts(input as *const u8, len) };
+        let output = input.to_ascii_uppercase();
+        unsafe { git2_shim_filter_sink_write(sink, output.as_ptr().cast(), output.len()) };
+        0
+    }
+
+    #[test]
+    fn registered_filter_smudges_on_checkout() {
+        let t = TestRepo::new();
+        stage(t.repo, ".gitattributes", "*.txt filter=upper\n");
+        stage(t.repo, "a.txt", "hello\n");
+        stage(t.repo, "b.md", "plain\n");
+        commit(t.repo, Some("HEAD"), &[], "one");
+
+        unsafe {
+            let filter = git2_shim_filter_new(
+                c"filter=upper".as_ptr(),
+                Some(check_upper),
+                smudge_upper,
+                ptr::null_mut(),
+            );
+            check(git2_shim_filter_register(c"upper".as_ptr(), filter, 200));
+            let opts = git2_shim_checkout_options_new();
+            // GIT_CHECKOUT_FORCE, as the files are missing from the work tree.
+            git2_shim_checkout_options_set_strategy(opts, 1 << 1);
+            let rc = git2_shim_checkout_branch(t.repo, c"master".as_ptr(), opts);
+            git2_shim_checkout_options_free(opts);
+            check(git2_shim_filter_unregister(c"upper".as_ptr()));
+            git2_shim_filter_free(filter);
+            check(rc);
+        }
+        let workdir = t.dir.join("repo");
+        assert_eq!(
+            std::fs::read_to_string(workdir.join("a.txt")).unwrap(),
+            "HELLO\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(workdir.join("b.md")).unwrap(),
+            "plain\n"
+        );
+    }
+
+    /// Run the git CLI in `dir`, which may use local file:// submodules.
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .current_dir(dir)
+            .args(["-c", "protocol.file.allow=always"])
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?}");
+    }
+
+    type SubmoduleOutcome = (String, c_uint, bool);
+
+    extern "C" fn collect_submodule_update(
+        path: *const c_char,
+        depth: c_uint,
+        error: c_int,
+        _message: *const c_char,
+        payload: *mut c_void,
+    ) -> c_int {
+        let outcomes = unsafe { &mut *(payload as *mut Vec<SubmoduleOutcome>) };
+        let path = unsafe { CStr::from_ptr(path) }
+            .to_string_lossy()
+            .into_owned();
+        outcomes.push((path, depth, error == 0));
+        0
+    }
+
+    fn update_submodules(
+        repo: *mut raw::git_repository,
+        max_depth: c_uint,
+    ) -> (c_int, Vec<SubmoduleOutcome>) {
+        let mut outcomes = Vec::new();
+        let rc = unsafe {
+            git2_shim_submodule_update_recursive(
+                repo,
+                1,
+                max_depth,
+                ptr::null_mut(),
+                Some(collect_submodule_update),
+                &mut outcomes as *mut _ as *mut c_void,
+            )
+        };
+        (rc, outcomes)
+    }
+
+    #[test]
+    fn submodule_update_recursive_descends_to_max_depth() {
+        let leaf = TestRepo::new();
+        stage(leaf.repo, "leaf", "leaf\n");
+        commit(leaf.repo, Some("HEAD"), &[], "leaf");
+        // A submodule whose own .gitmodules cannot be parsed.
+        let broken = TestRepo::new();
+        stage(broken.repo, ".gitmodules", "[[[ not a config file\n");
+        commit(broken.repo, Some("HEAD"), &[], "broken");
+
+        let mid = TestRepo::new();
+        let mid_dir = mid.dir.join("repo");
+        git(
+            &mid_dir,
+            &[
+                "submodule",
+                "add",
+                leaf.dir.join("repo").to_str().unwrap(),
+                "leaf",
+            ],
+        );
+        git(&mid_dir, &["commit", "-m", "add leaf"]);
+        let top = TestRepo::new();
+        let top_dir = top.dir.join("repo");
+        git(
+            &top_dir,
+            &["submodule", "add", mid_dir.to_str().unwrap(), "mid"],
+        );
+        git(
+            &top_dir,
+            &[
+                "submodule",
+                "add",
+                broken.dir.join("repo").to_str().unwrap(),
+                "broken",
+            ],
+        );
+        git(&top_dir, &["commit", "-m", "add submodules"]);
+
+        // A fresh clone has nothing checked out in its submodules yet.
+        git(&top.dir, &["clone", "-q", "repo", "clone"]);
+        let path = cstr(top.dir.join("clone").to_str().unwrap());
+        let mut clone = ptr::null_mut();
+        check(unsafe { git2_shim_repository_open(&mut clone, path.as_ptr()) });
+
+        let (rc, outcomes) = update_submodules(clone, 1);
+        assert_eq!(rc, 0);
+        assert_eq!(
+            outcomes,
+            [("broken".into(), 1, true), ("mid".into(), 1, true)]
+        );
+        assert!(!top.dir.join("clone/mid/leaf/leaf").exists());
+
+        let (rc, outcomes) = update_submodules(clone, 0);
+        assert_eq!(rc, 1);
+        assert_eq!(
+            outcomes,
+            [
+                ("broken".into(), 1, true),
+                ("broken".into(), 1, false),
+                ("mid".into(), 1, true),
+                ("mid/leaf".into(), 2, true),
+            ]
+        );
+        assert!(top.dir.join("clone/mid/leaf/leaf").exists());
+        unsafe { raw::git_repository_free(clone) };
+    }
+
+    extern "C" fn hide_matching(commit_id: *const raw::git_oid, payload: *mut c_void) -> c_int {
+        let hidden = unsafe { &*(payload as *const ObjectId) };
+        (unsafe { (*commit_id).id } == *hidden) as c_int
+    }
+
+    #[test]
+    fn revwalk_hide_callback_gets_its_own_payload() {
+        let t = TestRepo::new();
+        stage(t.repo, "a", "one\n");
+        let first = commit(t.repo, Some("HEAD"), &[], "first");
+        stage(t.repo, "a", "two\n");
+        let second = commit(t.repo, Some("HEAD"), &[first], "second");
+        stage(t.repo, "a", "three\n");
+        let third = commit(t.repo, Some("HEAD"), &[second], "third");
+        unsafe {
+            let mut walk = ptr::null_mut();
+            check(git2_shim_revwalk_new(&mut walk, t.repo));
+            check(git2_shim_revwalk_add_hide_cb(
+                walk,
+                Some(hide_matching),
+                &first as *const ObjectId as *mut c_void,
+            ));
+            // Replacing the callback must not leave libgit2 with a stale payload.
+            check(git2_shim_revwalk_add_hide_cb(
+                walk,
+                Some(hide_matching),
+                &second as *const ObjectId as *mut c_void,
+            ));
+            check(raw::git_revwalk_push(walk, &raw::git_oid { id: third }));
+            let mut seen = Vec::new();
+            let mut id = raw::git_oid {
+                id: [0; raw::GIT_OID_RAWSZ],
+            };
+            while raw::git_revwalk_next(&mut id, walk) == 0 {
+                seen.push(id.id);
+            }
+            git2_shim_revwalk_free(walk);
+            assert_eq!(seen, vec![third]);
+        }
+    }
 }
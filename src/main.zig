@@ -16,11 +16,92 @@ const std = @import("std");
 const git_repository = opaque {};
 const git_reference = opaque {};
 const git_status_list = opaque {};
+const git_signature = opaque {};
+const git2_shim_stash_save_options = opaque {};
+const git2_shim_stash_apply_options = opaque {};
+const git_annotated_commit = opaque {};
+const git2_shim_checkout_options = opaque {};
+const git2_shim_merge_options = opaque {};
+const git_commit = opaque {};
+const git_tree = opaque {};
+const git_index = opaque {};
+const git_index_entry = opaque {};
+const git2_shim_merge_file_options = opaque {};
+const git2_shim_merge_file_result = opaque {};
+const git_rebase = opaque {};
+const git_rebase_operation = opaque {};
+const git2_shim_rebase_options = opaque {};
+const git_blame = opaque {};
+const git_blame_hunk = opaque {};
+const git2_shim_blame_options = opaque {};
+const git_revwalk = opaque {};
+const git_object = opaque {};
+const git2_shim_clone_options = opaque {};
+const git2_shim_fetch_options = opaque {};
+const git2_shim_connect_options = opaque {};
+const git_remote = opaque {};
+const git2_shim_push_options = opaque {};
+const git2_shim_remote_callbacks = opaque {};
+const git2_shim_proxy_options = opaque {};
+const git2_shim_smart_subtransport = opaque {};
+const git2_shim_maintenance_options = opaque {};
+pub const git_credential = opaque {};
+pub const git_cert = opaque {};
+const git_refspec = opaque {};
+const git_config = opaque {};
+const git_config_iterator = opaque {};
+const git_transaction = opaque {};
+pub const git_submodule = opaque {};
+const git2_shim_submodule_update_options = opaque {};
+pub const git_worktree = opaque {};
+const git2_shim_repository_init_options = opaque {};
+const git_odb = opaque {};
+const git_refdb = opaque {};
+const git2_shim_worktree_add_options = opaque {};
+const git2_shim_filter = opaque {};
+const git2_shim_fsck_options = opaque {};
+const git_blob = opaque {};
+const git_filter_list = opaque {};
+
+const git_buf = extern struct {
+    ptr: ?[*]u8,
+    reserved: usize,
+    size: usize,
+};
+
+const git_strarray = extern struct {
+    strings: ?[*][*:0]u8,
+    count: usize,
+};
+
+const git_revspec = extern struct {
+    from: ?*git_object,
+    to: ?*git_object,
+    flags: c_uint,
+};
+
+const git2_shim_merge_file_input = extern struct {
+    ptr: ?[*]const u8,
+    size: usize,
+    path: ?[*:0]const u8,
+    mode: c_uint,
+};
 
 const git_oid = extern struct {
     id: [20]u8,
 };
 
+/// A ref advertised by a remote; custom transports build these for `ls`
+pub const git_remote_head = extern struct {
+    /// Set by libgit2 when the object already exists locally
+    local: c_int = 0,
+    oid: git_oid,
+    /// Local object id, filled in by libgit2
+    loid: git_oid = .{ .id = [_]u8{0} ** 20 },
+    name: [*:0]u8,
+    symref_target: ?[*:0]u8 = null,
+};
+
 const git_status_options = extern struct {
     version: c_uint,
     show: c_uint,
@@ -36,10 +117,22 @@ const git_status_options = extern struct {
 // Constants
 const GIT_STATUS_OPTIONS_VERSION: c_uint = 1;
 const GIT_STATUS_SHOW_INDEX_AND_WORKDIR: c_uint = 0;
+const GIT_ENOTFOUND: c_int = -3;
+const GIT_EUNMERGED: c_int = -10;
+const GIT_ENONFASTFORWARD: c_int = -11;
+const GIT_ECONFLICT: c_int = -13;
+const GIT_EAPPLIED: c_int = -18;
+const GIT_ITEROVER: c_int = -31;
 
 // Rust shim functions (from libgit2_shim.so)
 extern "C" fn git2_shim_init() c_int;
 extern "C" fn git2_shim_shutdown() c_int;
+extern "C" fn git2_shim_set_server_connect_timeout(timeout_ms: c_int) c_int;
+extern "C" fn git2_shim_get_server_connect_timeout(timeout_ms: *c_int) c_int;
+extern "C" fn git2_shim_set_server_timeout(timeout_ms: c_int) c_int;
+extern "C" fn git2_shim_get_server_timeout(timeout_ms: *c_int) c_int;
+extern "C" fn git2_shim_set_search_path(level: c_int, path: ?[*:0]const u8) c_int;
+extern "C" fn git2_shim_get_search_path(level: c_int, out: *git_buf) c_int;
 extern "C" fn git2_shim_repository_open(out: *?*git_repository, path: [*:0]const u8) c_int;
 extern "C" fn git2_shim_repository_free(repo: *git_repository) void;
 extern "C" fn git2_shim_repository_is_bare(repo: *git_repository) c_int;
@@ -52,6 +145,7 @@ extern "C" fn git2_shim_status_list_new(
 extern "C" fn git2_shim_status_list_free(list: *git_status_list) void;
 extern "C" fn git2_shim_status_list_entrycount(list: *git_status_list) usize;
 extern "C" fn git2_shim_repository_head(out: *?*git_reference, repo: *git_repository) c_int;
+extern "C" fn git2_shim_reference_lookup(out: *?*git_reference, repo: *git_repository, name: [*:0]const u8) c_int;
 extern "C" fn git2_shim_reference_free(ref: *git_reference) void;
 extern "C" fn git2_shim_reference_shorthand(ref: *git_reference) ?[*:0]const u8;
 extern "C" fn git2_shim_graph_ahead_behind(
@@ -62,6 +156,765 @@ extern "C" fn git2_shim_graph_ahead_behind(
     upstream: *const git_oid,
 ) c_int;
 extern "C" fn git2_shim_status_options_init(opts: *git_status_options, version: c_uint) c_int;
+extern "C" fn git2_shim_signature_default(out: *?*git_signature, repo: *git_repository) c_int;
+extern "C" fn git2_shim_repository_set_ident(repo: *git_repository, name: ?[*:0]const u8, email: ?[*:0]const u8) c_int;
+extern "C" fn git2_shim_repository_ident(name: *?[*:0]const u8, email: *?[*:0]const u8, repo: *const git_repository) c_int;
+extern "C" fn git2_shim_signature_now(out: *?*git_signature, name: [*:0]const u8, email: [*:0]const u8) c_int;
+extern "C" fn git2_shim_signature_free(sig: *git_signature) void;
+extern "C" fn git2_shim_signature_name(sig: *const git_signature) ?[*:0]const u8;
+extern "C" fn git2_shim_signature_email(sig: *const git_signature) ?[*:0]const u8;
+extern "C" fn git2_shim_signature_time(sig: *const git_signature) i64;
+extern "C" fn git2_shim_signature_offset(sig: *const git_signature) c_int;
+
+const git_stash_cb = *const fn (
+    index: usize,
+    message: ?[*:0]const u8,
+    stash_id: *const git_oid,
+    payload: ?*anyopaque,
+) callconv(.C) c_int;
+
+extern "C" fn git2_shim_stash_save(
+    out: *git_oid,
+    repo: *git_repository,
+    stasher: *const git_signature,
+    message: ?[*:0]const u8,
+    flags: u32,
+) c_int;
+extern "C" fn git2_shim_stash_save_options_new() ?*git2_shim_stash_save_options;
+extern "C" fn git2_shim_stash_save_options_free(opts: *git2_shim_stash_save_options) void;
+extern "C" fn git2_shim_stash_save_options_set_flags(opts: *git2_shim_stash_save_options, flags: u32) void;
+extern "C" fn git2_shim_stash_save_options_set_message(opts: *git2_shim_stash_save_options, message: ?[*:0]const u8) void;
+extern "C" fn git2_shim_stash_save_options_set_stasher(opts: *git2_shim_stash_save_options, stasher: ?*const git_signature) void;
+extern "C" fn git2_shim_stash_save_options_set_paths(opts: *git2_shim_stash_save_options, paths: ?[*]const [*:0]const u8, count: usize) void;
+extern "C" fn git2_shim_stash_save_with_opts(out: *git_oid, repo: *git_repository, opts: *git2_shim_stash_save_options) c_int;
+
+pub const git_stash_apply_progress_cb = *const fn (progress: c_int, payload: ?*anyopaque) callconv(.C) c_int;
+
+extern "C" fn git2_shim_stash_apply_options_new() ?*git2_shim_stash_apply_options;
+extern "C" fn git2_shim_stash_apply_options_free(opts: *git2_shim_stash_apply_options) void;
+extern "C" fn git2_shim_stash_apply_options_set_reinstate_index(opts: *git2_shim_stash_apply_options, enabled: c_int) void;
+extern "C" fn git2_shim_stash_apply_options_set_progress_cb(
+    opts: *git2_shim_stash_apply_options,
+    callback: ?git_stash_apply_progress_cb,
+    payload: ?*anyopaque,
+) void;
+extern "C" fn git2_shim_stash_apply(repo: *git_repository, index: usize, opts: ?*git2_shim_stash_apply_options) c_int;
+extern "C" fn git2_shim_stash_pop(repo: *git_repository, index: usize, opts: ?*git2_shim_stash_apply_options) c_int;
+
+pub const git_checkout_progress_cb = *const fn (
+    path: ?[*:0]const u8,
+    completed_steps: usize,
+    total_steps: usize,
+    payload: ?*anyopaque,
+) callconv(.C) void;
+
+extern "C" fn git2_shim_checkout_options_new() ?*git2_shim_checkout_options;
+extern "C" fn git2_shim_checkout_options_free(opts: *git2_shim_checkout_options) void;
+extern "C" fn git2_shim_checkout_options_set_strategy(opts: *git2_shim_checkout_options, strategy: c_uint) void;
+extern "C" fn git2_shim_checkout_options_set_paths(opts: *git2_shim_checkout_options, paths: ?[*]const [*:0]const u8, count: usize) void;
+extern "C" fn git2_shim_checkout_options_set_target_directory(opts: *git2_shim_checkout_options, path: ?[*:0]const u8) void;
+extern "C" fn git2_shim_checkout_options_set_conflict_labels(
+    opts: *git2_shim_checkout_options,
+    ancestor: ?[*:0]const u8,
+    ours: ?[*:0]const u8,
+    theirs: ?[*:0]const u8,
+) void;
+extern "C" fn git2_shim_checkout_options_set_progress_cb(
+    opts: *git2_shim_checkout_options,
+    callback: ?git_checkout_progress_cb,
+    payload: ?*anyopaque,
+) void;
+
+extern "C" fn git2_shim_annotated_commit_from_ref(out: *?*git_annotated_commit, repo: *git_repository, ref: *const git_reference) c_int;
+extern "C" fn git2_shim_annotated_commit_lookup(out: *?*git_annotated_commit, repo: *git_repository, id: *const git_oid) c_int;
+extern "C" fn git2_shim_annotated_commit_from_revspec(out: *?*git_annotated_commit, repo: *git_repository, revspec: [*:0]const u8) c_int;
+extern "C" fn git2_shim_annotated_commit_id(commit: *const git_annotated_commit) *const git_oid;
+extern "C" fn git2_shim_annotated_commit_ref(commit: *const git_annotated_commit) ?[*:0]const u8;
+extern "C" fn git2_shim_annotated_commit_free(commit: *git_annotated_commit) void;
+
+extern "C" fn git2_shim_merge_options_new() ?*git2_shim_merge_options;
+extern "C" fn git2_shim_merge_options_free(opts: *git2_shim_merge_options) void;
+extern "C" fn git2_shim_merge_options_set_flags(opts: *git2_shim_merge_options, flags: u32) void;
+extern "C" fn git2_shim_merge_options_set_file_favor(opts: *git2_shim_merge_options, favor: c_uint) void;
+extern "C" fn git2_shim_merge_options_set_file_flags(opts: *git2_shim_merge_options, flags: u32) void;
+extern "C" fn git2_shim_merge_options_set_rename_threshold(opts: *git2_shim_merge_options, threshold: c_uint) void;
+extern "C" fn git2_shim_merge_options_set_target_limit(opts: *git2_shim_merge_options, limit: c_uint) void;
+extern "C" fn git2_shim_merge_options_set_recursion_limit(opts: *git2_shim_merge_options, limit: c_uint) void;
+extern "C" fn git2_shim_merge_options_set_default_driver(opts: *git2_shim_merge_options, driver: ?[*:0]const u8) void;
+extern "C" fn git2_shim_merge(
+    repo: *git_repository,
+    their_heads: [*]const *const git_annotated_commit,
+    their_heads_len: usize,
+    merge_opts: ?*const git2_shim_merge_options,
+    checkout_opts: ?*git2_shim_checkout_options,
+) c_int;
+extern "C" fn git2_shim_merge_analysis(
+    analysis_out: *c_uint,
+    preference_out: *c_uint,
+    repo: *git_repository,
+    their_heads: [*]const *const git_annotated_commit,
+    their_heads_len: usize,
+) c_int;
+extern "C" fn git2_shim_merge_commits(
+    out: *?*git_index,
+    repo: *git_repository,
+    our_commit: *const git_commit,
+    their_commit: *const git_commit,
+    opts: ?*const git2_shim_merge_options,
+) c_int;
+extern "C" fn git2_shim_merge_trees(
+    out: *?*git_index,
+    repo: *git_repository,
+    ancestor_tree: ?*const git_tree,
+    our_tree: *const git_tree,
+    their_tree: *const git_tree,
+    opts: ?*const git2_shim_merge_options,
+) c_int;
+
+extern "C" fn git2_shim_merge_base(out: *git_oid, repo: *git_repository, one: *const git_oid, two: *const git_oid) c_int;
+extern "C" fn git2_shim_merge_base_many(out: *git_oid, repo: *git_repository, length: usize, input_array: [*]const git_oid) c_int;
+extern "C" fn git2_shim_merge_base_octopus(out: *git_oid, repo: *git_repository, length: usize, input_array: [*]const git_oid) c_int;
+
+extern "C" fn git2_shim_merge_file_options_new() ?*git2_shim_merge_file_options;
+extern "C" fn git2_shim_merge_file_options_free(opts: *git2_shim_merge_file_options) void;
+extern "C" fn git2_shim_merge_file_options_set_labels(
+    opts: *git2_shim_merge_file_options,
+    ancestor: ?[*:0]const u8,
+    ours: ?[*:0]const u8,
+    theirs: ?[*:0]const u8,
+) void;
+extern "C" fn git2_shim_merge_file_options_set_favor(opts: *git2_shim_merge_file_options, favor: c_uint) void;
+extern "C" fn git2_shim_merge_file_options_set_flags(opts: *git2_shim_merge_file_options, flags: u32) void;
+extern "C" fn git2_shim_merge_file_options_set_marker_size(opts: *git2_shim_merge_file_options, marker_size: u16) void;
+extern "C" fn git2_shim_merge_file(
+    out: *?*git2_shim_merge_file_result,
+    ancestor: ?*const git2_shim_merge_file_input,
+    ours: *const git2_shim_merge_file_input,
+    theirs: *const git2_shim_merge_file_input,
+    opts: ?*const git2_shim_merge_file_options,
+) c_int;
+extern "C" fn git2_shim_merge_file_from_index(
+    out: *?*git2_shim_merge_file_result,
+    repo: *git_repository,
+    ancestor: ?*const git_index_entry,
+    ours: ?*const git_index_entry,
+    theirs: ?*const git_index_entry,
+    opts: ?*const git2_shim_merge_file_options,
+) c_int;
+extern "C" fn git2_shim_merge_file_result_free(result: *git2_shim_merge_file_result) void;
+extern "C" fn git2_shim_merge_file_result_automergeable(result: *const git2_shim_merge_file_result) c_int;
+extern "C" fn git2_shim_merge_file_result_content(result: *const git2_shim_merge_file_result, len_out: *usize) ?[*]const u8;
+extern "C" fn git2_shim_merge_file_result_path(result: *const git2_shim_merge_file_result) ?[*:0]const u8;
+extern "C" fn git2_shim_merge_file_result_mode(result: *const git2_shim_merge_file_result) c_uint;
+
+extern "C" fn git2_shim_cherrypick(
+    repo: *git_repository,
+    commit: *git_commit,
+    mainline: c_uint,
+    merge_opts: ?*const git2_shim_merge_options,
+    checkout_opts: ?*git2_shim_checkout_options,
+) c_int;
+
+extern "C" fn git2_shim_cherrypick_commit(
+    out: *?*git_index,
+    repo: *git_repository,
+    cherrypick_commit: *git_commit,
+    our_commit: *git_commit,
+    mainline: c_uint,
+    merge_opts: ?*const git2_shim_merge_options,
+) c_int;
+
+extern "C" fn git2_shim_revert(
+    repo: *git_repository,
+    commit: *git_commit,
+    mainline: c_uint,
+    merge_opts: ?*const git2_shim_merge_options,
+    checkout_opts: ?*git2_shim_checkout_options,
+) c_int;
+extern "C" fn git2_shim_revert_commit(
+    out: *?*git_index,
+    repo: *git_repository,
+    revert_commit: *git_commit,
+    our_commit: *git_commit,
+    mainline: c_uint,
+    merge_opts: ?*const git2_shim_merge_options,
+) c_int;
+
+extern "C" fn git2_shim_rebase_options_new() ?*git2_shim_rebase_options;
+extern "C" fn git2_shim_rebase_options_free(opts: *git2_shim_rebase_options) void;
+extern "C" fn git2_shim_rebase_options_set_quiet(opts: *git2_shim_rebase_options, quiet: c_int) void;
+extern "C" fn git2_shim_rebase_options_set_inmemory(opts: *git2_shim_rebase_options, inmemory: c_int) void;
+extern "C" fn git2_shim_rebase_options_set_rewrite_notes_ref(opts: *git2_shim_rebase_options, notes_ref: ?[*:0]const u8) void;
+extern "C" fn git2_shim_rebase_options_set_merge_options(opts: *git2_shim_rebase_options, merge: ?*const git2_shim_merge_options) void;
+extern "C" fn git2_shim_rebase_options_set_checkout_options(opts: *git2_shim_rebase_options, checkout: ?*const git2_shim_checkout_options) void;
+extern "C" fn git2_shim_rebase_options_set_signing_cb(
+    opts: *git2_shim_rebase_options,
+    callback: ?git_commit_signing_cb,
+    payload: ?*anyopaque,
+) void;
+extern "C" fn git2_shim_rebase_init(
+    out: *?*git_rebase,
+    repo: *git_repository,
+    branch: ?*const git_annotated_commit,
+    upstream: ?*const git_annotated_commit,
+    onto: ?*const git_annotated_commit,
+    opts: ?*git2_shim_rebase_options,
+) c_int;
+extern "C" fn git2_shim_rebase_open(out: *?*git_rebase, repo: *git_repository, opts: ?*git2_shim_rebase_options) c_int;
+extern "C" fn git2_shim_rebase_operation_entrycount(rebase: *git_rebase) usize;
+extern "C" fn git2_shim_rebase_operation_current(rebase: *git_rebase) usize;
+extern "C" fn git2_shim_rebase_operation_byindex(rebase: *git_rebase, idx: usize) ?*git_rebase_operation;
+extern "C" fn git2_shim_rebase_next(operation: *?*git_rebase_operation, rebase: *git_rebase) c_int;
+extern "C" fn git2_shim_rebase_commit(
+    id: *git_oid,
+    rebase: *git_rebase,
+    author: ?*const git_signature,
+    committer: *const git_signature,
+    message: ?[*:0]const u8,
+) c_int;
+extern "C" fn git2_shim_rebase_inmemory_index(index: *?*git_index, rebase: *git_rebase) c_int;
+extern "C" fn git2_shim_rebase_abort(rebase: *git_rebase) c_int;
+extern "C" fn git2_shim_rebase_finish(rebase: *git_rebase, signature: ?*const git_signature) c_int;
+extern "C" fn git2_shim_rebase_free(rebase: *git_rebase) void;
+extern "C" fn git2_shim_rebase_operation_type(operation: *const git_rebase_operation) c_uint;
+extern "C" fn git2_shim_rebase_operation_id(operation: *const git_rebase_operation) *const git_oid;
+extern "C" fn git2_shim_rebase_operation_exec(operation: *const git_rebase_operation) ?[*:0]const u8;
+extern "C" fn git2_shim_rebase_orig_head_name(rebase: *git_rebase) ?[*:0]const u8;
+extern "C" fn git2_shim_rebase_orig_head_id(rebase: *git_rebase) *const git_oid;
+extern "C" fn git2_shim_rebase_onto_name(rebase: *git_rebase) [*:0]const u8;
+extern "C" fn git2_shim_rebase_onto_id(rebase: *git_rebase) *const git_oid;
+
+extern "C" fn git2_shim_blame_options_new() ?*git2_shim_blame_options;
+extern "C" fn git2_shim_blame_options_free(opts: *git2_shim_blame_options) void;
+extern "C" fn git2_shim_blame_options_set_flags(opts: *git2_shim_blame_options, flags: u32) void;
+extern "C" fn git2_shim_blame_options_set_min_match_characters(opts: *git2_shim_blame_options, count: u16) void;
+extern "C" fn git2_shim_blame_options_set_line_range(opts: *git2_shim_blame_options, min_line: usize, max_line: usize) void;
+extern "C" fn git2_shim_blame_options_set_newest_commit(opts: *git2_shim_blame_options, id: ?*const git_oid) void;
+extern "C" fn git2_shim_blame_options_set_oldest_commit(opts: *git2_shim_blame_options, id: ?*const git_oid) void;
+extern "C" fn git2_shim_blame_file(out: *?*git_blame, repo: *git_repository, path: [*:0]const u8, opts: ?*git2_shim_blame_options) c_int;
+extern "C" fn git2_shim_blame_buffer(out: *?*git_blame, reference: *git_blame, buffer: [*]const u8, buffer_len: usize) c_int;
+extern "C" fn git2_shim_blame_free(blame: *git_blame) void;
+extern "C" fn git2_shim_blame_get_hunk_count(blame: *git_blame) u32;
+extern "C" fn git2_shim_blame_get_hunk_byindex(blame: *git_blame, index: u32) ?*const git_blame_hunk;
+extern "C" fn git2_shim_blame_get_hunk_byline(blame: *git_blame, lineno: usize) ?*const git_blame_hunk;
+extern "C" fn git2_shim_blame_hunk_lines(hunk: *const git_blame_hunk) usize;
+extern "C" fn git2_shim_blame_hunk_final_commit_id(hunk: *const git_blame_hunk) *const git_oid;
+extern "C" fn git2_shim_blame_hunk_final_start_line(hunk: *const git_blame_hunk) usize;
+extern "C" fn git2_shim_blame_hunk_final_signature(hunk: *const git_blame_hunk) ?*const git_signature;
+extern "C" fn git2_shim_blame_hunk_orig_commit_id(hunk: *const git_blame_hunk) *const git_oid;
+extern "C" fn git2_shim_blame_hunk_orig_path(hunk: *const git_blame_hunk) ?[*:0]const u8;
+extern "C" fn git2_shim_blame_hunk_orig_start_line(hunk: *const git_blame_hunk) usize;
+extern "C" fn git2_shim_blame_hunk_orig_signature(hunk: *const git_blame_hunk) ?*const git_signature;
+extern "C" fn git2_shim_blame_hunk_is_boundary(hunk: *const git_blame_hunk) c_int;
+
+extern "C" fn git2_shim_revwalk_new(out: *?*git_revwalk, repo: *git_repository) c_int;
+extern "C" fn git2_shim_revwalk_free(walk: *git_revwalk) void;
+extern "C" fn git2_shim_revwalk_reset(walk: *git_revwalk) c_int;
+extern "C" fn git2_shim_revwalk_push(walk: *git_revwalk, id: *const git_oid) c_int;
+extern "C" fn git2_shim_revwalk_hide(walk: *git_revwalk, id: *const git_oid) c_int;
+extern "C" fn git2_shim_revwalk_push_range(walk: *git_revwalk, range: [*:0]const u8) c_int;
+extern "C" fn git2_shim_revwalk_push_glob(walk: *git_revwalk, glob: [*:0]const u8) c_int;
+extern "C" fn git2_shim_revwalk_push_head(walk: *git_revwalk) c_int;
+extern "C" fn git2_shim_revwalk_push_ref(walk: *git_revwalk, refname: [*:0]const u8) c_int;
+extern "C" fn git2_shim_revwalk_hide_glob(walk: *git_revwalk, glob: [*:0]const u8) c_int;
+extern "C" fn git2_shim_revwalk_hide_head(walk: *git_revwalk) c_int;
+extern "C" fn git2_shim_revwalk_hide_ref(walk: *git_revwalk, refname: [*:0]const u8) c_int;
+extern "C" fn git2_shim_revwalk_simplify_first_parent(walk: *git_revwalk) c_int;
+extern "C" fn git2_shim_revwalk_add_hide_cb(walk: *git_revwalk, callback: ?git_revwalk_hide_cb, payload: ?*anyopaque) c_int;
+extern "C" fn git2_shim_revwalk_next(out: *git_oid, walk: *git_revwalk) c_int;
+extern "C" fn git2_shim_revwalk_sorting(walk: *git_revwalk, sort_mode: c_uint) c_int;
+extern "C" fn git2_shim_object_free(object: *git_object) void;
+extern "C" fn git2_shim_object_id(object: *const git_object) *const git_oid;
+extern "C" fn git2_shim_object_type(object: *const git_object) c_int;
+extern "C" fn git2_shim_reference_name(ref: *const git_reference) [*:0]const u8;
+extern "C" fn git2_shim_revparse_single(out: *?*git_object, repo: *git_repository, spec: [*:0]const u8) c_int;
+extern "C" fn git2_shim_revparse_ext(object_out: *?*git_object, reference_out: *?*git_reference, repo: *git_repository, spec: [*:0]const u8) c_int;
+extern "C" fn git2_shim_revparse(revspec: *git_revspec, repo: *git_repository, spec: [*:0]const u8) c_int;
+extern "C" fn git2_shim_error_last_message() ?[*:0]const u8;
+extern "C" fn git2_shim_error_last_class() c_int;
+extern "C" fn git2_shim_remote_callbacks_new() ?*git2_shim_remote_callbacks;
+extern "C" fn git2_shim_remote_callbacks_free(callbacks: *git2_shim_remote_callbacks) void;
+extern "C" fn git2_shim_remote_callbacks_set_sideband_progress_cb(callbacks: *git2_shim_remote_callbacks, callback: ?git_sideband_progress_cb, payload: ?*anyopaque) void;
+extern "C" fn git2_shim_remote_callbacks_set_credentials_cb(callbacks: *git2_shim_remote_callbacks, callback: ?git_credential_acquire_cb, payload: ?*anyopaque) void;
+extern "C" fn git2_shim_remote_callbacks_set_certificate_check_cb(callbacks: *git2_shim_remote_callbacks, callback: ?git_certificate_check_cb, payload: ?*anyopaque) void;
+extern "C" fn git2_shim_remote_callbacks_set_transfer_progress_cb(callbacks: *git2_shim_remote_callbacks, callback: ?git_transfer_progress_cb, payload: ?*anyopaque) void;
+extern "C" fn git2_shim_remote_callbacks_set_update_tips_cb(callbacks: *git2_shim_remote_callbacks, callback: ?git_update_tips_cb, payload: ?*anyopaque) void;
+extern "C" fn git2_shim_remote_callbacks_set_push_update_reference_cb(callbacks: *git2_shim_remote_callbacks, callback: ?git_push_update_reference_cb, payload: ?*anyopaque) void;
+extern "C" fn git2_shim_remote_callbacks_set_push_transfer_progress_cb(callbacks: *git2_shim_remote_callbacks, callback: ?git_push_transfer_progress_cb, payload: ?*anyopaque) void;
+extern "C" fn git2_shim_remote_callbacks_set_push_negotiation_cb(callbacks: *git2_shim_remote_callbacks, callback: ?git_push_negotiation_cb, payload: ?*anyopaque) void;
+extern "C" fn git2_shim_remote_callbacks_set_repository(callbacks: *git2_shim_remote_callbacks, repo: ?*git_repository) void;
+extern "C" fn git2_shim_proxy_options_new() ?*git2_shim_proxy_options;
+extern "C" fn git2_shim_proxy_options_free(opts: *git2_shim_proxy_options) void;
+extern "C" fn git2_shim_proxy_options_set_type(opts: *git2_shim_proxy_options, kind: c_uint) void;
+extern "C" fn git2_shim_proxy_options_set_url(opts: *git2_shim_proxy_options, url: ?[*:0]const u8) void;
+extern "C" fn git2_shim_proxy_options_set_credentials_cb(opts: *git2_shim_proxy_options, callback: ?git_credential_acquire_cb, payload: ?*anyopaque) void;
+extern "C" fn git2_shim_proxy_options_set_certificate_check_cb(opts: *git2_shim_proxy_options, callback: ?git_certificate_check_cb, payload: ?*anyopaque) void;
+extern "C" fn git2_shim_fetch_options_set_proxy_options(opts: *git2_shim_fetch_options, proxy: ?*const git2_shim_proxy_options) void;
+extern "C" fn git2_shim_fetch_options_set_custom_headers(opts: *git2_shim_fetch_options, headers: ?[*]const [*:0]const u8, count: usize) void;
+extern "C" fn git2_shim_push_options_set_proxy_options(opts: *git2_shim_push_options, proxy: ?*const git2_shim_proxy_options) void;
+extern "C" fn git2_shim_push_options_set_custom_headers(opts: *git2_shim_push_options, headers: ?[*]const [*:0]const u8, count: usize) void;
+extern "C" fn git2_shim_fetch_options_new() ?*git2_shim_fetch_options;
+extern "C" fn git2_shim_fetch_options_free(opts: *git2_shim_fetch_options) void;
+extern "C" fn git2_shim_fetch_options_set_callbacks(opts: *git2_shim_fetch_options, callbacks: ?*const git2_shim_remote_callbacks) void;
+extern "C" fn git2_shim_fetch_options_set_depth(opts: *git2_shim_fetch_options, depth: c_int) void;
+extern "C" fn git2_shim_fetch_options_set_prune(opts: *git2_shim_fetch_options, prune: c_uint) void;
+extern "C" fn git2_shim_fetch_options_set_download_tags(opts: *git2_shim_fetch_options, download_tags: c_uint) void;
+extern "C" fn git2_shim_fetch_filter_supported() c_int;
+extern "C" fn git2_shim_fetch_options_set_filter(opts: *git2_shim_fetch_options, filter: ?[*:0]const u8) c_int;
+extern "C" fn git2_shim_repository_is_shallow(repo: *git_repository) c_int;
+extern "C" fn git2_shim_clone_options_new() ?*git2_shim_clone_options;
+extern "C" fn git2_shim_clone_options_free(opts: *git2_shim_clone_options) void;
+extern "C" fn git2_shim_clone_options_set_bare(opts: *git2_shim_clone_options, bare: c_int) void;
+extern "C" fn git2_shim_clone_options_set_checkout_branch(opts: *git2_shim_clone_options, branch: ?[*:0]const u8) void;
+extern "C" fn git2_shim_clone_options_set_local(opts: *git2_shim_clone_options, local: c_uint) void;
+extern "C" fn git2_shim_clone_options_set_checkout_options(opts: *git2_shim_clone_options, checkout: ?*const git2_shim_checkout_options) void;
+extern "C" fn git2_shim_clone_options_set_fetch_options(opts: *git2_shim_clone_options, fetch: ?*const git2_shim_fetch_options) void;
+extern "C" fn git2_shim_clone(out: *?*git_repository, url: [*:0]const u8, local_path: [*:0]const u8, opts: ?*git2_shim_clone_options) c_int;
+extern "C" fn git2_shim_strarray_dispose(array: *git_strarray) void;
+extern "C" fn git2_shim_remote_list(out: *git_strarray, repo: *git_repository) c_int;
+extern "C" fn git2_shim_remote_lookup(out: *?*git_remote, repo: *git_repository, name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_remote_create(out: *?*git_remote, repo: *git_repository, name: [*:0]const u8, url: [*:0]const u8) c_int;
+extern "C" fn git2_shim_remote_create_anonymous(out: *?*git_remote, repo: *git_repository, url: [*:0]const u8) c_int;
+extern "C" fn git2_shim_remote_delete(repo: *git_repository, name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_remote_rename(problems: *git_strarray, repo: *git_repository, name: [*:0]const u8, new_name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_remote_free(remote: *git_remote) void;
+extern "C" fn git2_shim_remote_name(remote: *const git_remote) ?[*:0]const u8;
+extern "C" fn git2_shim_remote_url(remote: *const git_remote) ?[*:0]const u8;
+extern "C" fn git2_shim_remote_pushurl(remote: *const git_remote) ?[*:0]const u8;
+extern "C" fn git2_shim_remote_set_url(repo: *git_repository, name: [*:0]const u8, url: [*:0]const u8) c_int;
+extern "C" fn git2_shim_remote_set_pushurl(repo: *git_repository, name: [*:0]const u8, url: ?[*:0]const u8) c_int;
+extern "C" fn git2_shim_remote_autotag(remote: *const git_remote) c_uint;
+extern "C" fn git2_shim_remote_set_autotag(repo: *git_repository, name: [*:0]const u8, value: c_uint) c_int;
+extern "C" fn git2_shim_fetch_options_set_follow_redirects(opts: *git2_shim_fetch_options, follow_redirects: c_uint) void;
+extern "C" fn git2_shim_push_options_set_follow_redirects(opts: *git2_shim_push_options, follow_redirects: c_uint) void;
+extern "C" fn git2_shim_remote_fetch(
+    remote: *git_remote,
+    refspecs: ?[*]const [*:0]const u8,
+    refspec_count: usize,
+    opts: ?*git2_shim_fetch_options,
+    reflog_message: ?[*:0]const u8,
+) c_int;
+extern "C" fn git2_shim_push_options_new() ?*git2_shim_push_options;
+extern "C" fn git2_shim_push_options_free(opts: *git2_shim_push_options) void;
+extern "C" fn git2_shim_push_options_set_callbacks(opts: *git2_shim_push_options, callbacks: ?*const git2_shim_remote_callbacks) void;
+extern "C" fn git2_shim_push_options_set_pb_parallelism(opts: *git2_shim_push_options, threads: c_uint) void;
+extern "C" fn git2_shim_push_options_set_remote_push_options(opts: *git2_shim_push_options, options: ?[*]const [*:0]const u8, count: usize) void;
+extern "C" fn git2_shim_remote_push(
+    remote: *git_remote,
+    refspecs: ?[*]const [*:0]const u8,
+    refspec_count: usize,
+    opts: ?*git2_shim_push_options,
+) c_int;
+extern "C" fn git2_shim_credential_free(cred: *git_credential) void;
+extern "C" fn git2_shim_credential_username_new(out: *?*git_credential, username: [*:0]const u8) c_int;
+extern "C" fn git2_shim_credential_userpass_plaintext_new(out: *?*git_credential, username: [*:0]const u8, password: [*:0]const u8) c_int;
+extern "C" fn git2_shim_credential_ssh_key_new(
+    out: *?*git_credential,
+    username: [*:0]const u8,
+    publickey: ?[*:0]const u8,
+    privatekey: [*:0]const u8,
+    passphrase: ?[*:0]const u8,
+) c_int;
+extern "C" fn git2_shim_credential_ssh_key_memory_new(
+    out: *?*git_credential,
+    username: [*:0]const u8,
+    publickey: ?[*:0]const u8,
+    privatekey: [*:0]const u8,
+    passphrase: ?[*:0]const u8,
+) c_int;
+extern "C" fn git2_shim_credential_ssh_key_from_agent(out: *?*git_credential, username: [*:0]const u8) c_int;
+extern "C" fn git2_shim_credential_default_new(out: *?*git_credential) c_int;
+extern "C" fn git2_shim_credential_helper_fill(out: *?*git_credential, repo: ?*git_repository, url: [*:0]const u8, username_from_url: ?[*:0]const u8) c_int;
+extern "C" fn git2_shim_cert_type(cert: *const git_cert) c_int;
+extern "C" fn git2_shim_cert_x509_data(cert: *const git_cert, len_out: *usize) ?[*]const u8;
+extern "C" fn git2_shim_cert_hostkey_hash(cert: *const git_cert, kind: c_uint) ?[*]const u8;
+extern "C" fn git2_shim_cert_hostkey_raw(cert: *const git_cert, len_out: *usize, type_out: *c_uint) ?[*]const u8;
+extern "C" fn git2_shim_connect_options_new() ?*git2_shim_connect_options;
+extern "C" fn git2_shim_connect_options_free(opts: *git2_shim_connect_options) void;
+extern "C" fn git2_shim_connect_options_set_callbacks(opts: *git2_shim_connect_options, callbacks: ?*const git2_shim_remote_callbacks) void;
+extern "C" fn git2_shim_connect_options_set_proxy_options(opts: *git2_shim_connect_options, proxy: ?*const git2_shim_proxy_options) void;
+extern "C" fn git2_shim_connect_options_set_custom_headers(opts: *git2_shim_connect_options, headers: ?[*]const [*:0]const u8, count: usize) void;
+extern "C" fn git2_shim_connect_options_set_follow_redirects(opts: *git2_shim_connect_options, follow_redirects: c_uint) void;
+extern "C" fn git2_shim_remote_connect(remote: *git_remote, direction: c_int, opts: ?*git2_shim_connect_options) c_int;
+extern "C" fn git2_shim_remote_connected(remote: *const git_remote) c_int;
+extern "C" fn git2_shim_remote_disconnect(remote: *git_remote) c_int;
+extern "C" fn git2_shim_remote_prune(remote: *git_remote, callbacks: ?*git2_shim_remote_callbacks) c_int;
+extern "C" fn git2_shim_remote_prune_refs(remote: *const git_remote) c_int;
+extern "C" fn git2_shim_remote_ls(out: *?[*]const *const git_remote_head, size: *usize, remote: *git_remote) c_int;
+extern "C" fn git2_shim_remote_head_name(head: *const git_remote_head) [*:0]const u8;
+extern "C" fn git2_shim_remote_head_oid(head: *const git_remote_head) *const git_oid;
+extern "C" fn git2_shim_remote_head_symref_target(head: *const git_remote_head) ?[*:0]const u8;
+extern "C" fn git2_shim_buf_dispose(buf: *git_buf) void;
+extern "C" fn git2_shim_refspec_parse(out: *?*git_refspec, input: [*:0]const u8, is_fetch: c_int) c_int;
+extern "C" fn git2_shim_refspec_free(refspec: *git_refspec) void;
+extern "C" fn git2_shim_refspec_src(refspec: *const git_refspec) [*:0]const u8;
+extern "C" fn git2_shim_refspec_dst(refspec: *const git_refspec) [*:0]const u8;
+extern "C" fn git2_shim_refspec_string(refspec: *const git_refspec) [*:0]const u8;
+extern "C" fn git2_shim_refspec_force(refspec: *const git_refspec) c_int;
+extern "C" fn git2_shim_refspec_direction(refspec: *const git_refspec) c_int;
+extern "C" fn git2_shim_refspec_src_matches(refspec: *const git_refspec, refname: [*:0]const u8) c_int;
+extern "C" fn git2_shim_refspec_dst_matches(refspec: *const git_refspec, refname: [*:0]const u8) c_int;
+extern "C" fn git2_shim_refspec_transform(out: *git_buf, refspec: *const git_refspec, name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_refspec_rtransform(out: *git_buf, refspec: *const git_refspec, name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_transport_register(prefix: [*:0]const u8, callback: git_transport_cb, param: ?*anyopaque) c_int;
+extern "C" fn git2_shim_transport_unregister(prefix: [*:0]const u8) c_int;
+extern "C" fn git2_shim_smart_subtransport_new(callback: git_smart_subtransport_cb, rpc: c_uint, param: ?*anyopaque) ?*git2_shim_smart_subtransport;
+extern "C" fn git2_shim_smart_subtransport_free(subtransport: *git2_shim_smart_subtransport) void;
+extern "C" fn git2_shim_smart_subtransport_register(prefix: [*:0]const u8, subtransport: *git2_shim_smart_subtransport) c_int;
+extern "C" fn git2_shim_transport_smart_credentials(out: *?*git_credential, transport: *Transport, user: ?[*:0]const u8, methods: c_uint) c_int;
+extern "C" fn git2_shim_transport_smart_certificate_check(transport: *Transport, cert: *git_cert, valid: c_int, hostname: [*:0]const u8) c_int;
+const git_fetchhead_cb = *const fn (
+    ref_name: ?[*:0]const u8,
+    remote_url: ?[*:0]const u8,
+    oid: *const git_oid,
+    is_merge: c_uint,
+    payload: ?*anyopaque,
+) callconv(.C) c_int;
+extern "C" fn git2_shim_repository_fetchhead_foreach(repo: *git_repository, callback: git_fetchhead_cb, payload: ?*anyopaque) c_int;
+extern "C" fn git2_shim_remote_download(
+    remote: *git_remote,
+    refspecs: ?[*]const [*:0]const u8,
+    refspec_count: usize,
+    opts: ?*git2_shim_fetch_options,
+) c_int;
+extern "C" fn git2_shim_remote_update_tips(
+    remote: *git_remote,
+    callbacks: ?*git2_shim_remote_callbacks,
+    update_flags: c_uint,
+    download_tags: c_uint,
+    reflog_message: ?[*:0]const u8,
+) c_int;
+extern "C" fn git2_shim_maintenance_options_new() ?*git2_shim_maintenance_options;
+extern "C" fn git2_shim_maintenance_options_free(opts: *git2_shim_maintenance_options) void;
+extern "C" fn git2_shim_maintenance_options_set_repack(opts: *git2_shim_maintenance_options, repack: c_int) void;
+extern "C" fn git2_shim_maintenance_options_set_prune(opts: *git2_shim_maintenance_options, prune: c_int) void;
+extern "C" fn git2_shim_maintenance_options_set_prune_expire(opts: *git2_shim_maintenance_options, expire: i64) void;
+extern "C" fn git2_shim_maintenance_run(repo: *git_repository, opts: ?*const git2_shim_maintenance_options, stats: ?*MaintenanceStats) c_int;
+extern "C" fn git2_shim_bundle_create(
+    repo: *git_repository,
+    path: [*:0]const u8,
+    refs: [*]const [*:0]const u8,
+    ref_count: usize,
+    basis: ?[*]const [*:0]const u8,
+    basis_count: usize,
+) c_int;
+extern "C" fn git2_shim_bundle_unbundle(
+    repo: *git_repository,
+    path: [*:0]const u8,
+    refspec: ?[*:0]const u8,
+    callback: ?git_update_tips_cb,
+    payload: ?*anyopaque,
+) c_int;
+extern "C" fn git2_shim_repository_config(out: *?*git_config, repo: *git_repository) c_int;
+extern "C" fn git2_shim_config_free(cfg: *git_config) void;
+extern "C" fn git2_shim_config_get_string_buf(out: *git_buf, cfg: *const git_config, name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_config_set_string(cfg: *git_config, name: [*:0]const u8, value: [*:0]const u8) c_int;
+extern "C" fn git2_shim_config_get_bool(out: *c_int, cfg: *const git_config, name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_config_get_int32(out: *i32, cfg: *const git_config, name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_config_get_int64(out: *i64, cfg: *const git_config, name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_config_get_path(out: *git_buf, cfg: *const git_config, name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_config_set_bool(cfg: *git_config, name: [*:0]const u8, value: c_int) c_int;
+extern "C" fn git2_shim_config_set_int32(cfg: *git_config, name: [*:0]const u8, value: i32) c_int;
+extern "C" fn git2_shim_config_set_int64(cfg: *git_config, name: [*:0]const u8, value: i64) c_int;
+pub const git_config_entry_cb = *const fn (name: [*:0]const u8, value: ?[*:0]const u8, level: c_int, payload: ?*anyopaque) callconv(.C) c_int;
+extern "C" fn git2_shim_config_foreach_match(cfg: *const git_config, regexp: ?[*:0]const u8, callback: git_config_entry_cb, payload: ?*anyopaque) c_int;
+extern "C" fn git2_shim_config_iterator_new(out: *?*git_config_iterator, cfg: *const git_config, regexp: ?[*:0]const u8) c_int;
+extern "C" fn git2_shim_config_next(name: *[*:0]const u8, value: *?[*:0]const u8, level: *c_int, iter: *git_config_iterator) c_int;
+extern "C" fn git2_shim_config_iterator_free(iter: *git_config_iterator) void;
+extern "C" fn git2_shim_config_open_level(out: *?*git_config, parent: *const git_config, level: c_int) c_int;
+extern "C" fn git2_shim_config_open_default(out: *?*git_config) c_int;
+extern "C" fn git2_shim_config_delete_entry(cfg: *git_config, name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_config_delete_multivar(cfg: *git_config, name: [*:0]const u8, regexp: [*:0]const u8) c_int;
+extern "C" fn git2_shim_config_lock(tx: *?*git_transaction, cfg: *git_config) c_int;
+extern "C" fn git2_shim_transaction_commit(tx: *git_transaction) c_int;
+extern "C" fn git2_shim_transaction_free(tx: *git_transaction) void;
+extern "C" fn git2_shim_config_parse_bool(out: *c_int, value: [*:0]const u8) c_int;
+extern "C" fn git2_shim_config_parse_int32(out: *i32, value: [*:0]const u8) c_int;
+extern "C" fn git2_shim_config_parse_int64(out: *i64, value: [*:0]const u8) c_int;
+extern "C" fn git2_shim_config_parse_path(out: *git_buf, value: [*:0]const u8) c_int;
+extern "C" fn git2_shim_config_new(out: *?*git_config) c_int;
+extern "C" fn git2_shim_config_add_file_ondisk(cfg: *git_config, path: [*:0]const u8, level: c_int, repo: ?*const git_repository, force: c_int) c_int;
+extern "C" fn git2_shim_config_snapshot(out: *?*git_config, config: *git_config) c_int;
+extern "C" fn git2_shim_repository_config_snapshot(out: *?*git_config, repo: *git_repository) c_int;
+extern "C" fn git2_shim_config_get_multivar_foreach(cfg: *const git_config, name: [*:0]const u8, regexp: ?[*:0]const u8, callback: git_config_entry_cb, payload: ?*anyopaque) c_int;
+extern "C" fn git2_shim_config_set_multivar(cfg: *git_config, name: [*:0]const u8, regexp: [*:0]const u8, value: [*:0]const u8) c_int;
+pub const git_submodule_cb = *const fn (submodule: *git_submodule, name: [*:0]const u8, payload: ?*anyopaque) callconv(.C) c_int;
+extern "C" fn git2_shim_submodule_lookup(out: *?*git_submodule, repo: *git_repository, name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_submodule_free(submodule: *git_submodule) void;
+extern "C" fn git2_shim_submodule_foreach(repo: *git_repository, callback: git_submodule_cb, payload: ?*anyopaque) c_int;
+extern "C" fn git2_shim_submodule_status(status: *c_uint, repo: *git_repository, name: [*:0]const u8, ignore: c_int) c_int;
+extern "C" fn git2_shim_submodule_location(location_status: *c_uint, submodule: *git_submodule) c_int;
+extern "C" fn git2_shim_submodule_init(submodule: *git_submodule, overwrite: c_int) c_int;
+extern "C" fn git2_shim_submodule_update_options_new() ?*git2_shim_submodule_update_options;
+extern "C" fn git2_shim_submodule_update_options_free(opts: *git2_shim_submodule_update_options) void;
+extern "C" fn git2_shim_submodule_update_options_set_checkout_options(opts: *git2_shim_submodule_update_options, checkout: ?*const git2_shim_checkout_options) void;
+extern "C" fn git2_shim_submodule_update_options_set_fetch_options(opts: *git2_shim_submodule_update_options, fetch: ?*const git2_shim_fetch_options) void;
+extern "C" fn git2_shim_submodule_update_options_set_allow_fetch(opts: *git2_shim_submodule_update_options, allow_fetch: c_int) void;
+extern "C" fn git2_shim_submodule_update(submodule: *git_submodule, init: c_int, opts: ?*git2_shim_submodule_update_options) c_int;
+extern "C" fn git2_shim_submodule_add_setup(out: *?*git_submodule, repo: *git_repository, url: [*:0]const u8, path: [*:0]const u8, use_gitlink: c_int) c_int;
+extern "C" fn git2_shim_submodule_clone(out: *?*git_repository, submodule: *git_submodule, opts: ?*git2_shim_submodule_update_options) c_int;
+extern "C" fn git2_shim_submodule_add_finalize(submodule: *git_submodule) c_int;
+extern "C" fn git2_shim_submodule_add_to_index(submodule: *git_submodule, write_index: c_int) c_int;
+extern "C" fn git2_shim_submodule_name(submodule: *git_submodule) [*:0]const u8;
+extern "C" fn git2_shim_submodule_path(submodule: *git_submodule) [*:0]const u8;
+extern "C" fn git2_shim_submodule_url(submodule: *git_submodule) ?[*:0]const u8;
+extern "C" fn git2_shim_submodule_branch(submodule: *git_submodule) ?[*:0]const u8;
+extern "C" fn git2_shim_submodule_head_id(submodule: *git_submodule) ?*const git_oid;
+extern "C" fn git2_shim_submodule_index_id(submodule: *git_submodule) ?*const git_oid;
+extern "C" fn git2_shim_submodule_wd_id(submodule: *git_submodule) ?*const git_oid;
+extern "C" fn git2_shim_submodule_set_url(repo: *git_repository, name: [*:0]const u8, url: [*:0]const u8) c_int;
+extern "C" fn git2_shim_submodule_set_branch(repo: *git_repository, name: [*:0]const u8, branch: ?[*:0]const u8) c_int;
+extern "C" fn git2_shim_submodule_set_update(repo: *git_repository, name: [*:0]const u8, update: c_int) c_int;
+extern "C" fn git2_shim_submodule_set_ignore(repo: *git_repository, name: [*:0]const u8, ignore: c_int) c_int;
+extern "C" fn git2_shim_submodule_sync(submodule: *git_submodule) c_int;
+extern "C" fn git2_shim_submodule_open(repo: *?*git_repository, submodule: *git_submodule) c_int;
+extern "C" fn git2_shim_submodule_repo_init(out: *?*git_repository, submodule: *const git_submodule, use_gitlink: c_int) c_int;
+pub const git2_shim_submodule_update_cb = *const fn (path: [*:0]const u8, depth: c_uint, err: c_int, message: ?[*:0]const u8, payload: ?*anyopaque) callconv(.C) c_int;
+extern "C" fn git2_shim_submodule_update_recursive(repo: *git_repository, init: c_int, max_depth: c_uint, opts: ?*git2_shim_submodule_update_options, callback: ?git2_shim_submodule_update_cb, payload: ?*anyopaque) c_int;
+extern "C" fn git2_shim_worktree_list(out: *git_strarray, repo: *git_repository) c_int;
+extern "C" fn git2_shim_worktree_lookup(out: *?*git_worktree, repo: *git_repository, name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_worktree_open_from_repository(out: *?*git_worktree, repo: *git_repository) c_int;
+extern "C" fn git2_shim_worktree_free(worktree: *git_worktree) void;
+extern "C" fn git2_shim_worktree_add_options_new() ?*git2_shim_worktree_add_options;
+extern "C" fn git2_shim_worktree_add_options_free(opts: *git2_shim_worktree_add_options) void;
+extern "C" fn git2_shim_worktree_add_options_set_lock(opts: *git2_shim_worktree_add_options, lock: c_int) void;
+extern "C" fn git2_shim_worktree_add_options_set_checkout_existing(opts: *git2_shim_worktree_add_options, checkout_existing: c_int) void;
+extern "C" fn git2_shim_worktree_add_options_set_branch(opts: *git2_shim_worktree_add_options, branch: ?[*:0]const u8) void;
+extern "C" fn git2_shim_worktree_add_options_set_checkout_options(opts: *git2_shim_worktree_add_options, checkout: ?*const git2_shim_checkout_options) void;
+extern "C" fn git2_shim_worktree_add(out: *?*git_worktree, repo: *git_repository, name: [*:0]const u8, path: [*:0]const u8, opts: ?*git2_shim_worktree_add_options) c_int;
+extern "C" fn git2_shim_worktree_name(worktree: *const git_worktree) [*:0]const u8;
+extern "C" fn git2_shim_worktree_path(worktree: *const git_worktree) [*:0]const u8;
+extern "C" fn git2_shim_worktree_validate(worktree: *const git_worktree) c_int;
+extern "C" fn git2_shim_worktree_lock(worktree: *git_worktree, reason: ?[*:0]const u8) c_int;
+extern "C" fn git2_shim_worktree_unlock(worktree: *git_worktree) c_int;
+extern "C" fn git2_shim_worktree_is_locked(reason: ?*git_buf, worktree: *const git_worktree) c_int;
+extern "C" fn git2_shim_worktree_is_prunable(worktree: *git_worktree, flags: u32) c_int;
+extern "C" fn git2_shim_worktree_prune(worktree: *git_worktree, flags: u32) c_int;
+extern "C" fn git2_shim_repository_open_from_worktree(out: *?*git_repository, worktree: *git_worktree) c_int;
+extern "C" fn git2_shim_repository_is_worktree(repo: *const git_repository) c_int;
+extern "C" fn git2_shim_repository_path(repo: *const git_repository) [*:0]const u8;
+extern "C" fn git2_shim_repository_commondir(repo: *const git_repository) [*:0]const u8;
+extern "C" fn git2_shim_repository_init(out: *?*git_repository, path: [*:0]const u8, is_bare: c_uint) c_int;
+extern "C" fn git2_shim_repository_init_options_new() ?*git2_shim_repository_init_options;
+extern "C" fn git2_shim_repository_init_options_free(opts: *git2_shim_repository_init_options) void;
+extern "C" fn git2_shim_repository_init_options_set_flags(opts: *git2_shim_repository_init_options, flags: u32) void;
+extern "C" fn git2_shim_repository_init_options_set_mode(opts: *git2_shim_repository_init_options, mode: u32) void;
+extern "C" fn git2_shim_repository_init_options_set_workdir_path(opts: *git2_shim_repository_init_options, path: ?[*:0]const u8) void;
+extern "C" fn git2_shim_repository_init_options_set_description(opts: *git2_shim_repository_init_options, description: ?[*:0]const u8) void;
+extern "C" fn git2_shim_repository_init_options_set_template_path(opts: *git2_shim_repository_init_options, path: ?[*:0]const u8) void;
+extern "C" fn git2_shim_repository_init_options_set_initial_head(opts: *git2_shim_repository_init_options, head: ?[*:0]const u8) void;
+extern "C" fn git2_shim_repository_init_options_set_origin_url(opts: *git2_shim_repository_init_options, url: ?[*:0]const u8) void;
+extern "C" fn git2_shim_repository_init_ext(out: *?*git_repository, repo_path: [*:0]const u8, opts: ?*git2_shim_repository_init_options) c_int;
+extern "C" fn git2_shim_repository_discover(out: *git_buf, start_path: [*:0]const u8, across_fs: c_int, ceiling_dirs: ?[*:0]const u8) c_int;
+extern "C" fn git2_shim_repository_open_ext(out: ?*?*git_repository, path: ?[*:0]const u8, flags: c_uint, ceiling_dirs: ?[*:0]const u8) c_int;
+extern "C" fn git2_shim_repository_open_bare(out: *?*git_repository, bare_path: [*:0]const u8) c_int;
+extern "C" fn git2_shim_repository_open_with_workdir(out: *?*git_repository, gitdir: [*:0]const u8, workdir: [*:0]const u8) c_int;
+extern "C" fn git2_shim_repository_item_path(out: *git_buf, repo: *const git_repository, item: c_int) c_int;
+extern "C" fn git2_shim_repository_message(out: *git_buf, repo: *git_repository) c_int;
+extern "C" fn git2_shim_repository_message_remove(repo: *git_repository) c_int;
+extern "C" fn git2_shim_repository_set_workdir(repo: *git_repository, workdir: [*:0]const u8, update_gitlink: c_int) c_int;
+extern "C" fn git2_shim_repository_index(out: *?*git_index, repo: *git_repository) c_int;
+extern "C" fn git2_shim_index_read(index: *git_index, force: c_int) c_int;
+extern "C" fn git2_shim_index_write(index: *git_index) c_int;
+extern "C" fn git2_shim_repository_odb(out: *?*git_odb, repo: *git_repository) c_int;
+extern "C" fn git2_shim_odb_free(odb: *git_odb) void;
+extern "C" fn git2_shim_odb_exists(odb: *git_odb, id: *const git_oid) c_int;
+extern "C" fn git2_shim_odb_refresh(odb: *git_odb) c_int;
+extern "C" fn git2_shim_odb_new(out: *?*git_odb) c_int;
+extern "C" fn git2_shim_odb_open(out: *?*git_odb, objects_dir: [*:0]const u8) c_int;
+extern "C" fn git2_shim_odb_add_disk_alternate(odb: *git_odb, path: [*:0]const u8) c_int;
+extern "C" fn git2_shim_odb_add_pack(odb: *git_odb, index_path: [*:0]const u8) c_int;
+extern "C" fn git2_shim_repository_wrap_odb(out: *?*git_repository, odb: *git_odb) c_int;
+extern "C" fn git2_shim_repository_refdb(out: *?*git_refdb, repo: *git_repository) c_int;
+extern "C" fn git2_shim_refdb_free(refdb: *git_refdb) void;
+extern "C" fn git2_shim_refdb_compress(refdb: *git_refdb) c_int;
+extern "C" fn git2_shim_repository_hashfile(out: *git_oid, repo: *git_repository, path: [*:0]const u8, kind: c_int, as_path: ?[*:0]const u8) c_int;
+extern "C" fn git2_shim_ignore_add_rule(repo: *git_repository, rules: [*:0]const u8) c_int;
+extern "C" fn git2_shim_ignore_clear_internal_rules(repo: *git_repository) c_int;
+extern "C" fn git2_shim_ignore_path_is_ignored(ignored: *c_int, repo: *git_repository, path: [*:0]const u8) c_int;
+extern "C" fn git2_shim_attr_value(value: ?[*:0]const u8) c_int;
+extern "C" fn git2_shim_attr_get(value_out: *?[*:0]const u8, repo: *git_repository, flags: u32, path: [*:0]const u8, name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_attr_get_many(values_out: [*]?[*:0]const u8, repo: *git_repository, flags: u32, path: [*:0]const u8, num_attr: usize, names: [*]const [*:0]const u8) c_int;
+pub const git_attr_foreach_cb = *const fn (name: [*:0]const u8, value: ?[*:0]const u8, payload: ?*anyopaque) callconv(.C) c_int;
+extern "C" fn git2_shim_attr_foreach(repo: *git_repository, flags: u32, path: [*:0]const u8, callback: git_attr_foreach_cb, payload: ?*anyopaque) c_int;
+extern "C" fn git2_shim_attr_add_macro(repo: *git_repository, name: [*:0]const u8, values: [*:0]const u8) c_int;
+extern "C" fn git2_shim_attr_cache_flush(repo: *git_repository) c_int;
+pub const git2_shim_shallow_cb = *const fn (id: *const git_oid, payload: ?*anyopaque) callconv(.C) c_int;
+extern "C" fn git2_shim_repository_shallow_foreach(repo: *git_repository, callback: git2_shim_shallow_cb, payload: ?*anyopaque) c_int;
+extern "C" fn git2_shim_repository_state(repo: *git_repository) c_int;
+extern "C" fn git2_shim_repository_cherrypick_head(out: *git_oid, repo: *git_repository) c_int;
+extern "C" fn git2_shim_repository_revert_head(out: *git_oid, repo: *git_repository) c_int;
+pub const git2_shim_mergehead_cb = *const fn (id: *const git_oid, payload: ?*anyopaque) callconv(.C) c_int;
+extern "C" fn git2_shim_repository_mergehead_foreach(repo: *git_repository, callback: git2_shim_mergehead_cb, payload: ?*anyopaque) c_int;
+extern "C" fn git2_shim_checkout_branch(repo: *git_repository, name: [*:0]const u8, opts: ?*git2_shim_checkout_options) c_int;
+extern "C" fn git2_shim_filter_sink_write(sink: *FilterSink, data: [*]const u8, len: usize) void;
+extern "C" fn git2_shim_filter_new(attributes: ?[*:0]const u8, check: ?git_filter_check_cb, apply: git_filter_apply_cb, payload: ?*anyopaque) ?*git2_shim_filter;
+extern "C" fn git2_shim_filter_free(filter: *git2_shim_filter) void;
+extern "C" fn git2_shim_filter_register(name: [*:0]const u8, filter: *git2_shim_filter, priority: c_int) c_int;
+extern "C" fn git2_shim_filter_unregister(name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_filter_source_path(src: *const FilterSource) [*:0]const u8;
+extern "C" fn git2_shim_filter_source_mode(src: *const FilterSource) c_uint;
+extern "C" fn git2_shim_filter_source_id(src: *const FilterSource) ?*const git_oid;
+extern "C" fn git2_shim_filter_source_filemode(src: *const FilterSource) u16;
+extern "C" fn git2_shim_filter_source_repo(src: *const FilterSource) *git_repository;
+extern "C" fn git2_shim_blob_lookup(out: *?*git_blob, repo: *git_repository, id: *const git_oid) c_int;
+extern "C" fn git2_shim_blob_free(blob: *git_blob) void;
+extern "C" fn git2_shim_filter_list_load(out: *?*git_filter_list, repo: *git_repository, blob: ?*git_blob, path: [*:0]const u8, mode: c_uint, flags: u32) c_int;
+extern "C" fn git2_shim_filter_list_contains(filters: ?*git_filter_list, name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_filter_list_apply_to_buffer(out: *git_buf, filters: ?*git_filter_list, input: [*]const u8, len: usize) c_int;
+extern "C" fn git2_shim_filter_list_apply_to_blob(out: *git_buf, filters: ?*git_filter_list, blob: *git_blob) c_int;
+extern "C" fn git2_shim_filter_list_free(filters: ?*git_filter_list) void;
+extern "C" fn git2_shim_object_type2string(kind: c_int) [*:0]const u8;
+extern "C" fn git2_shim_object_string2type(name: [*:0]const u8) c_int;
+extern "C" fn git2_shim_object_typeisloose(kind: c_int) c_int;
+extern "C" fn git2_shim_fsck_options_new() ?*git2_shim_fsck_options;
+extern "C" fn git2_shim_fsck_options_free(opts: *git2_shim_fsck_options) void;
+extern "C" fn git2_shim_fsck_options_set_connectivity_only(opts: *git2_shim_fsck_options, enabled: c_int) void;
+extern "C" fn git2_shim_fsck_options_set_unreachable(opts: *git2_shim_fsck_options, enabled: c_int) void;
+extern "C" fn git2_shim_fsck_options_set_reflogs(opts: *git2_shim_fsck_options, enabled: c_int) void;
+pub const git2_shim_fsck_cb = *const fn (problem: c_int, id: *const git_oid, kind: c_int, referrer: ?[*:0]const u8, detail: ?[*:0]const u8, payload: ?*anyopaque) callconv(.C) c_int;
+extern "C" fn git2_shim_fsck(repo: *git_repository, opts: ?*const git2_shim_fsck_options, callback: ?git2_shim_fsck_cb, payload: ?*anyopaque) c_int;
+extern "C" fn git2_shim_commit_lookup(out: *?*git_commit, repo: *git_repository, id: *const git_oid) c_int;
+extern "C" fn git2_shim_commit_free(commit: *git_commit) void;
+extern "C" fn git2_shim_commit_create(
+    id: *git_oid,
+    repo: *git_repository,
+    update_ref: ?[*:0]const u8,
+    author: *const git_signature,
+    committer: *const git_signature,
+    message: [*:0]const u8,
+    tree: *const git_tree,
+    parent_count: usize,
+    parents: [*]const *const git_commit,
+) c_int;
+
+/// Sink a signing callback writes its signature into
+pub const SignatureSink = opaque {
+    /// Store the signature for the commit being created. `field` names the
+    /// commit header it goes in; null means "gpgsig" (GPG and SSH alike).
+    pub fn set(self: *SignatureSink, signature: [*:0]const u8, field: ?[*:0]const u8) void {
+        git2_shim_signature_sink_set(self, signature, field);
+    }
+};
+
+/// Return non-zero to hide `commit_id` and all of its ancestors
+pub const git_revwalk_hide_cb = *const fn (commit_id: *const git_oid, payload: ?*anyopaque) callconv(.C) c_int;
+
+/// Server-side message such as "Enumerating objects..." or hook output;
+/// `text[0..len]` is not NUL-terminated and may end in '\r' to redraw
+/// the line. Negative cancels the operation.
+pub const git_sideband_progress_cb = *const fn (text: [*]const u8, len: c_int, payload: ?*anyopaque) callconv(.C) c_int;
+
+/// Authentication request from a remote. Store a credential of one of the
+/// `allowed_types` (`CredentialType` bits) in `out` using a `Credential`
+/// constructor and return 0; return positive if none is available,
+/// `Credential.ask_git` to use git's credential helpers, or negative to
+/// abort. May be called again if the credential is rejected.
+pub const git_credential_acquire_cb = *const fn (
+    out: *?*git_credential,
+    url: [*:0]const u8,
+    username_from_url: ?[*:0]const u8,
+    allowed_types: c_uint,
+    payload: ?*anyopaque,
+) callconv(.C) c_int;
+
+/// Verify the server's identity; decode `cert` with `Certificate.decode`.
+/// `valid` is libgit2's own TLS verdict (always false for SSH host keys).
+/// Return 0 to accept, negative to reject, or positive to defer to `valid`.
+pub const git_certificate_check_cb = *const fn (cert: *git_cert, valid: c_int, host: [*:0]const u8, payload: ?*anyopaque) callconv(.C) c_int;
+
+/// Download and indexing counters reported during fetch and clone
+pub const TransferProgress = extern struct {
+    total_objects: c_uint,
+    indexed_objects: c_uint,
+    received_objects: c_uint,
+    /// Objects already present locally, used to complete a thin pack
+    local_objects: c_uint,
+    total_deltas: c_uint,
+    indexed_deltas: c_uint,
+    received_bytes: usize,
+};
+
+/// Fetch/clone progress: objects arrive (`received_objects`), get indexed,
+/// then deltas resolve (`indexed_deltas` of `total_deltas`). Negative
+/// cancels the transfer.
+pub const git_transfer_progress_cb = *const fn (stats: *const TransferProgress, payload: ?*anyopaque) callconv(.C) c_int;
+
+/// A local ref moved from `old_id` to `new_id`; an all-zero `new_id`
+/// means it was deleted (pruned), an all-zero `old_id` that it was created
+pub const git_update_tips_cb = *const fn (refname: [*:0]const u8, old_id: *const git_oid, new_id: *const git_oid, payload: ?*anyopaque) callconv(.C) c_int;
+
+/// Server verdict on one pushed ref: `status` is null if it was updated,
+/// otherwise the rejection reason. Non-zero aborts the push.
+pub const git_push_update_reference_cb = *const fn (refname: [*:0]const u8, status: ?[*:0]const u8, payload: ?*anyopaque) callconv(.C) c_int;
+
+/// Push upload progress: objects sent of `total`, and bytes sent
+pub const git_push_transfer_progress_cb = *const fn (current: c_uint, total: c_uint, bytes: usize, payload: ?*anyopaque) callconv(.C) c_int;
+
+/// One ref update a push proposes; an all-zero `src` deletes `dst_refname`
+pub const git_push_update = extern struct {
+    src_refname: [*:0]const u8,
+    dst_refname: [*:0]const u8,
+    /// Local commit being pushed
+    src: git_oid,
+    /// Commit the remote ref currently points at
+    dst: git_oid,
+};
+
+/// Everything a push is about to update, before the pack is sent;
+/// negative aborts the push, e.g. to veto an unexpected force-push
+pub const git_push_negotiation_cb = *const fn (updates: [*]const *const git_push_update, len: usize, payload: ?*anyopaque) callconv(.C) c_int;
+
+/// Sign the commit buffer and store the result with `SignatureSink.set`.
+/// Leaving the sink empty creates the commit unsigned; non-zero aborts.
+pub const git_commit_signing_cb = *const fn (
+    sink: *SignatureSink,
+    commit_content: [*]const u8,
+    content_len: usize,
+    payload: ?*anyopaque,
+) callconv(.C) c_int;
+
+extern "C" fn git2_shim_signature_sink_set(sink: *SignatureSink, signature: [*:0]const u8, field: ?[*:0]const u8) void;
+extern "C" fn git2_shim_commit_create_signed(
+    id: *git_oid,
+    repo: *git_repository,
+    update_ref: ?[*:0]const u8,
+    author: *const git_signature,
+    committer: *const git_signature,
+    message: [*:0]const u8,
+    tree: *const git_tree,
+    parent_count: usize,
+    parents: [*]const *const git_commit,
+    sign_cb: git_commit_signing_cb,
+    payload: ?*anyopaque,
+) c_int;
+extern "C" fn git2_shim_commit_tree(tree_out: *?*git_tree, commit: *const git_commit) c_int;
+extern "C" fn git2_shim_commit_tree_id(commit: *const git_commit) *const git_oid;
+extern "C" fn git2_shim_tree_lookup(out: *?*git_tree, repo: *git_repository, id: *const git_oid) c_int;
+extern "C" fn git2_shim_tree_free(tree: *git_tree) void;
+
+extern "C" fn git2_shim_index_free(index: *git_index) void;
+extern "C" fn git2_shim_index_entrycount(index: *const git_index) usize;
+extern "C" fn git2_shim_index_has_conflicts(index: *const git_index) c_int;
+extern "C" fn git2_shim_index_conflict_get(
+    ancestor_out: *?*const git_index_entry,
+    our_out: *?*const git_index_entry,
+    their_out: *?*const git_index_entry,
+    index: *git_index,
+    path: [*:0]const u8,
+) c_int;
+extern "C" fn git2_shim_index_write_tree_to(out: *git_oid, index: *git_index, repo: *git_repository) c_int;
+
+extern "C" fn git2_shim_merge_analysis_for_ref(
+    analysis_out: *c_uint,
+    preference_out: *c_uint,
+    repo: *git_repository,
+    our_ref: *git_reference,
+    their_heads: [*]const *const git_annotated_commit,
+    their_heads_len: usize,
+) c_int;
+extern "C" fn git2_shim_stash_drop(repo: *git_repository, index: usize) c_int;
+extern "C" fn git2_shim_stash_foreach(repo: *git_repository, callback: git_stash_cb, payload: ?*anyopaque) c_int;
 
 // =============================================================================
 // Zig API
@@ -73,9 +926,154 @@ pub const Error = error{
     StatusFailed,
     CommitFailed,
     ReferenceFailed,
+    SignatureFailed,
+    StashFailed,
+    MergeFailed,
+    CherrypickFailed,
+    RevertFailed,
+    RebaseFailed,
+    /// The rebase step's changes are already upstream; skip it with `next`
+    RebaseAlreadyApplied,
+    /// The rebase step left conflicts in the index to resolve first
+    RebaseConflicts,
+    BlameFailed,
+    RevwalkFailed,
+    RevparseFailed,
+    CloneFailed,
+    RemoteFailed,
+    RefspecFailed,
+    FetchFailed,
+    PushFailed,
+    /// An unforced push would have discarded commits on the remote
+    NonFastForward,
+    SettingFailed,
+    /// The linked libgit2 lacks the requested feature
+    Unsupported,
+    TransportFailed,
+    MaintenanceFailed,
+    BundleFailed,
+    ConfigFailed,
+    SubmoduleFailed,
+    WorktreeFailed,
+    IgnoreFailed,
+    AttrFailed,
+    CheckoutFailed,
+    /// Uncommitted changes would have been overwritten; nothing changed
+    CheckoutConflict,
+    FilterFailed,
+    FsckFailed,
+    LookupFailed,
+    IndexFailed,
+    OdbFailed,
     AllocationFailed,
 };
 
+/// Details of the most recent libgit2 failure on this thread
+/// Subsystem that recorded an error (libgit2's `GIT_ERROR_*`)
+pub const ErrorClass = enum(c_int) {
+    none = 0,
+    nomemory = 1,
+    os = 2,
+    invalid = 3,
+    reference = 4,
+    zlib = 5,
+    repository = 6,
+    config = 7,
+    regex = 8,
+    odb = 9,
+    index = 10,
+    object = 11,
+    net = 12,
+    tag = 13,
+    tree = 14,
+    indexer = 15,
+    ssl = 16,
+    submodule = 17,
+    thread = 18,
+    stash = 19,
+    checkout = 20,
+    fetchhead = 21,
+    merge = 22,
+    ssh = 23,
+    filter = 24,
+    revert = 25,
+    callback = 26,
+    cherrypick = 27,
+    describe = 28,
+    rebase = 29,
+    filesystem = 30,
+    patch = 31,
+    worktree = 32,
+    sha = 33,
+    http = 34,
+    internal = 35,
+    grafts = 36,
+    _,
+};
+
+pub const LastError = struct {
+    class: ErrorClass,
+    /// Borrowed; valid until the next libgit2 call on this thread
+    message: []const u8,
+};
+
+/// Explain the last `Error` returned on this thread, e.g. the HTTP status
+/// or SSH failure behind `CloneFailed`; null if libgit2 recorded nothing
+pub fn lastError() ?LastError {
+    const message = git2_shim_error_last_message() orelse return null;
+    return LastError{
+        .class = @enumFromInt(git2_shim_error_last_class()),
+        .message = std.mem.span(message),
+    };
+}
+
+/// Bound, in milliseconds, on connecting to any remote server in this
+/// process; 0 restores the system default (about 75 seconds)
+pub fn setServerConnectTimeout(timeout_ms: c_int) Error!void {
+    if (git2_shim_set_server_connect_timeout(timeout_ms) < 0) return Error.SettingFailed;
+}
+
+pub fn serverConnectTimeout() Error!c_int {
+    var timeout_ms: c_int = 0;
+    if (git2_shim_get_server_connect_timeout(&timeout_ms) < 0) return Error.SettingFailed;
+    return timeout_ms;
+}
+
+/// Bound, in milliseconds, on waiting for a connected server to send or
+/// accept data, so a dead server fails a fetch or push instead of hanging
+/// it; 0 waits forever
+pub fn setServerTimeout(timeout_ms: c_int) Error!void {
+    if (git2_shim_set_server_timeout(timeout_ms) < 0) return Error.SettingFailed;
+}
+
+pub fn serverTimeout() Error!c_int {
+    var timeout_ms: c_int = 0;
+    if (git2_shim_get_server_timeout(&timeout_ms) < 0) return Error.SettingFailed;
+    return timeout_ms;
+}
+
+/// Process-wide directories searched for the `level` (programdata,
+/// system, xdg or global) config file and the attributes and ignore files
+/// beside it, so a sandboxed application can use its own instead of the
+/// user's. Separate directories with `std.fs.path.delimiter`; "$PATH"
+/// stands for the previous value and null restores the default. Applies
+/// to configurations opened afterwards.
+pub fn setSearchPath(allocator: std.mem.Allocator, level: ConfigLevel, path: ?[]const u8) Error!void {
+    const path_z = try dupeZOpt(allocator, path);
+    defer if (path_z) |p| allocator.free(p);
+
+    if (git2_shim_set_search_path(@intFromEnum(level), if (path_z) |p| p.ptr else null) < 0) {
+        return Error.SettingFailed;
+    }
+}
+
+/// Current search path for `level`; free with `allocator.free`
+pub fn searchPath(allocator: std.mem.Allocator, level: ConfigLevel) Error![:0]u8 {
+    var buf = git_buf{ .ptr = null, .reserved = 0, .size = 0 };
+    if (git2_shim_get_search_path(@intFromEnum(level), &buf) < 0) return Error.SettingFailed;
+    return takeBuf(allocator, &buf);
+}
+
 /// File status flags
 pub const StatusFlags = struct {
     index_new: bool = false,
@@ -91,112 +1089,5066 @@ pub const StatusFlags = struct {
     }
 };
 
-/// Repository handle
-pub const Repository = struct {
-    repo: *git_repository,
+/// Duplicate a list of strings as NUL-terminated copies for the shim.
+/// Free with `freeZList`.
+fn dupeZList(allocator: std.mem.Allocator, items: []const []const u8) Error![][*:0]const u8 {
+    const list = allocator.alloc([*:0]const u8, items.len) catch return Error.AllocationFailed;
+    var done: usize = 0;
+    errdefer {
+        for (list[0..done]) |item| allocator.free(std.mem.span(item));
+        allocator.free(list);
+    }
+    for (items) |item| {
+        list[done] = (allocator.dupeZ(u8, item) catch return Error.AllocationFailed).ptr;
+        done += 1;
+    }
+    return list;
+}
 
-    var initialized: bool = false;
+fn dupeZOpt(allocator: std.mem.Allocator, item: ?[]const u8) Error!?[:0]u8 {
+    const value = item orelse return null;
+    return allocator.dupeZ(u8, value) catch return Error.AllocationFailed;
+}
 
-    fn ensureInit() Error!void {
-        if (!initialized) {
-            if (git2_shim_init() < 0) {
-                return Error.InitFailed;
-            }
-            initialized = true;
+fn freeZList(allocator: std.mem.Allocator, list: [][*:0]const u8) void {
+    for (list) |item| allocator.free(std.mem.span(item));
+    allocator.free(list);
+}
+
+/// Copy a libgit2-filled string array into allocator-owned strings and
+/// dispose of the original
+fn takeStrarray(allocator: std.mem.Allocator, array: *git_strarray) Error![][:0]u8 {
+    defer git2_shim_strarray_dispose(array);
+
+    const list = allocator.alloc([:0]u8, array.count) catch return Error.AllocationFailed;
+    var done: usize = 0;
+    errdefer {
+        for (list[0..done]) |item| allocator.free(item);
+        allocator.free(list);
+    }
+    if (array.strings) |strings| {
+        for (strings[0..array.count]) |item| {
+            list[done] = allocator.dupeZ(u8, std.mem.span(item)) catch return Error.AllocationFailed;
+            done += 1;
         }
     }
+    return list;
+}
 
-    pub fn open(allocator: std.mem.Allocator, path: []const u8) Error!Repository {
-        try ensureInit();
+/// Copy a libgit2-filled buffer into an allocator-owned string and
+/// dispose of the original
+fn takeBuf(allocator: std.mem.Allocator, buf: *git_buf) Error![:0]u8 {
+    defer git2_shim_buf_dispose(buf);
+    const bytes = if (buf.ptr) |p| p[0..buf.size] else "";
+    return allocator.dupeZ(u8, bytes) catch return Error.AllocationFailed;
+}
 
-        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
-        defer allocator.free(path_z);
+/// Free a string list returned by e.g. `Repository.remoteList`
+pub fn freeStringList(allocator: std.mem.Allocator, list: [][:0]u8) void {
+    for (list) |item| allocator.free(item);
+    allocator.free(list);
+}
 
-        var repo: ?*git_repository = null;
-        if (git2_shim_repository_open(&repo, path_z.ptr) < 0) {
-            return Error.OpenFailed;
-        }
-        return Repository{ .repo = repo.? };
+/// Stash save flags
+pub const StashFlags = struct {
+    keep_index: bool = false,
+    include_untracked: bool = false,
+    include_ignored: bool = false,
+    keep_all: bool = false,
+
+    fn bits(self: StashFlags) u32 {
+        var flags: u32 = 0;
+        if (self.keep_index) flags |= 1 << 0;
+        if (self.include_untracked) flags |= 1 << 1;
+        if (self.include_ignored) flags |= 1 << 2;
+        if (self.keep_all) flags |= 1 << 3;
+        return flags;
     }
+};
 
-    pub fn close(self: *Repository) void {
-        git2_shim_repository_free(self.repo);
+/// Options for `Repository.stashSave`
+pub const StashSaveOptions = struct {
+    message: ?[]const u8 = null,
+    flags: StashFlags = .{},
+    /// Only stash changes matching these pathspecs
+    paths: []const []const u8 = &.{},
+};
+
+/// Stage reported to a stash apply progress callback
+pub const StashApplyProgress = enum(c_int) {
+    none = 0,
+    loading_stash,
+    analyze_index,
+    analyze_modified,
+    analyze_untracked,
+    checkout_untracked,
+    checkout_modified,
+    done,
+};
+
+/// Options for `Repository.stashApply` and `Repository.stashPop`
+pub const StashApplyOptions = struct {
+    /// Also restore the stashed index, like `git stash apply --index`
+    reinstate_index: bool = false,
+    /// Receives a `StashApplyProgress` value; return non-zero to abort
+    progress_cb: ?git_stash_apply_progress_cb = null,
+    progress_payload: ?*anyopaque = null,
+
+    fn create(self: StashApplyOptions) Error!*git2_shim_stash_apply_options {
+        const opts = git2_shim_stash_apply_options_new() orelse return Error.AllocationFailed;
+        git2_shim_stash_apply_options_set_reinstate_index(opts, @intFromBool(self.reinstate_index));
+        git2_shim_stash_apply_options_set_progress_cb(opts, self.progress_cb, self.progress_payload);
+        return opts;
     }
+};
 
-    /// Check if repository is bare
-    pub fn isBare(self: *Repository) bool {
-        return git2_shim_repository_is_bare(self.repo) != 0;
+/// Checkout strategy flags (`GIT_CHECKOUT_*`)
+pub const CheckoutStrategy = struct {
+    pub const none: c_uint = 0;
+    pub const safe: c_uint = 1 << 0;
+    pub const force: c_uint = 1 << 1;
+    pub const recreate_missing: c_uint = 1 << 2;
+    pub const allow_conflicts: c_uint = 1 << 4;
+    pub const remove_untracked: c_uint = 1 << 5;
+    pub const remove_ignored: c_uint = 1 << 6;
+    pub const update_only: c_uint = 1 << 7;
+    pub const dont_update_index: c_uint = 1 << 8;
+    pub const no_refresh: c_uint = 1 << 9;
+    pub const skip_unmerged: c_uint = 1 << 10;
+    pub const use_ours: c_uint = 1 << 11;
+    pub const use_theirs: c_uint = 1 << 12;
+    pub const disable_pathspec_match: c_uint = 1 << 13;
+    pub const update_submodules: c_uint = 1 << 16;
+    pub const update_submodules_if_changed: c_uint = 1 << 17;
+    pub const skip_locked_directories: c_uint = 1 << 18;
+    pub const dont_overwrite_ignored: c_uint = 1 << 19;
+    pub const conflict_style_merge: c_uint = 1 << 20;
+    pub const conflict_style_diff3: c_uint = 1 << 21;
+    pub const dont_remove_existing: c_uint = 1 << 22;
+    pub const dont_write_index: c_uint = 1 << 23;
+    pub const dry_run: c_uint = 1 << 24;
+    pub const conflict_style_zdiff3: c_uint = 1 << 25;
+};
+
+/// Options controlling how files are written to the working directory
+pub const CheckoutOptions = struct {
+    strategy: c_uint = CheckoutStrategy.safe,
+    /// Only check out paths matching these pathspecs
+    paths: []const []const u8 = &.{},
+    /// Write files here instead of the repository workdir
+    target_directory: ?[]const u8 = null,
+    ancestor_label: ?[]const u8 = null,
+    our_label: ?[]const u8 = null,
+    their_label: ?[]const u8 = null,
+    progress_cb: ?git_checkout_progress_cb = null,
+    progress_payload: ?*anyopaque = null,
+
+    fn create(self: CheckoutOptions, allocator: std.mem.Allocator) Error!*git2_shim_checkout_options {
+        const opts = git2_shim_checkout_options_new() orelse return Error.AllocationFailed;
+        errdefer git2_shim_checkout_options_free(opts);
+
+        git2_shim_checkout_options_set_strategy(opts, self.strategy);
+        git2_shim_checkout_options_set_progress_cb(opts, self.progress_cb, self.progress_payload);
+
+        const paths = try dupeZList(allocator, self.paths);
+        defer freeZList(allocator, paths);
+        git2_shim_checkout_options_set_paths(opts, paths.ptr, paths.len);
+
+        const target = try dupeZOpt(allocator, self.target_directory);
+        defer if (target) |t| allocator.free(t);
+        git2_shim_checkout_options_set_target_directory(opts, if (target) |t| t.ptr else null);
+
+        const ancestor = try dupeZOpt(allocator, self.ancestor_label);
+        defer if (ancestor) |a| allocator.free(a);
+        const ours = try dupeZOpt(allocator, self.our_label);
+        defer if (ours) |o| allocator.free(o);
+        const theirs = try dupeZOpt(allocator, self.their_label);
+        defer if (theirs) |t| allocator.free(t);
+        git2_shim_checkout_options_set_conflict_labels(
+            opts,
+            if (ancestor) |a| a.ptr else null,
+            if (ours) |o| o.ptr else null,
+            if (theirs) |t| t.ptr else null,
+        );
+        return opts;
     }
+};
 
-    /// Get repository workdir path
-    pub fn workdir(self: *Repository) ?[]const u8 {
-        const path = git2_shim_repository_workdir(self.repo);
-        if (path == null) return null;
-        return std.mem.span(path.?);
+/// Merge flags (`GIT_MERGE_*`)
+pub const MergeFlags = struct {
+    find_renames: bool = true,
+    fail_on_conflict: bool = false,
+    skip_reuc: bool = false,
+    no_recursive: bool = false,
+    virtual_base: bool = false,
+
+    fn bits(self: MergeFlags) u32 {
+        var flags: u32 = 0;
+        if (self.find_renames) flags |= 1 << 0;
+        if (self.fail_on_conflict) flags |= 1 << 1;
+        if (self.skip_reuc) flags |= 1 << 2;
+        if (self.no_recursive) flags |= 1 << 3;
+        if (self.virtual_base) flags |= 1 << 4;
+        return flags;
     }
+};
 
-    /// Check if working directory is clean
-    pub fn isClean(self: *Repository) Error!bool {
-        var opts: git_status_options = undefined;
-        _ = git2_shim_status_options_init(&opts, GIT_STATUS_OPTIONS_VERSION);
-        opts.show = GIT_STATUS_SHOW_INDEX_AND_WORKDIR;
+/// How conflicting hunks are resolved during a merge
+pub const MergeFileFavor = enum(c_uint) {
+    normal = 0,
+    ours = 1,
+    theirs = 2,
+    @"union" = 3,
+};
 
-        var list: ?*git_status_list = null;
-        if (git2_shim_status_list_new(&list, self.repo, &opts) < 0) {
-            return Error.StatusFailed;
-        }
-        defer git2_shim_status_list_free(list.?);
+/// File-level merge flags (`GIT_MERGE_FILE_*`)
+pub const MergeFileFlags = struct {
+    pub const default: u32 = 0;
+    pub const style_merge: u32 = 1 << 0;
+    pub const style_diff3: u32 = 1 << 1;
+    pub const simplify_alnum: u32 = 1 << 2;
+    pub const ignore_whitespace: u32 = 1 << 3;
+    pub const ignore_whitespace_change: u32 = 1 << 4;
+    pub const ignore_whitespace_eol: u32 = 1 << 5;
+    pub const diff_patience: u32 = 1 << 6;
+    pub const diff_minimal: u32 = 1 << 7;
+    pub const style_zdiff3: u32 = 1 << 8;
+    pub const accept_conflicts: u32 = 1 << 9;
+};
 
-        return git2_shim_status_list_entrycount(list.?) == 0;
+/// Options for `Repository.merge`
+pub const MergeOptions = struct {
+    flags: MergeFlags = .{},
+    file_favor: MergeFileFavor = .normal,
+    file_flags: u32 = MergeFileFlags.default,
+    /// Similarity percentage for rename detection; null keeps libgit2's default (50)
+    rename_threshold: ?c_uint = null,
+    /// Maximum files inspected for renames; null keeps libgit2's default
+    target_limit: ?c_uint = null,
+
+    fn create(self: MergeOptions) Error!*git2_shim_merge_options {
+        const opts = git2_shim_merge_options_new() orelse return Error.AllocationFailed;
+        git2_shim_merge_options_set_flags(opts, self.flags.bits());
+        git2_shim_merge_options_set_file_favor(opts, @intFromEnum(self.file_favor));
+        git2_shim_merge_options_set_file_flags(opts, self.file_flags);
+        if (self.rename_threshold) |t| git2_shim_merge_options_set_rename_threshold(opts, t);
+        if (self.target_limit) |l| git2_shim_merge_options_set_target_limit(opts, l);
+        return opts;
     }
+};
 
-    /// Get HEAD reference name
-    pub fn headName(self: *Repository, allocator: std.mem.Allocator) Error![]const u8 {
-        var head: ?*git_reference = null;
-        if (git2_shim_repository_head(&head, self.repo) < 0) {
-            return Error.ReferenceFailed;
-        }
-        defer git2_shim_reference_free(head.?);
+/// Author or committer identity, borrowed from the object it belongs to
+pub const Signature = struct {
+    name: []const u8,
+    email: []const u8,
+    /// Seconds since the epoch
+    time: i64,
+    /// Timezone offset in minutes
+    offset: c_int,
 
-        const name = git2_shim_reference_shorthand(head.?);
-        if (name == null) return Error.ReferenceFailed;
+    fn fromRaw(sig: *const git_signature) Signature {
+        const name = git2_shim_signature_name(sig);
+        const email = git2_shim_signature_email(sig);
+        return .{
+            .name = if (name) |n| std.mem.span(n) else "",
+            .email = if (email) |e| std.mem.span(e) else "",
+            .time = git2_shim_signature_time(sig),
+            .offset = git2_shim_signature_offset(sig),
+        };
+    }
+};
 
-        return allocator.dupe(u8, std.mem.span(name.?)) catch return Error.AllocationFailed;
+/// How far blame looks for lines moved or copied from elsewhere.
+/// Each level includes the ones before it.
+pub const BlameCopyTracking = enum {
+    none,
+    /// Lines moved within the same file
+    same_file,
+    /// Lines moved or copied from files changed in the same commit
+    same_commit_moves,
+    /// Lines copied from files changed in the same commit
+    same_commit_copies,
+    /// Lines copied from any file in any commit
+    any_commit_copies,
+
+    fn bits(self: BlameCopyTracking) u32 {
+        return switch (self) {
+            .none => 0,
+            .same_file => 1 << 0,
+            .same_commit_moves => (1 << 0) | (1 << 1),
+            .same_commit_copies => (1 << 0) | (1 << 1) | (1 << 2),
+            .any_commit_copies => (1 << 0) | (1 << 1) | (1 << 2) | (1 << 3),
+        };
     }
+};
 
-    /// Count commits ahead/behind a remote branch
-    pub fn aheadBehind(self: *Repository, local_oid: *const git_oid, remote_oid: *const git_oid) Error!struct { ahead: usize, behind: usize } {
-        var ahead: usize = 0;
-        var behind: usize = 0;
-        if (git2_shim_graph_ahead_behind(&ahead, &behind, self.repo, local_oid, remote_oid) < 0) {
-            return Error.CommitFailed;
+/// Options for `Repository.blameFile`
+pub const BlameOptions = struct {
+    copy_tracking: BlameCopyTracking = .none,
+    /// Follow only the first parent of merge commits
+    first_parent: bool = false,
+    /// Map authors and committers through the repository's mailmap
+    use_mailmap: bool = false,
+    ignore_whitespace: bool = false,
+    /// Minimum characters a moved or copied line must match; null keeps
+    /// libgit2's default (20)
+    min_match_characters: ?u16 = null,
+    /// 1-based, inclusive line range; 0 leaves that end unbounded
+    min_line: usize = 0,
+    max_line: usize = 0,
+    /// Blame as of this commit instead of HEAD
+    newest_commit: ?git_oid = null,
+    /// Stop at this commit; hunks reaching it are marked as boundaries
+    oldest_commit: ?git_oid = null,
+
+    fn create(self: BlameOptions) Error!*git2_shim_blame_options {
+        const opts = git2_shim_blame_options_new() orelse return Error.AllocationFailed;
+        errdefer git2_shim_blame_options_free(opts);
+
+        var flags = self.copy_tracking.bits();
+        if (self.first_parent) flags |= 1 << 4;
+        if (self.use_mailmap) flags |= 1 << 5;
+        if (self.ignore_whitespace) flags |= 1 << 6;
+        git2_shim_blame_options_set_flags(opts, flags);
+
+        if (self.min_match_characters) |count| git2_shim_blame_options_set_min_match_characters(opts, count);
+        git2_shim_blame_options_set_line_range(opts, self.min_line, self.max_line);
+        if (self.newest_commit) |*id| git2_shim_blame_options_set_newest_commit(opts, id);
+        if (self.oldest_commit) |*id| git2_shim_blame_options_set_oldest_commit(opts, id);
+        return opts;
+    }
+};
+
+/// A run of consecutive lines last changed by the same commit
+pub const BlameHunk = struct {
+    lines: usize,
+    final_commit_id: git_oid,
+    /// 1-based first line in the blamed file
+    final_start_line: usize,
+    final_signature: ?Signature,
+    /// Commit that introduced the lines (differs when copies are tracked)
+    orig_commit_id: git_oid,
+    orig_path: ?[]const u8,
+    /// 1-based first line in the original file
+    orig_start_line: usize,
+    orig_signature: ?Signature,
+    /// The hunk reached the oldest commit searched
+    boundary: bool,
+
+    fn fromRaw(hunk: *const git_blame_hunk) BlameHunk {
+        const orig_path = git2_shim_blame_hunk_orig_path(hunk);
+        return .{
+            .lines = git2_shim_blame_hunk_lines(hunk),
+            .final_commit_id = git2_shim_blame_hunk_final_commit_id(hunk).*,
+            .final_start_line = git2_shim_blame_hunk_final_start_line(hunk),
+            .final_signature = if (git2_shim_blame_hunk_final_signature(hunk)) |sig| Signature.fromRaw(sig) else null,
+            .orig_commit_id = git2_shim_blame_hunk_orig_commit_id(hunk).*,
+            .orig_path = if (orig_path) |p| std.mem.span(p) else null,
+            .orig_start_line = git2_shim_blame_hunk_orig_start_line(hunk),
+            .orig_signature = if (git2_shim_blame_hunk_orig_signature(hunk)) |sig| Signature.fromRaw(sig) else null,
+            .boundary = git2_shim_blame_hunk_is_boundary(hunk) != 0,
+        };
+    }
+};
+
+/// Line-by-line authorship of a file. Hunks borrow from the blame and are
+/// only valid until `deinit`.
+pub const Blame = struct {
+    blame: *git_blame,
+
+    pub fn deinit(self: *Blame) void {
+        git2_shim_blame_free(self.blame);
+    }
+
+    pub fn hunkCount(self: *Blame) u32 {
+        return git2_shim_blame_get_hunk_count(self.blame);
+    }
+
+    pub fn hunk(self: *Blame, index: u32) ?BlameHunk {
+        const h = git2_shim_blame_get_hunk_byindex(self.blame, index) orelse return null;
+        return BlameHunk.fromRaw(h);
+    }
+
+    /// Hunk containing the 1-based line `line`
+    pub fn hunkForLine(self: *Blame, line: usize) ?BlameHunk {
+        const h = git2_shim_blame_get_hunk_byline(self.blame, line) orelse return null;
+        return BlameHunk.fromRaw(h);
+    }
+
+    /// Blame `contents`, e.g. a modified but unsaved editor buffer, layered
+    /// over this blame of the committed file. Lines that differ from the
+    /// committed version get a zero commit id. The returned blame is
+    /// independent and needs its own `deinit`.
+    pub fn buffer(self: *Blame, contents: []const u8) Error!Blame {
+        var blame: ?*git_blame = null;
+        if (git2_shim_blame_buffer(&blame, self.blame, contents.ptr, contents.len) < 0) {
+            return Error.BlameFailed;
         }
-        return .{ .ahead = ahead, .behind = behind };
+        return Blame{ .blame = blame.? };
     }
 };
 
-// =============================================================================
-// C FFI exports
-// =============================================================================
+/// Order in which `Revwalk` yields commits. With no flags set the order
+/// is unspecified (currently reverse chronological, but not guaranteed).
+pub const SortMode = struct {
+    /// Parents are never shown before all of their children
+    topological: bool = false,
+    /// Sort by commit time, newest first
+    time: bool = false,
+    /// Reverse the selected order, e.g. oldest first
+    reverse: bool = false,
 
-var global_allocator: std.mem.Allocator = std.heap.c_allocator;
+    fn bits(self: SortMode) c_uint {
+        var mode: c_uint = 0;
+        if (self.topological) mode |= 1 << 0;
+        if (self.time) mode |= 1 << 1;
+        if (self.reverse) mode |= 1 << 2;
+        return mode;
+    }
+};
 
-export fn git2_init() bool {
-    _ = git2_shim_init();
-    Repository.initialized = true;
-    return true;
-}
+/// Walks commit history from pushed commits, skipping hidden ones
+pub const Revwalk = struct {
+    walk: *git_revwalk,
 
-export fn git2_shutdown() void {
-    _ = git2_shim_shutdown();
-    Repository.initialized = false;
-}
+    pub fn deinit(self: *Revwalk) void {
+        git2_shim_revwalk_free(self.walk);
+    }
 
-export fn git2_open(path: [*:0]const u8) ?*Repository {
-    const repo = Repository.open(global_allocator, std.mem.span(path)) catch return null;
-    const ptr = global_allocator.create(Repository) catch return null;
-    ptr.* = repo;
-    return ptr;
+    /// Start walking from `id`
+    pub fn push(self: *Revwalk, id: *const git_oid) Error!void {
+        if (git2_shim_revwalk_push(self.walk, id) < 0) return Error.RevwalkFailed;
+    }
+
+    /// Exclude `id` and all of its ancestors
+    pub fn hide(self: *Revwalk, id: *const git_oid) Error!void {
+        if (git2_shim_revwalk_hide(self.walk, id) < 0) return Error.RevwalkFailed;
+    }
+
+    /// Push and hide in one step from a range like "main..feature";
+    /// symmetric "a...b" ranges are not supported
+    pub fn pushRange(self: *Revwalk, allocator: std.mem.Allocator, range: []const u8) Error!void {
+        try self.callWithName(allocator, &git2_shim_revwalk_push_range, range);
+    }
+
+    /// Push every reference matching `glob`, e.g. "refs/tags/*"; a glob
+    /// without wildcards matches everything below it
+    pub fn pushGlob(self: *Revwalk, allocator: std.mem.Allocator, glob: []const u8) Error!void {
+        try self.callWithName(allocator, &git2_shim_revwalk_push_glob, glob);
+    }
+
+    pub fn pushHead(self: *Revwalk) Error!void {
+        if (git2_shim_revwalk_push_head(self.walk) < 0) return Error.RevwalkFailed;
+    }
+
+    /// Push the commit a reference such as "refs/heads/main" points to
+    pub fn pushRef(self: *Revwalk, allocator: std.mem.Allocator, refname: []const u8) Error!void {
+        try self.callWithName(allocator, &git2_shim_revwalk_push_ref, refname);
+    }
+
+    pub fn hideGlob(self: *Revwalk, allocator: std.mem.Allocator, glob: []const u8) Error!void {
+        try self.callWithName(allocator, &git2_shim_revwalk_hide_glob, glob);
+    }
+
+    pub fn hideHead(self: *Revwalk) Error!void {
+        if (git2_shim_revwalk_hide_head(self.walk) < 0) return Error.RevwalkFailed;
+    }
+
+    pub fn hideRef(self: *Revwalk, allocator: std.mem.Allocator, refname: []const u8) Error!void {
+        try self.callWithName(allocator, &git2_shim_revwalk_hide_ref, refname);
+    }
+
+    fn callWithName(
+        self: *Revwalk,
+        allocator: std.mem.Allocator,
+        func: *const fn (*git_revwalk, [*:0]const u8) callconv(.C) c_int,
+        name: []const u8,
+    ) Error!void {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+        if (func(self.walk, name_z.ptr) < 0) return Error.RevwalkFailed;
+    }
+
+    /// Follow only the first parent of merge commits, like
+    /// `git log --first-parent`
+    pub fn simplifyFirstParent(self: *Revwalk) Error!void {
+        if (git2_shim_revwalk_simplify_first_parent(self.walk) < 0) return Error.RevwalkFailed;
+    }
+
+    /// Hide commits for which `callback` returns non-zero, along with their
+    /// ancestors; null removes the callback. `payload` must outlive the walk.
+    pub fn setHideCallback(self: *Revwalk, callback: ?git_revwalk_hide_cb, payload: ?*anyopaque) Error!void {
+        if (git2_shim_revwalk_add_hide_cb(self.walk, callback, payload) < 0) return Error.RevwalkFailed;
+    }
+
+    /// Change the sort order; this also resets the walk
+    pub fn sorting(self: *Revwalk, mode: SortMode) Error!void {
+        if (git2_shim_revwalk_sorting(self.walk, mode.bits()) < 0) return Error.RevwalkFailed;
+    }
+
+    /// Forget all pushed and hidden commits so the walker can be reused
+    pub fn reset(self: *Revwalk) Error!void {
+        if (git2_shim_revwalk_reset(self.walk) < 0) return Error.RevwalkFailed;
+    }
+
+    /// Next commit id; null once the walk is exhausted
+    pub fn next(self: *Revwalk) Error!?git_oid {
+        var oid: git_oid = undefined;
+        const rc = git2_shim_revwalk_next(&oid, self.walk);
+        if (rc == GIT_ITEROVER) return null;
+        if (rc < 0) return Error.RevwalkFailed;
+        return oid;
+    }
+};
+
+/// Kind of a git object
+pub const ObjectType = enum(c_int) {
+    any = -2,
+    invalid = -1,
+    commit = 1,
+    tree = 2,
+    blob = 3,
+    tag = 4,
+    /// Pack-only delta against an object earlier in the same pack
+    ofs_delta = 6,
+    /// Pack-only delta against an object named by id
+    ref_delta = 7,
+    _,
+
+    /// Name git uses in object headers and `git cat-file -t`, e.g. "tree";
+    /// null for `any`, `invalid` and unknown values
+    pub fn toString(self: ObjectType) ?[:0]const u8 {
+        const name = std.mem.span(git2_shim_object_type2string(@intFromEnum(self)));
+        if (name.len == 0) return null;
+        return name;
+    }
+
+    /// Inverse of `toString`; `invalid` for unrecognised names
+    pub fn fromString(name: [:0]const u8) ObjectType {
+        return @enumFromInt(git2_shim_object_string2type(name.ptr));
+    }
+
+    /// Whether objects of this kind can be stored loose, i.e. commit, tree,
+    /// blob or tag
+    pub fn isLoose(self: ObjectType) bool {
+        return git2_shim_object_typeisloose(@intFromEnum(self)) != 0;
+    }
+};
+
+/// Mode of a tree or index entry, as git stores it
+pub const FileMode = enum(u32) {
+    unreadable = 0o000000,
+    tree = 0o040000,
+    blob = 0o100644,
+    blob_executable = 0o100755,
+    link = 0o120000,
+    /// Submodule (gitlink) pointing at a commit of another repository
+    commit = 0o160000,
+    _,
+
+    /// Kind of object an entry with this mode points at; `invalid` for
+    /// `unreadable` and unknown modes
+    pub fn objectType(self: FileMode) ObjectType {
+        return switch (self) {
+            .tree => .tree,
+            .blob, .blob_executable, .link => .blob,
+            .commit => .commit,
+            else => .invalid,
+        };
+    }
+};
+
+/// Any git object: commit, tree, blob, or tag
+pub const Object = struct {
+    object: *git_object,
+
+    pub fn deinit(self: *Object) void {
+        git2_shim_object_free(self.object);
+    }
+
+    pub fn id(self: Object) git_oid {
+        return git2_shim_object_id(self.object).*;
+    }
+
+    pub fn kind(self: Object) ObjectType {
+        return @enumFromInt(git2_shim_object_type(self.object));
+    }
+};
+
+/// Result of `Repository.revparseExt`
+pub const RevparseResult = struct {
+    object: Object,
+    /// Full name of the reference the spec went through, e.g.
+    /// "refs/heads/main" for "main~2"; null if it named none
+    reference: ?[:0]u8,
+
+    pub fn deinit(self: *RevparseResult, allocator: std.mem.Allocator) void {
+        self.object.deinit();
+        if (self.reference) |name| allocator.free(name);
+    }
+};
+
+/// Result of `Repository.revparse`: a single revision or a range
+pub const Revspec = struct {
+    /// The revision, or the left side of a range
+    from: ?Object,
+    /// The right side of a range; null for a single revision
+    to: ?Object,
+    /// Parsed from "a..b" or "a...b"
+    range: bool,
+    /// Parsed from "a...b" (symmetric difference)
+    merge_base: bool,
+
+    pub fn deinit(self: *Revspec) void {
+        if (self.from) |*obj| obj.deinit();
+        if (self.to) |*obj| obj.deinit();
+    }
+};
+
+/// A configured or anonymous remote
+pub const Remote = struct {
+    remote: *git_remote,
+    /// Options of the open connection; libgit2 uses their callbacks until
+    /// disconnect
+    connection: ?*git2_shim_connect_options = null,
+
+    pub fn deinit(self: *Remote) void {
+        git2_shim_remote_free(self.remote);
+        if (self.connection) |opts| git2_shim_connect_options_free(opts);
+    }
+
+    /// Connect without transferring anything, e.g. to list refs with `ls`
+    pub fn connect(self: *Remote, allocator: std.mem.Allocator, direction: Direction, options: ConnectOptions) Error!void {
+        const opts = try options.create(allocator);
+        errdefer git2_shim_connect_options_free(opts);
+        if (git2_shim_remote_connect(self.remote, @intFromEnum(direction), opts) < 0) {
+            return Error.RemoteFailed;
+        }
+        if (self.connection) |old| git2_shim_connect_options_free(old);
+        self.connection = opts;
+    }
+
+    pub fn connected(self: Remote) bool {
+        return git2_shim_remote_connected(self.remote) != 0;
+    }
+
+    pub fn disconnect(self: *Remote) void {
+        _ = git2_shim_remote_disconnect(self.remote);
+        if (self.connection) |opts| git2_shim_connect_options_free(opts);
+        self.connection = null;
+    }
+
+    /// Delete remote-tracking refs whose branches are gone from the
+    /// connected remote; `callbacks.update_tips_cb` hears about each one
+    pub fn prune(self: *Remote, callbacks: RemoteCallbacks) Error!void {
+        const handle = try callbacks.create();
+        defer git2_shim_remote_callbacks_free(handle);
+        if (git2_shim_remote_prune(self.remote, handle) < 0) {
+            return Error.RemoteFailed;
+        }
+    }
+
+    /// Tag-following setting (`remote.<name>.tagOpt`) used when the fetch
+    /// options leave `download_tags` unspecified
+    pub fn autotag(self: Remote) DownloadTags {
+        return @enumFromInt(git2_shim_remote_autotag(self.remote));
+    }
+
+    /// Whether `remote.<name>.prune` makes fetches prune by default
+    pub fn pruneRefs(self: Remote) bool {
+        return git2_shim_remote_prune_refs(self.remote) != 0;
+    }
+
+    /// Refs the connected remote advertises, like `git ls-remote`; free
+    /// with `freeRemoteHeads`
+    pub fn ls(self: *Remote, allocator: std.mem.Allocator) Error![]RemoteHead {
+        var heads: ?[*]const *const git_remote_head = null;
+        var count: usize = 0;
+        if (git2_shim_remote_ls(&heads, &count, self.remote) < 0) {
+            return Error.RemoteFailed;
+        }
+
+        const list = allocator.alloc(RemoteHead, count) catch return Error.AllocationFailed;
+        var done: usize = 0;
+        errdefer {
+            for (list[0..done]) |head| {
+                allocator.free(head.name);
+                if (head.symref_target) |t| allocator.free(t);
+            }
+            allocator.free(list);
+        }
+        if (heads) |items| {
+            for (items[0..count]) |head| {
+                const head_name = allocator.dupeZ(u8, std.mem.span(git2_shim_remote_head_name(head))) catch return Error.AllocationFailed;
+                errdefer allocator.free(head_name);
+                const target = if (git2_shim_remote_head_symref_target(head)) |t|
+                    allocator.dupeZ(u8, std.mem.span(t)) catch return Error.AllocationFailed
+                else
+                    null;
+                list[done] = .{ .name = head_name, .id = git2_shim_remote_head_oid(head).*, .symref_target = target };
+                done += 1;
+            }
+        }
+        return list;
+    }
+
+    /// Configured name, e.g. "origin"; null for an anonymous remote
+    pub fn name(self: Remote) ?[]const u8 {
+        const n = git2_shim_remote_name(self.remote) orelse return null;
+        return std.mem.span(n);
+    }
+
+    pub fn url(self: Remote) ?[]const u8 {
+        const u = git2_shim_remote_url(self.remote) orelse return null;
+        return std.mem.span(u);
+    }
+
+    /// Push URL if one is configured separately from `url`
+    pub fn pushurl(self: Remote) ?[]const u8 {
+        const u = git2_shim_remote_pushurl(self.remote) orelse return null;
+        return std.mem.span(u);
+    }
+
+    /// Download from the remote and update its remote-tracking refs.
+    /// Empty `refspecs` uses the configured fetch refspecs; a null
+    /// `reflog_message` defaults to "fetch".
+    pub fn fetch(
+        self: *Remote,
+        allocator: std.mem.Allocator,
+        refspecs: []const []const u8,
+        options: FetchOptions,
+        reflog_message: ?[]const u8,
+    ) Error!void {
+        const specs = try dupeZList(allocator, refspecs);
+        defer freeZList(allocator, specs);
+        const message = try dupeZOpt(allocator, reflog_message);
+        defer if (message) |m| allocator.free(m);
+
+        const opts = try options.create(allocator);
+        defer git2_shim_fetch_options_free(opts);
+
+        const specs_ptr = if (specs.len == 0) null else specs.ptr;
+        if (git2_shim_remote_fetch(self.remote, specs_ptr, specs.len, opts, if (message) |m| m.ptr else null) < 0) {
+            return Error.FetchFailed;
+        }
+    }
+
+    /// First half of `fetch`: download the objects for `refspecs` (empty
+    /// for the configured ones) without moving any refs
+    pub fn download(self: *Remote, allocator: std.mem.Allocator, refspecs: []const []const u8, options: FetchOptions) Error!void {
+        const specs = try dupeZList(allocator, refspecs);
+        defer freeZList(allocator, specs);
+
+        const opts = try options.create(allocator);
+        defer git2_shim_fetch_options_free(opts);
+
+        const specs_ptr = if (specs.len == 0) null else specs.ptr;
+        if (git2_shim_remote_download(self.remote, specs_ptr, specs.len, opts) < 0) {
+            return Error.FetchFailed;
+        }
+    }
+
+    /// Second half of `fetch`: move the remote-tracking refs to what
+    /// `download` fetched. `callbacks.update_tips_cb` hears about every
+    /// ref that moved, and with `report_unchanged` also the ones that did
+    /// not. Pass the `download_tags` the download used.
+    pub fn updateTips(
+        self: *Remote,
+        allocator: std.mem.Allocator,
+        callbacks: RemoteCallbacks,
+        flags: UpdateTipsFlags,
+        download_tags: DownloadTags,
+        reflog_message: ?[]const u8,
+    ) Error!void {
+        const message = try dupeZOpt(allocator, reflog_message);
+        defer if (message) |m| allocator.free(m);
+
+        const handle = try callbacks.create();
+        defer git2_shim_remote_callbacks_free(handle);
+
+        if (git2_shim_remote_update_tips(self.remote, handle, flags.bits(), @intFromEnum(download_tags), if (message) |m| m.ptr else null) < 0) {
+            return Error.FetchFailed;
+        }
+    }
+
+    /// Push `refspecs` such as "refs/heads/main" or "+refs/tags/v1.0";
+    /// empty uses the configured push refspecs. Returns `NonFastForward`
+    /// when an unforced update would lose remote commits; `lastError` has
+    /// the details of other failures.
+    pub fn push(self: *Remote, allocator: std.mem.Allocator, refspecs: []const []const u8, options: PushOptions) Error!void {
+        const specs = try dupeZList(allocator, refspecs);
+        defer freeZList(allocator, specs);
+
+        const opts = try options.create(allocator);
+        defer git2_shim_push_options_free(opts);
+
+        const specs_ptr = if (specs.len == 0) null else specs.ptr;
+        const rc = git2_shim_remote_push(self.remote, specs_ptr, specs.len, opts);
+        if (rc == GIT_ENONFASTFORWARD) return Error.NonFastForward;
+        if (rc < 0) return Error.PushFailed;
+    }
+};
+
+/// Credential kinds a remote may accept, as passed to
+/// `git_credential_acquire_cb`
+pub const CredentialType = struct {
+    pub const userpass_plaintext: c_uint = 1 << 0;
+    pub const ssh_key: c_uint = 1 << 1;
+    pub const ssh_custom: c_uint = 1 << 2;
+    pub const default: c_uint = 1 << 3;
+    pub const ssh_interactive: c_uint = 1 << 4;
+    pub const username: c_uint = 1 << 5;
+    pub const ssh_memory: c_uint = 1 << 6;
+};
+
+/// Credential constructors for use inside a `git_credential_acquire_cb`.
+/// Each returns the libgit2 result, so the callback can return it directly.
+pub const Credential = struct {
+    /// Username only; SSH asks for this first when the URL names no user
+    pub fn username(out: *?*git_credential, name: [*:0]const u8) c_int {
+        return git2_shim_credential_username_new(out, name);
+    }
+
+    /// Username and password for HTTPS. For token auth (GitHub PATs,
+    /// GitLab deploy tokens) pass the token as `password` with any
+    /// non-empty username, e.g. "x-access-token" or "oauth2".
+    pub fn userpassPlaintext(out: *?*git_credential, user: [*:0]const u8, password: [*:0]const u8) c_int {
+        return git2_shim_credential_userpass_plaintext_new(out, user, password);
+    }
+
+    /// SSH key pair from files. A null `publickey` is derived from the
+    /// private key; `passphrase` is null for unencrypted keys.
+    pub fn sshKey(
+        out: *?*git_credential,
+        user: [*:0]const u8,
+        publickey: ?[*:0]const u8,
+        privatekey: [*:0]const u8,
+        passphrase: ?[*:0]const u8,
+    ) c_int {
+        return git2_shim_credential_ssh_key_new(out, user, publickey, privatekey, passphrase);
+    }
+
+    /// SSH key pair from in-memory key text, e.g. fetched from a secret
+    /// store; same null rules as `sshKey`
+    pub fn sshKeyMemory(
+        out: *?*git_credential,
+        user: [*:0]const u8,
+        publickey: ?[*:0]const u8,
+        privatekey: [*:0]const u8,
+        passphrase: ?[*:0]const u8,
+    ) c_int {
+        return git2_shim_credential_ssh_key_memory_new(out, user, publickey, privatekey, passphrase);
+    }
+
+    /// Keys offered by the running ssh-agent; pass `username_from_url`
+    /// (or "git") as `user` for zero-config SSH
+    pub fn sshKeyFromAgent(out: *?*git_credential, user: [*:0]const u8) c_int {
+        return git2_shim_credential_ssh_key_from_agent(out, user);
+    }
+
+    /// The logged-in user's platform credentials (NTLM, Negotiate /
+    /// Kerberos); answer `CredentialType.default` requests with this
+    pub fn default(out: *?*git_credential) c_int {
+        return git2_shim_credential_default_new(out);
+    }
+
+    /// Callback result that hands the request to the `credential.helper`
+    /// programs, just as the git CLI would: those of
+    /// `RemoteCallbacks.repository` if set, else of the global and system
+    /// config. If the helper's credential is rejected, the next `ask_git`
+    /// for that URL reports it to the helpers and fails the request.
+    /// Otherwise the helpers are told once the operation ends: approved
+    /// when it succeeds, rejected when it fails to authenticate.
+    pub const ask_git: c_int = 2;
+
+    /// Ask git's configured credential helpers for a username and password
+    /// for `url`, using `repo`'s config if given (so repository-level
+    /// `credential.helper` entries count) and the global config otherwise.
+    /// Returns 0 with the credential in `out`, or 1 if none was found.
+    pub fn fromHelper(out: *?*git_credential, repo: ?*const Repository, url: [*:0]const u8, username_from_url: ?[*:0]const u8) c_int {
+        return git2_shim_credential_helper_fill(out, if (repo) |r| r.repo else null, url, username_from_url);
+    }
+
+    /// Free a credential that was not returned to libgit2
+    pub fn free(cred: *git_credential) void {
+        git2_shim_credential_free(cred);
+    }
+};
+
+/// Algorithm of a raw SSH host key
+pub const HostKeyType = enum(c_uint) {
+    unknown = 0,
+    rsa = 1,
+    dss = 2,
+    ecdsa_256 = 3,
+    ecdsa_384 = 4,
+    ecdsa_521 = 5,
+    ed25519 = 6,
+    _,
+};
+
+/// SSH host key details; each field is null if the transport did not
+/// provide it
+pub const HostKey = struct {
+    md5: ?*const [16]u8,
+    sha1: ?*const [20]u8,
+    sha256: ?*const [32]u8,
+    /// Key blob as stored in `known_hosts` (before base64)
+    raw: ?[]const u8,
+    raw_type: HostKeyType,
+};
+
+/// Server identity passed to `git_certificate_check_cb`; borrows from the
+/// certificate and is only valid during the callback
+pub const Certificate = union(enum) {
+    /// DER-encoded X.509 certificate presented over HTTPS
+    x509: []const u8,
+    hostkey: HostKey,
+    other,
+
+    pub fn decode(cert: *const git_cert) Certificate {
+        switch (git2_shim_cert_type(cert)) {
+            1 => {
+                var len: usize = 0;
+                const data = git2_shim_cert_x509_data(cert, &len) orelse return .other;
+                return .{ .x509 = data[0..len] };
+            },
+            2 => {
+                var len: usize = 0;
+                var raw_type: c_uint = 0;
+                const raw = git2_shim_cert_hostkey_raw(cert, &len, &raw_type);
+                return .{ .hostkey = .{
+                    .md5 = if (git2_shim_cert_hostkey_hash(cert, 1 << 0)) |h| h[0..16] else null,
+                    .sha1 = if (git2_shim_cert_hostkey_hash(cert, 1 << 1)) |h| h[0..20] else null,
+                    .sha256 = if (git2_shim_cert_hostkey_hash(cert, 1 << 2)) |h| h[0..32] else null,
+                    .raw = if (raw) |r| r[0..len] else null,
+                    .raw_type = @enumFromInt(raw_type),
+                } };
+            },
+            else => return .other,
+        }
+    }
+};
+
+/// Callbacks for network operations; every callback receives `payload`
+pub const RemoteCallbacks = struct {
+    /// Relays server messages (progress, pre-receive hook output)
+    sideband_progress_cb: ?git_sideband_progress_cb = null,
+    /// Supplies credentials when the remote requires authentication
+    credentials_cb: ?git_credential_acquire_cb = null,
+    /// Accepts or rejects the server's TLS certificate or SSH host key
+    certificate_check_cb: ?git_certificate_check_cb = null,
+    /// Download progress for fetches and clones
+    transfer_progress_cb: ?git_transfer_progress_cb = null,
+    /// Reports each local ref created, moved, or deleted by fetch or prune
+    update_tips_cb: ?git_update_tips_cb = null,
+    /// Called once per pushed ref to report whether the server accepted it
+    push_update_reference_cb: ?git_push_update_reference_cb = null,
+    push_transfer_progress_cb: ?git_push_transfer_progress_cb = null,
+    /// Last chance to inspect and refuse the ref updates of a push
+    push_negotiation_cb: ?git_push_negotiation_cb = null,
+    payload: ?*anyopaque = null,
+    /// Whose config names the credential helpers behind
+    /// `Credential.ask_git`; null uses the global and system config. It
+    /// must outlive every operation using these callbacks.
+    repository: ?*const Repository = null,
+
+    fn create(self: RemoteCallbacks) Error!*git2_shim_remote_callbacks {
+        const callbacks = git2_shim_remote_callbacks_new() orelse return Error.AllocationFailed;
+        errdefer git2_shim_remote_callbacks_free(callbacks);
+
+        git2_shim_remote_callbacks_set_sideband_progress_cb(callbacks, self.sideband_progress_cb, self.payload);
+        git2_shim_remote_callbacks_set_credentials_cb(callbacks, self.credentials_cb, self.payload);
+        git2_shim_remote_callbacks_set_certificate_check_cb(callbacks, self.certificate_check_cb, self.payload);
+        git2_shim_remote_callbacks_set_transfer_progress_cb(callbacks, self.transfer_progress_cb, self.payload);
+        git2_shim_remote_callbacks_set_update_tips_cb(callbacks, self.update_tips_cb, self.payload);
+        git2_shim_remote_callbacks_set_push_update_reference_cb(callbacks, self.push_update_reference_cb, self.payload);
+        git2_shim_remote_callbacks_set_push_transfer_progress_cb(callbacks, self.push_transfer_progress_cb, self.payload);
+        git2_shim_remote_callbacks_set_push_negotiation_cb(callbacks, self.push_negotiation_cb, self.payload);
+        git2_shim_remote_callbacks_set_repository(callbacks, if (self.repository) |r| r.repo else null);
+        return callbacks;
+    }
+};
+
+/// How network operations reach the remote
+pub const ProxyType = enum(c_uint) {
+    /// Connect directly
+    none = 0,
+    /// Use `http.proxy` from git config or the proxy environment variables
+    auto = 1,
+    /// Use `ProxyOptions.url`
+    specified = 2,
+};
+
+/// Proxy settings for fetch, push and `Remote.connect`
+pub const ProxyOptions = struct {
+    kind: ProxyType = .none,
+    /// e.g. "http://proxy.corp:3128"; only used with `.specified`
+    url: ?[]const u8 = null,
+    /// Credentials for an authenticating proxy
+    credentials_cb: ?git_credential_acquire_cb = null,
+    /// Certificate check for a TLS proxy
+    certificate_check_cb: ?git_certificate_check_cb = null,
+    payload: ?*anyopaque = null,
+
+    fn create(self: ProxyOptions, allocator: std.mem.Allocator) Error!*git2_shim_proxy_options {
+        const opts = git2_shim_proxy_options_new() orelse return Error.AllocationFailed;
+        errdefer git2_shim_proxy_options_free(opts);
+
+        git2_shim_proxy_options_set_type(opts, @intFromEnum(self.kind));
+
+        const url_z = try dupeZOpt(allocator, self.url);
+        defer if (url_z) |u| allocator.free(u);
+        git2_shim_proxy_options_set_url(opts, if (url_z) |u| u.ptr else null);
+
+        git2_shim_proxy_options_set_credentials_cb(opts, self.credentials_cb, self.payload);
+        git2_shim_proxy_options_set_certificate_check_cb(opts, self.certificate_check_cb, self.payload);
+        return opts;
+    }
+};
+
+/// Options for `Remote.connect`
+pub const ConnectOptions = struct {
+    callbacks: RemoteCallbacks = .{},
+    follow_redirects: FollowRedirects = .unspecified,
+    proxy: ProxyOptions = .{},
+    /// Extra HTTP headers, e.g. "Authorization: Bearer <token>"
+    custom_headers: []const []const u8 = &.{},
+
+    fn create(self: ConnectOptions, allocator: std.mem.Allocator) Error!*git2_shim_connect_options {
+        const opts = git2_shim_connect_options_new() orelse return Error.AllocationFailed;
+        errdefer git2_shim_connect_options_free(opts);
+
+        git2_shim_connect_options_set_follow_redirects(opts, @intFromEnum(self.follow_redirects));
+
+        const callbacks = try self.callbacks.create();
+        defer git2_shim_remote_callbacks_free(callbacks);
+        git2_shim_connect_options_set_callbacks(opts, callbacks);
+
+        const proxy = try self.proxy.create(allocator);
+        defer git2_shim_proxy_options_free(proxy);
+        git2_shim_connect_options_set_proxy_options(opts, proxy);
+
+        const headers = try dupeZList(allocator, self.custom_headers);
+        defer freeZList(allocator, headers);
+        git2_shim_connect_options_set_custom_headers(opts, headers.ptr, headers.len);
+
+        return opts;
+    }
+};
+
+/// Options for `Remote.push`
+pub const PushOptions = struct {
+    /// Packbuilder threads; 0 auto-detects
+    pb_parallelism: c_uint = 1,
+    callbacks: RemoteCallbacks = .{},
+    follow_redirects: FollowRedirects = .unspecified,
+    proxy: ProxyOptions = .{},
+    /// Extra HTTP headers, e.g. "Authorization: Bearer <token>"
+    custom_headers: []const []const u8 = &.{},
+    /// Server-side options like `git push -o`, e.g. "ci.skip"
+    remote_push_options: []const []const u8 = &.{},
+
+    fn create(self: PushOptions, allocator: std.mem.Allocator) Error!*git2_shim_push_options {
+        const opts = git2_shim_push_options_new() orelse return Error.AllocationFailed;
+        errdefer git2_shim_push_options_free(opts);
+
+        git2_shim_push_options_set_pb_parallelism(opts, self.pb_parallelism);
+        git2_shim_push_options_set_follow_redirects(opts, @intFromEnum(self.follow_redirects));
+
+        const callbacks = try self.callbacks.create();
+        defer git2_shim_remote_callbacks_free(callbacks);
+        git2_shim_push_options_set_callbacks(opts, callbacks);
+
+        const proxy = try self.proxy.create(allocator);
+        defer git2_shim_proxy_options_free(proxy);
+        git2_shim_push_options_set_proxy_options(opts, proxy);
+
+        const headers = try dupeZList(allocator, self.custom_headers);
+        defer freeZList(allocator, headers);
+        git2_shim_push_options_set_custom_headers(opts, headers.ptr, headers.len);
+
+        const push_options = try dupeZList(allocator, self.remote_push_options);
+        defer freeZList(allocator, push_options);
+        git2_shim_push_options_set_remote_push_options(opts, push_options.ptr, push_options.len);
+
+        return opts;
+    }
+};
+
+/// Whether a fetch removes remote-tracking refs that no longer exist on
+/// the remote
+pub const FetchPrune = enum(c_uint) {
+    /// Follow `remote.<name>.prune` / `fetch.prune`
+    unspecified = 0,
+    prune = 1,
+    no_prune = 2,
+};
+
+/// Which HTTP redirects fetch, push and `Remote.connect` follow
+pub const FollowRedirects = enum(c_uint) {
+    /// Follow `http.followRedirects`
+    unspecified = 0,
+    none = 1,
+    /// Only redirects of the first request, which may move to another host
+    initial = 2,
+    all = 4,
+};
+
+/// Which tags a fetch downloads alongside the requested refs
+pub const DownloadTags = enum(c_uint) {
+    /// Follow `remote.<name>.tagOpt`
+    unspecified = 0,
+    /// Tags pointing at objects the fetch downloads
+    auto = 1,
+    none = 2,
+    all = 3,
+};
+
+/// Direction of a remote connection
+pub const Direction = enum(c_int) {
+    fetch = 0,
+    push = 1,
+};
+
+/// A ref advertised by a remote
+pub const RemoteHead = struct {
+    name: [:0]u8,
+    id: git_oid,
+    /// Target of a symbolic ref such as HEAD, if advertised
+    symref_target: ?[:0]u8,
+};
+
+pub fn freeRemoteHeads(allocator: std.mem.Allocator, heads: []RemoteHead) void {
+    for (heads) |head| {
+        allocator.free(head.name);
+        if (head.symref_target) |t| allocator.free(t);
+    }
+    allocator.free(heads);
+}
+
+/// Connection settings (callbacks, proxy, custom headers) libgit2 hands a
+/// transport; opaque here
+pub const git_remote_connect_options = opaque {};
+
+/// libgit2-internal push state passed to `Transport.push`
+pub const git_push = opaque {};
+
+pub const git_oidarray = extern struct {
+    ids: ?[*]git_oid = null,
+    count: usize = 0,
+};
+
+/// What a fetch asks a transport for in `Transport.negotiate_fetch`
+pub const FetchNegotiation = extern struct {
+    /// Remote refs the fetch wants
+    refs: [*]const *const git_remote_head,
+    refs_len: usize,
+    /// Current shallow boundary of the local repository
+    shallow_roots: ?[*]git_oid,
+    shallow_roots_len: usize,
+    /// Requested history depth; 0 for all of it
+    depth: c_int,
+};
+
+/// A custom transport: libgit2's `git_transport` vtable. Embed it as the
+/// first field of the implementation's state, return a pointer to it from
+/// a `git_transport_cb`, and recover the state in each slot with
+/// `@fieldParentPtr`. Slots return 0 on success or a negative error.
+pub const Transport = extern struct {
+    version: c_uint = 1,
+    /// `direction` is a `Direction`
+    connect: *const fn (transport: *Transport, url: [*:0]const u8, direction: c_int, connect_opts: *const git_remote_connect_options) callconv(.C) c_int,
+    /// Replace the options of an already-connected transport
+    set_connect_opts: *const fn (transport: *Transport, connect_opts: *const git_remote_connect_options) callconv(.C) c_int,
+    /// `GIT_REMOTE_CAPABILITY_*` bits the remote supports
+    capabilities: *const fn (capabilities: *c_uint, transport: *Transport) callconv(.C) c_int,
+    /// Advertised refs; the array stays owned by the transport and must
+    /// remain valid until its next call
+    ls: *const fn (out: *[*]const *const git_remote_head, size: *usize, transport: *Transport) callconv(.C) c_int,
+    /// Null for a fetch-only transport
+    push: ?*const fn (transport: *Transport, push: *git_push) callconv(.C) c_int = null,
+    negotiate_fetch: *const fn (transport: *Transport, repo: *git_repository, fetch_data: *const FetchNegotiation) callconv(.C) c_int,
+    /// Shallow boundary after the fetch; libgit2 disposes of `out`
+    shallow_roots: *const fn (out: *git_oidarray, transport: *Transport) callconv(.C) c_int,
+    /// Write the pack into `repo`'s object database, updating `stats`
+    download_pack: *const fn (transport: *Transport, repo: *git_repository, stats: *TransferProgress) callconv(.C) c_int,
+    is_connected: *const fn (transport: *Transport) callconv(.C) c_int,
+    cancel: ?*const fn (transport: *Transport) callconv(.C) void = null,
+    close: *const fn (transport: *Transport) callconv(.C) c_int,
+    /// Destroy the transport and its state
+    free: *const fn (transport: *Transport) callconv(.C) void,
+
+    /// From inside a smart subtransport owned by this transport: run the
+    /// remote's credentials callback for one of `methods` (`CredentialType`
+    /// bits) and return its result
+    pub fn smartCredentials(self: *Transport, out: *?*git_credential, user: ?[*:0]const u8, methods: c_uint) c_int {
+        return git2_shim_transport_smart_credentials(out, self, user, methods);
+    }
+
+    /// From inside a smart subtransport that set up its own TLS or SSH
+    /// session: run the remote's certificate check on `cert`
+    pub fn smartCertificateCheck(self: *Transport, cert: *git_cert, valid: bool, hostname: [*:0]const u8) c_int {
+        return git2_shim_transport_smart_certificate_check(self, cert, @intFromBool(valid), hostname);
+    }
+};
+
+/// Create a transport for `owner`, which is being connected to a URL
+/// matching the registered prefix; store it in `out`
+pub const git_transport_cb = *const fn (out: *?*Transport, owner: *git_remote, param: ?*anyopaque) callconv(.C) c_int;
+
+/// Handle URLs starting with `prefix` (e.g. "ipc://") with transports made
+/// by `factory`, process-wide. `param` must outlive the registration.
+pub fn registerTransport(allocator: std.mem.Allocator, prefix: []const u8, factory: git_transport_cb, param: ?*anyopaque) Error!void {
+    const prefix_z = allocator.dupeZ(u8, prefix) catch return Error.AllocationFailed;
+    defer allocator.free(prefix_z);
+    if (git2_shim_transport_register(prefix_z.ptr, factory, param) < 0) return Error.TransportFailed;
+}
+
+pub fn unregisterTransport(allocator: std.mem.Allocator, prefix: []const u8) Error!void {
+    const prefix_z = allocator.dupeZ(u8, prefix) catch return Error.AllocationFailed;
+    defer allocator.free(prefix_z);
+    if (git2_shim_transport_unregister(prefix_z.ptr) < 0) return Error.TransportFailed;
+}
+
+/// Git service a smart subtransport is asked to open a stream for
+pub const SmartService = enum(c_int) {
+    upload_pack_ls = 1,
+    upload_pack = 2,
+    receive_pack_ls = 3,
+    receive_pack = 4,
+};
+
+/// One byte stream of a smart subtransport (`git_smart_subtransport_stream`)
+pub const SmartStream = extern struct {
+    subtransport: *Subtransport,
+    /// Read up to `buf_size` bytes; fewer is fine
+    read: *const fn (stream: *SmartStream, buffer: [*]u8, buf_size: usize, bytes_read: *usize) callconv(.C) c_int,
+    /// Write all `len` bytes or fail
+    write: *const fn (stream: *SmartStream, buffer: [*]const u8, len: usize) callconv(.C) c_int,
+    free: *const fn (stream: *SmartStream) callconv(.C) void,
+};
+
+/// Byte-stream provider under libgit2's smart protocol
+/// (`git_smart_subtransport`); embed as the first field of its state
+pub const Subtransport = extern struct {
+    /// Open a stream to `url` for `service` (a `SmartService`). Stateful
+    /// subtransports may reuse one stream from `*_ls` to the matching
+    /// service; otherwise `close` runs between actions.
+    action: *const fn (out: *?*SmartStream, subtransport: *Subtransport, url: [*:0]const u8, service: c_int) callconv(.C) c_int,
+    close: *const fn (subtransport: *Subtransport) callconv(.C) c_int,
+    free: *const fn (subtransport: *Subtransport) callconv(.C) void,
+};
+
+/// Create a subtransport for the smart transport `owner`; store it in `out`
+pub const git_smart_subtransport_cb = *const fn (out: *?*Subtransport, owner: *Transport, param: ?*anyopaque) callconv(.C) c_int;
+
+/// A registered smart subtransport: fetch and push to URLs with `prefix`
+/// run the git protocol over streams the host application supplies, such
+/// as an existing authenticated tunnel
+pub const SmartTransport = struct {
+    allocator: std.mem.Allocator,
+    handle: *git2_shim_smart_subtransport,
+    prefix: [:0]u8,
+
+    /// `rpc` marks a stateless protocol where every request gets a fresh
+    /// stream, like HTTP; otherwise one stream carries the whole session
+    pub fn register(allocator: std.mem.Allocator, prefix: []const u8, factory: git_smart_subtransport_cb, rpc: bool, param: ?*anyopaque) Error!SmartTransport {
+        const handle = git2_shim_smart_subtransport_new(factory, @intFromBool(rpc), param) orelse return Error.AllocationFailed;
+        errdefer git2_shim_smart_subtransport_free(handle);
+
+        const prefix_z = allocator.dupeZ(u8, prefix) catch return Error.AllocationFailed;
+        errdefer allocator.free(prefix_z);
+
+        if (git2_shim_smart_subtransport_register(prefix_z.ptr, handle) < 0) return Error.TransportFailed;
+        return SmartTransport{ .allocator = allocator, .handle = handle, .prefix = prefix_z };
+    }
+
+    /// Unregister the prefix and release the registration
+    pub fn deinit(self: *SmartTransport) void {
+        _ = git2_shim_transport_unregister(self.prefix.ptr);
+        git2_shim_smart_subtransport_free(self.handle);
+        self.allocator.free(self.prefix);
+    }
+};
+
+/// A parsed refspec such as "+refs/heads/*:refs/remotes/origin/*"
+pub const Refspec = struct {
+    spec: *git_refspec,
+
+    pub fn parse(allocator: std.mem.Allocator, input: []const u8, kind: Direction) Error!Refspec {
+        const input_z = allocator.dupeZ(u8, input) catch return Error.AllocationFailed;
+        defer allocator.free(input_z);
+
+        var spec: ?*git_refspec = null;
+        if (git2_shim_refspec_parse(&spec, input_z.ptr, @intFromBool(kind == .fetch)) < 0) {
+            return Error.RefspecFailed;
+        }
+        return Refspec{ .spec = spec.? };
+    }
+
+    pub fn deinit(self: *Refspec) void {
+        git2_shim_refspec_free(self.spec);
+    }
+
+    pub fn src(self: Refspec) []const u8 {
+        return std.mem.span(git2_shim_refspec_src(self.spec));
+    }
+
+    pub fn dst(self: Refspec) []const u8 {
+        return std.mem.span(git2_shim_refspec_dst(self.spec));
+    }
+
+    /// The refspec as written
+    pub fn string(self: Refspec) []const u8 {
+        return std.mem.span(git2_shim_refspec_string(self.spec));
+    }
+
+    /// Whether updates are forced ("+" prefix)
+    pub fn force(self: Refspec) bool {
+        return git2_shim_refspec_force(self.spec) != 0;
+    }
+
+    pub fn direction(self: Refspec) Direction {
+        return @enumFromInt(git2_shim_refspec_direction(self.spec));
+    }
+
+    pub fn srcMatches(self: Refspec, allocator: std.mem.Allocator, refname: []const u8) Error!bool {
+        const refname_z = allocator.dupeZ(u8, refname) catch return Error.AllocationFailed;
+        defer allocator.free(refname_z);
+        return git2_shim_refspec_src_matches(self.spec, refname_z.ptr) != 0;
+    }
+
+    pub fn dstMatches(self: Refspec, allocator: std.mem.Allocator, refname: []const u8) Error!bool {
+        const refname_z = allocator.dupeZ(u8, refname) catch return Error.AllocationFailed;
+        defer allocator.free(refname_z);
+        return git2_shim_refspec_dst_matches(self.spec, refname_z.ptr) != 0;
+    }
+
+    /// Map a source ref to its destination, e.g. "refs/heads/foo" to
+    /// "refs/remotes/origin/foo"; caller owns the result
+    pub fn transform(self: Refspec, allocator: std.mem.Allocator, refname: []const u8) Error![:0]u8 {
+        return self.map(allocator, &git2_shim_refspec_transform, refname);
+    }
+
+    /// Map a destination ref back to its source; caller owns the result
+    pub fn rtransform(self: Refspec, allocator: std.mem.Allocator, refname: []const u8) Error![:0]u8 {
+        return self.map(allocator, &git2_shim_refspec_rtransform, refname);
+    }
+
+    fn map(
+        self: Refspec,
+        allocator: std.mem.Allocator,
+        func: *const fn (*git_buf, *const git_refspec, [*:0]const u8) callconv(.C) c_int,
+        refname: []const u8,
+    ) Error![:0]u8 {
+        const refname_z = allocator.dupeZ(u8, refname) catch return Error.AllocationFailed;
+        defer allocator.free(refname_z);
+
+        var buf = git_buf{ .ptr = null, .reserved = 0, .size = 0 };
+        if (func(&buf, self.spec, refname_z.ptr) < 0) {
+            return Error.RefspecFailed;
+        }
+        return takeBuf(allocator, &buf);
+    }
+};
+
+/// One ref recorded in FETCH_HEAD by the last fetch
+pub const FetchHeadEntry = struct {
+    /// e.g. "refs/heads/main"; null when the remote's HEAD itself was
+    /// fetched
+    ref_name: ?[:0]u8,
+    /// null in FETCH_HEAD files written without one
+    remote_url: ?[:0]u8,
+    id: git_oid,
+    /// Whether a pull would merge this ref, i.e. it was the branch fetched
+    /// for the current branch's upstream
+    is_merge: bool,
+};
+
+/// Free a list returned by `Repository.fetchHead`
+pub fn freeFetchHeadList(allocator: std.mem.Allocator, entries: []FetchHeadEntry) void {
+    for (entries) |entry| {
+        if (entry.ref_name) |name| allocator.free(name);
+        if (entry.remote_url) |url| allocator.free(url);
+    }
+    allocator.free(entries);
+}
+
+const FetchHeadCollector = struct {
+    allocator: std.mem.Allocator,
+    entries: std.ArrayList(FetchHeadEntry),
+
+    fn collect(ref_name: ?[*:0]const u8, remote_url: ?[*:0]const u8, oid: *const git_oid, is_merge: c_uint, payload: ?*anyopaque) callconv(.C) c_int {
+        const self: *FetchHeadCollector = @ptrCast(@alignCast(payload.?));
+        const name: ?[:0]u8 = if (ref_name) |n| self.allocator.dupeZ(u8, std.mem.span(n)) catch return -1 else null;
+        const url: ?[:0]u8 = if (remote_url) |u| self.allocator.dupeZ(u8, std.mem.span(u)) catch {
+            if (name) |n| self.allocator.free(n);
+            return -1;
+        } else null;
+        self.entries.append(.{ .ref_name = name, .remote_url = url, .id = oid.*, .is_merge = is_merge != 0 }) catch {
+            if (name) |n| self.allocator.free(n);
+            if (url) |u| self.allocator.free(u);
+            return -1;
+        };
+        return 0;
+    }
+};
+
+/// What `Remote.updateTips` writes and reports besides moving refs
+pub const UpdateTipsFlags = struct {
+    /// Record the fetched refs in FETCH_HEAD
+    fetchhead: bool = true,
+    /// Call `update_tips_cb` for refs that did not move, too
+    report_unchanged: bool = false,
+
+    fn bits(self: UpdateTipsFlags) c_uint {
+        var flags: c_uint = 0;
+        if (self.fetchhead) flags |= 1 << 0;
+        if (self.report_unchanged) flags |= 1 << 1;
+        return flags;
+    }
+};
+
+/// Options for fetches, including the fetch a clone performs
+pub const FetchOptions = struct {
+    /// Commits of history to fetch from each tip; 0 fetches everything
+    /// and `unshallow` completes a shallow repository
+    depth: c_int = 0,
+    prune: FetchPrune = .unspecified,
+    download_tags: DownloadTags = .unspecified,
+    /// Partial clone filter such as "blob:none" or "tree:0"; fails with
+    /// `Unsupported` unless `supportsFilter()`
+    filter: ?[]const u8 = null,
+    callbacks: RemoteCallbacks = .{},
+    follow_redirects: FollowRedirects = .unspecified,
+    proxy: ProxyOptions = .{},
+    /// Extra HTTP headers, e.g. "Authorization: Bearer <token>"
+    custom_headers: []const []const u8 = &.{},
+
+    pub const unshallow: c_int = std.math.maxInt(c_int);
+
+    /// Whether the linked libgit2 can do partial (blobless or treeless)
+    /// fetches; when false, fall back to `depth` or a full fetch
+    pub fn supportsFilter() bool {
+        return git2_shim_fetch_filter_supported() != 0;
+    }
+
+    fn create(self: FetchOptions, allocator: std.mem.Allocator) Error!*git2_shim_fetch_options {
+        const opts = git2_shim_fetch_options_new() orelse return Error.AllocationFailed;
+        errdefer git2_shim_fetch_options_free(opts);
+
+        git2_shim_fetch_options_set_depth(opts, self.depth);
+        git2_shim_fetch_options_set_prune(opts, @intFromEnum(self.prune));
+        git2_shim_fetch_options_set_download_tags(opts, @intFromEnum(self.download_tags));
+        git2_shim_fetch_options_set_follow_redirects(opts, @intFromEnum(self.follow_redirects));
+
+        const filter_z = try dupeZOpt(allocator, self.filter);
+        defer if (filter_z) |f| allocator.free(f);
+        if (git2_shim_fetch_options_set_filter(opts, if (filter_z) |f| f.ptr else null) < 0) {
+            return Error.Unsupported;
+        }
+
+        const callbacks = try self.callbacks.create();
+        defer git2_shim_remote_callbacks_free(callbacks);
+        git2_shim_fetch_options_set_callbacks(opts, callbacks);
+
+        const proxy = try self.proxy.create(allocator);
+        defer git2_shim_proxy_options_free(proxy);
+        git2_shim_fetch_options_set_proxy_options(opts, proxy);
+
+        const headers = try dupeZList(allocator, self.custom_headers);
+        defer freeZList(allocator, headers);
+        git2_shim_fetch_options_set_custom_headers(opts, headers.ptr, headers.len);
+
+        return opts;
+    }
+};
+
+/// How `Repository.clone` treats a source that is a local path
+pub const CloneLocal = enum(c_uint) {
+    /// Local shortcuts for plain paths, regular transport for file:// URLs
+    auto = 0,
+    /// Bypass the transport and hardlink objects where possible
+    local = 1,
+    /// Always use the regular transport
+    no_local = 2,
+    /// Bypass the transport but copy objects instead of hardlinking
+    local_no_links = 3,
+};
+
+/// Options for `Repository.clone`
+pub const CloneOptions = struct {
+    /// Create a bare repository with no working directory
+    bare: bool = false,
+    /// Branch to check out instead of the remote's default
+    checkout_branch: ?[]const u8 = null,
+    local: CloneLocal = .auto,
+    checkout: CheckoutOptions = .{},
+    /// Set `fetch.depth = 1` for a fast single-commit CI clone
+    fetch: FetchOptions = .{},
+
+    fn create(self: CloneOptions, allocator: std.mem.Allocator) Error!*git2_shim_clone_options {
+        const opts = git2_shim_clone_options_new() orelse return Error.AllocationFailed;
+        errdefer git2_shim_clone_options_free(opts);
+
+        git2_shim_clone_options_set_bare(opts, @intFromBool(self.bare));
+        git2_shim_clone_options_set_local(opts, @intFromEnum(self.local));
+
+        const branch = try dupeZOpt(allocator, self.checkout_branch);
+        defer if (branch) |b| allocator.free(b);
+        git2_shim_clone_options_set_checkout_branch(opts, if (branch) |b| b.ptr else null);
+
+        const checkout_opts = try self.checkout.create(allocator);
+        defer git2_shim_checkout_options_free(checkout_opts);
+        git2_shim_clone_options_set_checkout_options(opts, checkout_opts);
+
+        const fetch_opts = try self.fetch.create(allocator);
+        defer git2_shim_fetch_options_free(fetch_opts);
+        git2_shim_clone_options_set_fetch_options(opts, fetch_opts);
+
+        return opts;
+    }
+};
+
+/// Options for `Repository.maintenance`
+pub const MaintenanceOptions = struct {
+    /// Pack all reachable objects into one pack, then delete their loose
+    /// copies and the older packs (without a `.keep` file) it replaces
+    repack: bool = true,
+    /// Delete unreachable loose objects modified before `prune_expire`
+    prune: bool = true,
+    /// Unix time of the prune cutoff; null means two weeks ago
+    prune_expire: ?i64 = null,
+
+    fn create(self: MaintenanceOptions) Error!*git2_shim_maintenance_options {
+        const opts = git2_shim_maintenance_options_new() orelse return Error.AllocationFailed;
+        git2_shim_maintenance_options_set_repack(opts, @intFromBool(self.repack));
+        git2_shim_maintenance_options_set_prune(opts, @intFromBool(self.prune));
+        if (self.prune_expire) |expire| git2_shim_maintenance_options_set_prune_expire(opts, expire);
+        return opts;
+    }
+};
+
+/// What `Repository.maintenance` did
+pub const MaintenanceStats = extern struct {
+    objects_packed: usize = 0,
+    packs_removed: usize = 0,
+    /// Loose objects deleted because the new pack holds them
+    loose_removed: usize = 0,
+    /// Unreachable loose objects deleted as older than the cutoff
+    loose_pruned: usize = 0,
+};
+
+/// What `Repository.fsck` checks; the defaults match `git fsck`
+pub const FsckOptions = struct {
+    /// Only check that reachable objects exist and link up, like
+    /// `git fsck --connectivity-only`: blobs are only looked up and
+    /// unreachable objects skipped; commits, trees and tags are still read
+    /// (and so hash-checked by libgit2)
+    connectivity_only: bool = false,
+    /// Also report stored objects nothing reaches, like
+    /// `git fsck --unreachable`
+    unreachable: bool = false,
+    /// Count reflog entries as roots, as git does by default
+    reflogs: bool = true,
+
+    fn create(self: FsckOptions) Error!*git2_shim_fsck_options {
+        const opts = git2_shim_fsck_options_new() orelse return Error.AllocationFailed;
+        git2_shim_fsck_options_set_connectivity_only(opts, @intFromBool(self.connectivity_only));
+        git2_shim_fsck_options_set_unreachable(opts, @intFromBool(self.unreachable));
+        git2_shim_fsck_options_set_reflogs(opts, @intFromBool(self.reflogs));
+        return opts;
+    }
+};
+
+/// Kind of problem found by `Repository.fsck`
+pub const FsckProblem = enum(c_int) {
+    /// Linked to but absent from the object database
+    missing = 1,
+    /// Unreadable, unparsable, or not the type its link claims
+    corrupt = 2,
+    /// Content does not hash to the object's id
+    hash_mismatch = 3,
+    /// Stored but not reachable; only with `FsckOptions.unreachable`
+    unreachable = 4,
+    _,
+};
+
+/// One problem found by `Repository.fsck`
+pub const FsckReport = struct {
+    problem: FsckProblem,
+    id: git_oid,
+    /// The object's type, or the type its link expects when missing
+    kind: ObjectType,
+    /// What links to the object: a ref, a reflog entry such as
+    /// "HEAD@{2}", ":<path>" for an index entry, or the hex id of a
+    /// commit, tree or tag; null for unreachable objects
+    referrer: ?[:0]u8,
+    /// What is wrong with corrupt and mismatched objects
+    detail: ?[:0]u8,
+};
+
+/// Free a list returned by `Repository.fsck`
+pub fn freeFsckReports(allocator: std.mem.Allocator, reports: []FsckReport) void {
+    for (reports) |report| {
+        if (report.referrer) |r| allocator.free(r);
+        if (report.detail) |d| allocator.free(d);
+    }
+    allocator.free(reports);
+}
+
+const FsckCollector = struct {
+    allocator: std.mem.Allocator,
+    reports: std.ArrayList(FsckReport),
+
+    fn deinit(self: *FsckCollector) void {
+        for (self.reports.items) |report| {
+            if (report.referrer) |r| self.allocator.free(r);
+            if (report.detail) |d| self.allocator.free(d);
+        }
+        self.reports.deinit();
+    }
+
+    fn collect(problem: c_int, id: *const git_oid, kind: c_int, referrer: ?[*:0]const u8, detail: ?[*:0]const u8, payload: ?*anyopaque) callconv(.C) c_int {
+        const self: *FsckCollector = @ptrCast(@alignCast(payload.?));
+        const referrer_copy = if (referrer) |r| self.allocator.dupeZ(u8, std.mem.span(r)) catch return -1 else null;
+        const detail_copy = if (detail) |d| self.allocator.dupeZ(u8, std.mem.span(d)) catch {
+            if (referrer_copy) |r| self.allocator.free(r);
+            return -1;
+        } else null;
+        self.reports.append(.{
+            .problem = @enumFromInt(problem),
+            .id = id.*,
+            .kind = @enumFromInt(kind),
+            .referrer = referrer_copy,
+            .detail = detail_copy,
+        }) catch {
+            if (referrer_copy) |r| self.allocator.free(r);
+            if (detail_copy) |d| self.allocator.free(d);
+            return -1;
+        };
+        return 0;
+    }
+};
+
+/// Result of `Repository.mergeAnalysis`
+pub const MergeAnalysis = struct {
+    /// Both sides diverged; a real merge is required
+    normal: bool = false,
+    /// All inputs are already reachable; nothing to do
+    up_to_date: bool = false,
+    /// The input can be checked out as a fast-forward
+    fastforward: bool = false,
+    /// HEAD is unborn; the caller may simply point HEAD at the input
+    unborn: bool = false,
+    /// `merge.ff=false` is configured
+    prefer_no_fastforward: bool = false,
+    /// `merge.ff=only` is configured
+    prefer_fastforward_only: bool = false,
+
+    fn fromBits(analysis: c_uint, preference: c_uint) MergeAnalysis {
+        return .{
+            .normal = analysis & (1 << 0) != 0,
+            .up_to_date = analysis & (1 << 1) != 0,
+            .fastforward = analysis & (1 << 2) != 0,
+            .unborn = analysis & (1 << 3) != 0,
+            .prefer_no_fastforward = preference & (1 << 0) != 0,
+            .prefer_fastforward_only = preference & (1 << 1) != 0,
+        };
+    }
+};
+
+/// Annotated commits resolved from revspecs, as passed to merge calls
+const AnnotatedHeads = struct {
+    allocator: std.mem.Allocator,
+    items: []*const git_annotated_commit,
+
+    fn resolve(repo: *git_repository, allocator: std.mem.Allocator, revspecs: []const []const u8) Error!AnnotatedHeads {
+        const items = allocator.alloc(*const git_annotated_commit, revspecs.len) catch return Error.AllocationFailed;
+        var resolved: usize = 0;
+        errdefer {
+            for (items[0..resolved]) |head| git2_shim_annotated_commit_free(@constCast(head));
+            allocator.free(items);
+        }
+
+        for (revspecs) |revspec| {
+            const revspec_z = allocator.dupeZ(u8, revspec) catch return Error.AllocationFailed;
+            defer allocator.free(revspec_z);
+
+            var commit: ?*git_annotated_commit = null;
+            if (git2_shim_annotated_commit_from_revspec(&commit, repo, revspec_z.ptr) < 0) {
+                return Error.ReferenceFailed;
+            }
+            items[resolved] = commit.?;
+            resolved += 1;
+        }
+        return .{ .allocator = allocator, .items = items };
+    }
+
+    fn deinit(self: *AnnotatedHeads) void {
+        for (self.items) |head| git2_shim_annotated_commit_free(@constCast(head));
+        self.allocator.free(self.items);
+    }
+
+    fn first(self: *const AnnotatedHeads) ?*const git_annotated_commit {
+        return if (self.items.len > 0) self.items[0] else null;
+    }
+};
+
+fn resolveOptionalHead(repo: *git_repository, allocator: std.mem.Allocator, revspec: ?[]const u8) Error!AnnotatedHeads {
+    const spec = revspec orelse return AnnotatedHeads.resolve(repo, allocator, &.{});
+    return AnnotatedHeads.resolve(repo, allocator, &.{spec});
+}
+
+/// Options for `Repository.cherrypick`
+pub const CherrypickOptions = struct {
+    /// 1-based parent to diff against when picking a merge commit; 0 otherwise
+    mainline: c_uint = 0,
+    merge: MergeOptions = .{},
+    checkout: CheckoutOptions = .{},
+};
+
+/// Options for `Repository.revert`
+pub const RevertOptions = struct {
+    /// 1-based parent whose side is kept when reverting a merge commit; 0 otherwise
+    mainline: c_uint = 0,
+    merge: MergeOptions = .{},
+    checkout: CheckoutOptions = .{},
+};
+
+/// Kind of step in a rebase plan
+pub const RebaseOperationType = enum(c_uint) {
+    pick = 0,
+    reword,
+    edit,
+    squash,
+    fixup,
+    exec,
+};
+
+/// A single step of a rebase
+pub const RebaseOperation = struct {
+    type: RebaseOperationType,
+    /// Commit being applied; zeroed for exec steps
+    id: git_oid,
+    /// Command to run for exec steps
+    exec: ?[]const u8,
+
+    fn fromRaw(op: *const git_rebase_operation) RebaseOperation {
+        const exec = git2_shim_rebase_operation_exec(op);
+        return .{
+            .type = @enumFromInt(git2_shim_rebase_operation_type(op)),
+            .id = git2_shim_rebase_operation_id(op).*,
+            .exec = if (exec) |e| std.mem.span(e) else null,
+        };
+    }
+};
+
+/// Options for `Repository.rebaseInit` and `Repository.rebaseOpen`
+pub const RebaseOptions = struct {
+    quiet: bool = false,
+    /// Leave the working directory and repository index alone: each step's
+    /// result is available via `Rebase.inmemoryIndex`, commits are written
+    /// without moving any ref, and `finish` does nothing. Works on bare repos.
+    inmemory: bool = false,
+    /// Notes ref to rewrite on finish; null uses `notes.rewriteRef`
+    rewrite_notes_ref: ?[]const u8 = null,
+    merge: MergeOptions = .{},
+    checkout: CheckoutOptions = .{},
+    /// Sign every commit the rebase writes
+    signing_cb: ?git_commit_signing_cb = null,
+    signing_payload: ?*anyopaque = null,
+
+    fn create(self: RebaseOptions, allocator: std.mem.Allocator) Error!*git2_shim_rebase_options {
+        const opts = git2_shim_rebase_options_new() orelse return Error.AllocationFailed;
+        errdefer git2_shim_rebase_options_free(opts);
+
+        git2_shim_rebase_options_set_quiet(opts, @intFromBool(self.quiet));
+        git2_shim_rebase_options_set_inmemory(opts, @intFromBool(self.inmemory));
+        git2_shim_rebase_options_set_signing_cb(opts, self.signing_cb, self.signing_payload);
+
+        const notes_ref = try dupeZOpt(allocator, self.rewrite_notes_ref);
+        defer if (notes_ref) |r| allocator.free(r);
+        git2_shim_rebase_options_set_rewrite_notes_ref(opts, if (notes_ref) |r| r.ptr else null);
+
+        const merge_opts = try self.merge.create();
+        defer git2_shim_merge_options_free(merge_opts);
+        git2_shim_rebase_options_set_merge_options(opts, merge_opts);
+
+        const checkout_opts = try self.checkout.create(allocator);
+        defer git2_shim_checkout_options_free(checkout_opts);
+        git2_shim_rebase_options_set_checkout_options(opts, checkout_opts);
+
+        return opts;
+    }
+};
+
+/// A rebase in progress, driven one operation at a time
+pub const Rebase = struct {
+    rebase: *git_rebase,
+    opts: *git2_shim_rebase_options,
+    repo: *git_repository,
+
+    pub fn deinit(self: *Rebase) void {
+        git2_shim_rebase_free(self.rebase);
+        git2_shim_rebase_options_free(self.opts);
+    }
+
+    /// Number of operations in the rebase plan
+    pub fn operationCount(self: *Rebase) usize {
+        return git2_shim_rebase_operation_entrycount(self.rebase);
+    }
+
+    /// Index of the operation being applied; null before the first `next`
+    pub fn currentOperation(self: *Rebase) ?usize {
+        const idx = git2_shim_rebase_operation_current(self.rebase);
+        if (idx == std.math.maxInt(usize)) return null;
+        return idx;
+    }
+
+    pub fn operationAt(self: *Rebase, idx: usize) ?RebaseOperation {
+        const op = git2_shim_rebase_operation_byindex(self.rebase, idx) orelse return null;
+        return RebaseOperation.fromRaw(op);
+    }
+
+    /// Apply the next operation; null once the plan is exhausted
+    pub fn next(self: *Rebase) Error!?RebaseOperation {
+        var op: ?*git_rebase_operation = null;
+        const rc = git2_shim_rebase_next(&op, self.rebase);
+        if (rc == GIT_ITEROVER) return null;
+        try rebaseError(rc);
+        return RebaseOperation.fromRaw(op.?);
+    }
+
+    /// Commit the current operation as the configured user, keeping the
+    /// original author. A null `message` keeps the original message.
+    /// Fails with `RebaseConflicts` while the index has conflicts and with
+    /// `RebaseAlreadyApplied` when the step changed nothing.
+    pub fn commit(self: *Rebase, allocator: std.mem.Allocator, message: ?[]const u8) Error!git_oid {
+        var sig: ?*git_signature = null;
+        if (git2_shim_signature_default(&sig, self.repo) < 0) {
+            return Error.SignatureFailed;
+        }
+        defer git2_shim_signature_free(sig.?);
+
+        const message_z = try dupeZOpt(allocator, message);
+        defer if (message_z) |m| allocator.free(m);
+
+        var oid: git_oid = undefined;
+        try rebaseError(git2_shim_rebase_commit(&oid, self.rebase, null, sig.?, if (message_z) |m| m.ptr else null));
+        return oid;
+    }
+
+    fn rebaseError(rc: c_int) Error!void {
+        if (rc == GIT_EAPPLIED) return Error.RebaseAlreadyApplied;
+        if (rc == GIT_EUNMERGED) return Error.RebaseConflicts;
+        if (rc < 0) return Error.RebaseFailed;
+    }
+
+    /// Index holding the current step's result in an in-memory rebase
+    pub fn inmemoryIndex(self: *Rebase) Error!Index {
+        var index: ?*git_index = null;
+        if (git2_shim_rebase_inmemory_index(&index, self.rebase) < 0) {
+            return Error.RebaseFailed;
+        }
+        return Index{ .index = index.? };
+    }
+
+    /// Abort, restoring the branch and working directory
+    pub fn abort(self: *Rebase) Error!void {
+        if (git2_shim_rebase_abort(self.rebase) < 0) {
+            return Error.RebaseFailed;
+        }
+    }
+
+    /// Finish, moving the branch to the rewritten commits
+    pub fn finish(self: *Rebase) Error!void {
+        if (git2_shim_rebase_finish(self.rebase, null) < 0) {
+            return Error.RebaseFailed;
+        }
+    }
+
+    /// Full name of the branch being rebased (e.g. `refs/heads/feature`);
+    /// null when rebasing a detached HEAD
+    pub fn branchName(self: *Rebase) ?[]const u8 {
+        const name = git2_shim_rebase_orig_head_name(self.rebase) orelse return null;
+        return std.mem.span(name);
+    }
+
+    /// Commit the branch pointed at before the rebase started
+    pub fn origHeadId(self: *Rebase) git_oid {
+        return git2_shim_rebase_orig_head_id(self.rebase).*;
+    }
+
+    /// Reference name the rebase was started onto (e.g. `refs/heads/main`),
+    /// or the commit id in hex when it was given as a bare commit
+    pub fn ontoName(self: *Rebase) []const u8 {
+        return std.mem.span(git2_shim_rebase_onto_name(self.rebase));
+    }
+
+    /// Commit the rewritten operations are applied on top of
+    pub fn ontoId(self: *Rebase) git_oid {
+        return git2_shim_rebase_onto_id(self.rebase).*;
+    }
+};
+
+/// One side of a file-content merge
+pub const MergeFileInput = struct {
+    content: []const u8,
+    /// Used to pick the result's path; optional
+    path: ?[]const u8 = null,
+    /// Used to pick the result's mode; 0 if unknown
+    mode: c_uint = 0,
+};
+
+/// Options for `mergeFile` and `Repository.mergeFileFromIndex`
+pub const MergeFileOptions = struct {
+    ancestor_label: ?[]const u8 = null,
+    our_label: ?[]const u8 = null,
+    their_label: ?[]const u8 = null,
+    favor: MergeFileFavor = .normal,
+    /// `MergeFileFlags`, including the conflict marker style
+    flags: u32 = MergeFileFlags.default,
+    /// Length of conflict markers; null keeps libgit2's default (7)
+    marker_size: ?u16 = null,
+
+    fn create(self: MergeFileOptions, allocator: std.mem.Allocator) Error!*git2_shim_merge_file_options {
+        const opts = git2_shim_merge_file_options_new() orelse return Error.AllocationFailed;
+        errdefer git2_shim_merge_file_options_free(opts);
+
+        const ancestor = try dupeZOpt(allocator, self.ancestor_label);
+        defer if (ancestor) |a| allocator.free(a);
+        const ours = try dupeZOpt(allocator, self.our_label);
+        defer if (ours) |o| allocator.free(o);
+        const theirs = try dupeZOpt(allocator, self.their_label);
+        defer if (theirs) |t| allocator.free(t);
+        git2_shim_merge_file_options_set_labels(
+            opts,
+            if (ancestor) |a| a.ptr else null,
+            if (ours) |o| o.ptr else null,
+            if (theirs) |t| t.ptr else null,
+        );
+
+        git2_shim_merge_file_options_set_favor(opts, @intFromEnum(self.favor));
+        git2_shim_merge_file_options_set_flags(opts, self.flags);
+        if (self.marker_size) |size| git2_shim_merge_file_options_set_marker_size(opts, size);
+        return opts;
+    }
+};
+
+/// Merged file contents produced by `mergeFile`
+pub const MergeFileResult = struct {
+    result: *git2_shim_merge_file_result,
+
+    pub fn deinit(self: *MergeFileResult) void {
+        git2_shim_merge_file_result_free(self.result);
+    }
+
+    /// True when the merge produced no conflicts
+    pub fn automergeable(self: *const MergeFileResult) bool {
+        return git2_shim_merge_file_result_automergeable(self.result) != 0;
+    }
+
+    /// Merged contents, including conflict markers when not automergeable
+    pub fn content(self: *const MergeFileResult) []const u8 {
+        var len: usize = 0;
+        const ptr = git2_shim_merge_file_result_content(self.result, &len) orelse return "";
+        return ptr[0..len];
+    }
+
+    /// Path chosen for the result; null if the sides disagree on a rename
+    pub fn path(self: *const MergeFileResult) ?[]const u8 {
+        const p = git2_shim_merge_file_result_path(self.result) orelse return null;
+        return std.mem.span(p);
+    }
+
+    pub fn mode(self: *const MergeFileResult) c_uint {
+        return git2_shim_merge_file_result_mode(self.result);
+    }
+};
+
+/// Three-way merge of in-memory file contents; a null `ancestor` merges
+/// without a common base
+pub fn mergeFile(
+    allocator: std.mem.Allocator,
+    ancestor: ?MergeFileInput,
+    ours: MergeFileInput,
+    theirs: MergeFileInput,
+    options: MergeFileOptions,
+) Error!MergeFileResult {
+    const ancestor_path = try dupeZOpt(allocator, if (ancestor) |a| a.path else null);
+    defer if (ancestor_path) |p| allocator.free(p);
+    const our_path = try dupeZOpt(allocator, ours.path);
+    defer if (our_path) |p| allocator.free(p);
+    const their_path = try dupeZOpt(allocator, theirs.path);
+    defer if (their_path) |p| allocator.free(p);
+
+    const ancestor_in: ?git2_shim_merge_file_input = if (ancestor) |a| .{
+        .ptr = a.content.ptr,
+        .size = a.content.len,
+        .path = if (ancestor_path) |p| p.ptr else null,
+        .mode = a.mode,
+    } else null;
+    const ours_in = git2_shim_merge_file_input{
+        .ptr = ours.content.ptr,
+        .size = ours.content.len,
+        .path = if (our_path) |p| p.ptr else null,
+        .mode = ours.mode,
+    };
+    const theirs_in = git2_shim_merge_file_input{
+        .ptr = theirs.content.ptr,
+        .size = theirs.content.len,
+        .path = if (their_path) |p| p.ptr else null,
+        .mode = theirs.mode,
+    };
+
+    const opts = try options.create(allocator);
+    defer git2_shim_merge_file_options_free(opts);
+
+    var result: ?*git2_shim_merge_file_result = null;
+    if (git2_shim_merge_file(&result, if (ancestor_in) |*a| a else null, &ours_in, &theirs_in, opts) < 0) {
+        return Error.MergeFailed;
+    }
+    return MergeFileResult{ .result = result.? };
+}
+
+/// Index handle, e.g. the in-memory result of `Repository.mergeCommits`
+pub const Index = struct {
+    index: *git_index,
+
+    pub fn deinit(self: *Index) void {
+        git2_shim_index_free(self.index);
+    }
+
+    /// Number of entries in the index
+    pub fn entryCount(self: *Index) usize {
+        return git2_shim_index_entrycount(self.index);
+    }
+
+    /// Check whether any entries are conflicted
+    pub fn hasConflicts(self: *Index) bool {
+        return git2_shim_index_has_conflicts(self.index) != 0;
+    }
+
+    /// Write the index as a tree into `repo`; fails while conflicts remain
+    pub fn writeTreeTo(self: *Index, repo: *Repository) Error!git_oid {
+        var oid: git_oid = undefined;
+        if (git2_shim_index_write_tree_to(&oid, self.index, repo.repo) < 0) {
+            return Error.IndexFailed;
+        }
+        return oid;
+    }
+
+    /// Reload from disk if the file changed, or always when `force` is set
+    /// (dropping unsaved changes)
+    pub fn read(self: *Index, force: bool) Error!void {
+        if (git2_shim_index_read(self.index, @intFromBool(force)) < 0) {
+            return Error.IndexFailed;
+        }
+    }
+
+    /// Save to the index file; only meaningful for `Repository.openIndex`
+    pub fn write(self: *Index) Error!void {
+        if (git2_shim_index_write(self.index) < 0) {
+            return Error.IndexFailed;
+        }
+    }
+};
+
+/// Object database of a repository (see `Repository.openOdb`), or a
+/// standalone one to wrap with `Repository.wrapOdb`
+pub const Odb = struct {
+    odb: *git_odb,
+
+    /// An empty database to fill with `addPack` and `addAlternate`
+    pub fn new() Error!Odb {
+        try Repository.ensureInit();
+
+        var handle: ?*git_odb = null;
+        if (git2_shim_odb_new(&handle) < 0) {
+            return Error.OdbFailed;
+        }
+        return Odb{ .odb = handle.? };
+    }
+
+    /// The loose objects and packs under `objects_dir` (e.g. a
+    /// `.git/objects` directory), following its `info/alternates`
+    pub fn open(allocator: std.mem.Allocator, objects_dir: []const u8) Error!Odb {
+        try Repository.ensureInit();
+
+        const dir_z = allocator.dupeZ(u8, objects_dir) catch return Error.AllocationFailed;
+        defer allocator.free(dir_z);
+
+        var handle: ?*git_odb = null;
+        if (git2_shim_odb_open(&handle, dir_z.ptr) < 0) {
+            return Error.OdbFailed;
+        }
+        return Odb{ .odb = handle.? };
+    }
+
+    /// Also read from another objects directory, after the existing sources
+    pub fn addAlternate(self: *Odb, allocator: std.mem.Allocator, objects_dir: []const u8) Error!void {
+        const dir_z = allocator.dupeZ(u8, objects_dir) catch return Error.AllocationFailed;
+        defer allocator.free(dir_z);
+
+        if (git2_shim_odb_add_disk_alternate(self.odb, dir_z.ptr) < 0) {
+            return Error.OdbFailed;
+        }
+    }
+
+    /// Also read from one pack, given the path of its `.idx` file
+    pub fn addPack(self: *Odb, allocator: std.mem.Allocator, index_path: []const u8) Error!void {
+        const path_z = allocator.dupeZ(u8, index_path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+
+        if (git2_shim_odb_add_pack(self.odb, path_z.ptr) < 0) {
+            return Error.OdbFailed;
+        }
+    }
+
+    pub fn deinit(self: *Odb) void {
+        git2_shim_odb_free(self.odb);
+    }
+
+    pub fn exists(self: *Odb, id: *const git_oid) bool {
+        return git2_shim_odb_exists(self.odb, id) == 1;
+    }
+
+    /// Pick up packs written by other processes since opening
+    pub fn refresh(self: *Odb) Error!void {
+        if (git2_shim_odb_refresh(self.odb) < 0) {
+            return Error.OdbFailed;
+        }
+    }
+};
+
+/// Reference database of a repository; see `Repository.openRefdb`
+pub const Refdb = struct {
+    refdb: *git_refdb,
+
+    pub fn deinit(self: *Refdb) void {
+        git2_shim_refdb_free(self.refdb);
+    }
+
+    /// Pack loose references into `packed-refs`, like `git pack-refs --all`
+    pub fn compress(self: *Refdb) Error!void {
+        if (git2_shim_refdb_compress(self.refdb) < 0) {
+            return Error.ReferenceFailed;
+        }
+    }
+};
+
+/// A single entry of the stash list
+pub const StashEntry = struct {
+    index: usize,
+    message: []const u8,
+    id: git_oid,
+};
+
+/// Free a list returned by `Repository.stashList`
+pub fn freeStashList(allocator: std.mem.Allocator, entries: []StashEntry) void {
+    for (entries) |entry| allocator.free(entry.message);
+    allocator.free(entries);
+}
+
+const StashCollector = struct {
+    allocator: std.mem.Allocator,
+    entries: std.ArrayList(StashEntry),
+
+    fn collect(index: usize, message: ?[*:0]const u8, stash_id: *const git_oid, payload: ?*anyopaque) callconv(.C) c_int {
+        const self: *StashCollector = @ptrCast(@alignCast(payload.?));
+        const msg = self.allocator.dupe(u8, if (message) |m| std.mem.span(m) else "") catch return -1;
+        self.entries.append(.{ .index = index, .message = msg, .id = stash_id.* }) catch {
+            self.allocator.free(msg);
+            return -1;
+        };
+        return 0;
+    }
+};
+
+/// Configuration file an entry comes from, lowest priority first
+pub const ConfigLevel = enum(c_int) {
+    programdata = 1,
+    system = 2,
+    xdg = 3,
+    global = 4,
+    local = 5,
+    worktree = 6,
+    /// Added by the application rather than read from a standard file
+    app = 7,
+    _,
+};
+
+/// One configuration entry
+pub const ConfigEntry = struct {
+    /// Normalized name, e.g. "remote.origin.url"
+    name: [:0]u8,
+    /// Null for a bare key such as `[core] bare`
+    value: ?[:0]u8,
+    level: ConfigLevel,
+};
+
+/// Free a list returned by `Config.entries`
+pub fn freeConfigEntryList(allocator: std.mem.Allocator, entries: []ConfigEntry) void {
+    for (entries) |entry| {
+        allocator.free(entry.name);
+        if (entry.value) |value| allocator.free(value);
+    }
+    allocator.free(entries);
+}
+
+const ConfigEntryCollector = struct {
+    allocator: std.mem.Allocator,
+    entries: std.ArrayList(ConfigEntry),
+
+    fn deinit(self: *ConfigEntryCollector) void {
+        for (self.entries.items) |entry| {
+            self.allocator.free(entry.name);
+            if (entry.value) |value| self.allocator.free(value);
+        }
+        self.entries.deinit();
+    }
+
+    fn collect(name: [*:0]const u8, value: ?[*:0]const u8, level: c_int, payload: ?*anyopaque) callconv(.C) c_int {
+        const self: *ConfigEntryCollector = @ptrCast(@alignCast(payload.?));
+        const name_copy = self.allocator.dupeZ(u8, std.mem.span(name)) catch return -1;
+        const value_copy = if (value) |v| self.allocator.dupeZ(u8, std.mem.span(v)) catch {
+            self.allocator.free(name_copy);
+            return -1;
+        } else null;
+        self.entries.append(.{ .name = name_copy, .value = value_copy, .level = @enumFromInt(level) }) catch {
+            self.allocator.free(name_copy);
+            if (value_copy) |v| self.allocator.free(v);
+            return -1;
+        };
+        return 0;
+    }
+};
+
+/// Entry seen through a `ConfigIterator`; the strings are valid until the
+/// next call to `next` or `deinit`
+pub const ConfigEntryView = struct {
+    name: [:0]const u8,
+    value: ?[:0]const u8,
+    level: ConfigLevel,
+};
+
+/// Lazily walks configuration entries; see `Config.iterator`
+pub const ConfigIterator = struct {
+    iter: *git_config_iterator,
+
+    pub fn deinit(self: *ConfigIterator) void {
+        git2_shim_config_iterator_free(self.iter);
+    }
+
+    pub fn next(self: *ConfigIterator) Error!?ConfigEntryView {
+        var name: [*:0]const u8 = undefined;
+        var value: ?[*:0]const u8 = null;
+        var level: c_int = 0;
+        const rc = git2_shim_config_next(&name, &value, &level, self.iter);
+        if (rc == GIT_ITEROVER) return null;
+        if (rc < 0) return Error.ConfigFailed;
+        return ConfigEntryView{
+            .name = std.mem.span(name),
+            .value = if (value) |v| std.mem.span(v) else null,
+            .level = @enumFromInt(level),
+        };
+    }
+};
+
+/// Held lock on a config file; see `Config.lock`
+pub const Transaction = struct {
+    tx: *git_transaction,
+
+    /// Release the lock, dropping changes that were not committed
+    pub fn deinit(self: *Transaction) void {
+        git2_shim_transaction_free(self.tx);
+    }
+
+    /// Write every change made under the lock in one atomic rename
+    pub fn commit(self: *Transaction) Error!void {
+        if (git2_shim_transaction_commit(self.tx) < 0) {
+            return Error.ConfigFailed;
+        }
+    }
+};
+
+/// Layered git configuration (repository, worktree, global, XDG, system)
+pub const Config = struct {
+    config: *git_config,
+
+    /// Global, XDG and system configuration without a repository
+    pub fn openDefault() Error!Config {
+        var cfg: ?*git_config = null;
+        if (git2_shim_config_open_default(&cfg) < 0) {
+            return Error.ConfigFailed;
+        }
+        return Config{ .config = cfg.? };
+    }
+
+    /// Configuration with no files, to be built up with `addFile`
+    pub fn empty() Error!Config {
+        var cfg: ?*git_config = null;
+        if (git2_shim_config_new(&cfg) < 0) {
+            return Error.ConfigFailed;
+        }
+        return Config{ .config = cfg.? };
+    }
+
+    pub fn deinit(self: *Config) void {
+        git2_shim_config_free(self.config);
+    }
+
+    /// Stack the file at `path` at `level`, e.g. a CI-provided file at
+    /// `.app` over a repository configuration. The file is created on
+    /// first write if missing. `repo` lets `includeIf` conditions match.
+    /// Taken levels fail unless `force` replaces the file there.
+    pub fn addFile(self: Config, allocator: std.mem.Allocator, path: []const u8, level: ConfigLevel, repo: ?*Repository, force: bool) Error!void {
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+
+        const repo_ptr = if (repo) |r| r.repo else null;
+        if (git2_shim_config_add_file_ondisk(self.config, path_z.ptr, @intFromEnum(level), repo_ptr, @intFromBool(force)) < 0) {
+            return Error.ConfigFailed;
+        }
+    }
+
+    /// Parse a value from outside any config file as `getBool` would
+    pub fn parseBool(allocator: std.mem.Allocator, value: []const u8) Error!bool {
+        const value_z = allocator.dupeZ(u8, value) catch return Error.AllocationFailed;
+        defer allocator.free(value_z);
+
+        var out: c_int = 0;
+        if (git2_shim_config_parse_bool(&out, value_z.ptr) < 0) return Error.ConfigFailed;
+        return out != 0;
+    }
+
+    /// Parse a value as `getInt32` would, including `k`/`m`/`g` suffixes
+    pub fn parseInt32(allocator: std.mem.Allocator, value: []const u8) Error!i32 {
+        const value_z = allocator.dupeZ(u8, value) catch return Error.AllocationFailed;
+        defer allocator.free(value_z);
+
+        var out: i32 = 0;
+        if (git2_shim_config_parse_int32(&out, value_z.ptr) < 0) return Error.ConfigFailed;
+        return out;
+    }
+
+    pub fn parseInt64(allocator: std.mem.Allocator, value: []const u8) Error!i64 {
+        const value_z = allocator.dupeZ(u8, value) catch return Error.AllocationFailed;
+        defer allocator.free(value_z);
+
+        var out: i64 = 0;
+        if (git2_shim_config_parse_int64(&out, value_z.ptr) < 0) return Error.ConfigFailed;
+        return out;
+    }
+
+    /// Expand a leading `~/` as `getPath` would; free with `allocator.free`
+    pub fn parsePath(allocator: std.mem.Allocator, value: []const u8) Error![:0]u8 {
+        const value_z = allocator.dupeZ(u8, value) catch return Error.AllocationFailed;
+        defer allocator.free(value_z);
+
+        var buf = git_buf{ .ptr = null, .reserved = 0, .size = 0 };
+        if (git2_shim_config_parse_path(&buf, value_z.ptr) < 0) return Error.ConfigFailed;
+        return takeBuf(allocator, &buf);
+    }
+
+    /// Just the file at `level`, so writes land there the way
+    /// `git config --global` does; `deinit` it separately
+    pub fn openLevel(self: Config, level: ConfigLevel) Error!Config {
+        var cfg: ?*git_config = null;
+        if (git2_shim_config_open_level(&cfg, self.config, @intFromEnum(level)) < 0) {
+            return Error.ConfigFailed;
+        }
+        return Config{ .config = cfg.? };
+    }
+
+    /// Lock the highest-priority writable file so the writes that follow
+    /// (e.g. a remote's URL plus its fetch refspecs) are held back and
+    /// land together on `commit`. `deinit` the transaction when done.
+    pub fn lock(self: Config) Error!Transaction {
+        var tx: ?*git_transaction = null;
+        if (git2_shim_config_lock(&tx, self.config) < 0) {
+            return Error.ConfigFailed;
+        }
+        return Transaction{ .tx = tx.? };
+    }
+
+    /// Read-only copy frozen now, for a burst of consistent reads that do
+    /// not re-check the files; `deinit` it separately
+    pub fn snapshot(self: Config) Error!Config {
+        var cfg: ?*git_config = null;
+        if (git2_shim_config_snapshot(&cfg, self.config) < 0) {
+            return Error.ConfigFailed;
+        }
+        return Config{ .config = cfg.? };
+    }
+
+    /// Value of `name` (e.g. "user.email") from the highest-priority file
+    /// that sets it, or null if unset. Free with `allocator.free`.
+    pub fn getString(self: Config, allocator: std.mem.Allocator, name: []const u8) Error!?[:0]u8 {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        var buf = git_buf{ .ptr = null, .reserved = 0, .size = 0 };
+        const rc = git2_shim_config_get_string_buf(&buf, self.config, name_z.ptr);
+        if (rc == GIT_ENOTFOUND) return null;
+        if (rc < 0) return Error.ConfigFailed;
+        return try takeBuf(allocator, &buf);
+    }
+
+    /// Set `name` (e.g. "remote.origin.url") in the highest-priority
+    /// writable file, normally the repository's `.git/config`
+    pub fn setString(self: Config, allocator: std.mem.Allocator, name: []const u8, value: []const u8) Error!void {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+        const value_z = allocator.dupeZ(u8, value) catch return Error.AllocationFailed;
+        defer allocator.free(value_z);
+
+        if (git2_shim_config_set_string(self.config, name_z.ptr, value_z.ptr) < 0) {
+            return Error.ConfigFailed;
+        }
+    }
+
+    /// Every entry, lowest-priority file first, or only those whose name
+    /// matches the regular expression `regexp` (e.g. "^remote\\..*\\.url$").
+    /// Free with `freeConfigEntryList`.
+    pub fn entries(self: Config, allocator: std.mem.Allocator, regexp: ?[]const u8) Error![]ConfigEntry {
+        const pattern = try dupeZOpt(allocator, regexp);
+        defer if (pattern) |p| allocator.free(p);
+
+        var collector = ConfigEntryCollector{
+            .allocator = allocator,
+            .entries = std.ArrayList(ConfigEntry).init(allocator),
+        };
+        errdefer collector.deinit();
+
+        if (git2_shim_config_foreach_match(self.config, if (pattern) |p| p.ptr else null, ConfigEntryCollector.collect, &collector) < 0) {
+            return Error.ConfigFailed;
+        }
+        return collector.entries.toOwnedSlice() catch return Error.AllocationFailed;
+    }
+
+    /// Each value of the multi-valued key `name` (e.g. "remote.origin.fetch"),
+    /// or only those matching `regexp`; empty if unset. Free with
+    /// `freeConfigEntryList`.
+    pub fn multivar(self: Config, allocator: std.mem.Allocator, name: []const u8, regexp: ?[]const u8) Error![]ConfigEntry {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+        const pattern = try dupeZOpt(allocator, regexp);
+        defer if (pattern) |p| allocator.free(p);
+
+        var collector = ConfigEntryCollector{
+            .allocator = allocator,
+            .entries = std.ArrayList(ConfigEntry).init(allocator),
+        };
+        errdefer collector.deinit();
+
+        const rc = git2_shim_config_get_multivar_foreach(self.config, name_z.ptr, if (pattern) |p| p.ptr else null, ConfigEntryCollector.collect, &collector);
+        if (rc < 0 and rc != GIT_ENOTFOUND) return Error.ConfigFailed;
+        return collector.entries.toOwnedSlice() catch return Error.AllocationFailed;
+    }
+
+    /// Replace the values of `name` matching `regexp` with `value`. A
+    /// pattern that matches nothing, such as "$^", appends instead.
+    pub fn setMultivar(self: Config, allocator: std.mem.Allocator, name: []const u8, regexp: []const u8, value: []const u8) Error!void {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+        const regexp_z = allocator.dupeZ(u8, regexp) catch return Error.AllocationFailed;
+        defer allocator.free(regexp_z);
+        const value_z = allocator.dupeZ(u8, value) catch return Error.AllocationFailed;
+        defer allocator.free(value_z);
+
+        if (git2_shim_config_set_multivar(self.config, name_z.ptr, regexp_z.ptr, value_z.ptr) < 0) {
+            return Error.ConfigFailed;
+        }
+    }
+
+    /// Remove single-valued `name` from the highest-priority writable
+    /// file; false if it was not set there
+    pub fn deleteEntry(self: Config, allocator: std.mem.Allocator, name: []const u8) Error!bool {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        const rc = git2_shim_config_delete_entry(self.config, name_z.ptr);
+        if (rc == GIT_ENOTFOUND) return false;
+        if (rc < 0) return Error.ConfigFailed;
+        return true;
+    }
+
+    /// Remove the values of `name` matching `regexp` ("." for all); false
+    /// if none matched
+    pub fn deleteMultivar(self: Config, allocator: std.mem.Allocator, name: []const u8, regexp: []const u8) Error!bool {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+        const regexp_z = allocator.dupeZ(u8, regexp) catch return Error.AllocationFailed;
+        defer allocator.free(regexp_z);
+
+        const rc = git2_shim_config_delete_multivar(self.config, name_z.ptr, regexp_z.ptr);
+        if (rc == GIT_ENOTFOUND) return false;
+        if (rc < 0) return Error.ConfigFailed;
+        return true;
+    }
+
+    /// Like `entries` without copying; `deinit` the iterator when done
+    pub fn iterator(self: Config, allocator: std.mem.Allocator, regexp: ?[]const u8) Error!ConfigIterator {
+        const pattern = try dupeZOpt(allocator, regexp);
+        defer if (pattern) |p| allocator.free(p);
+
+        var iter: ?*git_config_iterator = null;
+        if (git2_shim_config_iterator_new(&iter, self.config, if (pattern) |p| p.ptr else null) < 0) {
+            return Error.ConfigFailed;
+        }
+        return ConfigIterator{ .iter = iter.? };
+    }
+
+    /// `name` parsed as git does ("yes", "on", "1", a bare key, ...), or
+    /// null if unset. Values that are not booleans fail.
+    pub fn getBool(self: Config, allocator: std.mem.Allocator, name: []const u8) Error!?bool {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        var value: c_int = 0;
+        const rc = git2_shim_config_get_bool(&value, self.config, name_z.ptr);
+        if (rc == GIT_ENOTFOUND) return null;
+        if (rc < 0) return Error.ConfigFailed;
+        return value != 0;
+    }
+
+    /// `name` as an integer, with `k`/`m`/`g` suffixes scaled by 1024, or
+    /// null if unset
+    pub fn getInt32(self: Config, allocator: std.mem.Allocator, name: []const u8) Error!?i32 {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        var value: i32 = 0;
+        const rc = git2_shim_config_get_int32(&value, self.config, name_z.ptr);
+        if (rc == GIT_ENOTFOUND) return null;
+        if (rc < 0) return Error.ConfigFailed;
+        return value;
+    }
+
+    pub fn getInt64(self: Config, allocator: std.mem.Allocator, name: []const u8) Error!?i64 {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        var value: i64 = 0;
+        const rc = git2_shim_config_get_int64(&value, self.config, name_z.ptr);
+        if (rc == GIT_ENOTFOUND) return null;
+        if (rc < 0) return Error.ConfigFailed;
+        return value;
+    }
+
+    /// `name` as a path with `~/` expanded, or null if unset. Free with
+    /// `allocator.free`.
+    pub fn getPath(self: Config, allocator: std.mem.Allocator, name: []const u8) Error!?[:0]u8 {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        var buf = git_buf{ .ptr = null, .reserved = 0, .size = 0 };
+        const rc = git2_shim_config_get_path(&buf, self.config, name_z.ptr);
+        if (rc == GIT_ENOTFOUND) return null;
+        if (rc < 0) return Error.ConfigFailed;
+        return try takeBuf(allocator, &buf);
+    }
+
+    pub fn setBool(self: Config, allocator: std.mem.Allocator, name: []const u8, value: bool) Error!void {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        if (git2_shim_config_set_bool(self.config, name_z.ptr, @intFromBool(value)) < 0) {
+            return Error.ConfigFailed;
+        }
+    }
+
+    pub fn setInt32(self: Config, allocator: std.mem.Allocator, name: []const u8, value: i32) Error!void {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        if (git2_shim_config_set_int32(self.config, name_z.ptr, value) < 0) {
+            return Error.ConfigFailed;
+        }
+    }
+
+    pub fn setInt64(self: Config, allocator: std.mem.Allocator, name: []const u8, value: i64) Error!void {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        if (git2_shim_config_set_int64(self.config, name_z.ptr, value) < 0) {
+            return Error.ConfigFailed;
+        }
+    }
+};
+
+/// How much of a submodule's work tree counts when deciding it is dirty
+pub const SubmoduleIgnore = enum(c_int) {
+    /// Use the submodule's `ignore` setting
+    unspecified = -1,
+    /// Any change, including untracked files
+    none = 1,
+    /// Changes to tracked files only
+    untracked = 2,
+    /// Only a moved HEAD
+    dirty = 3,
+    /// Never dirty
+    all = 4,
+};
+
+/// What `git submodule update` does with a submodule
+pub const SubmoduleUpdate = enum(c_int) {
+    checkout = 1,
+    rebase = 2,
+    merge = 3,
+    none = 4,
+};
+
+/// Result of `Repository.submoduleStatus`
+pub const SubmoduleStatus = struct {
+    in_head: bool = false,
+    in_index: bool = false,
+    /// Listed in `.git/config`, i.e. initialized
+    in_config: bool = false,
+    in_workdir: bool = false,
+    index_added: bool = false,
+    index_deleted: bool = false,
+    /// The superproject index pins a different commit than HEAD
+    index_modified: bool = false,
+    /// Known but not checked out in the work tree
+    workdir_uninitialized: bool = false,
+    workdir_added: bool = false,
+    workdir_deleted: bool = false,
+    /// The submodule's HEAD differs from the pinned commit
+    workdir_modified: bool = false,
+    /// Staged changes inside the submodule
+    workdir_index_modified: bool = false,
+    /// Unstaged changes to tracked files inside the submodule
+    workdir_workdir_modified: bool = false,
+    /// Untracked files inside the submodule
+    workdir_untracked: bool = false,
+
+    fn fromBits(status: c_uint) SubmoduleStatus {
+        return .{
+            .in_head = status & (1 << 0) != 0,
+            .in_index = status & (1 << 1) != 0,
+            .in_config = status & (1 << 2) != 0,
+            .in_workdir = status & (1 << 3) != 0,
+            .index_added = status & (1 << 4) != 0,
+            .index_deleted = status & (1 << 5) != 0,
+            .index_modified = status & (1 << 6) != 0,
+            .workdir_uninitialized = status & (1 << 7) != 0,
+            .workdir_added = status & (1 << 8) != 0,
+            .workdir_deleted = status & (1 << 9) != 0,
+            .workdir_modified = status & (1 << 10) != 0,
+            .workdir_index_modified = status & (1 << 11) != 0,
+            .workdir_workdir_modified = status & (1 << 12) != 0,
+            .workdir_untracked = status & (1 << 13) != 0,
+        };
+    }
+
+    /// Whether the submodule shows up as changed in a status listing
+    pub fn isModified(self: SubmoduleStatus) bool {
+        return self.index_added or self.index_deleted or self.index_modified or
+            self.workdir_added or self.workdir_deleted or self.workdir_modified or
+            self.workdir_index_modified or self.workdir_workdir_modified or
+            self.workdir_untracked;
+    }
+};
+
+/// Options for `Submodule.update`
+pub const SubmoduleUpdateOptions = struct {
+    checkout: CheckoutOptions = .{},
+    /// Used to clone or fetch the submodule; set `callbacks` for
+    /// credentials to private submodules
+    fetch: FetchOptions = .{},
+    /// Fetch when the pinned commit is missing instead of failing
+    allow_fetch: bool = true,
+
+    fn create(self: SubmoduleUpdateOptions, allocator: std.mem.Allocator) Error!*git2_shim_submodule_update_options {
+        const opts = git2_shim_submodule_update_options_new() orelse return Error.AllocationFailed;
+        errdefer git2_shim_submodule_update_options_free(opts);
+
+        const checkout_opts = try self.checkout.create(allocator);
+        defer git2_shim_checkout_options_free(checkout_opts);
+        git2_shim_submodule_update_options_set_checkout_options(opts, checkout_opts);
+
+        const fetch_opts = try self.fetch.create(allocator);
+        defer git2_shim_fetch_options_free(fetch_opts);
+        git2_shim_submodule_update_options_set_fetch_options(opts, fetch_opts);
+
+        git2_shim_submodule_update_options_set_allow_fetch(opts, @intFromBool(self.allow_fetch));
+        return opts;
+    }
+};
+
+/// A submodule of a repository; see `Repository.submoduleLookup`
+pub const Submodule = struct {
+    submodule: *git_submodule,
+
+    pub fn deinit(self: *Submodule) void {
+        git2_shim_submodule_free(self.submodule);
+    }
+
+    /// Name from `.gitmodules`; often, but not always, the path
+    pub fn name(self: Submodule) []const u8 {
+        return std.mem.span(git2_shim_submodule_name(self.submodule));
+    }
+
+    /// Path relative to the superproject's work tree
+    pub fn path(self: Submodule) []const u8 {
+        return std.mem.span(git2_shim_submodule_path(self.submodule));
+    }
+
+    pub fn url(self: Submodule) ?[]const u8 {
+        const u = git2_shim_submodule_url(self.submodule) orelse return null;
+        return std.mem.span(u);
+    }
+
+    /// Branch tracked via `submodule.<name>.branch`
+    pub fn branch(self: Submodule) ?[]const u8 {
+        const b = git2_shim_submodule_branch(self.submodule) orelse return null;
+        return std.mem.span(b);
+    }
+
+    /// Commit pinned by the superproject's HEAD
+    pub fn headId(self: Submodule) ?git_oid {
+        const id = git2_shim_submodule_head_id(self.submodule) orelse return null;
+        return id.*;
+    }
+
+    /// Commit staged in the superproject's index
+    pub fn indexId(self: Submodule) ?git_oid {
+        const id = git2_shim_submodule_index_id(self.submodule) orelse return null;
+        return id.*;
+    }
+
+    /// HEAD of the checked-out submodule, as of when it was looked up
+    pub fn workdirId(self: Submodule) ?git_oid {
+        const id = git2_shim_submodule_wd_id(self.submodule) orelse return null;
+        return id.*;
+    }
+
+    /// Where the submodule is recorded (the `in_*` fields only), without
+    /// examining its work tree
+    pub fn location(self: Submodule) Error!SubmoduleStatus {
+        var bits: c_uint = 0;
+        if (git2_shim_submodule_location(&bits, self.submodule) < 0) {
+            return Error.SubmoduleFailed;
+        }
+        return SubmoduleStatus.fromBits(bits);
+    }
+
+    /// Copy the URL and update settings from `.gitmodules` into
+    /// `.git/config` (`git submodule init`), keeping existing entries
+    /// unless `overwrite` is set
+    pub fn init(self: *Submodule, overwrite: bool) Error!void {
+        if (git2_shim_submodule_init(self.submodule, @intFromBool(overwrite)) < 0) {
+            return Error.SubmoduleFailed;
+        }
+    }
+
+    /// Clone the submodule if needed and check out the pinned commit.
+    /// With `init_first` this is `git submodule update --init`; without it,
+    /// uninitialized submodules fail.
+    pub fn update(self: *Submodule, allocator: std.mem.Allocator, init_first: bool, options: SubmoduleUpdateOptions) Error!void {
+        const opts = try options.create(allocator);
+        defer git2_shim_submodule_update_options_free(opts);
+
+        if (git2_shim_submodule_update(self.submodule, @intFromBool(init_first), opts) < 0) {
+            return Error.SubmoduleFailed;
+        }
+    }
+
+    /// The checked-out submodule's repository, for recursing with the
+    /// usual `Repository` calls; `close` it when done
+    pub fn open(self: *Submodule) Error!Repository {
+        var repo: ?*git_repository = null;
+        if (git2_shim_submodule_open(&repo, self.submodule) < 0) {
+            return Error.SubmoduleFailed;
+        }
+        return Repository{ .repo = repo.? };
+    }
+
+    /// Create the submodule's empty repository with its remote configured,
+    /// to fetch into by hand; under `.git/modules/` when `use_gitlink` is
+    /// set. `close` it when done.
+    pub fn repoInit(self: *Submodule, use_gitlink: bool) Error!Repository {
+        var repo: ?*git_repository = null;
+        if (git2_shim_submodule_repo_init(&repo, self.submodule, @intFromBool(use_gitlink)) < 0) {
+            return Error.SubmoduleFailed;
+        }
+        return Repository{ .repo = repo.? };
+    }
+
+    /// Apply the URL in `.gitmodules` to `.git/config` and to the
+    /// checked-out submodule's remote (`git submodule sync`)
+    pub fn sync(self: *Submodule) Error!void {
+        if (git2_shim_submodule_sync(self.submodule) < 0) {
+            return Error.SubmoduleFailed;
+        }
+    }
+
+    /// Second step of adding a submodule: fetch and check out the
+    /// repository set up by `Repository.submoduleAddSetup`. `close` the
+    /// returned repository when done.
+    pub fn clone(self: *Submodule, allocator: std.mem.Allocator, options: SubmoduleUpdateOptions) Error!Repository {
+        const opts = try options.create(allocator);
+        defer git2_shim_submodule_update_options_free(opts);
+
+        var repo: ?*git_repository = null;
+        if (git2_shim_submodule_clone(&repo, self.submodule, opts) < 0) {
+            return Error.SubmoduleFailed;
+        }
+        return Repository{ .repo = repo.? };
+    }
+
+    /// Last step of adding a submodule: stage `.gitmodules` and the
+    /// checked-out commit in the superproject index
+    pub fn addFinalize(self: *Submodule) Error!void {
+        if (git2_shim_submodule_add_finalize(self.submodule) < 0) {
+            return Error.SubmoduleFailed;
+        }
+    }
+
+    /// Stage the submodule's current HEAD commit in the superproject
+    /// index, writing the index out if `write_index` is set
+    pub fn addToIndex(self: *Submodule, write_index: bool) Error!void {
+        if (git2_shim_submodule_add_to_index(self.submodule, @intFromBool(write_index)) < 0) {
+            return Error.SubmoduleFailed;
+        }
+    }
+};
+
+const SubmoduleNameCollector = struct {
+    allocator: std.mem.Allocator,
+    names: std.ArrayList([:0]u8),
+
+    fn collect(submodule: *git_submodule, name: [*:0]const u8, payload: ?*anyopaque) callconv(.C) c_int {
+        _ = submodule;
+        const self: *SubmoduleNameCollector = @ptrCast(@alignCast(payload.?));
+        const copy = self.allocator.dupeZ(u8, std.mem.span(name)) catch return -1;
+        self.names.append(copy) catch {
+            self.allocator.free(copy);
+            return -1;
+        };
+        return 0;
+    }
+};
+
+/// Options for `Repository.worktreeAdd`; by default a new branch named
+/// after the worktree is created at HEAD
+pub const WorktreeAddOptions = struct {
+    /// Lock the worktree straight away so pruning leaves it alone
+    lock: bool = false,
+    /// Reuse an existing local branch named after the worktree instead of
+    /// failing
+    checkout_existing: bool = false,
+    /// Existing local branch to check out, e.g. "main"; it must not be
+    /// checked out in another worktree
+    branch: ?[]const u8 = null,
+    checkout: CheckoutOptions = .{},
+
+    fn create(self: WorktreeAddOptions, allocator: std.mem.Allocator) Error!*git2_shim_worktree_add_options {
+        const opts = git2_shim_worktree_add_options_new() orelse return Error.AllocationFailed;
+        errdefer git2_shim_worktree_add_options_free(opts);
+
+        git2_shim_worktree_add_options_set_lock(opts, @intFromBool(self.lock));
+        git2_shim_worktree_add_options_set_checkout_existing(opts, @intFromBool(self.checkout_existing));
+
+        const branch_z = try dupeZOpt(allocator, self.branch);
+        defer if (branch_z) |b| allocator.free(b);
+        git2_shim_worktree_add_options_set_branch(opts, if (branch_z) |b| b.ptr else null);
+
+        const checkout_opts = try self.checkout.create(allocator);
+        defer git2_shim_checkout_options_free(checkout_opts);
+        git2_shim_worktree_add_options_set_checkout_options(opts, checkout_opts);
+        return opts;
+    }
+};
+
+/// What `Worktree.prune` may remove; by default only stale, unlocked
+/// worktrees whose work tree has gone are pruned
+pub const WorktreePruneFlags = struct {
+    /// Prune even if the worktree is still valid
+    valid: bool = false,
+    /// Prune even if the worktree is locked
+    locked: bool = false,
+    /// Delete the checked-out work tree as well, like `git worktree remove`
+    working_tree: bool = false,
+
+    fn bits(self: WorktreePruneFlags) u32 {
+        var flags: u32 = 0;
+        if (self.valid) flags |= 1 << 0;
+        if (self.locked) flags |= 1 << 1;
+        if (self.working_tree) flags |= 1 << 2;
+        return flags;
+    }
+};
+
+/// Outcome for one submodule of `Repository.submoduleUpdateRecursive`
+pub const SubmoduleUpdateResult = struct {
+    /// Relative to the top-level work tree, e.g. "libs/a/vendor/b"
+    path: [:0]u8,
+    /// 1 for direct submodules of the repository
+    depth: u32,
+    /// libgit2 error code; 0 on success
+    code: c_int,
+    /// What went wrong, when `code` is non-zero
+    message: ?[:0]u8,
+
+    pub fn ok(self: SubmoduleUpdateResult) bool {
+        return self.code == 0;
+    }
+};
+
+/// Free a list returned by `Repository.submoduleUpdateRecursive`
+pub fn freeSubmoduleUpdateResults(allocator: std.mem.Allocator, results: []SubmoduleUpdateResult) void {
+    for (results) |result| {
+        allocator.free(result.path);
+        if (result.message) |message| allocator.free(message);
+    }
+    allocator.free(results);
+}
+
+const SubmoduleUpdateCollector = struct {
+    allocator: std.mem.Allocator,
+    results: std.ArrayList(SubmoduleUpdateResult),
+
+    fn deinit(self: *SubmoduleUpdateCollector) void {
+        for (self.results.items) |result| {
+            self.allocator.free(result.path);
+            if (result.message) |message| self.allocator.free(message);
+        }
+        self.results.deinit();
+    }
+
+    fn collect(path: [*:0]const u8, depth: c_uint, err: c_int, message: ?[*:0]const u8, payload: ?*anyopaque) callconv(.C) c_int {
+        const self: *SubmoduleUpdateCollector = @ptrCast(@alignCast(payload.?));
+        const path_copy = self.allocator.dupeZ(u8, std.mem.span(path)) catch return -1;
+        const message_copy = if (message) |m| self.allocator.dupeZ(u8, std.mem.span(m)) catch {
+            self.allocator.free(path_copy);
+            return -1;
+        } else null;
+        self.results.append(.{ .path = path_copy, .depth = depth, .code = err, .message = message_copy }) catch {
+            self.allocator.free(path_copy);
+            if (message_copy) |m| self.allocator.free(m);
+            return -1;
+        };
+        return 0;
+    }
+};
+
+/// A linked worktree of a repository; see `Repository.worktreeLookup`
+pub const Worktree = struct {
+    worktree: *git_worktree,
+
+    pub fn deinit(self: *Worktree) void {
+        git2_shim_worktree_free(self.worktree);
+    }
+
+    /// Name under `.git/worktrees/`, as passed to `Repository.worktreeLookup`
+    pub fn name(self: Worktree) []const u8 {
+        return std.mem.span(git2_shim_worktree_name(self.worktree));
+    }
+
+    /// Absolute path of the checked-out work tree
+    pub fn path(self: Worktree) []const u8 {
+        return std.mem.span(git2_shim_worktree_path(self.worktree));
+    }
+
+    /// Whether the administrative files and work tree are intact; false
+    /// once the work tree was deleted or moved without libgit2
+    pub fn isValid(self: Worktree) bool {
+        return git2_shim_worktree_validate(self.worktree) == 0;
+    }
+
+    /// Lock against pruning, recording an optional `reason`
+    pub fn lock(self: *Worktree, allocator: std.mem.Allocator, reason: ?[]const u8) Error!void {
+        const reason_z = try dupeZOpt(allocator, reason);
+        defer if (reason_z) |r| allocator.free(r);
+
+        if (git2_shim_worktree_lock(self.worktree, if (reason_z) |r| r.ptr else null) < 0) {
+            return Error.WorktreeFailed;
+        }
+    }
+
+    /// Returns false if the worktree was not locked
+    pub fn unlock(self: *Worktree) Error!bool {
+        const rc = git2_shim_worktree_unlock(self.worktree);
+        if (rc < 0) return Error.WorktreeFailed;
+        return rc == 0;
+    }
+
+    /// The lock reason (empty if none was given), or null if the worktree
+    /// is not locked; free with `allocator`
+    pub fn lockReason(self: Worktree, allocator: std.mem.Allocator) Error!?[:0]u8 {
+        var buf = git_buf{ .ptr = null, .reserved = 0, .size = 0 };
+        const rc = git2_shim_worktree_is_locked(&buf, self.worktree);
+        if (rc < 0) return Error.WorktreeFailed;
+        if (rc == 0) {
+            git2_shim_buf_dispose(&buf);
+            return null;
+        }
+        return try takeBuf(allocator, &buf);
+    }
+
+    /// The repository checked out in this worktree; `close` it when done
+    pub fn open(self: *Worktree) Error!Repository {
+        var repo: ?*git_repository = null;
+        if (git2_shim_repository_open_from_worktree(&repo, self.worktree) < 0) {
+            return Error.WorktreeFailed;
+        }
+        return Repository{ .repo = repo.? };
+    }
+
+    /// Whether `prune` with the same flags would remove the worktree
+    pub fn isPrunable(self: *Worktree, flags: WorktreePruneFlags) bool {
+        return git2_shim_worktree_is_prunable(self.worktree, flags.bits()) > 0;
+    }
+
+    /// Remove the worktree's administrative files, like `git worktree
+    /// prune`; the handle must still be `deinit`ed
+    pub fn prune(self: *Worktree, flags: WorktreePruneFlags) Error!void {
+        if (git2_shim_worktree_prune(self.worktree, flags.bits()) < 0) {
+            return Error.WorktreeFailed;
+        }
+    }
+};
+
+/// Options for `Repository.initExt`
+pub const RepositoryInitOptions = struct {
+    bare: bool = false,
+    /// Fail instead of reinitializing an existing repository
+    no_reinit: bool = false,
+    /// Use the path itself as the git directory rather than `path/.git`
+    no_dotgit_dir: bool = false,
+    /// Create the repository directory and any missing parents
+    mkpath: bool = true,
+    /// Write a relative path in the `.git` gitlink of a separate work tree
+    relative_gitlink: bool = false,
+    /// Sharing permissions: 0 for the umask, 0o2775 for the group, 0o2777
+    /// for everyone
+    mode: u32 = 0,
+    /// Separate work tree; relative paths start at the repository path
+    workdir_path: ?[]const u8 = null,
+    description: ?[]const u8 = null,
+    /// Copy a template directory instead of the built-in template, as git
+    /// does: `template_path`, else `init.templateDir`, else git's installed
+    /// templates
+    external_template: bool = false,
+    /// Template directory to copy; implies `external_template`
+    template_path: ?[]const u8 = null,
+    /// Branch HEAD points at, e.g. "main"; defaults to `init.defaultBranch`
+    initial_head: ?[]const u8 = null,
+    /// URL for an "origin" remote to add
+    origin_url: ?[]const u8 = null,
+
+    fn create(self: RepositoryInitOptions, allocator: std.mem.Allocator) Error!*git2_shim_repository_init_options {
+        const opts = git2_shim_repository_init_options_new() orelse return Error.AllocationFailed;
+        errdefer git2_shim_repository_init_options_free(opts);
+
+        var flags: u32 = 0;
+        if (self.bare) flags |= 1 << 0;
+        if (self.no_reinit) flags |= 1 << 1;
+        if (self.no_dotgit_dir) flags |= 1 << 2;
+        if (self.mkpath) flags |= (1 << 3) | (1 << 4);
+        if (self.external_template) flags |= 1 << 5;
+        if (self.relative_gitlink) flags |= 1 << 6;
+        git2_shim_repository_init_options_set_flags(opts, flags);
+        git2_shim_repository_init_options_set_mode(opts, self.mode);
+
+        const workdir_z = try dupeZOpt(allocator, self.workdir_path);
+        defer if (workdir_z) |w| allocator.free(w);
+        git2_shim_repository_init_options_set_workdir_path(opts, if (workdir_z) |w| w.ptr else null);
+
+        const description_z = try dupeZOpt(allocator, self.description);
+        defer if (description_z) |d| allocator.free(d);
+        git2_shim_repository_init_options_set_description(opts, if (description_z) |d| d.ptr else null);
+
+        const template_z = try dupeZOpt(allocator, self.template_path);
+        defer if (template_z) |t| allocator.free(t);
+        git2_shim_repository_init_options_set_template_path(opts, if (template_z) |t| t.ptr else null);
+
+        const head_z = try dupeZOpt(allocator, self.initial_head);
+        defer if (head_z) |h| allocator.free(h);
+        git2_shim_repository_init_options_set_initial_head(opts, if (head_z) |h| h.ptr else null);
+
+        const url_z = try dupeZOpt(allocator, self.origin_url);
+        defer if (url_z) |u| allocator.free(u);
+        git2_shim_repository_init_options_set_origin_url(opts, if (url_z) |u| u.ptr else null);
+        return opts;
+    }
+};
+
+/// How `Repository.openExt` locates the repository
+pub const RepositoryOpenOptions = struct {
+    /// Only look at the given path instead of walking up parent
+    /// directories, e.g. for daemons that must not wander the filesystem
+    no_search: bool = false,
+    /// Let the walk up cross filesystem boundaries
+    cross_fs: bool = false,
+    /// Open as a bare repository even if it has a work tree
+    bare: bool = false,
+    /// Do not try `path/.git`; the path must be the git directory itself
+    no_dotgit: bool = false,
+    /// Locate the repository like the git CLI, honoring `GIT_DIR`,
+    /// `GIT_CEILING_DIRECTORIES` and friends; the other options are
+    /// ignored and a null path searches from the current directory
+    from_env: bool = false,
+    /// Directories the walk up stops at, e.g. a daemon's data root
+    ceiling_dirs: []const []const u8 = &.{},
+    /// Treat the path as the git directory itself and use this as its
+    /// work tree, ignoring `core.worktree` and `.git` files; for
+    /// detached git directories. The other options are ignored.
+    workdir: ?[]const u8 = null,
+
+    fn bits(self: RepositoryOpenOptions) c_uint {
+        var flags: c_uint = 0;
+        if (self.no_search) flags |= 1 << 0;
+        if (self.cross_fs) flags |= 1 << 1;
+        if (self.bare) flags |= 1 << 2;
+        if (self.no_dotgit) flags |= 1 << 3;
+        if (self.from_env) flags |= 1 << 4;
+        return flags;
+    }
+};
+
+/// Files and directories `Repository.itemPath` can locate
+pub const RepositoryItem = enum(c_int) {
+    gitdir = 0,
+    workdir = 1,
+    commondir = 2,
+    index = 3,
+    objects = 4,
+    refs = 5,
+    packed_refs = 6,
+    remotes = 7,
+    config = 8,
+    info = 9,
+    hooks = 10,
+    logs = 11,
+    modules = 12,
+    worktrees = 13,
+    worktree_config = 14,
+};
+
+/// Where attribute lookups read `.gitattributes` from
+pub const AttrOptions = struct {
+    source: Source = .file_then_index,
+    /// Skip the system-wide attributes file
+    no_system: bool = false,
+    /// Also read `.gitattributes` from the HEAD commit
+    include_head: bool = false,
+
+    pub const Source = enum(u32) {
+        file_then_index = 0,
+        index_then_file = 1,
+        index_only = 2,
+    };
+
+    fn bits(self: AttrOptions) u32 {
+        var flags: u32 = @intFromEnum(self.source);
+        if (self.no_system) flags |= 1 << 2;
+        if (self.include_head) flags |= 1 << 3;
+        return flags;
+    }
+};
+
+/// Borrowed state of one attribute, e.g. as handed to a filter's `check`
+pub const AttrView = union(enum) {
+    unspecified,
+    set,
+    unset,
+    /// `attr=value`; borrowed from libgit2
+    string: [:0]const u8,
+
+    /// Classify a raw attribute value without copying it
+    pub fn of(value: ?[*:0]const u8) AttrView {
+        return switch (git2_shim_attr_value(value)) {
+            1 => .set,
+            2 => .unset,
+            3 => .{ .string = std.mem.span(value.?) },
+            else => .unspecified,
+        };
+    }
+};
+
+/// State of one attribute for a path
+pub const AttrValue = union(enum) {
+    /// Not mentioned by any matching rule
+    unspecified,
+    /// `attr`
+    set,
+    /// `-attr`
+    unset,
+    /// `attr=value`; owned
+    string: [:0]u8,
+
+    fn take(allocator: std.mem.Allocator, value: ?[*:0]const u8) Error!AttrValue {
+        return switch (AttrView.of(value)) {
+            .unspecified => .unspecified,
+            .set => .set,
+            .unset => .unset,
+            .string => |text| .{ .string = allocator.dupeZ(u8, text) catch return Error.AllocationFailed },
+        };
+    }
+
+    pub fn deinit(self: AttrValue, allocator: std.mem.Allocator) void {
+        switch (self) {
+            .string => |value| allocator.free(value),
+            else => {},
+        }
+    }
+};
+
+/// Attribute applying to a path; see `Repository.attrEntries`
+pub const AttrEntry = struct {
+    name: [:0]u8,
+    value: AttrValue,
+};
+
+/// Free a list returned by `Repository.attrGetMany`
+pub fn freeAttrValues(allocator: std.mem.Allocator, values: []AttrValue) void {
+    for (values) |value| value.deinit(allocator);
+    allocator.free(values);
+}
+
+/// Free a list returned by `Repository.attrEntries`
+pub fn freeAttrEntries(allocator: std.mem.Allocator, entries: []AttrEntry) void {
+    for (entries) |entry| {
+        allocator.free(entry.name);
+        entry.value.deinit(allocator);
+    }
+    allocator.free(entries);
+}
+
+const AttrEntryCollector = struct {
+    allocator: std.mem.Allocator,
+    entries: std.ArrayList(AttrEntry),
+
+    fn deinit(self: *AttrEntryCollector) void {
+        for (self.entries.items) |entry| {
+            self.allocator.free(entry.name);
+            entry.value.deinit(self.allocator);
+        }
+        self.entries.deinit();
+    }
+
+    fn collect(name: [*:0]const u8, value: ?[*:0]const u8, payload: ?*anyopaque) callconv(.C) c_int {
+        const self: *AttrEntryCollector = @ptrCast(@alignCast(payload.?));
+        const name_copy = self.allocator.dupeZ(u8, std.mem.span(name)) catch return -1;
+        const value_copy = AttrValue.take(self.allocator, value) catch {
+            self.allocator.free(name_copy);
+            return -1;
+        };
+        self.entries.append(.{ .name = name_copy, .value = value_copy }) catch {
+            self.allocator.free(name_copy);
+            value_copy.deinit(self.allocator);
+            return -1;
+        };
+        return 0;
+    }
+};
+
+/// Operation a repository is in the middle of, from `Repository.state`
+pub const RepositoryState = enum(c_int) {
+    none = 0,
+    merge = 1,
+    revert = 2,
+    revert_sequence = 3,
+    cherrypick = 4,
+    cherrypick_sequence = 5,
+    bisect = 6,
+    rebase = 7,
+    rebase_interactive = 8,
+    rebase_merge = 9,
+    apply_mailbox = 10,
+    apply_mailbox_or_rebase = 11,
+    _,
+};
+
+const OidCollector = struct {
+    ids: std.ArrayList(git_oid),
+
+    fn collect(id: *const git_oid, payload: ?*anyopaque) callconv(.C) c_int {
+        const self: *OidCollector = @ptrCast(@alignCast(payload.?));
+        self.ids.append(id.*) catch return -1;
+        return 0;
+    }
+};
+
+/// Direction a filter is converting content in
+pub const FilterMode = enum(c_uint) {
+    /// Object database to working directory (checkout)
+    smudge = 0,
+    /// Working directory to object database (add, hashing)
+    clean = 1,
+};
+
+/// The file a filter callback is converting
+pub const FilterSource = opaque {
+    /// Path relative to the working directory
+    pub fn path(self: *const FilterSource) [:0]const u8 {
+        return std.mem.span(git2_shim_filter_source_path(self));
+    }
+
+    pub fn mode(self: *const FilterSource) FilterMode {
+        return @enumFromInt(git2_shim_filter_source_mode(self));
+    }
+
+    /// Blob being filtered; null when not known, e.g. cleaning a file that
+    /// has not been hashed yet
+    pub fn blobId(self: *const FilterSource) ?git_oid {
+        const oid = git2_shim_filter_source_id(self) orelse return null;
+        return oid.*;
+    }
+
+    /// `.unreadable` if not known
+    pub fn fileMode(self: *const FilterSource) FileMode {
+        return @enumFromInt(git2_shim_filter_source_filemode(self));
+    }
+
+    /// Borrowed; do not close
+    pub fn repository(self: *const FilterSource) Repository {
+        return Repository{ .repo = git2_shim_filter_source_repo(self) };
+    }
+};
+
+/// Sink a filter's apply callback writes the converted content into
+pub const FilterSink = opaque {
+    /// Append to the output; may be called repeatedly
+    pub fn write(self: *FilterSink, bytes: []const u8) void {
+        git2_shim_filter_sink_write(self, bytes.ptr, bytes.len);
+    }
+};
+
+/// Decide whether the filter runs for `src`. `attr_values` has one entry
+/// per name in the filter's attribute list: null if unspecified, while set
+/// and unset are sentinel pointers, so classify each with `AttrView.of`.
+/// Return 0 to apply the filter, `GIT_PASSTHROUGH` (-30) to skip the file,
+/// or negative to fail the checkout or add.
+pub const git_filter_check_cb = *const fn (src: *const FilterSource, attr_values: [*]const ?[*:0]const u8, payload: ?*anyopaque) callconv(.C) c_int;
+
+/// Convert `input[0..len]` for `src` and write the result to `sink`.
+/// Return 0 to use the sink's content, `GIT_PASSTHROUGH` (-30) to keep the
+/// input unchanged, or negative to fail the checkout or add.
+pub const git_filter_apply_cb = *const fn (sink: *FilterSink, input: [*]const u8, len: usize, src: *const FilterSource, payload: ?*anyopaque) callconv(.C) c_int;
+
+/// Priority of git's `filter.<driver>` filters; the built-in crlf and
+/// ident filters run at 0 and 100
+pub const filter_priority_driver: c_int = 200;
+
+/// A registered content filter, applied wherever libgit2 converts file
+/// content: smudging on checkout, cleaning on add and hashing. Register
+/// during setup, before other threads use libgit2.
+pub const Filter = struct {
+    allocator: std.mem.Allocator,
+    handle: *git2_shim_filter,
+    name: [:0]u8,
+
+    /// `attributes` selects the files the filter sees: "filter=crypt"
+    /// must match, a bare name is only passed to `check`, and null means
+    /// every file. A null `check` applies the filter whenever the
+    /// attributes match. Filters run in ascending `priority` when
+    /// smudging and descending when cleaning. `payload` must outlive the
+    /// registration.
+    pub fn register(
+        allocator: std.mem.Allocator,
+        name: []const u8,
+        attributes: ?[]const u8,
+        priority: c_int,
+        check: ?git_filter_check_cb,
+        apply: git_filter_apply_cb,
+        payload: ?*anyopaque,
+    ) Error!Filter {
+        const attributes_z = try dupeZOpt(allocator, attributes);
+        defer if (attributes_z) |a| allocator.free(a);
+
+        const handle = git2_shim_filter_new(if (attributes_z) |a| a.ptr else null, check, apply, payload) orelse return Error.AllocationFailed;
+        errdefer git2_shim_filter_free(handle);
+
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        errdefer allocator.free(name_z);
+
+        if (git2_shim_filter_register(name_z.ptr, handle, priority) < 0) return Error.FilterFailed;
+        return Filter{ .allocator = allocator, .handle = handle, .name = name_z };
+    }
+
+    /// Unregister the filter and release the registration
+    pub fn deinit(self: *Filter) void {
+        _ = git2_shim_filter_unregister(self.name.ptr);
+        git2_shim_filter_free(self.handle);
+        self.allocator.free(self.name);
+    }
+};
+
+/// How `Repository.loadFilters` picks the filters for a path
+pub const FilterFlags = struct {
+    /// Allow CRLF conversions that would not round-trip (`core.safecrlf`)
+    allow_unsafe: bool = false,
+    /// Skip the system-wide gitattributes file
+    no_system_attributes: bool = false,
+    /// Read `.gitattributes` from HEAD rather than the working directory,
+    /// e.g. for a bare repository
+    attributes_from_head: bool = false,
+
+    fn bits(self: FilterFlags) u32 {
+        var flags: u32 = 0;
+        if (self.allow_unsafe) flags |= 1 << 0;
+        if (self.no_system_attributes) flags |= 1 << 1;
+        if (self.attributes_from_head) flags |= 1 << 2;
+        return flags;
+    }
+};
+
+/// The filters (crlf, ident and registered `Filter`s) that apply to one
+/// path in one direction. Empty lists copy content through unchanged.
+pub const FilterList = struct {
+    list: ?*git_filter_list,
+    repo: *git_repository,
+
+    pub fn deinit(self: *FilterList) void {
+        git2_shim_filter_list_free(self.list);
+    }
+
+    /// Whether the filter registered as `name` (or "crlf", "ident") runs
+    pub fn contains(self: *FilterList, allocator: std.mem.Allocator, name: []const u8) Error!bool {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+        return git2_shim_filter_list_contains(self.list, name_z.ptr) == 1;
+    }
+
+    /// Filter `input`; free the result with `allocator`
+    pub fn applyToBuffer(self: *FilterList, allocator: std.mem.Allocator, input: []const u8) Error![:0]u8 {
+        var buf = git_buf{ .ptr = null, .reserved = 0, .size = 0 };
+        if (git2_shim_filter_list_apply_to_buffer(&buf, self.list, input.ptr, input.len) < 0) {
+            git2_shim_buf_dispose(&buf);
+            return Error.FilterFailed;
+        }
+        return takeBuf(allocator, &buf);
+    }
+
+    /// Filter the content of blob `id`; free the result with `allocator`
+    pub fn applyToBlob(self: *FilterList, allocator: std.mem.Allocator, id: git_oid) Error![:0]u8 {
+        var blob: ?*git_blob = null;
+        if (git2_shim_blob_lookup(&blob, self.repo, &id) < 0) {
+            return Error.LookupFailed;
+        }
+        defer git2_shim_blob_free(blob.?);
+
+        var buf = git_buf{ .ptr = null, .reserved = 0, .size = 0 };
+        if (git2_shim_filter_list_apply_to_blob(&buf, self.list, blob.?) < 0) {
+            git2_shim_buf_dispose(&buf);
+            return Error.FilterFailed;
+        }
+        return takeBuf(allocator, &buf);
+    }
+};
+
+/// Repository handle
+pub const Repository = struct {
+    repo: *git_repository,
+
+    var initialized: bool = false;
+
+    fn ensureInit() Error!void {
+        if (!initialized) {
+            if (git2_shim_init() < 0) {
+                return Error.InitFailed;
+            }
+            initialized = true;
+        }
+    }
+
+    pub fn open(allocator: std.mem.Allocator, path: []const u8) Error!Repository {
+        try ensureInit();
+
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+
+        var repo: ?*git_repository = null;
+        if (git2_shim_repository_open(&repo, path_z.ptr) < 0) {
+            return Error.OpenFailed;
+        }
+        return Repository{ .repo = repo.? };
+    }
+
+    /// Create a repository at `path`, or reinitialize an existing one, like
+    /// `git init [--bare]`
+    pub fn init(allocator: std.mem.Allocator, path: []const u8, bare: bool) Error!Repository {
+        try ensureInit();
+
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+
+        var repo: ?*git_repository = null;
+        if (git2_shim_repository_init(&repo, path_z.ptr, @intFromBool(bare)) < 0) {
+            return Error.InitFailed;
+        }
+        return Repository{ .repo = repo.? };
+    }
+
+    /// `init` with control over the initial branch, template, work tree
+    /// location and the like
+    pub fn initExt(allocator: std.mem.Allocator, path: []const u8, options: RepositoryInitOptions) Error!Repository {
+        try ensureInit();
+
+        const opts = try options.create(allocator);
+        defer git2_shim_repository_init_options_free(opts);
+
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+
+        var repo: ?*git_repository = null;
+        if (git2_shim_repository_init_ext(&repo, path_z.ptr, opts) < 0) {
+            return Error.InitFailed;
+        }
+        return Repository{ .repo = repo.? };
+    }
+
+    /// Git directory of the repository enclosing `start_path`, searching
+    /// parent directories like git does, to pass to `open`; null if there
+    /// is none. The search stops at `ceiling_dirs` and, unless `across_fs`
+    /// is set, at filesystem boundaries. Free with `allocator`.
+    pub fn discover(allocator: std.mem.Allocator, start_path: []const u8, across_fs: bool, ceiling_dirs: []const []const u8) Error!?[:0]u8 {
+        try ensureInit();
+
+        const start_z = allocator.dupeZ(u8, start_path) catch return Error.AllocationFailed;
+        defer allocator.free(start_z);
+        const delimiter = [_]u8{std.fs.path.delimiter};
+        const ceilings = std.mem.joinZ(allocator, &delimiter, ceiling_dirs) catch return Error.AllocationFailed;
+        defer allocator.free(ceilings);
+
+        var buf = git_buf{ .ptr = null, .reserved = 0, .size = 0 };
+        const rc = git2_shim_repository_discover(&buf, start_z.ptr, @intFromBool(across_fs), if (ceiling_dirs.len > 0) ceilings.ptr else null);
+        if (rc == GIT_ENOTFOUND) return null;
+        if (rc < 0) return Error.OpenFailed;
+        return try takeBuf(allocator, &buf);
+    }
+
+    /// Open the git directory at `path` as a bare repository, skipping the
+    /// search and work tree setup `open` does; the fast path for servers
+    /// handling many bare repositories
+    pub fn openBare(allocator: std.mem.Allocator, path: []const u8) Error!Repository {
+        try ensureInit();
+
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+
+        var repo: ?*git_repository = null;
+        if (git2_shim_repository_open_bare(&repo, path_z.ptr) < 0) {
+            return Error.OpenFailed;
+        }
+        return Repository{ .repo = repo.? };
+    }
+
+    /// A repository around `odb` alone, with no gitdir, work tree,
+    /// references or configuration: object lookups and walks from known
+    /// ids work, anything touching refs or files does not. `odb` may be
+    /// freed afterwards.
+    pub fn wrapOdb(odb: *Odb) Error!Repository {
+        var repo: ?*git_repository = null;
+        if (git2_shim_repository_wrap_odb(&repo, odb.odb) < 0) {
+            return Error.OpenFailed;
+        }
+        return Repository{ .repo = repo.? };
+    }
+
+    /// `open` with control over how the repository is searched for; `path`
+    /// may be null only with `from_env`. With `workdir` set, `path` is the
+    /// git directory to open.
+    pub fn openExt(allocator: std.mem.Allocator, path: ?[]const u8, options: RepositoryOpenOptions) Error!Repository {
+        try ensureInit();
+
+        const path_z = try dupeZOpt(allocator, path);
+        defer if (path_z) |p| allocator.free(p);
+
+        var repo: ?*git_repository = null;
+        if (options.workdir) |work_tree| {
+            const gitdir_z = path_z orelse return Error.OpenFailed;
+            const work_tree_z = allocator.dupeZ(u8, work_tree) catch return Error.AllocationFailed;
+            defer allocator.free(work_tree_z);
+
+            if (git2_shim_repository_open_with_workdir(&repo, gitdir_z.ptr, work_tree_z.ptr) < 0) {
+                return Error.OpenFailed;
+            }
+            return Repository{ .repo = repo.? };
+        }
+
+        const delimiter = [_]u8{std.fs.path.delimiter};
+        const ceilings = std.mem.joinZ(allocator, &delimiter, options.ceiling_dirs) catch return Error.AllocationFailed;
+        defer allocator.free(ceilings);
+
+        const ceilings_arg = if (options.ceiling_dirs.len > 0) ceilings.ptr else null;
+        if (git2_shim_repository_open_ext(&repo, if (path_z) |p| p.ptr else null, options.bits(), ceilings_arg) < 0) {
+            return Error.OpenFailed;
+        }
+        return Repository{ .repo = repo.? };
+    }
+
+    /// Clone `url` into `path` and open the result. On failure,
+    /// `lastError` describes the network or filesystem problem.
+    pub fn clone(allocator: std.mem.Allocator, url: []const u8, path: []const u8, options: CloneOptions) Error!Repository {
+        try ensureInit();
+
+        const opts = try options.create(allocator);
+        defer git2_shim_clone_options_free(opts);
+
+        const url_z = allocator.dupeZ(u8, url) catch return Error.AllocationFailed;
+        defer allocator.free(url_z);
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+
+        var repo: ?*git_repository = null;
+        if (git2_shim_clone(&repo, url_z.ptr, path_z.ptr, opts) < 0) {
+            return Error.CloneFailed;
+        }
+        return Repository{ .repo = repo.? };
+    }
+
+    pub fn close(self: *Repository) void {
+        git2_shim_repository_free(self.repo);
+    }
+
+    /// Check if repository is bare
+    pub fn isBare(self: *Repository) bool {
+        return git2_shim_repository_is_bare(self.repo) != 0;
+    }
+
+    /// Whether the repository has truncated history from a shallow
+    /// clone or fetch
+    pub fn isShallow(self: *Repository) bool {
+        return git2_shim_repository_is_shallow(self.repo) == 1;
+    }
+
+    /// Boundary commits of a shallow repository, whose parents are missing
+    /// locally; empty unless `isShallow`. Ahead/behind counts and merge
+    /// bases reaching them may be wrong. Free with `allocator`.
+    pub fn shallowRoots(self: *Repository, allocator: std.mem.Allocator) Error![]git_oid {
+        var collector = OidCollector{ .ids = std.ArrayList(git_oid).init(allocator) };
+        errdefer collector.ids.deinit();
+
+        if (git2_shim_repository_shallow_foreach(self.repo, OidCollector.collect, &collector) < 0) {
+            return Error.LookupFailed;
+        }
+        return collector.ids.toOwnedSlice() catch return Error.AllocationFailed;
+    }
+
+    /// Merge, revert, cherry-pick, rebase or bisect left in progress
+    pub fn state(self: *Repository) RepositoryState {
+        return @enumFromInt(git2_shim_repository_state(self.repo));
+    }
+
+    /// Commit being cherry-picked (`CHERRY_PICK_HEAD`); null unless a
+    /// cherry-pick is stopped
+    pub fn cherrypickHead(self: *Repository) Error!?git_oid {
+        var oid: git_oid = undefined;
+        const rc = git2_shim_repository_cherrypick_head(&oid, self.repo);
+        if (rc == GIT_ENOTFOUND) return null;
+        if (rc < 0) return Error.LookupFailed;
+        return oid;
+    }
+
+    /// Commit being reverted (`REVERT_HEAD`); null unless a revert is
+    /// stopped
+    pub fn revertHead(self: *Repository) Error!?git_oid {
+        var oid: git_oid = undefined;
+        const rc = git2_shim_repository_revert_head(&oid, self.repo);
+        if (rc == GIT_ENOTFOUND) return null;
+        if (rc < 0) return Error.LookupFailed;
+        return oid;
+    }
+
+    /// Commits being merged into HEAD (`MERGE_HEAD`), one per merged
+    /// branch; empty unless a merge is stopped. Free with `allocator`.
+    pub fn mergeHeads(self: *Repository, allocator: std.mem.Allocator) Error![]git_oid {
+        var collector = OidCollector{ .ids = std.ArrayList(git_oid).init(allocator) };
+        errdefer collector.ids.deinit();
+
+        const rc = git2_shim_repository_mergehead_foreach(self.repo, OidCollector.collect, &collector);
+        if (rc < 0 and rc != GIT_ENOTFOUND) return Error.LookupFailed;
+        return collector.ids.toOwnedSlice() catch return Error.AllocationFailed;
+    }
+
+    /// Get repository workdir path
+    pub fn workdir(self: *Repository) ?[]const u8 {
+        const path = git2_shim_repository_workdir(self.repo);
+        if (path == null) return null;
+        return std.mem.span(path.?);
+    }
+
+    /// Whether this repository was opened through a linked worktree
+    pub fn isWorktree(self: *Repository) bool {
+        return git2_shim_repository_is_worktree(self.repo) == 1;
+    }
+
+    /// The repository's own git directory; for a linked worktree this is
+    /// `.git/worktrees/<name>/`, holding only its HEAD and index
+    pub fn gitdir(self: *Repository) []const u8 {
+        return std.mem.span(git2_shim_repository_path(self.repo));
+    }
+
+    /// The git directory shared by all worktrees, holding objects, refs
+    /// and config; equal to `gitdir` outside linked worktrees
+    pub fn commondir(self: *Repository) []const u8 {
+        return std.mem.span(git2_shim_repository_commondir(self.repo));
+    }
+
+    /// Where `item` lives, following linked worktrees, `gitdir:` files and
+    /// the common directory; directories end in a slash. Null if the item
+    /// cannot exist, e.g. the workdir of a bare repository. Free with
+    /// `allocator`.
+    pub fn itemPath(self: *Repository, allocator: std.mem.Allocator, item: RepositoryItem) Error!?[:0]u8 {
+        var buf = git_buf{ .ptr = null, .reserved = 0, .size = 0 };
+        const rc = git2_shim_repository_item_path(&buf, self.repo, @intFromEnum(item));
+        if (rc == GIT_ENOTFOUND) return null;
+        if (rc < 0) return Error.LookupFailed;
+        return try takeBuf(allocator, &buf);
+    }
+
+    /// The prepared message in `MERGE_MSG` left by a merge, revert,
+    /// cherry-pick or squash, to prefill the commit message with; null if
+    /// there is none. Free with `allocator`.
+    pub fn mergeMessage(self: *Repository, allocator: std.mem.Allocator) Error!?[:0]u8 {
+        var buf = git_buf{ .ptr = null, .reserved = 0, .size = 0 };
+        const rc = git2_shim_repository_message(&buf, self.repo);
+        if (rc == GIT_ENOTFOUND) return null;
+        if (rc < 0) return Error.LookupFailed;
+        return try takeBuf(allocator, &buf);
+    }
+
+    /// Delete `MERGE_MSG` once the commit is made; fails if there is none
+    pub fn removeMergeMessage(self: *Repository) Error!void {
+        if (git2_shim_repository_message_remove(self.repo) < 0) {
+            return Error.CommitFailed;
+        }
+    }
+
+    /// Use `path` as the work tree, e.g. after relocating a checkout. With
+    /// `update_gitlink`, also write a `.git` file there pointing back at
+    /// the repository and set `core.worktree`, so other tools agree
+    pub fn setWorkdir(self: *Repository, allocator: std.mem.Allocator, path: []const u8, update_gitlink: bool) Error!void {
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+
+        if (git2_shim_repository_set_workdir(self.repo, path_z.ptr, @intFromBool(update_gitlink)) < 0) {
+            return Error.OpenFailed;
+        }
+    }
+
+    /// The repository's own index, shared with checkout, status and the
+    /// rest, so staged changes are seen everywhere; `deinit` when done
+    pub fn openIndex(self: *Repository) Error!Index {
+        var handle: ?*git_index = null;
+        if (git2_shim_repository_index(&handle, self.repo) < 0) {
+            return Error.IndexFailed;
+        }
+        return Index{ .index = handle.? };
+    }
+
+    /// The repository's own object database; `deinit` when done
+    pub fn openOdb(self: *Repository) Error!Odb {
+        var handle: ?*git_odb = null;
+        if (git2_shim_repository_odb(&handle, self.repo) < 0) {
+            return Error.OdbFailed;
+        }
+        return Odb{ .odb = handle.? };
+    }
+
+    /// The repository's own reference database; `deinit` when done
+    pub fn openRefdb(self: *Repository) Error!Refdb {
+        var handle: ?*git_refdb = null;
+        if (git2_shim_repository_refdb(&handle, self.repo) < 0) {
+            return Error.ReferenceFailed;
+        }
+        return Refdb{ .refdb = handle.? };
+    }
+
+    /// Blob id the file at `path` would get from `git add`, after clean
+    /// filters and CRLF conversion, for spotting unchanged files. Filters
+    /// are picked by `as_path` if given, else by `path`; an empty
+    /// `as_path` hashes the raw bytes.
+    pub fn hashFile(self: *Repository, allocator: std.mem.Allocator, path: []const u8, as_path: ?[]const u8) Error!git_oid {
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+        const as_path_z = try dupeZOpt(allocator, as_path);
+        defer if (as_path_z) |a| allocator.free(a);
+
+        var oid: git_oid = undefined;
+        if (git2_shim_repository_hashfile(&oid, self.repo, path_z.ptr, @intFromEnum(ObjectType.blob), if (as_path_z) |a| a.ptr else null) < 0) {
+            return Error.OdbFailed;
+        }
+        return oid;
+    }
+
+    /// Filters that apply to `path` when converting in `mode`, per its
+    /// attributes. Pass the blob about to be filtered as `blob_id` when
+    /// known; the ident filter needs it.
+    pub fn loadFilters(self: *Repository, allocator: std.mem.Allocator, path: []const u8, mode: FilterMode, blob_id: ?git_oid, flags: FilterFlags) Error!FilterList {
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+
+        var blob: ?*git_blob = null;
+        if (blob_id) |id| {
+            if (git2_shim_blob_lookup(&blob, self.repo, &id) < 0) {
+                return Error.LookupFailed;
+            }
+        }
+        defer if (blob) |b| git2_shim_blob_free(b);
+
+        var list: ?*git_filter_list = null;
+        if (git2_shim_filter_list_load(&list, self.repo, blob, path_z.ptr, @intFromEnum(mode), flags.bits()) < 0) {
+            return Error.FilterFailed;
+        }
+        return FilterList{ .list = list, .repo = self.repo };
+    }
+
+    /// Content of blob `id` as checkout would write it to `path`, with
+    /// line endings, ident and custom filters applied. Free with
+    /// `allocator`.
+    pub fn smudgedBlob(self: *Repository, allocator: std.mem.Allocator, id: git_oid, path: []const u8, flags: FilterFlags) Error![:0]u8 {
+        var filters = try self.loadFilters(allocator, path, .smudge, id, flags);
+        defer filters.deinit();
+        return filters.applyToBlob(allocator, id);
+    }
+
+    /// Layer extra ignore rules in `.gitignore` syntax, one per line (e.g.
+    /// "zig-out/\n.zig-cache/"), over the repository's own; they last as
+    /// long as this handle and are never written out
+    pub fn addIgnoreRules(self: *Repository, allocator: std.mem.Allocator, rules: []const u8) Error!void {
+        const rules_z = allocator.dupeZ(u8, rules) catch return Error.AllocationFailed;
+        defer allocator.free(rules_z);
+
+        if (git2_shim_ignore_add_rule(self.repo, rules_z.ptr) < 0) {
+            return Error.IgnoreFailed;
+        }
+    }
+
+    /// Drop every rule added with `addIgnoreRules`
+    pub fn clearIgnoreRules(self: *Repository) Error!void {
+        if (git2_shim_ignore_clear_internal_rules(self.repo) < 0) {
+            return Error.IgnoreFailed;
+        }
+    }
+
+    /// Whether the ignore rules match `path` (relative to the work tree;
+    /// end it with a slash to test a directory), tracked or not
+    pub fn isPathIgnored(self: *Repository, allocator: std.mem.Allocator, path: []const u8) Error!bool {
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+
+        var ignored: c_int = 0;
+        if (git2_shim_ignore_path_is_ignored(&ignored, self.repo, path_z.ptr) < 0) {
+            return Error.IgnoreFailed;
+        }
+        return ignored != 0;
+    }
+
+    /// Attribute `name` (e.g. "eol", "diff", "binary") of `path`, resolved
+    /// through `.gitattributes`, `info/attributes` and macros as git does;
+    /// the file need not exist. `deinit` the value with `allocator`.
+    pub fn attrGet(self: *Repository, allocator: std.mem.Allocator, path: []const u8, name: []const u8, options: AttrOptions) Error!AttrValue {
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        var value: ?[*:0]const u8 = null;
+        if (git2_shim_attr_get(&value, self.repo, options.bits(), path_z.ptr, name_z.ptr) < 0) {
+            return Error.AttrFailed;
+        }
+        return AttrValue.take(allocator, value);
+    }
+
+    /// `attrGet` for several names at once, in the same order; free with
+    /// `freeAttrValues`
+    pub fn attrGetMany(self: *Repository, allocator: std.mem.Allocator, path: []const u8, names: []const []const u8, options: AttrOptions) Error![]AttrValue {
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+        const names_z = try dupeZList(allocator, names);
+        defer freeZList(allocator, names_z);
+
+        const raw_values = allocator.alloc(?[*:0]const u8, names.len) catch return Error.AllocationFailed;
+        defer allocator.free(raw_values);
+        if (git2_shim_attr_get_many(raw_values.ptr, self.repo, options.bits(), path_z.ptr, names.len, names_z.ptr) < 0) {
+            return Error.AttrFailed;
+        }
+
+        const values = allocator.alloc(AttrValue, names.len) catch return Error.AllocationFailed;
+        var done: usize = 0;
+        errdefer {
+            for (values[0..done]) |value| value.deinit(allocator);
+            allocator.free(values);
+        }
+        for (raw_values) |raw_value| {
+            values[done] = try AttrValue.take(allocator, raw_value);
+            done += 1;
+        }
+        return values;
+    }
+
+    /// Every attribute set or unset for `path`; free with
+    /// `freeAttrEntries`
+    pub fn attrEntries(self: *Repository, allocator: std.mem.Allocator, path: []const u8, options: AttrOptions) Error![]AttrEntry {
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+
+        var collector = AttrEntryCollector{
+            .allocator = allocator,
+            .entries = std.ArrayList(AttrEntry).init(allocator),
+        };
+        errdefer collector.deinit();
+
+        if (git2_shim_attr_foreach(self.repo, options.bits(), path_z.ptr, AttrEntryCollector.collect, &collector) < 0) {
+            return Error.AttrFailed;
+        }
+        return collector.entries.toOwnedSlice() catch return Error.AllocationFailed;
+    }
+
+    /// Define macro attribute `name` as `values` (e.g. "-diff -merge"),
+    /// like an `[attr]` line, for this handle only
+    pub fn attrAddMacro(self: *Repository, allocator: std.mem.Allocator, name: []const u8, values: []const u8) Error!void {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+        const values_z = allocator.dupeZ(u8, values) catch return Error.AllocationFailed;
+        defer allocator.free(values_z);
+
+        if (git2_shim_attr_add_macro(self.repo, name_z.ptr, values_z.ptr) < 0) {
+            return Error.AttrFailed;
+        }
+    }
+
+    /// Reread attribute files on the next lookup, dropping macros added
+    /// with `attrAddMacro`
+    pub fn flushAttrCache(self: *Repository) Error!void {
+        if (git2_shim_attr_cache_flush(self.repo) < 0) {
+            return Error.AttrFailed;
+        }
+    }
+
+    /// Switch to local branch `name` like `git switch`: check out its tree,
+    /// keeping non-conflicting local changes, then move HEAD. Returns
+    /// `CheckoutConflict` without touching anything if local changes
+    /// would be lost (unless `options` forces it), `ReferenceFailed` if
+    /// the branch does not exist.
+    pub fn checkoutBranch(self: *Repository, allocator: std.mem.Allocator, name: []const u8, options: CheckoutOptions) Error!void {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+        const opts = try options.create(allocator);
+        defer git2_shim_checkout_options_free(opts);
+
+        const rc = git2_shim_checkout_branch(self.repo, name_z.ptr, opts);
+        if (rc == GIT_ECONFLICT) return Error.CheckoutConflict;
+        if (rc == GIT_ENOTFOUND) return Error.ReferenceFailed;
+        if (rc < 0) return Error.CheckoutFailed;
+    }
+
+    /// Check if working directory is clean
+    pub fn isClean(self: *Repository) Error!bool {
+        var opts: git_status_options = undefined;
+        _ = git2_shim_status_options_init(&opts, GIT_STATUS_OPTIONS_VERSION);
+        opts.show = GIT_STATUS_SHOW_INDEX_AND_WORKDIR;
+
+        var list: ?*git_status_list = null;
+        if (git2_shim_status_list_new(&list, self.repo, &opts) < 0) {
+            return Error.StatusFailed;
+        }
+        defer git2_shim_status_list_free(list.?);
+
+        return git2_shim_status_list_entrycount(list.?) == 0;
+    }
+
+    /// Get HEAD reference name
+    pub fn headName(self: *Repository, allocator: std.mem.Allocator) Error![]const u8 {
+        var head: ?*git_reference = null;
+        if (git2_shim_repository_head(&head, self.repo) < 0) {
+            return Error.ReferenceFailed;
+        }
+        defer git2_shim_reference_free(head.?);
+
+        const name = git2_shim_reference_shorthand(head.?);
+        if (name == null) return Error.ReferenceFailed;
+
+        return allocator.dupe(u8, std.mem.span(name.?)) catch return Error.AllocationFailed;
+    }
+
+    /// Count commits ahead/behind a remote branch
+    pub fn aheadBehind(self: *Repository, local_oid: *const git_oid, remote_oid: *const git_oid) Error!struct { ahead: usize, behind: usize } {
+        var ahead: usize = 0;
+        var behind: usize = 0;
+        if (git2_shim_graph_ahead_behind(&ahead, &behind, self.repo, local_oid, remote_oid) < 0) {
+            return Error.CommitFailed;
+        }
+        return .{ .ahead = ahead, .behind = behind };
+    }
+
+    /// Stash local changes using the configured user identity
+    pub fn stashSave(self: *Repository, allocator: std.mem.Allocator, options: StashSaveOptions) Error!git_oid {
+        var sig: ?*git_signature = null;
+        if (git2_shim_signature_default(&sig, self.repo) < 0) {
+            return Error.SignatureFailed;
+        }
+        defer git2_shim_signature_free(sig.?);
+
+        const opts = git2_shim_stash_save_options_new() orelse return Error.AllocationFailed;
+        defer git2_shim_stash_save_options_free(opts);
+
+        if (options.message) |m| {
+            const message_z = allocator.dupeZ(u8, m) catch return Error.AllocationFailed;
+            defer allocator.free(message_z);
+            git2_shim_stash_save_options_set_message(opts, message_z.ptr);
+        }
+
+        const paths = try dupeZList(allocator, options.paths);
+        defer freeZList(allocator, paths);
+
+        git2_shim_stash_save_options_set_flags(opts, options.flags.bits());
+        git2_shim_stash_save_options_set_stasher(opts, sig.?);
+        git2_shim_stash_save_options_set_paths(opts, paths.ptr, paths.len);
+
+        var oid: git_oid = undefined;
+        if (git2_shim_stash_save_with_opts(&oid, self.repo, opts) < 0) {
+            return Error.StashFailed;
+        }
+        return oid;
+    }
+
+    /// Apply the stash at `index`, keeping it in the stash list
+    pub fn stashApply(self: *Repository, index: usize, options: StashApplyOptions) Error!void {
+        const opts = try options.create();
+        defer git2_shim_stash_apply_options_free(opts);
+
+        if (git2_shim_stash_apply(self.repo, index, opts) < 0) {
+            return Error.StashFailed;
+        }
+    }
+
+    /// Apply the stash at `index` and drop it on success
+    pub fn stashPop(self: *Repository, index: usize, options: StashApplyOptions) Error!void {
+        const opts = try options.create();
+        defer git2_shim_stash_apply_options_free(opts);
+
+        if (git2_shim_stash_pop(self.repo, index, opts) < 0) {
+            return Error.StashFailed;
+        }
+    }
+
+    /// Remove the stash at `index`
+    pub fn stashDrop(self: *Repository, index: usize) Error!void {
+        if (git2_shim_stash_drop(self.repo, index) < 0) {
+            return Error.StashFailed;
+        }
+    }
+
+    /// Merge the given revisions (e.g. "origin/main") into HEAD.
+    ///
+    /// Like `git merge --no-commit`: conflicts are left in the index and
+    /// working tree, MERGE_HEAD and MERGE_MSG are written, and committing
+    /// the result is up to the caller.
+    pub fn merge(
+        self: *Repository,
+        allocator: std.mem.Allocator,
+        revspecs: []const []const u8,
+        merge_options: MergeOptions,
+        checkout_options: CheckoutOptions,
+    ) Error!void {
+        var heads = try AnnotatedHeads.resolve(self.repo, allocator, revspecs);
+        defer heads.deinit();
+
+        const merge_opts = try merge_options.create();
+        defer git2_shim_merge_options_free(merge_opts);
+        const checkout_opts = try checkout_options.create(allocator);
+        defer git2_shim_checkout_options_free(checkout_opts);
+
+        if (git2_shim_merge(self.repo, heads.items.ptr, heads.items.len, merge_opts, checkout_opts) < 0) {
+            return Error.MergeFailed;
+        }
+    }
+
+    /// Decide how merging the given revisions into HEAD would proceed
+    pub fn mergeAnalysis(self: *Repository, allocator: std.mem.Allocator, revspecs: []const []const u8) Error!MergeAnalysis {
+        var heads = try AnnotatedHeads.resolve(self.repo, allocator, revspecs);
+        defer heads.deinit();
+
+        var analysis: c_uint = 0;
+        var preference: c_uint = 0;
+        if (git2_shim_merge_analysis(&analysis, &preference, self.repo, heads.items.ptr, heads.items.len) < 0) {
+            return Error.MergeFailed;
+        }
+        return MergeAnalysis.fromBits(analysis, preference);
+    }
+
+    /// Like `mergeAnalysis`, but against the reference `our_ref` instead of HEAD
+    pub fn mergeAnalysisForRef(
+        self: *Repository,
+        allocator: std.mem.Allocator,
+        our_ref: []const u8,
+        revspecs: []const []const u8,
+    ) Error!MergeAnalysis {
+        const ref_z = allocator.dupeZ(u8, our_ref) catch return Error.AllocationFailed;
+        defer allocator.free(ref_z);
+
+        var ref: ?*git_reference = null;
+        if (git2_shim_reference_lookup(&ref, self.repo, ref_z.ptr) < 0) {
+            return Error.ReferenceFailed;
+        }
+        defer git2_shim_reference_free(ref.?);
+
+        var heads = try AnnotatedHeads.resolve(self.repo, allocator, revspecs);
+        defer heads.deinit();
+
+        var analysis: c_uint = 0;
+        var preference: c_uint = 0;
+        if (git2_shim_merge_analysis_for_ref(&analysis, &preference, self.repo, ref.?, heads.items.ptr, heads.items.len) < 0) {
+            return Error.MergeFailed;
+        }
+        return MergeAnalysis.fromBits(analysis, preference);
+    }
+
+    /// Merge two commits into an in-memory index without touching HEAD or
+    /// any working directory, so it also works on bare repositories
+    pub fn mergeCommits(self: *Repository, ours: *const git_oid, theirs: *const git_oid, options: MergeOptions) Error!Index {
+        var our_commit: ?*git_commit = null;
+        if (git2_shim_commit_lookup(&our_commit, self.repo, ours) < 0) {
+            return Error.LookupFailed;
+        }
+        defer git2_shim_commit_free(our_commit.?);
+
+        var their_commit: ?*git_commit = null;
+        if (git2_shim_commit_lookup(&their_commit, self.repo, theirs) < 0) {
+            return Error.LookupFailed;
+        }
+        defer git2_shim_commit_free(their_commit.?);
+
+        const opts = try options.create();
+        defer git2_shim_merge_options_free(opts);
+
+        var index: ?*git_index = null;
+        if (git2_shim_merge_commits(&index, self.repo, our_commit.?, their_commit.?, opts) < 0) {
+            return Error.MergeFailed;
+        }
+        return Index{ .index = index.? };
+    }
+
+    /// Three-way merge of trees (by tree id) into an in-memory index.
+    /// A null `ancestor` merges without a common base.
+    pub fn mergeTrees(
+        self: *Repository,
+        ancestor: ?*const git_oid,
+        ours: *const git_oid,
+        theirs: *const git_oid,
+        options: MergeOptions,
+    ) Error!Index {
+        var ancestor_tree: ?*git_tree = null;
+        if (ancestor) |id| {
+            if (git2_shim_tree_lookup(&ancestor_tree, self.repo, id) < 0) {
+                return Error.LookupFailed;
+            }
+        }
+        defer if (ancestor_tree) |t| git2_shim_tree_free(t);
+
+        var our_tree: ?*git_tree = null;
+        if (git2_shim_tree_lookup(&our_tree, self.repo, ours) < 0) {
+            return Error.LookupFailed;
+        }
+        defer git2_shim_tree_free(our_tree.?);
+
+        var their_tree: ?*git_tree = null;
+        if (git2_shim_tree_lookup(&their_tree, self.repo, theirs) < 0) {
+            return Error.LookupFailed;
+        }
+        defer git2_shim_tree_free(their_tree.?);
+
+        const opts = try options.create();
+        defer git2_shim_merge_options_free(opts);
+
+        var index: ?*git_index = null;
+        if (git2_shim_merge_trees(&index, self.repo, ancestor_tree, our_tree.?, their_tree.?, opts) < 0) {
+            return Error.MergeFailed;
+        }
+        return Index{ .index = index.? };
+    }
+
+    /// Find where two commits diverged; null if the histories are unrelated
+    pub fn mergeBase(self: *Repository, one: *const git_oid, two: *const git_oid) Error!?git_oid {
+        var oid: git_oid = undefined;
+        const rc = git2_shim_merge_base(&oid, self.repo, one, two);
+        if (rc == GIT_ENOTFOUND) return null;
+        if (rc < 0) return Error.MergeFailed;
+        return oid;
+    }
+
+    /// Merge base of `commits[0]` and a hypothetical merge of the rest,
+    /// like `git merge-base A B C`; null if there is none
+    pub fn mergeBaseMany(self: *Repository, commits: []const git_oid) Error!?git_oid {
+        var oid: git_oid = undefined;
+        const rc = git2_shim_merge_base_many(&oid, self.repo, commits.len, commits.ptr);
+        if (rc == GIT_ENOTFOUND) return null;
+        if (rc < 0) return Error.MergeFailed;
+        return oid;
+    }
+
+    /// Common ancestor of all commits, like `git merge-base --octopus`;
+    /// null if there is none
+    pub fn mergeBaseOctopus(self: *Repository, commits: []const git_oid) Error!?git_oid {
+        var oid: git_oid = undefined;
+        const rc = git2_shim_merge_base_octopus(&oid, self.repo, commits.len, commits.ptr);
+        if (rc == GIT_ENOTFOUND) return null;
+        if (rc < 0) return Error.MergeFailed;
+        return oid;
+    }
+
+    /// Merge the conflicted entries for `path` in `index` (e.g. one returned
+    /// by `mergeCommits`), producing the file contents with conflict markers
+    pub fn mergeFileFromIndex(
+        self: *Repository,
+        allocator: std.mem.Allocator,
+        index: *Index,
+        path: []const u8,
+        options: MergeFileOptions,
+    ) Error!MergeFileResult {
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+
+        var ancestor: ?*const git_index_entry = null;
+        var ours: ?*const git_index_entry = null;
+        var theirs: ?*const git_index_entry = null;
+        if (git2_shim_index_conflict_get(&ancestor, &ours, &theirs, index.index, path_z.ptr) < 0) {
+            return Error.IndexFailed;
+        }
+
+        const opts = try options.create(allocator);
+        defer git2_shim_merge_file_options_free(opts);
+
+        var result: ?*git2_shim_merge_file_result = null;
+        if (git2_shim_merge_file_from_index(&result, self.repo, ancestor, ours, theirs, opts) < 0) {
+            return Error.MergeFailed;
+        }
+        return MergeFileResult{ .result = result.? };
+    }
+
+    /// Cherry-pick a commit onto HEAD, like `git cherry-pick --no-commit`:
+    /// index and workdir are updated, conflicts are left in place, and
+    /// CHERRY_PICK_HEAD is written
+    pub fn cherrypick(self: *Repository, allocator: std.mem.Allocator, id: *const git_oid, options: CherrypickOptions) Error!void {
+        var commit: ?*git_commit = null;
+        if (git2_shim_commit_lookup(&commit, self.repo, id) < 0) {
+            return Error.LookupFailed;
+        }
+        defer git2_shim_commit_free(commit.?);
+
+        const merge_opts = try options.merge.create();
+        defer git2_shim_merge_options_free(merge_opts);
+        const checkout_opts = try options.checkout.create(allocator);
+        defer git2_shim_checkout_options_free(checkout_opts);
+
+        if (git2_shim_cherrypick(self.repo, commit.?, options.mainline, merge_opts, checkout_opts) < 0) {
+            return Error.CherrypickFailed;
+        }
+    }
+
+    /// Cherry-pick `pick` onto `ours` into an in-memory index, leaving HEAD
+    /// and any working directory untouched. If the result has no conflicts
+    /// it can be written with `Index.writeTreeTo` and committed directly.
+    pub fn cherrypickCommit(
+        self: *Repository,
+        pick: *const git_oid,
+        ours: *const git_oid,
+        mainline: c_uint,
+        options: MergeOptions,
+    ) Error!Index {
+        var pick_commit: ?*git_commit = null;
+        if (git2_shim_commit_lookup(&pick_commit, self.repo, pick) < 0) {
+            return Error.LookupFailed;
+        }
+        defer git2_shim_commit_free(pick_commit.?);
+
+        var our_commit: ?*git_commit = null;
+        if (git2_shim_commit_lookup(&our_commit, self.repo, ours) < 0) {
+            return Error.LookupFailed;
+        }
+        defer git2_shim_commit_free(our_commit.?);
+
+        const opts = try options.create();
+        defer git2_shim_merge_options_free(opts);
+
+        var index: ?*git_index = null;
+        if (git2_shim_cherrypick_commit(&index, self.repo, pick_commit.?, our_commit.?, mainline, opts) < 0) {
+            return Error.CherrypickFailed;
+        }
+        return Index{ .index = index.? };
+    }
+
+    /// Revert a commit on HEAD, like `git revert --no-commit`: index and
+    /// workdir are updated, conflicts are left in place, and REVERT_HEAD
+    /// and MERGE_MSG are written
+    pub fn revert(self: *Repository, allocator: std.mem.Allocator, id: *const git_oid, options: RevertOptions) Error!void {
+        var commit: ?*git_commit = null;
+        if (git2_shim_commit_lookup(&commit, self.repo, id) < 0) {
+            return Error.LookupFailed;
+        }
+        defer git2_shim_commit_free(commit.?);
+
+        const merge_opts = try options.merge.create();
+        defer git2_shim_merge_options_free(merge_opts);
+        const checkout_opts = try options.checkout.create(allocator);
+        defer git2_shim_checkout_options_free(checkout_opts);
+
+        if (git2_shim_revert(self.repo, commit.?, options.mainline, merge_opts, checkout_opts) < 0) {
+            return Error.RevertFailed;
+        }
+    }
+
+    /// Revert `target` against `ours` into an in-memory index, leaving HEAD
+    /// and any working directory untouched
+    pub fn revertCommit(
+        self: *Repository,
+        target: *const git_oid,
+        ours: *const git_oid,
+        mainline: c_uint,
+        options: MergeOptions,
+    ) Error!Index {
+        var revert_commit: ?*git_commit = null;
+        if (git2_shim_commit_lookup(&revert_commit, self.repo, target) < 0) {
+            return Error.LookupFailed;
+        }
+        defer git2_shim_commit_free(revert_commit.?);
+
+        var our_commit: ?*git_commit = null;
+        if (git2_shim_commit_lookup(&our_commit, self.repo, ours) < 0) {
+            return Error.LookupFailed;
+        }
+        defer git2_shim_commit_free(our_commit.?);
+
+        const opts = try options.create();
+        defer git2_shim_merge_options_free(opts);
+
+        var index: ?*git_index = null;
+        if (git2_shim_revert_commit(&index, self.repo, revert_commit.?, our_commit.?, mainline, opts) < 0) {
+            return Error.RevertFailed;
+        }
+        return Index{ .index = index.? };
+    }
+
+    /// Start rebasing `branch` (null: HEAD) onto `onto` (null: `upstream`).
+    /// Revisions are revspecs such as "feature" or "origin/main".
+    pub fn rebaseInit(
+        self: *Repository,
+        allocator: std.mem.Allocator,
+        branch: ?[]const u8,
+        upstream: ?[]const u8,
+        onto: ?[]const u8,
+        options: RebaseOptions,
+    ) Error!Rebase {
+        var branch_head = try resolveOptionalHead(self.repo, allocator, branch);
+        defer branch_head.deinit();
+        var upstream_head = try resolveOptionalHead(self.repo, allocator, upstream);
+        defer upstream_head.deinit();
+        var onto_head = try resolveOptionalHead(self.repo, allocator, onto);
+        defer onto_head.deinit();
+
+        const opts = try options.create(allocator);
+        errdefer git2_shim_rebase_options_free(opts);
+
+        var rebase: ?*git_rebase = null;
+        if (git2_shim_rebase_init(&rebase, self.repo, branch_head.first(), upstream_head.first(), onto_head.first(), opts) < 0) {
+            return Error.RebaseFailed;
+        }
+        return Rebase{ .rebase = rebase.?, .opts = opts, .repo = self.repo };
+    }
+
+    /// Reopen a rebase in progress, e.g. one stopped for conflicts
+    pub fn rebaseOpen(self: *Repository, allocator: std.mem.Allocator, options: RebaseOptions) Error!Rebase {
+        const opts = try options.create(allocator);
+        errdefer git2_shim_rebase_options_free(opts);
+
+        var rebase: ?*git_rebase = null;
+        if (git2_shim_rebase_open(&rebase, self.repo, opts) < 0) {
+            return Error.RebaseFailed;
+        }
+        return Rebase{ .rebase = rebase.?, .opts = opts, .repo = self.repo };
+    }
+
+    /// Blame `path` (relative to the workdir); pass `.{}` for the whole file as of HEAD
+    pub fn blameFile(self: *Repository, allocator: std.mem.Allocator, path: []const u8, options: BlameOptions) Error!Blame {
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+
+        const opts = try options.create();
+        defer git2_shim_blame_options_free(opts);
+
+        var blame: ?*git_blame = null;
+        if (git2_shim_blame_file(&blame, self.repo, path_z.ptr, opts) < 0) {
+            return Error.BlameFailed;
+        }
+        return Blame{ .blame = blame.? };
+    }
+
+    /// New revision walker; push at least one commit before calling `next`
+    pub fn revwalk(self: *Repository) Error!Revwalk {
+        var walk: ?*git_revwalk = null;
+        if (git2_shim_revwalk_new(&walk, self.repo) < 0) {
+            return Error.RevwalkFailed;
+        }
+        return Revwalk{ .walk = walk.? };
+    }
+
+    /// Resolve a revision such as "HEAD~3" or "main@{yesterday}" exactly as
+    /// `git rev-parse` does
+    pub fn revparseSingle(self: *Repository, allocator: std.mem.Allocator, spec: []const u8) Error!Object {
+        const spec_z = allocator.dupeZ(u8, spec) catch return Error.AllocationFailed;
+        defer allocator.free(spec_z);
+
+        var object: ?*git_object = null;
+        if (git2_shim_revparse_single(&object, self.repo, spec_z.ptr) < 0) {
+            return Error.RevparseFailed;
+        }
+        return Object{ .object = object.? };
+    }
+
+    /// Like `revparseSingle`, also reporting the reference the spec
+    /// resolved through
+    pub fn revparseExt(self: *Repository, allocator: std.mem.Allocator, spec: []const u8) Error!RevparseResult {
+        const spec_z = allocator.dupeZ(u8, spec) catch return Error.AllocationFailed;
+        defer allocator.free(spec_z);
+
+        var object: ?*git_object = null;
+        var ref: ?*git_reference = null;
+        if (git2_shim_revparse_ext(&object, &ref, self.repo, spec_z.ptr) < 0) {
+            return Error.RevparseFailed;
+        }
+        errdefer git2_shim_object_free(object.?);
+        defer if (ref) |r| git2_shim_reference_free(r);
+
+        const name = if (ref) |r|
+            allocator.dupeZ(u8, std.mem.span(git2_shim_reference_name(r))) catch return Error.AllocationFailed
+        else
+            null;
+        return RevparseResult{ .object = .{ .object = object.? }, .reference = name };
+    }
+
+    /// Parse a revision or a range like "v1.0..v2.0" or "main...feature"
+    pub fn revparse(self: *Repository, allocator: std.mem.Allocator, spec: []const u8) Error!Revspec {
+        const spec_z = allocator.dupeZ(u8, spec) catch return Error.AllocationFailed;
+        defer allocator.free(spec_z);
+
+        var revspec = git_revspec{ .from = null, .to = null, .flags = 0 };
+        if (git2_shim_revparse(&revspec, self.repo, spec_z.ptr) < 0) {
+            return Error.RevparseFailed;
+        }
+        return Revspec{
+            .from = if (revspec.from) |obj| Object{ .object = obj } else null,
+            .to = if (revspec.to) |obj| Object{ .object = obj } else null,
+            .range = revspec.flags & (1 << 1) != 0,
+            .merge_base = revspec.flags & (1 << 2) != 0,
+        };
+    }
+
+    /// The repository's configuration; `deinit` when done
+    pub fn config(self: *Repository) Error!Config {
+        var cfg: ?*git_config = null;
+        if (git2_shim_repository_config(&cfg, self.repo) < 0) {
+            return Error.ConfigFailed;
+        }
+        return Config{ .config = cfg.? };
+    }
+
+    /// Read-only snapshot of the configuration; see `Config.snapshot`
+    pub fn configSnapshot(self: *Repository) Error!Config {
+        var cfg: ?*git_config = null;
+        if (git2_shim_repository_config_snapshot(&cfg, self.repo) < 0) {
+            return Error.ConfigFailed;
+        }
+        return Config{ .config = cfg.? };
+    }
+
+    /// Author, committer and reflog identity for this handle only,
+    /// overriding `user.name`/`user.email` without writing any config.
+    /// Pass null for both to go back to the configuration.
+    pub fn setIdent(self: *Repository, allocator: std.mem.Allocator, name: ?[]const u8, email: ?[]const u8) Error!void {
+        const name_z = try dupeZOpt(allocator, name);
+        defer if (name_z) |n| allocator.free(n);
+        const email_z = try dupeZOpt(allocator, email);
+        defer if (email_z) |e| allocator.free(e);
+
+        if (git2_shim_repository_set_ident(self.repo, if (name_z) |n| n.ptr else null, if (email_z) |e| e.ptr else null) < 0) {
+            return Error.ConfigFailed;
+        }
+    }
+
+    /// Identity override set with `setIdent`
+    pub const Ident = struct {
+        name: ?[]const u8,
+        email: ?[]const u8,
+    };
+
+    /// The `setIdent` override, each part null if unset (the configuration
+    /// applies then); borrowed until the identity is changed
+    pub fn ident(self: *const Repository) Error!Ident {
+        var name: ?[*:0]const u8 = null;
+        var email: ?[*:0]const u8 = null;
+        if (git2_shim_repository_ident(&name, &email, self.repo) < 0) {
+            return Error.ConfigFailed;
+        }
+        return .{
+            .name = if (name) |n| std.mem.span(n) else null,
+            .email = if (email) |e| std.mem.span(e) else null,
+        };
+    }
+
+    /// Submodule by name or by path in the work tree; `deinit` when done
+    pub fn submoduleLookup(self: *Repository, allocator: std.mem.Allocator, name: []const u8) Error!Submodule {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        var submodule: ?*git_submodule = null;
+        if (git2_shim_submodule_lookup(&submodule, self.repo, name_z.ptr) < 0) {
+            return Error.SubmoduleFailed;
+        }
+        return Submodule{ .submodule = submodule.? };
+    }
+
+    /// First step of `git submodule add`: record `url` for a new
+    /// submodule at `path` and create its empty repository (under
+    /// `.git/modules/` when `use_gitlink` is set, as git does). Continue
+    /// with `Submodule.clone` and `Submodule.addFinalize`.
+    pub fn submoduleAddSetup(self: *Repository, allocator: std.mem.Allocator, url: []const u8, path: []const u8, use_gitlink: bool) Error!Submodule {
+        const url_z = allocator.dupeZ(u8, url) catch return Error.AllocationFailed;
+        defer allocator.free(url_z);
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+
+        var submodule: ?*git_submodule = null;
+        if (git2_shim_submodule_add_setup(&submodule, self.repo, url_z.ptr, path_z.ptr, @intFromBool(use_gitlink)) < 0) {
+            return Error.SubmoduleFailed;
+        }
+        return Submodule{ .submodule = submodule.? };
+    }
+
+    /// Point submodule `name` at `url` in `.gitmodules`, e.g. a mirror.
+    /// The setters edit the work tree file and leave open `Submodule`
+    /// handles alone; look it up again and `sync` to apply the URL.
+    pub fn submoduleSetUrl(self: *Repository, allocator: std.mem.Allocator, name: []const u8, url: []const u8) Error!void {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+        const url_z = allocator.dupeZ(u8, url) catch return Error.AllocationFailed;
+        defer allocator.free(url_z);
+
+        if (git2_shim_submodule_set_url(self.repo, name_z.ptr, url_z.ptr) < 0) {
+            return Error.SubmoduleFailed;
+        }
+    }
+
+    /// Branch for submodule `name` to track; null removes the setting
+    pub fn submoduleSetBranch(self: *Repository, allocator: std.mem.Allocator, name: []const u8, branch: ?[]const u8) Error!void {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+        const branch_z = try dupeZOpt(allocator, branch);
+        defer if (branch_z) |b| allocator.free(b);
+
+        const rc = git2_shim_submodule_set_branch(self.repo, name_z.ptr, if (branch_z) |b| b.ptr else null);
+        if (rc < 0 and !(rc == GIT_ENOTFOUND and branch == null)) return Error.SubmoduleFailed;
+    }
+
+    pub fn submoduleSetUpdate(self: *Repository, allocator: std.mem.Allocator, name: []const u8, update: SubmoduleUpdate) Error!void {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        if (git2_shim_submodule_set_update(self.repo, name_z.ptr, @intFromEnum(update)) < 0) {
+            return Error.SubmoduleFailed;
+        }
+    }
+
+    pub fn submoduleSetIgnore(self: *Repository, allocator: std.mem.Allocator, name: []const u8, ignore: SubmoduleIgnore) Error!void {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        if (git2_shim_submodule_set_ignore(self.repo, name_z.ptr, @intFromEnum(ignore)) < 0) {
+            return Error.SubmoduleFailed;
+        }
+    }
+
+    /// Status of submodule `name`, examining its work tree as deep as
+    /// `ignore` allows
+    pub fn submoduleStatus(self: *Repository, allocator: std.mem.Allocator, name: []const u8, ignore: SubmoduleIgnore) Error!SubmoduleStatus {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        var bits: c_uint = 0;
+        if (git2_shim_submodule_status(&bits, self.repo, name_z.ptr, @intFromEnum(ignore)) < 0) {
+            return Error.SubmoduleFailed;
+        }
+        return SubmoduleStatus.fromBits(bits);
+    }
+
+    /// Call `callback` for each submodule in `.gitmodules`, the index,
+    /// HEAD or the configuration; the submodule is only valid during the
+    /// call. A non-zero return stops the walk.
+    pub fn submoduleForeach(self: *Repository, callback: git_submodule_cb, payload: ?*anyopaque) Error!void {
+        if (git2_shim_submodule_foreach(self.repo, callback, payload) < 0) {
+            return Error.SubmoduleFailed;
+        }
+    }
+
+    /// Names of all submodules, to pass to `submoduleLookup`; free with
+    /// `freeStringList`
+    pub fn submoduleNames(self: *Repository, allocator: std.mem.Allocator) Error![][:0]u8 {
+        var collector = SubmoduleNameCollector{
+            .allocator = allocator,
+            .names = std.ArrayList([:0]u8).init(allocator),
+        };
+        errdefer {
+            for (collector.names.items) |name| allocator.free(name);
+            collector.names.deinit();
+        }
+
+        if (git2_shim_submodule_foreach(self.repo, SubmoduleNameCollector.collect, &collector) < 0) {
+            return Error.SubmoduleFailed;
+        }
+        return collector.names.toOwnedSlice() catch return Error.AllocationFailed;
+    }
+
+    /// Names of the linked worktrees (the main work tree is not listed);
+    /// free with `freeStringList`
+    pub fn worktreeList(self: *Repository, allocator: std.mem.Allocator) Error![][:0]u8 {
+        var names = git_strarray{ .strings = null, .count = 0 };
+        if (git2_shim_worktree_list(&names, self.repo) < 0) {
+            return Error.WorktreeFailed;
+        }
+        return takeStrarray(allocator, &names);
+    }
+
+    /// Create linked worktree `name` with its work tree at `path`, like
+    /// `git worktree add`; `deinit` the result when done
+    pub fn worktreeAdd(self: *Repository, allocator: std.mem.Allocator, name: []const u8, path: []const u8, options: WorktreeAddOptions) Error!Worktree {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+        const opts = try options.create(allocator);
+        defer git2_shim_worktree_add_options_free(opts);
+
+        var handle: ?*git_worktree = null;
+        if (git2_shim_worktree_add(&handle, self.repo, name_z.ptr, path_z.ptr, opts) < 0) {
+            return Error.WorktreeFailed;
+        }
+        return Worktree{ .worktree = handle.? };
+    }
+
+    /// Linked worktree by name; `deinit` when done
+    pub fn worktreeLookup(self: *Repository, allocator: std.mem.Allocator, name: []const u8) Error!Worktree {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        var handle: ?*git_worktree = null;
+        if (git2_shim_worktree_lookup(&handle, self.repo, name_z.ptr) < 0) {
+            return Error.WorktreeFailed;
+        }
+        return Worktree{ .worktree = handle.? };
+    }
+
+    /// The worktree this repository was opened from; fails unless the
+    /// repository is a linked worktree. `deinit` when done
+    pub fn worktree(self: *Repository) Error!Worktree {
+        var handle: ?*git_worktree = null;
+        if (git2_shim_worktree_open_from_repository(&handle, self.repo) < 0) {
+            return Error.WorktreeFailed;
+        }
+        return Worktree{ .worktree = handle.? };
+    }
+
+    /// `git submodule update --recursive`: update every submodule, then
+    /// the submodules inside it, down to `max_depth` levels (0 for no
+    /// limit). Uninitialized submodules are skipped unless `init_first`
+    /// is set. A failing submodule does not stop the others; check each
+    /// result, and free the list with `freeSubmoduleUpdateResults`. A
+    /// submodule whose own submodules cannot be listed appears twice, the
+    /// second time with the error.
+    pub fn submoduleUpdateRecursive(self: *Repository, allocator: std.mem.Allocator, init_first: bool, max_depth: u32, options: SubmoduleUpdateOptions) Error![]SubmoduleUpdateResult {
+        const opts = try options.create(allocator);
+        defer git2_shim_submodule_update_options_free(opts);
+
+        var collector = SubmoduleUpdateCollector{
+            .allocator = allocator,
+            .results = std.ArrayList(SubmoduleUpdateResult).init(allocator),
+        };
+        errdefer collector.deinit();
+
+        if (git2_shim_submodule_update_recursive(self.repo, @intFromBool(init_first), max_depth, opts, SubmoduleUpdateCollector.collect, &collector) < 0) {
+            return Error.SubmoduleFailed;
+        }
+        return collector.results.toOwnedSlice() catch return Error.AllocationFailed;
+    }
+
+    /// Names of all configured remotes; free with `freeStringList`
+    pub fn remoteList(self: *Repository, allocator: std.mem.Allocator) Error![][:0]u8 {
+        var names = git_strarray{ .strings = null, .count = 0 };
+        if (git2_shim_remote_list(&names, self.repo) < 0) {
+            return Error.RemoteFailed;
+        }
+        return takeStrarray(allocator, &names);
+    }
+
+    pub fn remoteLookup(self: *Repository, allocator: std.mem.Allocator, name: []const u8) Error!Remote {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        var remote: ?*git_remote = null;
+        if (git2_shim_remote_lookup(&remote, self.repo, name_z.ptr) < 0) {
+            return Error.RemoteFailed;
+        }
+        return Remote{ .remote = remote.? };
+    }
+
+    /// Add a remote with the default fetch refspec
+    pub fn remoteCreate(self: *Repository, allocator: std.mem.Allocator, name: []const u8, url: []const u8) Error!Remote {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+        const url_z = allocator.dupeZ(u8, url) catch return Error.AllocationFailed;
+        defer allocator.free(url_z);
+
+        var remote: ?*git_remote = null;
+        if (git2_shim_remote_create(&remote, self.repo, name_z.ptr, url_z.ptr) < 0) {
+            return Error.RemoteFailed;
+        }
+        return Remote{ .remote = remote.? };
+    }
+
+    /// Remote for a one-off fetch or push to `url` that leaves the
+    /// repository config untouched; it has no name and no refspecs, so a
+    /// fetch without explicit refspecs only records FETCH_HEAD
+    pub fn remoteCreateAnonymous(self: *Repository, allocator: std.mem.Allocator, url: []const u8) Error!Remote {
+        const url_z = allocator.dupeZ(u8, url) catch return Error.AllocationFailed;
+        defer allocator.free(url_z);
+
+        var remote: ?*git_remote = null;
+        if (git2_shim_remote_create_anonymous(&remote, self.repo, url_z.ptr) < 0) {
+            return Error.RemoteFailed;
+        }
+        return Remote{ .remote = remote.? };
+    }
+
+    /// Remove a remote along with its remote-tracking branches and config
+    pub fn remoteDelete(self: *Repository, allocator: std.mem.Allocator, name: []const u8) Error!void {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        if (git2_shim_remote_delete(self.repo, name_z.ptr) < 0) {
+            return Error.RemoteFailed;
+        }
+    }
+
+    /// Change a remote's URL in the configuration, e.g. to switch between
+    /// SSH and HTTPS. Loaded `Remote`s keep the old URL; look it up again.
+    pub fn remoteSetUrl(self: *Repository, allocator: std.mem.Allocator, name: []const u8, url: []const u8) Error!void {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+        const url_z = allocator.dupeZ(u8, url) catch return Error.AllocationFailed;
+        defer allocator.free(url_z);
+
+        if (git2_shim_remote_set_url(self.repo, name_z.ptr, url_z.ptr) < 0) {
+            return Error.RemoteFailed;
+        }
+    }
+
+    /// Set a separate push URL; null removes it so pushes use the fetch URL
+    pub fn remoteSetPushurl(self: *Repository, allocator: std.mem.Allocator, name: []const u8, url: ?[]const u8) Error!void {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+        const url_z = try dupeZOpt(allocator, url);
+        defer if (url_z) |u| allocator.free(u);
+
+        if (git2_shim_remote_set_pushurl(self.repo, name_z.ptr, if (url_z) |u| u.ptr else null) < 0) {
+            return Error.RemoteFailed;
+        }
+    }
+
+    /// Store the tag-following setting of remote `name`; already loaded
+    /// `Remote`s keep their old value
+    pub fn remoteSetAutotag(self: *Repository, allocator: std.mem.Allocator, name: []const u8, value: DownloadTags) Error!void {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+
+        if (git2_shim_remote_set_autotag(self.repo, name_z.ptr, @intFromEnum(value)) < 0) {
+            return Error.RemoteFailed;
+        }
+    }
+
+    /// Rename a remote and its remote-tracking branches. Returns the custom
+    /// fetch refspecs that could not be rewritten (free with
+    /// `freeStringList`); these need fixing by hand.
+    pub fn remoteRename(self: *Repository, allocator: std.mem.Allocator, name: []const u8, new_name: []const u8) Error![][:0]u8 {
+        const name_z = allocator.dupeZ(u8, name) catch return Error.AllocationFailed;
+        defer allocator.free(name_z);
+        const new_name_z = allocator.dupeZ(u8, new_name) catch return Error.AllocationFailed;
+        defer allocator.free(new_name_z);
+
+        var problems = git_strarray{ .strings = null, .count = 0 };
+        if (git2_shim_remote_rename(&problems, self.repo, name_z.ptr, new_name_z.ptr) < 0) {
+            return Error.RemoteFailed;
+        }
+        return takeStrarray(allocator, &problems);
+    }
+
+    /// Refs recorded by the last fetch, in FETCH_HEAD order; empty if
+    /// nothing has been fetched. Free with `freeFetchHeadList`.
+    pub fn fetchHead(self: *Repository, allocator: std.mem.Allocator) Error![]FetchHeadEntry {
+        var collector = FetchHeadCollector{
+            .allocator = allocator,
+            .entries = std.ArrayList(FetchHeadEntry).init(allocator),
+        };
+        errdefer {
+            for (collector.entries.items) |entry| {
+                if (entry.ref_name) |name| allocator.free(name);
+                if (entry.remote_url) |url| allocator.free(url);
+            }
+            collector.entries.deinit();
+        }
+
+        const rc = git2_shim_repository_fetchhead_foreach(self.repo, FetchHeadCollector.collect, &collector);
+        if (rc < 0 and rc != GIT_ENOTFOUND) return Error.FetchFailed;
+        return collector.entries.toOwnedSlice() catch return Error.AllocationFailed;
+    }
+
+    /// Repack and prune the object store, like `git gc`. Refs, reflogs and
+    /// the HEAD and index of every valid worktree keep objects alive;
+    /// invalid ones, e.g. with their directory deleted, are skipped. Fails
+    /// if a valid linked worktree cannot be opened.
+    pub fn maintenance(self: *Repository, options: MaintenanceOptions) Error!MaintenanceStats {
+        const opts = try options.create();
+        defer git2_shim_maintenance_options_free(opts);
+
+        var stats = MaintenanceStats{};
+        if (git2_shim_maintenance_run(self.repo, opts, &stats) < 0) {
+            return Error.MaintenanceFailed;
+        }
+        return stats;
+    }
+
+    /// Check the repository's integrity like `git fsck`: walk from refs,
+    /// HEAD, reflogs and the index through every linked object, verifying
+    /// each exists and reads back intact, then check the remaining stored
+    /// objects. An empty result means no problems. Free with
+    /// `freeFsckReports`.
+    pub fn fsck(self: *Repository, allocator: std.mem.Allocator, options: FsckOptions) Error![]FsckReport {
+        const opts = try options.create();
+        defer git2_shim_fsck_options_free(opts);
+
+        var collector = FsckCollector{
+            .allocator = allocator,
+            .reports = std.ArrayList(FsckReport).init(allocator),
+        };
+        errdefer collector.deinit();
+
+        if (git2_shim_fsck(self.repo, opts, FsckCollector.collect, &collector) < 0) {
+            return Error.FsckFailed;
+        }
+        return collector.reports.toOwnedSlice() catch return Error.AllocationFailed;
+    }
+
+    /// Write a `git bundle` file at `path` holding `refs` (e.g. "main",
+    /// "refs/tags/v1", "HEAD") and their history, minus whatever the
+    /// `basis` revspecs (e.g. "origin/main") reach. The receiver must
+    /// already have the commits the bundle builds on.
+    pub fn bundleCreate(
+        self: *Repository,
+        allocator: std.mem.Allocator,
+        path: []const u8,
+        refs: []const []const u8,
+        basis: []const []const u8,
+    ) Error!void {
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+        const refs_z = try dupeZList(allocator, refs);
+        defer freeZList(allocator, refs_z);
+        const basis_z = try dupeZList(allocator, basis);
+        defer freeZList(allocator, basis_z);
+
+        const basis_ptr = if (basis_z.len == 0) null else basis_z.ptr;
+        if (git2_shim_bundle_create(self.repo, path_z.ptr, refs_z.ptr, refs_z.len, basis_ptr, basis_z.len) < 0) {
+            return Error.BundleFailed;
+        }
+    }
+
+    /// Import a bundle written by `bundleCreate` or `git bundle create`.
+    /// `refspec` (e.g. "+refs/heads/*:refs/remotes/bundle/*") maps its
+    /// refs to local ones; null keeps their names and only fast-forwards.
+    /// `update_tips_cb` hears about each ref written. Returns
+    /// `NonFastForward` if an unforced update was refused, after writing
+    /// the rest; a branch checked out in a worktree of a non-bare
+    /// repository is never moved, and fails with `BundleFailed`.
+    pub fn unbundle(
+        self: *Repository,
+        allocator: std.mem.Allocator,
+        path: []const u8,
+        refspec: ?[]const u8,
+        update_tips_cb: ?git_update_tips_cb,
+        payload: ?*anyopaque,
+    ) Error!void {
+        const path_z = allocator.dupeZ(u8, path) catch return Error.AllocationFailed;
+        defer allocator.free(path_z);
+        const spec = try dupeZOpt(allocator, refspec);
+        defer if (spec) |s| allocator.free(s);
+
+        const rc = git2_shim_bundle_unbundle(self.repo, path_z.ptr, if (spec) |s| s.ptr else null, update_tips_cb, payload);
+        if (rc == GIT_ENONFASTFORWARD) return Error.NonFastForward;
+        if (rc < 0) return Error.BundleFailed;
+    }
+
+    /// Commit `tree` with the given parents as the configured user.
+    /// `update_ref` (e.g. "HEAD" or "refs/heads/main") is moved to the new
+    /// commit; pass null to only write the object.
+    pub fn commitCreate(
+        self: *Repository,
+        allocator: std.mem.Allocator,
+        update_ref: ?[]const u8,
+        message: []const u8,
+        tree_id: *const git_oid,
+        parent_ids: []const git_oid,
+    ) Error!git_oid {
+        return self.createCommit(allocator, update_ref, message, tree_id, parent_ids, null, null);
+    }
+
+    /// Like `commitCreate`, but `sign_cb` signs the commit buffer (GPG, SSH,
+    /// ...) before the commit object is written
+    pub fn commitCreateSigned(
+        self: *Repository,
+        allocator: std.mem.Allocator,
+        update_ref: ?[]const u8,
+        message: []const u8,
+        tree_id: *const git_oid,
+        parent_ids: []const git_oid,
+        sign_cb: git_commit_signing_cb,
+        sign_payload: ?*anyopaque,
+    ) Error!git_oid {
+        return self.createCommit(allocator, update_ref, message, tree_id, parent_ids, sign_cb, sign_payload);
+    }
+
+    fn createCommit(
+        self: *Repository,
+        allocator: std.mem.Allocator,
+        update_ref: ?[]const u8,
+        message: []const u8,
+        tree_id: *const git_oid,
+        parent_ids: []const git_oid,
+        sign_cb: ?git_commit_signing_cb,
+        sign_payload: ?*anyopaque,
+    ) Error!git_oid {
+        var sig: ?*git_signature = null;
+        if (git2_shim_signature_default(&sig, self.repo) < 0) {
+            return Error.SignatureFailed;
+        }
+        defer git2_shim_signature_free(sig.?);
+
+        var tree: ?*git_tree = null;
+        if (git2_shim_tree_lookup(&tree, self.repo, tree_id) < 0) {
+            return Error.LookupFailed;
+        }
+        defer git2_shim_tree_free(tree.?);
+
+        const parents = allocator.alloc(*const git_commit, parent_ids.len) catch return Error.AllocationFailed;
+        defer allocator.free(parents);
+        var found: usize = 0;
+        defer for (parents[0..found]) |p| git2_shim_commit_free(@constCast(p));
+        for (parent_ids) |*id| {
+            var parent: ?*git_commit = null;
+            if (git2_shim_commit_lookup(&parent, self.repo, id) < 0) {
+                return Error.LookupFailed;
+            }
+            parents[found] = parent.?;
+            found += 1;
+        }
+
+        const ref_z = try dupeZOpt(allocator, update_ref);
+        defer if (ref_z) |r| allocator.free(r);
+        const message_z = allocator.dupeZ(u8, message) catch return Error.AllocationFailed;
+        defer allocator.free(message_z);
+
+        var oid: git_oid = undefined;
+        const rc = if (sign_cb) |cb| git2_shim_commit_create_signed(
+            &oid,
+            self.repo,
+            if (ref_z) |r| r.ptr else null,
+            sig.?,
+            sig.?,
+            message_z.ptr,
+            tree.?,
+            parents.len,
+            parents.ptr,
+            cb,
+            sign_payload,
+        ) else git2_shim_commit_create(
+            &oid,
+            self.repo,
+            if (ref_z) |r| r.ptr else null,
+            sig.?,
+            sig.?,
+            message_z.ptr,
+            tree.?,
+            parents.len,
+            parents.ptr,
+        );
+        if (rc < 0) {
+            return Error.CommitFailed;
+        }
+        return oid;
+    }
+
+    /// List stash entries, newest first. Free with `freeStashList`.
+    pub fn stashList(self: *Repository, allocator: std.mem.Allocator) Error![]StashEntry {
+        var collector = StashCollector{
+            .allocator = allocator,
+            .entries = std.ArrayList(StashEntry).init(allocator),
+        };
+        errdefer {
+            for (collector.entries.items) |entry| allocator.free(entry.message);
+            collector.entries.deinit();
+        }
+
+        if (git2_shim_stash_foreach(self.repo, StashCollector.collect, &collector) < 0) {
+            return Error.StashFailed;
+        }
+        return collector.entries.toOwnedSlice() catch return Error.AllocationFailed;
+    }
+};
+
+// =============================================================================
+// C FFI exports
+// =============================================================================
+
+var global_allocator: std.mem.Allocator = std.heap.c_allocator;
+
+export fn git2_init() bool {
+    _ = git2_shim_init();
+    Repository.initialized = true;
+    return true;
+}
+
+export fn git2_shutdown() void {
+    _ = git2_shim_shutdown();
+    Repository.initialized = false;
+}
+
+export fn git2_open(path: [*:0]const u8) ?*Repository {
+    const repo = Repository.open(global_allocator, std.mem.span(path)) catch return null;
+    const ptr = global_allocator.create(Repository) catch return null;
+    ptr.* = repo;
+    return ptr;
+}
+
+/// Clone `url` into `path`; close the result with `git2_close`
+export fn git2_clone(url: [*:0]const u8, path: [*:0]const u8) ?*Repository {
+    var repo = Repository.clone(global_allocator, std.mem.span(url), std.mem.span(path), .{}) catch return null;
+    const ptr = global_allocator.create(Repository) catch {
+        repo.close();
+        return null;
+    };
+    ptr.* = repo;
+    return ptr;
+}
+
+/// Message for the last failed call on this thread, or null
+export fn git2_last_error_message() ?[*:0]const u8 {
+    return git2_shim_error_last_message();
 }
 
 export fn git2_close(repo: *Repository) void {